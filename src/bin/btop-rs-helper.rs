@@ -0,0 +1,138 @@
+// btop-rs 的特权辅助程序。主程序保持普通权限运行，只有少数需要更高权限才能读取的文件
+// 通过这个程序读取，例如部分 hwmon 传感器，以及自己进程中受限的 smaps_rollup、io。
+// RAPL 的能量计数器只有 root 可读是为了防止侧信道攻击（CVE-2020-8694），不经过这个程序读取。
+//
+// 安装方式（二选一）：
+//   setcap cap_dac_read_search,cap_sys_ptrace+ep btop-rs-helper
+//   chown root btop-rs-helper && chmod u+s btop-rs-helper
+//
+// 协议：每行一个请求 "read <path>"，应答为 "ok <长度>\n" 加文件内容，或 "err <原因>\n"。
+// 只允许读取下面白名单中的路径，不接受其它任何操作。/proc/<pid>/ 下的文件只能读取
+// 属于调用者真实用户的进程，root 不受限制。
+
+use std::{
+    fs::File,
+    io::{self, BufRead, Read, Write},
+    os::unix::fs::MetadataExt,
+};
+
+// 单个应答的最大长度，超过部分截断
+const MAX_SIZE: u64 = 1 << 20;
+
+// /proc/<pid>/ 下允许读取的文件
+const PROC_FILES: &[&str] = &["io", "smaps_rollup", "smaps", "status", "limits"];
+
+fn is_pid(name: &str) -> bool {
+    !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit())
+}
+
+// 只按字面比较路径的各个部分，拒绝 "."、".." 和重复的 "/"
+fn allowed(path: &str) -> bool {
+    let Some(rest) = path.strip_prefix('/') else {
+        return false;
+    };
+    let parts: Vec<&str> = rest.split('/').collect();
+    if parts.iter().any(|part| matches!(*part, "" | "." | "..")) {
+        return false;
+    }
+    match parts.as_slice() {
+        ["proc", pid, file] => is_pid(pid) && PROC_FILES.contains(file),
+        ["sys", "class", "hwmon", dir, file] => {
+            dir.strip_prefix("hwmon").is_some_and(is_pid) && hwmon_file(file)
+        }
+        _ => false,
+    }
+}
+
+// hwmon 中只允许 name 以及温度、风扇、电压的 <类型><N>_input 和 <类型><N>_label。
+// power 和 energy 不在其中：与 RAPL 一样，功耗计数器可以被用作侧信道（CVE-2020-8694）
+fn hwmon_file(file: &str) -> bool {
+    if file == "name" {
+        return true;
+    }
+    let Some(sensor) = file
+        .strip_suffix("_input")
+        .or_else(|| file.strip_suffix("_label"))
+    else {
+        return false;
+    };
+    ["temp", "fan", "in"]
+        .iter()
+        .any(|kind| sensor.strip_prefix(kind).is_some_and(is_pid))
+}
+
+// 打开之后按文件的属主检查，而不是先检查 /proc/<pid> 再打开，避免 pid 在两者之间被复用。
+// 进程文件的属主是进程的有效用户，不可 dump 的进程（例如 setuid 程序）属于 root
+fn read(path: &str, uid: u32) -> io::Result<Vec<u8>> {
+    if !allowed(path) {
+        return Err(io::ErrorKind::PermissionDenied.into());
+    }
+    let file = File::open(path)?;
+    if path.starts_with("/proc/") && uid != 0 && file.metadata()?.uid() != uid {
+        return Err(io::ErrorKind::PermissionDenied.into());
+    }
+    let mut data = Vec::new();
+    file.take(MAX_SIZE).read_to_end(&mut data)?;
+    Ok(data)
+}
+
+fn main() {
+    // 以 setuid 安装时有效用户是 root，真实用户才是调用者
+    let uid = unsafe { libc::getuid() };
+    let stdin = io::stdin();
+    let mut stdout = io::stdout().lock();
+    for line in stdin.lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        let result = match line.split_once(' ') {
+            Some(("read", path)) => read(path, uid),
+            _ => Err(io::ErrorKind::InvalidInput.into()),
+        };
+        let written = match result {
+            Ok(data) => writeln!(stdout, "ok {}", data.len()).and_then(|_| stdout.write_all(&data)),
+            Err(err) => writeln!(stdout, "err {}", err.kind()),
+        };
+        if written.and_then(|_| stdout.flush()).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowed_paths() {
+        assert!(allowed("/proc/1/smaps_rollup"));
+        assert!(allowed("/sys/class/hwmon/hwmon0/temp1_input"));
+        assert!(allowed("/sys/class/hwmon/hwmon3/fan2_label"));
+        assert!(allowed("/sys/class/hwmon/hwmon0/name"));
+        assert!(!allowed("/sys/class/powercap/intel-rapl:0/energy_uj"));
+        assert!(!allowed("/sys/class/hwmon/hwmon0/power1_input"));
+        assert!(!allowed("/sys/class/hwmon/hwmon0/energy1_input"));
+        assert!(!allowed("/sys/class/hwmon/hwmon0/temp_input"));
+        assert!(!allowed("/sys/class/hwmon/hwmon0/temp1_max"));
+        assert!(!allowed("/proc/self/io"));
+        assert!(!allowed("/proc/1/environ"));
+        assert!(!allowed("/proc/1/../1/io"));
+        assert!(!allowed("/proc//1/io"));
+        assert!(!allowed("proc/1/io"));
+        assert!(!allowed("/sys/class/hwmon/hwmon0/device/uevent"));
+    }
+
+    // 其它用户的进程即使在白名单中也不能读取，自己的进程可以
+    #[test]
+    fn other_users_processes_are_denied() {
+        let own = unsafe { libc::geteuid() };
+        let other = own.wrapping_add(12345);
+        let path = format!("/proc/{}/status", std::process::id());
+        assert!(read(&path, own).is_ok_and(|data| !data.is_empty()));
+        let err = read(&path, other).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+        // 不在白名单中的路径与属主无关
+        let err = read("/etc/shadow", 0).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+}
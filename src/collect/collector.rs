@@ -0,0 +1,200 @@
+use std::io;
+
+use super::{
+    battery, cpu::CpuCollector, disk::DiskCollector, klog::KlogReader, mem, net::NetCollector,
+    proc::ProcCollector,
+};
+use crate::shared::{
+    battery::{BatteryEstimator, BatteryInfo},
+    cpu::CpuInfo,
+    disk::{DiskFilter, DiskInfo},
+    klog::KlogInfo,
+    mem::MemInfo,
+    net::NetInfo,
+    proc::ProcInfo,
+};
+
+// 每次采集时传给所有采集器的参数，由 Runner 从配置中读取
+#[derive(Clone, Default)]
+pub struct CollectCtx {
+    pub check_temp: bool,
+    pub proc_per_core: bool,
+    pub show_battery: bool,
+    pub show_disks: bool,
+    pub show_disk_temps: bool,
+    pub only_physical: bool,
+    pub disks_filter: String,
+    // shown_boxes 中包含 log
+    pub show_log: bool,
+}
+
+// 各个子系统的采集结果，新增子系统（磁盘、传感器等）时在这里增加对应的变体
+pub enum Sample {
+    Cpu(CpuInfo),
+    Mem(MemInfo),
+    // show_disks 关闭时为空
+    Disks(DiskInfo),
+    Net(NetInfo),
+    Procs(Vec<ProcInfo>),
+    // 没有电池或 show_battery 关闭时为 None
+    Battery(Option<BatteryInfo>),
+    // log box 没有显示时为空
+    Klog(KlogInfo),
+}
+
+// 平台后端和插件共用的采集接口
+pub trait Collector {
+    fn name(&self) -> &'static str;
+    fn collect(&mut self, ctx: &CollectCtx) -> io::Result<Sample>;
+}
+
+impl Collector for CpuCollector {
+    fn name(&self) -> &'static str {
+        "cpu"
+    }
+
+    // check_temp 关闭时不显示温度
+    fn collect(&mut self, ctx: &CollectCtx) -> io::Result<Sample> {
+        let mut info = CpuCollector::collect(self)?;
+        if !ctx.check_temp {
+            info.temp = None;
+        }
+        Ok(Sample::Cpu(info))
+    }
+}
+
+// 内存没有需要保存的状态，平台接口只提供一个函数
+pub struct MemCollector;
+
+impl Collector for MemCollector {
+    fn name(&self) -> &'static str {
+        "mem"
+    }
+
+    fn collect(&mut self, _ctx: &CollectCtx) -> io::Result<Sample> {
+        mem::collect().map(Sample::Mem)
+    }
+}
+
+// 平台接口列出挂载的文件系统，disks_filter 的过滤在这里完成
+impl Collector for DiskCollector {
+    fn name(&self) -> &'static str {
+        "disk"
+    }
+
+    fn collect(&mut self, ctx: &CollectCtx) -> io::Result<Sample> {
+        if !ctx.show_disks {
+            return Ok(Sample::Disks(DiskInfo::default()));
+        }
+        let mut info = DiskCollector::collect(self, ctx.only_physical, ctx.show_disk_temps)?;
+        let filter = DiskFilter::parse(&ctx.disks_filter);
+        info.disks.retain(|disk| filter.matches(&disk.mount));
+        Ok(Sample::Disks(info))
+    }
+}
+
+// 平台接口只负责读取，剩余时间的平滑在这里完成
+#[derive(Default)]
+pub struct BatteryCollector {
+    estimator: BatteryEstimator,
+}
+
+impl Collector for BatteryCollector {
+    fn name(&self) -> &'static str {
+        "battery"
+    }
+
+    fn collect(&mut self, ctx: &CollectCtx) -> io::Result<Sample> {
+        if !ctx.show_battery {
+            return Ok(Sample::Battery(None));
+        }
+        let reading = battery::read()?;
+        Ok(Sample::Battery(
+            reading.map(|reading| self.estimator.update(reading)),
+        ))
+    }
+}
+
+impl Collector for NetCollector {
+    fn name(&self) -> &'static str {
+        "net"
+    }
+
+    fn collect(&mut self, _ctx: &CollectCtx) -> io::Result<Sample> {
+        NetCollector::collect(self).map(Sample::Net)
+    }
+}
+
+impl Collector for ProcCollector {
+    fn name(&self) -> &'static str {
+        "proc"
+    }
+
+    fn collect(&mut self, ctx: &CollectCtx) -> io::Result<Sample> {
+        ProcCollector::collect(self, ctx.proc_per_core).map(Sample::Procs)
+    }
+}
+
+impl Collector for KlogReader {
+    fn name(&self) -> &'static str {
+        "klog"
+    }
+
+    fn collect(&mut self, ctx: &CollectCtx) -> io::Result<Sample> {
+        if !ctx.show_log {
+            return Ok(Sample::Klog(KlogInfo::default()));
+        }
+        Ok(Sample::Klog(KlogReader::collect(self)))
+    }
+}
+
+// 按注册顺序依次调用采集器
+pub struct Registry {
+    collectors: Vec<Box<dyn Collector>>,
+}
+
+impl Default for Registry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Registry {
+    pub fn new() -> Self {
+        Registry {
+            collectors: Vec::new(),
+        }
+    }
+
+    // 当前平台后端提供的全部采集器
+    pub fn platform() -> Self {
+        let mut registry = Registry::new();
+        registry.register(Box::new(CpuCollector::new()));
+        registry.register(Box::new(MemCollector));
+        registry.register(Box::new(DiskCollector::new()));
+        registry.register(Box::new(NetCollector::new()));
+        registry.register(Box::new(ProcCollector::new()));
+        registry.register(Box::new(BatteryCollector::default()));
+        registry.register(Box::new(KlogReader::new()));
+        registry
+    }
+
+    pub fn register(&mut self, collector: Box<dyn Collector>) {
+        self.collectors.push(collector);
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.collectors.iter().map(|c| c.name()).collect()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Collector>> {
+        self.collectors.iter_mut()
+    }
+
+    pub fn collect_all(&mut self, ctx: &CollectCtx) -> Vec<(&'static str, io::Result<Sample>)> {
+        self.collectors
+            .iter_mut()
+            .map(|c| (c.name(), c.collect(ctx)))
+            .collect()
+    }
+}
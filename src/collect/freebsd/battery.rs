@@ -0,0 +1,40 @@
+use std::io;
+
+use libc::c_int;
+
+use super::sysctl_by_name;
+use crate::shared::battery::{BatteryReading, BatteryStatus};
+
+// acpi_battery(4) 中的状态位
+const ACPI_BATT_STAT_DISCHARG: c_int = 0x0001;
+const ACPI_BATT_STAT_CHARGING: c_int = 0x0002;
+
+pub fn read() -> io::Result<Option<BatteryReading>> {
+    // 没有电池时 hw.acpi.battery.life 不存在或为 -1
+    let Some(life) = sysctl_by_name::<c_int>("hw.acpi.battery.life").filter(|life| *life >= 0)
+    else {
+        return Ok(None);
+    };
+    let state = sysctl_by_name::<c_int>("hw.acpi.battery.state").unwrap_or(0);
+    let status = if state & ACPI_BATT_STAT_CHARGING != 0 {
+        BatteryStatus::Charging
+    } else if state & ACPI_BATT_STAT_DISCHARG != 0 {
+        BatteryStatus::Discharging
+    } else if life >= 100 {
+        BatteryStatus::Full
+    } else {
+        BatteryStatus::Unknown
+    };
+    // 剩余分钟数，只在放电时有效
+    let seconds = sysctl_by_name::<c_int>("hw.acpi.battery.time")
+        .filter(|time| *time >= 0 && status == BatteryStatus::Discharging)
+        .map(|time| time as u64 * 60);
+
+    Ok(Some(BatteryReading {
+        percent: life.min(100) as f64,
+        status,
+        watts: None,
+        energy: None,
+        seconds,
+    }))
+}
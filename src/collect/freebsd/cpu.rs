@@ -0,0 +1,121 @@
+use std::io;
+
+use libc::{c_int, c_long, CPUSTATES};
+
+use super::{sysctl_by_name, sysctl_mib, sysctl_vec};
+use crate::{
+    shared::cpu::{clean_model, state_stats, CpuInfo},
+    time_s,
+};
+
+// kern.cp_times 中每个核心的节拍数：user, nice, sys, intr, idle
+type Ticks = [u64; CPUSTATES as usize];
+const CP_IDLE: usize = 4;
+const STATE_NAMES: [&str; CPUSTATES as usize] = ["user", "nice", "system", "irq", "idle"];
+
+fn usage(last: &Ticks, now: &Ticks) -> f64 {
+    let total: u64 = now.iter().sum::<u64>().saturating_sub(last.iter().sum());
+    let idle = now[CP_IDLE].saturating_sub(last[CP_IDLE]);
+    if total == 0 {
+        return 0.0;
+    }
+    (total.saturating_sub(idle) as f64 * 100.0 / total as f64).clamp(0.0, 100.0)
+}
+
+pub struct CpuCollector {
+    model: String,
+    cp_times_mib: Vec<libc::c_int>,
+    last_total: Ticks,
+    last_cores: Vec<Ticks>,
+}
+
+impl Default for CpuCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuCollector {
+    pub fn new() -> Self {
+        CpuCollector {
+            model: sysctl_mib("hw.model")
+                .and_then(|mib| sysctl_vec::<u8>(&mib))
+                .map(|model| clean_model(&String::from_utf8_lossy(&model)))
+                .unwrap_or_default(),
+            cp_times_mib: sysctl_mib("kern.cp_times").unwrap_or_default(),
+            last_total: [0; CPUSTATES as usize],
+            last_cores: Vec::new(),
+        }
+    }
+
+    fn read_ticks(&self) -> io::Result<Vec<Ticks>> {
+        let raw = sysctl_vec::<c_long>(&self.cp_times_mib)
+            .ok_or_else(|| io::Error::other("sysctl kern.cp_times failed"))?;
+        Ok(raw
+            .chunks_exact(CPUSTATES as usize)
+            .map(|chunk| {
+                let mut t: Ticks = [0; CPUSTATES as usize];
+                for (dst, &src) in t.iter_mut().zip(chunk) {
+                    *dst = src.max(0) as u64;
+                }
+                t
+            })
+            .collect())
+    }
+
+    pub fn collect(&mut self) -> io::Result<CpuInfo> {
+        let mut info = CpuInfo {
+            model: self.model.clone(),
+            ..Default::default()
+        };
+        let mut cores = self.read_ticks()?;
+        // kern.cp_times 按 mp_maxid 分配，未上线的核心全为 0
+        while cores.len() > 1 && cores.last().is_some_and(|t| t.iter().all(|&v| v == 0)) {
+            cores.pop();
+        }
+
+        let mut total: Ticks = [0; CPUSTATES as usize];
+        for core in &cores {
+            for (sum, t) in total.iter_mut().zip(core.iter()) {
+                *sum += t;
+            }
+        }
+        info.cpu_percent = usage(&self.last_total, &total);
+        let delta = total
+            .iter()
+            .sum::<u64>()
+            .saturating_sub(self.last_total.iter().sum());
+        info.stats = state_stats(
+            info.cpu_percent,
+            &STATE_NAMES,
+            &self.last_total,
+            &total,
+            delta,
+        );
+        self.last_total = total;
+
+        info.core_percent = cores
+            .iter()
+            .enumerate()
+            .map(|(i, now)| match self.last_cores.get(i) {
+                Some(last) => usage(last, now),
+                None => usage(&[0; CPUSTATES as usize], now),
+            })
+            .collect();
+        self.last_cores = cores;
+
+        unsafe { libc::getloadavg(info.load_avg.as_mut_ptr(), 3) };
+        // 需要加载 coretemp 或 amdtemp，单位为十分之一开尔文
+        info.temp = sysctl_by_name::<c_int>("dev.cpu.0.temperature")
+            .map(|decikelvin| (decikelvin - 2731) as f64 / 10.0);
+        Ok(info)
+    }
+}
+
+// 系统启动以来的秒数
+pub fn uptime() -> u64 {
+    match sysctl_by_name::<libc::timeval>("kern.boottime") {
+        Some(boot) => time_s().saturating_sub(boot.tv_sec.max(0) as u64),
+        None => 0,
+    }
+}
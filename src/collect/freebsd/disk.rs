@@ -0,0 +1,137 @@
+use std::{collections::HashMap, ffi::CStr, io, time::Instant};
+
+use libc::{bintime, c_long, devstat};
+
+use super::{sysctl_mib, sysctl_vec};
+use crate::shared::disk::{self, DiskInfo, DiskStat};
+
+// devstat 中 bytes 数组按传输类型索引，与 devstat_trans_flags 的取值相同
+const DEVSTAT_READ: usize = 1;
+const DEVSTAT_WRITE: usize = 2;
+
+// 块设备的累计读写字节数和忙于读写的秒数
+#[derive(Clone, Copy)]
+struct IoCounters {
+    read: u64,
+    write: u64,
+    busy: f64,
+}
+
+pub struct DiskCollector {
+    last: HashMap<String, IoCounters>,
+    last_time: Option<Instant>,
+    devstat_mib: Vec<libc::c_int>,
+}
+
+impl Default for DiskCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn seconds(time: &bintime) -> f64 {
+    time.sec as f64 + time.frac as f64 / 2f64.powi(64)
+}
+
+// kern.devstat.all 的内容为一个 long 类型的代数，之后是所有设备的 struct devstat。
+// GEOM 分区的条目没有名称，只保留 ada0、nvd0 这类整块设备
+fn devstats(mib: &[libc::c_int]) -> HashMap<String, IoCounters> {
+    let mut counters = HashMap::new();
+    let Some(raw) = sysctl_vec::<u8>(mib) else {
+        return counters;
+    };
+    let entries = raw
+        .get(std::mem::size_of::<c_long>()..)
+        .unwrap_or_default()
+        .chunks_exact(std::mem::size_of::<devstat>());
+    for entry in entries {
+        let stat = unsafe { std::ptr::read_unaligned(entry.as_ptr() as *const devstat) };
+        let name = unsafe { CStr::from_ptr(stat.device_name.as_ptr()) }.to_string_lossy();
+        if name.is_empty() || stat.unit_number < 0 {
+            continue;
+        }
+        counters.insert(
+            format!("{}{}", name, stat.unit_number),
+            IoCounters {
+                read: stat.bytes[DEVSTAT_READ],
+                write: stat.bytes[DEVSTAT_WRITE],
+                busy: seconds(&stat.busy_time),
+            },
+        );
+    }
+    counters
+}
+
+// /dev/ada0p2、/dev/da1s1a 所在的整块设备，去掉分区和切片的后缀
+fn whole_disk(device: &str) -> Option<String> {
+    let name = device.strip_prefix("/dev/")?;
+    let letters = name.find(|c: char| c.is_ascii_digit())?;
+    let digits = name[letters..]
+        .find(|c: char| !c.is_ascii_digit())
+        .map_or(name.len(), |end| letters + end);
+    Some(name[..digits].to_owned())
+}
+
+impl DiskCollector {
+    pub fn new() -> Self {
+        DiskCollector {
+            last: HashMap::new(),
+            last_time: None,
+            devstat_mib: sysctl_mib("kern.devstat.all").unwrap_or_default(),
+        }
+    }
+
+    // 硬盘温度目前只在 Linux 上读取。ZFS 数据集没有对应的设备，只提供容量
+    pub fn collect(&mut self, only_physical: bool, _temps: bool) -> io::Result<DiskInfo> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_time
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+        let counters = devstats(&self.devstat_mib);
+
+        let mut mounts: *mut libc::statfs = std::ptr::null_mut();
+        // 结果由 libc 管理，下次调用时复用，不需要释放
+        let count = unsafe { libc::getmntinfo(&mut mounts, libc::MNT_NOWAIT) };
+        if count <= 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mounts = unsafe { std::slice::from_raw_parts(mounts, count as usize) };
+        let mut info = DiskInfo::default();
+        for st in mounts {
+            let text = |raw: &[libc::c_char]| {
+                unsafe { CStr::from_ptr(raw.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            let (device, mount) = (text(&st.f_mntfromname), text(&st.f_mntonname));
+            if only_physical && !disk::is_physical(&device, &text(&st.f_fstypename)) {
+                continue;
+            }
+            let bsize = st.f_bsize;
+            let mut stat = DiskStat::new(
+                device,
+                mount,
+                st.f_blocks * bsize,
+                st.f_blocks.saturating_sub(st.f_bfree) * bsize,
+                st.f_bavail.max(0) as u64 * bsize,
+            );
+            // 同一块设备上的多个分区显示的是整块设备的读写
+            let io = whole_disk(&stat.device)
+                .and_then(|name| Some((counters.get(&name)?, self.last.get(&name)?)));
+            if let Some((now, last)) = io.filter(|_| elapsed > 0.0) {
+                let rate = |now: u64, last: u64| (now.saturating_sub(last) as f64 / elapsed) as u64;
+                stat.read_rate = rate(now.read, last.read);
+                stat.write_rate = rate(now.write, last.write);
+                let busy = (now.busy - last.busy).max(0.0);
+                stat.io_percent = Some((busy * 100.0 / elapsed).min(100.0));
+            }
+            info.push(stat);
+        }
+        info.sort();
+
+        self.last = counters;
+        self.last_time = Some(now);
+        Ok(info)
+    }
+}
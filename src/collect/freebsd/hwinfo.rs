@@ -0,0 +1,20 @@
+use libc::c_int;
+
+use super::sysctl_by_name;
+use crate::shared::hwinfo::HwInfo;
+
+// FreeBSD 没有统一的缓存大小接口，这里只给出拓扑和内存总量
+pub fn collect() -> HwInfo {
+    let threads = sysctl_by_name::<c_int>("hw.ncpu").unwrap_or(0).max(0) as usize;
+    let cores = sysctl_by_name::<c_int>("kern.smp.cores")
+        .filter(|&n| n > 0)
+        .map(|n| n as usize)
+        .unwrap_or(threads);
+    HwInfo {
+        sockets: 1,
+        cores,
+        threads,
+        mem_total: sysctl_by_name::<u64>("hw.physmem").unwrap_or(0),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,19 @@
+use libc::c_int;
+
+use super::sysctl_by_name;
+use crate::shared::limits::{Limit, SysLimits};
+
+pub fn collect() -> SysLimits {
+    let mut limits = Vec::new();
+    if let (Some(open), Some(max)) = (
+        sysctl_by_name::<c_int>("kern.openfiles"),
+        sysctl_by_name::<c_int>("kern.maxfiles"),
+    ) {
+        limits.push(Limit::new(
+            "Open files",
+            open.max(0) as u64,
+            max.max(0) as u64,
+        ));
+    }
+    SysLimits { limits }
+}
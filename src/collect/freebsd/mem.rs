@@ -0,0 +1,75 @@
+use std::io;
+
+use libc::{c_int, c_long, c_uint};
+
+use super::{sysctl_by_name, sysctl_mib};
+use crate::shared::mem::MemInfo;
+
+// sys/conf.h 中的 struct xswdev，libc 未提供
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct XswDev {
+    xsw_version: c_uint,
+    xsw_dev: u64,
+    xsw_flags: c_int,
+    xsw_nblks: c_int,
+    xsw_used: c_int,
+}
+
+fn vm_pages(name: &str) -> u64 {
+    sysctl_by_name::<c_uint>(&format!("vm.stats.vm.{}", name)).unwrap_or(0) as u64
+}
+
+// 逐个读取 vm.swap_info.<n>，等价于 kvm_getswapinfo 但不需要 kvm
+fn swap_pages() -> (u64, u64) {
+    let Some(mut mib) = sysctl_mib("vm.swap_info") else {
+        return (0, 0);
+    };
+    mib.push(0);
+    let (mut total, mut used) = (0, 0);
+    for index in 0.. {
+        *mib.last_mut().unwrap() = index;
+        let mut dev: XswDev = unsafe { std::mem::zeroed() };
+        let mut len = std::mem::size_of::<XswDev>();
+        let ret = unsafe {
+            libc::sysctl(
+                mib.as_ptr(),
+                mib.len() as u32,
+                &mut dev as *mut XswDev as *mut libc::c_void,
+                &mut len,
+                std::ptr::null(),
+                0,
+            )
+        };
+        if ret != 0 {
+            break;
+        }
+        total += dev.xsw_nblks.max(0) as u64;
+        used += dev.xsw_used.max(0) as u64;
+    }
+    (total, used)
+}
+
+pub fn collect() -> io::Result<MemInfo> {
+    let total = sysctl_by_name::<u64>("hw.physmem")
+        .ok_or_else(|| io::Error::other("sysctl hw.physmem failed"))?;
+    let page_size = sysctl_by_name::<c_uint>("vm.stats.vm.v_page_size").unwrap_or(4096) as u64;
+
+    let mut info = MemInfo {
+        total,
+        ..Default::default()
+    };
+    // 与 top(1) 一致：已用 = 活跃 + 联动，缓存 = 非活跃 + 待清洗 + 缓冲区
+    info.used = (vm_pages("v_active_count") + vm_pages("v_wire_count")) * page_size;
+    info.free = vm_pages("v_free_count") * page_size;
+    info.cached = (vm_pages("v_inactive_count") + vm_pages("v_laundry_count")) * page_size
+        + sysctl_by_name::<c_long>("vfs.bufspace").unwrap_or(0).max(0) as u64;
+    info.available = info.total.saturating_sub(info.used);
+
+    let (swap_total, swap_used) = swap_pages();
+    info.swap_total = swap_total * page_size;
+    info.swap_used = swap_used * page_size;
+    info.swap_free = info.swap_total.saturating_sub(info.swap_used);
+
+    Ok(info)
+}
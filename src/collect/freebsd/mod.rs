@@ -0,0 +1,82 @@
+use std::{ffi::CString, ptr};
+
+use libc::{c_int, c_void};
+
+pub mod battery;
+pub mod cpu;
+pub mod disk;
+pub mod hwinfo;
+pub mod klog;
+pub mod limits;
+pub mod mem;
+pub mod net;
+pub mod proc;
+pub mod sensors;
+pub mod socket;
+
+// 读取定长的 sysctl 值，T 必须是可以按位初始化为 0 的 C 结构体或整数
+pub fn sysctl_by_name<T: Copy>(name: &str) -> Option<T> {
+    let c_name = CString::new(name).ok()?;
+    let mut value: T = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<T>();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            c_name.as_ptr(),
+            &mut value as *mut T as *mut c_void,
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    (ret == 0).then_some(value)
+}
+
+// 读取长度不定的 sysctl 数组（如 kern.cp_times、kern.proc.proc），先查询大小再读取
+pub fn sysctl_vec<T: Copy>(mib: &[c_int]) -> Option<Vec<T>> {
+    let elem = std::mem::size_of::<T>();
+    for _ in 0..3 {
+        let mut len = 0;
+        let ret = unsafe {
+            libc::sysctl(
+                mib.as_ptr(),
+                mib.len() as u32,
+                ptr::null_mut(),
+                &mut len,
+                ptr::null(),
+                0,
+            )
+        };
+        if ret != 0 {
+            return None;
+        }
+        // 预留一些空间给两次调用之间的变化（例如新建的进程）
+        let mut buf: Vec<T> = Vec::with_capacity(len / elem + 16);
+        let mut len = buf.capacity() * elem;
+        let ret = unsafe {
+            libc::sysctl(
+                mib.as_ptr(),
+                mib.len() as u32,
+                buf.as_mut_ptr() as *mut c_void,
+                &mut len,
+                ptr::null(),
+                0,
+            )
+        };
+        if ret == 0 {
+            unsafe { buf.set_len(len / elem) };
+            return Some(buf);
+        }
+        if std::io::Error::last_os_error().raw_os_error() != Some(libc::ENOMEM) {
+            return None;
+        }
+    }
+    None
+}
+
+pub fn sysctl_mib(name: &str) -> Option<Vec<c_int>> {
+    let c_name = CString::new(name).ok()?;
+    let mut mib = [0 as c_int; libc::CTL_MAXNAME as usize];
+    let mut len = mib.len();
+    let ret = unsafe { libc::sysctlnametomib(c_name.as_ptr(), mib.as_mut_ptr(), &mut len) };
+    (ret == 0).then(|| mib[..len].to_vec())
+}
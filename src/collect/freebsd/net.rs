@@ -0,0 +1,77 @@
+use std::{collections::HashMap, ffi::CStr, io, ptr, time::Instant};
+
+use libc::{freeifaddrs, getifaddrs, if_data, ifaddrs, AF_LINK};
+
+use crate::shared::net::{NetInfo, NetStat};
+
+pub struct NetCollector {
+    last: HashMap<String, (u64, u64)>,
+    last_time: Option<Instant>,
+}
+
+impl Default for NetCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetCollector {
+    pub fn new() -> Self {
+        NetCollector {
+            last: HashMap::new(),
+            last_time: None,
+        }
+    }
+
+    pub fn collect(&mut self) -> io::Result<NetInfo> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_time
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut addrs: *mut ifaddrs = ptr::null_mut();
+        if unsafe { getifaddrs(&mut addrs) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut info = NetInfo::default();
+        let mut current = HashMap::new();
+        let mut cur = addrs;
+        while !cur.is_null() {
+            let ifa = unsafe { &*cur };
+            cur = ifa.ifa_next;
+            if ifa.ifa_addr.is_null()
+                || ifa.ifa_data.is_null()
+                || unsafe { (*ifa.ifa_addr).sa_family } as i32 != AF_LINK
+            {
+                continue;
+            }
+
+            let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
+                .to_string_lossy()
+                .into_owned();
+            // FreeBSD 的 if_data 计数器是 64 位的，不需要处理回绕
+            let data = unsafe { &*(ifa.ifa_data as *const if_data) };
+            let mut stat = NetStat {
+                name: name.clone(),
+                rx_bytes: data.ifi_ibytes,
+                tx_bytes: data.ifi_obytes,
+                ..Default::default()
+            };
+            if let Some(&(rx, tx)) = self.last.get(&name) {
+                if elapsed > 0.0 {
+                    stat.rx_rate = (stat.rx_bytes.saturating_sub(rx) as f64 / elapsed) as u64;
+                    stat.tx_rate = (stat.tx_bytes.saturating_sub(tx) as f64 / elapsed) as u64;
+                }
+            }
+            current.insert(name, (stat.rx_bytes, stat.tx_bytes));
+            info.interfaces.push(stat);
+        }
+        unsafe { freeifaddrs(addrs) };
+
+        self.last = current;
+        self.last_time = Some(now);
+        Ok(info)
+    }
+}
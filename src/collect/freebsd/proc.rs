@@ -0,0 +1,210 @@
+use std::{collections::HashMap, ffi::CStr, io, time::Instant};
+
+use libc::{
+    c_char, c_int, c_uint, kinfo_proc, CTL_KERN, KERN_PROC, KERN_PROC_ARGS, KERN_PROC_ENV,
+    KERN_PROC_PROC, SIDL, SLOCK, SRUN, SSLEEP, SSTOP, SWAIT, SZOMB,
+};
+
+use super::{sysctl_by_name, sysctl_vec};
+use crate::shared::proc::{
+    parse_environ, IoPriority, ProcFd, ProcInfo, ProcLimit, ProcStack, ProcThread,
+};
+
+// 与 Linux 保持一致，cpu_time 以 1/100 秒为单位
+const TICKS_PER_SEC: u64 = 100;
+
+fn user_name(uid: u32) -> Option<String> {
+    let pw = unsafe { libc::getpwuid(uid) };
+    if pw.is_null() {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr((*pw).pw_name) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+// kern.proc.args 返回以 '\0' 分隔的 argv
+fn read_cmdline(pid: i32) -> String {
+    sysctl_vec::<u8>(&[CTL_KERN, KERN_PROC, KERN_PROC_ARGS, pid])
+        .map(|buf| {
+            buf.split(|&b| b == 0)
+                .filter(|arg| !arg.is_empty())
+                .map(|arg| String::from_utf8_lossy(arg).into_owned())
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default()
+}
+
+fn state_char(stat: c_char) -> char {
+    match stat {
+        SIDL => 'I',
+        SRUN => 'R',
+        SSLEEP => 'S',
+        SSTOP => 'T',
+        SZOMB => 'Z',
+        SWAIT => 'W',
+        SLOCK => 'L',
+        _ => '?',
+    }
+}
+
+fn c_name(raw: &[c_char]) -> String {
+    let bytes: Vec<u8> = raw
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+pub struct ProcCollector {
+    last_times: HashMap<i32, u64>,
+    last_collect: Option<Instant>,
+    users: HashMap<u32, String>,
+    page_size: u64,
+    core_count: usize,
+}
+
+impl Default for ProcCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcCollector {
+    pub fn new() -> Self {
+        ProcCollector {
+            last_times: HashMap::new(),
+            last_collect: None,
+            users: HashMap::new(),
+            page_size: sysctl_by_name::<c_uint>("vm.stats.vm.v_page_size").unwrap_or(4096) as u64,
+            core_count: sysctl_by_name::<c_int>("hw.ncpu").unwrap_or(1).max(1) as usize,
+        }
+    }
+
+    fn convert(&mut self, kp: &kinfo_proc) -> ProcInfo {
+        let uid = kp.ki_uid;
+        let user = self
+            .users
+            .entry(uid)
+            .or_insert_with(|| user_name(uid).unwrap_or_else(|| uid.to_string()))
+            .clone();
+
+        ProcInfo {
+            pid: kp.ki_pid,
+            ppid: kp.ki_ppid,
+            name: c_name(&kp.ki_comm),
+            cmd: read_cmdline(kp.ki_pid),
+            user,
+            uid,
+            state: state_char(kp.ki_stat),
+            threads: kp.ki_numthreads.max(0) as u32,
+            nice: kp.ki_nice as i32,
+            mem: kp.ki_rssize.max(0) as u64 * self.page_size,
+            cpu_p: 0.0,
+            // ki_runtime 以微秒为单位
+            cpu_time: kp.ki_runtime / (1_000_000 / TICKS_PER_SEC),
+            // jail 中的进程视为容器进程
+            container: kp.ki_jid != 0,
+            io_bytes: None,
+            io_rate: None,
+        }
+    }
+
+    pub fn collect(&mut self, per_core: bool) -> io::Result<Vec<ProcInfo>> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_collect
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        // KERN_PROC_PROC 每个进程只返回一条记录（不按线程展开）
+        let kprocs = sysctl_vec::<kinfo_proc>(&[CTL_KERN, KERN_PROC, KERN_PROC_PROC, 0])
+            .ok_or_else(io::Error::last_os_error)?;
+
+        let mut procs = Vec::with_capacity(kprocs.len());
+        let mut times = HashMap::new();
+        for kp in kprocs.iter().filter(|kp| kp.ki_pid > 0) {
+            let mut proc = self.convert(kp);
+            if let Some(last) = self.last_times.get(&proc.pid) {
+                if elapsed > 0.0 {
+                    let ticks = proc.cpu_time.saturating_sub(*last) as f64;
+                    proc.cpu_p = ticks / TICKS_PER_SEC as f64 / elapsed * 100.0;
+                    if !per_core {
+                        proc.cpu_p /= self.core_count as f64;
+                    }
+                }
+            }
+            times.insert(proc.pid, proc.cpu_time);
+            procs.push(proc);
+        }
+
+        self.last_times = times;
+        self.last_collect = Some(now);
+        Ok(procs)
+    }
+}
+
+// I/O 调度优先级是 Linux 特有的接口
+pub fn get_ioprio(_pid: i32) -> io::Result<IoPriority> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn set_ioprio(_pid: i32, _prio: IoPriority) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+// 降低 nice 值（提高优先级）需要 root，修改其它用户的进程返回 EPERM
+pub fn set_nice(pid: i32, nice: i32) -> io::Result<()> {
+    let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid, nice) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// 向进程发送信号，其它用户的进程需要 root 权限
+pub fn send_signal(pid: i32, signal: i32) -> io::Result<()> {
+    if unsafe { libc::kill(pid, signal) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub fn get_pss(_pid: i32) -> io::Result<u64> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_limits(_pid: i32) -> io::Result<Vec<ProcLimit>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_stack(_pid: i32) -> io::Result<ProcStack> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_fds(_pid: i32) -> io::Result<Vec<ProcFd>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+// 其它用户的进程需要 root 权限
+pub fn get_environ(pid: i32) -> io::Result<Vec<String>> {
+    sysctl_vec::<u8>(&[CTL_KERN, KERN_PROC, KERN_PROC_ENV, pid])
+        .map(|buf| parse_environ(&buf))
+        .ok_or_else(io::Error::last_os_error)
+}
+
+pub fn get_threads(_pid: i32) -> io::Result<Vec<ProcThread>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_affinity(_pid: i32) -> io::Result<Vec<usize>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn set_affinity(_pid: i32, _cpus: &[usize]) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,59 @@
+use std::{fs, io, path::Path};
+
+use crate::shared::battery::{BatteryReading, BatteryStatus};
+
+fn read_value(dir: &Path, name: &str) -> Option<f64> {
+    fs::read_to_string(dir.join(name)).ok()?.trim().parse().ok()
+}
+
+// 鼠标、手柄等外设的电池 scope 为 Device，不是系统电池
+fn find_battery() -> Option<std::path::PathBuf> {
+    fs::read_dir("/sys/class/power_supply")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|dir| {
+            let read = |name| fs::read_to_string(dir.join(name)).unwrap_or_default();
+            read("type").trim() == "Battery" && read("scope").trim() != "Device"
+        })
+}
+
+// sysfs 中的数值单位为 µW、µWh、µA、µAh 和 µV
+pub fn read() -> io::Result<Option<BatteryReading>> {
+    let Some(dir) = find_battery() else {
+        return Ok(None);
+    };
+    let value = |name| read_value(&dir, name);
+    let status = fs::read_to_string(dir.join("status")).unwrap_or_default();
+
+    let voltage = value("voltage_now").map(|v| v / 1e6);
+    let energy = match (value("energy_now"), value("energy_full")) {
+        (Some(now), Some(full)) => Some((now / 1e6, full / 1e6)),
+        // 只提供电荷量时按当前电压换算
+        _ => match (value("charge_now"), value("charge_full"), voltage) {
+            (Some(now), Some(full), Some(volt)) => Some((now / 1e6 * volt, full / 1e6 * volt)),
+            _ => None,
+        },
+    };
+    let watts = match value("power_now") {
+        Some(power) => Some(power / 1e6),
+        None => value("current_now")
+            .zip(voltage)
+            .map(|(cur, volt)| cur / 1e6 * volt),
+    };
+    let percent = match value("capacity") {
+        Some(capacity) => capacity,
+        None => match energy {
+            Some((now, full)) if full > 0.0 => now / full * 100.0,
+            _ => return Ok(None),
+        },
+    };
+
+    Ok(Some(BatteryReading {
+        percent: percent.clamp(0.0, 100.0),
+        status: BatteryStatus::from_name(&status),
+        watts: watts.map(f64::abs).filter(|watts| *watts > 0.0),
+        energy,
+        seconds: None,
+    }))
+}
@@ -0,0 +1,534 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use super::procfs::{self, ProcFile};
+use crate::{
+    helper::Helper,
+    shared::cpu::{clean_model, state_stats, CpuEvents, CpuInfo, Throttled},
+};
+
+// /proc/stat 中每个 cpu 行的各列，旧内核没有后面几列
+const STATE_NAMES: [&str; 10] = [
+    "user",
+    "nice",
+    "system",
+    "idle",
+    "iowait",
+    "irq",
+    "softirq",
+    "steal",
+    "guest",
+    "guest_nice",
+];
+
+#[derive(Clone, Copy, Default)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+    // guest 时间已经计入 user 和 nice
+    pub guest: u64,
+    pub guest_nice: u64,
+    // 实际存在的列数
+    pub columns: usize,
+}
+
+impl CpuTimes {
+    fn parse<'a>(fields: impl Iterator<Item = &'a [u8]>) -> Self {
+        let mut val = [0u64; 10];
+        let mut columns = 0;
+        for (v, field) in val.iter_mut().zip(fields) {
+            *v = procfs::parse_u64(field);
+            columns += 1;
+        }
+        CpuTimes {
+            user: val[0],
+            nice: val[1],
+            system: val[2],
+            idle: val[3],
+            iowait: val[4],
+            irq: val[5],
+            softirq: val[6],
+            steal: val[7],
+            guest: val[8],
+            guest_nice: val[9],
+            columns,
+        }
+    }
+
+    fn values(&self) -> [u64; 10] {
+        [
+            self.user,
+            self.nice,
+            self.system,
+            self.idle,
+            self.iowait,
+            self.irq,
+            self.softirq,
+            self.steal,
+            self.guest,
+            self.guest_nice,
+        ]
+    }
+
+    pub fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+
+    pub fn idle_total(&self) -> u64 {
+        self.idle + self.iowait
+    }
+}
+
+// hwmon 中提供 cpu 温度的驱动，temp1 为整个封装或 SoC 的温度
+const CPU_SENSORS: &[&str] = &[
+    "coretemp",
+    "k10temp",
+    "zenpower",
+    "cpu_thermal",
+    "soc_thermal",
+];
+
+// 没有上面这些 hwmon 驱动时（多数 ARM 开发板）使用 thermal zone，
+// 优先选择类型中带有 cpu 或 soc 的，例如 "cpu-thermal"、"soc_thermal"、"x86_pkg_temp"
+const CPU_ZONES: &[&str] = &["cpu", "soc", "x86_pkg_temp"];
+
+fn find_temp_input() -> Option<String> {
+    let hwmon = fs::read_dir("/sys/class/hwmon")
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            fs::read_to_string(path.join("name"))
+                .is_ok_and(|name| CPU_SENSORS.contains(&name.trim()))
+        });
+    if let Some(path) = hwmon {
+        return Some(format!("{}/temp1_input", path.display()));
+    }
+    let zones = fs::read_dir("/sys/class/thermal")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.starts_with("thermal_zone") && path.join("temp").exists()
+        })
+        .map(|path| {
+            let kind = fs::read_to_string(path.join("type")).unwrap_or_default();
+            (path, kind)
+        })
+        .collect();
+    let zone = pick_zone(zones)?;
+    Some(format!("{}/temp", zone.display()))
+}
+
+// zones 为 thermal_zoneN 目录和其中 type 的内容，按编号选择第一个 cpu 的，没有时选择编号最小的
+fn pick_zone(mut zones: Vec<(PathBuf, String)>) -> Option<PathBuf> {
+    zones.sort_by_key(|(path, _)| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        name.trim_start_matches("thermal_zone")
+            .parse::<u32>()
+            .unwrap_or(u32::MAX)
+    });
+    let cpu = zones.iter().position(|(_, kind)| {
+        let kind = kind.trim().to_lowercase();
+        CPU_ZONES.iter().any(|zone| kind.contains(zone))
+    });
+    zones
+        .into_iter()
+        .nth(cpu.unwrap_or(0))
+        .map(|(path, _)| path)
+}
+
+// 树莓派固件的降频状态，与 vcgencmd get_throttled 相同，较新的内核只在 rpi_volt
+// 驱动中提供欠压告警
+const PI_THROTTLED: &str = "/sys/devices/platform/soc/soc:firmware/get_throttled";
+
+enum ThrottleSource {
+    Firmware,
+    UnderVoltAlarm(String),
+}
+
+fn find_throttle_source() -> Option<ThrottleSource> {
+    if Path::new(PI_THROTTLED).exists() {
+        return Some(ThrottleSource::Firmware);
+    }
+    fs::read_dir("/sys/class/hwmon")
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            fs::read_to_string(path.join("name")).is_ok_and(|name| name.trim() == "rpi_volt")
+        })
+        .map(|path| ThrottleSource::UnderVoltAlarm(format!("{}/in0_lcrit_alarm", path.display())))
+}
+
+// RAPL 中每个 cpu 封装的能量计数器，AMD Zen 在 5.8 以后的内核中同样注册为 intel-rapl。
+// energy_uj 为微焦耳，到达 max_energy_range_uj 后从 0 重新开始
+struct RaplDomain {
+    energy: String,
+    max_range: u64,
+}
+
+// 只取 intel-rapl:N 这一层的 package 域，intel-rapl:N:M 是其中的 core、uncore、dram
+fn find_rapl() -> Vec<RaplDomain> {
+    let Ok(entries) = fs::read_dir("/sys/class/powercap") else {
+        return Vec::new();
+    };
+    let mut domains: Vec<_> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| {
+            let name = path.file_name().unwrap_or_default().to_string_lossy();
+            name.strip_prefix("intel-rapl:")
+                .is_some_and(|id| id.parse::<u32>().is_ok())
+        })
+        .filter(|path| {
+            fs::read_to_string(path.join("name")).is_ok_and(|name| name.starts_with("package"))
+        })
+        .map(|path| RaplDomain {
+            energy: format!("{}/energy_uj", path.display()),
+            max_range: fs::read_to_string(path.join("max_energy_range_uj"))
+                .ok()
+                .and_then(|range| range.trim().parse().ok())
+                .unwrap_or(0),
+        })
+        .collect();
+    domains.sort_by(|a, b| a.energy.cmp(&b.energy));
+    domains
+}
+
+// 内核 5.10 以后 energy_uj 只有 root 可读（CVE-2020-8694），普通用户不显示功耗
+fn read_energy(domain: &RaplDomain) -> Option<u64> {
+    fs::read_to_string(&domain.energy).ok()?.trim().parse().ok()
+}
+
+fn usage(last: &CpuTimes, now: &CpuTimes) -> f64 {
+    let total = now.total().saturating_sub(last.total());
+    let idle = now.idle_total().saturating_sub(last.idle_total());
+    if total == 0 {
+        return 0.0;
+    }
+    (total.saturating_sub(idle) as f64 * 100.0 / total as f64).clamp(0.0, 100.0)
+}
+
+// x86 使用 "model name"，部分 ARM 和 MIPS 内核只提供 "Hardware" 或 "cpu model"
+fn model() -> String {
+    let Ok(cpuinfo) = fs::read_to_string("/proc/cpuinfo") else {
+        return String::new();
+    };
+    ["model name", "Hardware", "cpu model", "Processor"]
+        .iter()
+        .find_map(|key| {
+            cpuinfo.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                (name.trim() == *key && !value.trim().is_empty()).then(|| value.trim().to_owned())
+            })
+        })
+        .map(|model| clean_model(&model))
+        .unwrap_or_default()
+}
+
+// cpu 温度、降频和功耗的来源
+struct CpuSensors {
+    temp_input: Option<String>,
+    throttle: Option<ThrottleSource>,
+    rapl: Vec<RaplDomain>,
+}
+
+impl CpuSensors {
+    fn find() -> Self {
+        CpuSensors {
+            temp_input: find_temp_input(),
+            throttle: find_throttle_source(),
+            rapl: find_rapl(),
+        }
+    }
+}
+
+// /proc/stat 中 intr、ctxt、softirq 行的累计次数，
+// intr 和 softirq 行的第一列为总数，后面是各个中断号的计数
+fn parse_events(stat: &[u8]) -> CpuEvents {
+    let mut events = CpuEvents::default();
+    for line in procfs::lines(stat) {
+        let mut fields = procfs::fields(line);
+        let label = fields.next().unwrap_or_default();
+        let count = procfs::parse_u64(fields.next().unwrap_or_default());
+        match label {
+            b"intr" => events.intr = count,
+            b"ctxt" => events.ctxt = count,
+            b"softirq" => events.softirq = count,
+            _ => {}
+        }
+    }
+    events
+}
+
+// get_throttled 的内容为十六进制数，例如 "50005"，老版本的固件带有 0x 前缀
+fn parse_throttled(data: &[u8]) -> Option<Throttled> {
+    let data = String::from_utf8_lossy(data.trim_ascii());
+    u32::from_str_radix(data.trim_start_matches("0x"), 16)
+        .ok()
+        .map(Throttled::from_bits)
+}
+
+pub struct CpuCollector {
+    model: String,
+    last_total: CpuTimes,
+    // /proc/stat 中 intr、ctxt、softirq 行的累计次数
+    last_events: Option<(CpuEvents, Instant)>,
+    // 按核心编号索引，离线的核心为 None
+    last_cores: Vec<Option<CpuTimes>>,
+    cores: Vec<Option<CpuTimes>>,
+    // 第一次采集时才扫描 hwmon、thermal 和 powercap，不拖慢启动
+    sensors: Option<CpuSensors>,
+    last_energy: Option<(Vec<u64>, Instant)>,
+    file: ProcFile,
+}
+
+impl Default for CpuCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuCollector {
+    pub fn new() -> Self {
+        CpuCollector {
+            model: model(),
+            last_total: CpuTimes::default(),
+            last_events: None,
+            last_cores: Vec::new(),
+            cores: Vec::new(),
+            sensors: None,
+            last_energy: None,
+            file: ProcFile::new(),
+        }
+    }
+
+    pub fn collect(&mut self) -> io::Result<CpuInfo> {
+        let mut info = CpuInfo {
+            model: self.model.clone(),
+            ..Default::default()
+        };
+        let stat = self.file.read(format_args!("/proc/stat"))?;
+
+        self.cores.clear();
+        let events = parse_events(stat);
+        for line in procfs::lines(stat) {
+            let mut fields = procfs::fields(line);
+            let label = fields.next().unwrap_or_default();
+            if !label.starts_with(b"cpu") {
+                continue;
+            }
+            let times = CpuTimes::parse(fields);
+            if label == b"cpu" {
+                info.cpu_percent = usage(&self.last_total, &times);
+                let columns = times.columns.min(STATE_NAMES.len());
+                info.stats = state_stats(
+                    info.cpu_percent,
+                    &STATE_NAMES[..columns],
+                    &self.last_total.values(),
+                    &times.values(),
+                    times.total().saturating_sub(self.last_total.total()),
+                );
+                self.last_total = times;
+            } else {
+                // 离线的核心不出现在 /proc/stat 中，按编号放置以免后面的核心错位
+                let id = procfs::parse_u64(&label[3..]) as usize;
+                if self.cores.len() <= id {
+                    self.cores.resize(id + 1, None);
+                }
+                self.cores[id] = Some(times);
+            }
+        }
+        // 编号最大的几个核心离线时也要显示出来
+        if let Ok(present) = self
+            .file
+            .read(format_args!("/sys/devices/system/cpu/present"))
+        {
+            let last = present.rsplit(|b| matches!(b, b'-' | b',')).next();
+            let count = last.map_or(0, |last| procfs::parse_u64(last.trim_ascii()) as usize + 1);
+            if self.cores.len() < count {
+                self.cores.resize(count, None);
+            }
+        }
+
+        // 第一次采样时计算开机以来的平均值，重新上线的核心在下一次采样前显示为 0
+        let first = self.last_cores.is_empty();
+        info.core_percent = self
+            .cores
+            .iter()
+            .enumerate()
+            .map(|(i, now)| match (self.last_cores.get(i), now) {
+                (Some(Some(last)), Some(now)) => usage(last, now),
+                (_, Some(now)) if first => usage(&CpuTimes::default(), now),
+                _ => 0.0,
+            })
+            .collect();
+        info.offline = (0..self.cores.len())
+            .filter(|&i| self.cores[i].is_none())
+            .collect();
+        std::mem::swap(&mut self.last_cores, &mut self.cores);
+
+        let now = Instant::now();
+        if let Some((last, time)) = self.last_events {
+            info.events = events.rate(&last, now.duration_since(time).as_secs_f64());
+        }
+        self.last_events = Some((events, now));
+
+        let sensors = self.sensors.get_or_insert_with(CpuSensors::find);
+        // 单位为千分之一摄氏度
+        if let Some(path) = &sensors.temp_input {
+            let parse = |data: &[u8]| {
+                procfs::fields(data)
+                    .next()
+                    .map(|field| procfs::parse_i64(field) as f64 / 1000.0)
+            };
+            // 部分传感器只有 root 可读，交给辅助程序读取
+            info.temp = match self.file.read(format_args!("{}", path)) {
+                Ok(data) => parse(data),
+                Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                    let data = Helper::get_instance().lock().unwrap().read(path);
+                    data.ok().and_then(|data| parse(&data))
+                }
+                Err(_) => None,
+            };
+        }
+
+        info.throttled = match &sensors.throttle {
+            Some(ThrottleSource::Firmware) => self
+                .file
+                .read(format_args!("{}", PI_THROTTLED))
+                .ok()
+                .and_then(parse_throttled),
+            Some(ThrottleSource::UnderVoltAlarm(path)) => self
+                .file
+                .read(format_args!("{}", path))
+                .ok()
+                .map(|data| Throttled {
+                    under_voltage: procfs::parse_u64(data.trim_ascii()) != 0,
+                    ..Default::default()
+                }),
+            None => None,
+        };
+
+        // 任何一个封装读取失败时不显示功耗
+        let energy: Option<Vec<u64>> = match sensors.rapl.is_empty() {
+            true => None,
+            false => sensors.rapl.iter().map(read_energy).collect(),
+        };
+        if let (Some((last, time)), Some(energy)) = (&self.last_energy, &energy) {
+            let elapsed = now.duration_since(*time).as_secs_f64();
+            if elapsed > 0.0 {
+                let joules: u64 = sensors
+                    .rapl
+                    .iter()
+                    .zip(last.iter().zip(energy))
+                    .map(|(domain, (&last, &now))| match now >= last {
+                        true => now - last,
+                        false => (now + domain.max_range).saturating_sub(last),
+                    })
+                    .sum();
+                info.power = Some(joules as f64 / 1e6 / elapsed);
+            }
+        }
+        self.last_energy = energy.map(|energy| (energy, now));
+
+        let loadavg = self.file.read(format_args!("/proc/loadavg"))?;
+        for (i, val) in procfs::fields(loadavg).take(3).enumerate() {
+            info.load_avg[i] = procfs::parse_f64(val);
+        }
+
+        Ok(info)
+    }
+}
+
+// 系统启动以来的秒数
+pub fn uptime() -> u64 {
+    let mut file = ProcFile::new();
+    match file.read(format_args!("/proc/uptime")) {
+        Ok(data) => procfs::fields(data).next().map_or(0, procfs::parse_u64),
+        Err(_) => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{parse_events, parse_throttled, pick_zone};
+
+    #[test]
+    fn events_from_proc_stat() {
+        let stat = b"\
+cpu  10132153 290696 3084719 46828483 16683 0 25195 0 0 0
+cpu0 1393280 32966 572056 13343292 6130 0 17875 0 0 0
+intr 1462898 39 9 0 0 0 0 3 0 1 0 0 36 0 0 0 0
+ctxt 2918521
+btime 1062191376
+processes 22742
+procs_running 2
+softirq 620112 4 239478 12 4519 20341 0 5 190283 0 165470
+";
+        let events = parse_events(stat);
+        assert_eq!(events.intr, 1462898);
+        assert_eq!(events.ctxt, 2918521);
+        assert_eq!(events.softirq, 620112);
+        // 缺少的行为 0
+        let events = parse_events(b"cpu  1 2 3 4\nctxt 7\n");
+        assert_eq!((events.intr, events.ctxt, events.softirq), (0, 7, 0));
+    }
+
+    #[test]
+    fn throttled_from_firmware() {
+        let throttled = parse_throttled(b"50005\n").unwrap();
+        assert!(throttled.under_voltage && throttled.throttled);
+        assert!(!throttled.freq_capped && !throttled.soft_temp_limit);
+        assert!(parse_throttled(b"0x8").unwrap().soft_temp_limit);
+        assert!(parse_throttled(b"").is_none());
+        assert!(parse_throttled(b"error").is_none());
+    }
+
+    #[test]
+    fn thermal_zone_selection() {
+        let zone = |id: u32, kind: &str| {
+            (
+                PathBuf::from(format!("/sys/class/thermal/thermal_zone{}", id)),
+                format!("{}\n", kind),
+            )
+        };
+        // 按编号排序而不是按名称，优先选择 cpu 或 soc 类型的
+        let zones = vec![
+            zone(10, "cpu-thermal"),
+            zone(2, "acpitz"),
+            zone(3, "x86_pkg_temp"),
+        ];
+        assert_eq!(
+            pick_zone(zones),
+            Some(PathBuf::from("/sys/class/thermal/thermal_zone3"))
+        );
+        let zones = vec![zone(1, "battery"), zone(0, "acpitz")];
+        assert_eq!(
+            pick_zone(zones),
+            Some(PathBuf::from("/sys/class/thermal/thermal_zone0"))
+        );
+        assert_eq!(pick_zone(Vec::new()), None);
+    }
+}
@@ -0,0 +1,352 @@
+use std::{
+    collections::HashMap,
+    ffi::CString,
+    fs, io,
+    path::{Path, PathBuf},
+    time::Instant,
+};
+
+use super::procfs::{self, ProcFile};
+use crate::shared::disk::{self, DiskInfo, DiskStat, RaidStatus};
+
+// /proc/diskstats 中的扇区固定为 512 字节，与设备实际的扇区大小无关
+const SECTOR_SIZE: u64 = 512;
+
+// 块设备的累计读写扇区数和忙于读写的毫秒数
+#[derive(Clone, Copy)]
+struct IoCounters {
+    read: u64,
+    write: u64,
+    ticks: u64,
+}
+
+pub struct DiskCollector {
+    last: HashMap<String, IoCounters>,
+    last_time: Option<Instant>,
+    // 每个整块设备的温度传感器路径，没有传感器的为 None，避免每次都遍历 sysfs
+    temp_inputs: HashMap<String, Option<PathBuf>>,
+    file: ProcFile,
+}
+
+impl Default for DiskCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiskCollector {
+    pub fn new() -> Self {
+        DiskCollector {
+            last: HashMap::new(),
+            last_time: None,
+            temp_inputs: HashMap::new(),
+            file: ProcFile::new(),
+        }
+    }
+
+    // temps 为 show_disk_temps，读取温度可能唤醒休眠的硬盘
+    pub fn collect(&mut self, only_physical: bool, temps: bool) -> io::Result<DiskInfo> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_time
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut counters = HashMap::new();
+        for line in procfs::lines(self.file.read(format_args!("/proc/diskstats"))?) {
+            let fields: Vec<&[u8]> = procfs::fields(line).collect();
+            if fields.len() < 13 {
+                continue;
+            }
+            counters.insert(
+                String::from_utf8_lossy(fields[2]).into_owned(),
+                IoCounters {
+                    read: procfs::parse_u64(fields[5]),
+                    write: procfs::parse_u64(fields[9]),
+                    ticks: procfs::parse_u64(fields[12]),
+                },
+            );
+        }
+
+        // 没有 md 驱动时文件不存在
+        let arrays = match self.file.read(format_args!("/proc/mdstat")) {
+            Ok(data) => mdstat(&String::from_utf8_lossy(data)),
+            Err(_) => HashMap::new(),
+        };
+
+        let mut info = DiskInfo::default();
+        for line in procfs::lines(self.file.read(format_args!("/proc/self/mounts"))?) {
+            let mut fields = procfs::fields(line);
+            let (Some(device), Some(mount), Some(fstype)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                continue;
+            };
+            let (device, mount) = (procfs::unescape(device), procfs::unescape(mount));
+            if only_physical && !disk::is_physical(&device, &String::from_utf8_lossy(fstype)) {
+                continue;
+            }
+            let Some((total, used, free)) = space(&mount) else {
+                continue;
+            };
+            let mut stat = DiskStat::new(device, mount, total, used, free);
+            // /dev/mapper/* 和 /dev/disk/by-* 是指向 dm-0、sda1 这类名称的符号链接
+            let block = fs::canonicalize(&stat.device)
+                .ok()
+                .and_then(|path| Some(path.file_name()?.to_string_lossy().into_owned()));
+            stat.raid = block.as_deref().and_then(|name| raid_status(name, &arrays));
+            if let Some(disk) = block.as_deref().filter(|_| temps).map(whole_disk) {
+                let input = self
+                    .temp_inputs
+                    .entry(disk)
+                    .or_insert_with_key(|disk| drive_temp_input(disk));
+                // 单位为千分之一摄氏度
+                stat.temp = input
+                    .as_ref()
+                    .and_then(|path| fs::read_to_string(path).ok())
+                    .and_then(|temp| temp.trim().parse::<f64>().ok())
+                    .map(|temp| temp / 1000.0);
+            }
+            let io = block.and_then(|name| Some((counters.get(&name)?, self.last.get(&name)?)));
+            if let Some((now, last)) = io.filter(|_| elapsed > 0.0) {
+                let rate = |now: u64, last: u64| {
+                    (now.saturating_sub(last) as f64 * SECTOR_SIZE as f64 / elapsed) as u64
+                };
+                stat.read_rate = rate(now.read, last.read);
+                stat.write_rate = rate(now.write, last.write);
+                let busy = now.ticks.saturating_sub(last.ticks) as f64 / 1000.0;
+                stat.io_percent = Some((busy * 100.0 / elapsed).min(100.0));
+            }
+            info.push(stat);
+        }
+        info.sort();
+
+        self.last = counters;
+        self.last_time = Some(now);
+        Ok(info)
+    }
+}
+
+// 返回 (总大小, 已用, 普通用户可用)，挂载点无法访问时为 None
+fn space(mount: &str) -> Option<(u64, u64, u64)> {
+    let path = CString::new(mount).ok()?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut st) } != 0 {
+        return None;
+    }
+    let frsize = st.f_frsize;
+    Some((
+        st.f_blocks * frsize,
+        st.f_blocks.saturating_sub(st.f_bfree) * frsize,
+        st.f_bavail * frsize,
+    ))
+}
+
+// /proc/mdstat 中各阵列的状态，按名称（md0）索引。格式如下：
+//   md0 : active raid1 sdb1[1] sda1[0](F)
+//         1046528 blocks super 1.2 [2/1] [U_]
+//         [=>...................]  recovery =  8.5% (89344/1046528) finish=0.5min speed=29781K/sec
+fn mdstat(text: &str) -> HashMap<String, RaidStatus> {
+    let mut arrays = HashMap::new();
+    let mut current: Option<&mut RaidStatus> = None;
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            current = None;
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            current = None;
+            let Some((name, members)) = line.split_once(" : ") else {
+                continue;
+            };
+            if !name.starts_with("md") {
+                continue;
+            }
+            let status = arrays.entry(name.trim().to_owned()).or_default();
+            *status = RaidStatus {
+                degraded: members.contains("(F)") || members.starts_with("inactive"),
+                ..Default::default()
+            };
+            current = Some(status);
+            continue;
+        }
+        let Some(status) = current.as_deref_mut() else {
+            continue;
+        };
+        for field in line.split_whitespace() {
+            // [2/1] 为应有和正常的成员数，[U_] 中的 _ 为缺少的成员
+            if let Some(inner) = field.strip_prefix('[').and_then(|f| f.strip_suffix(']')) {
+                if let Some((want, have)) = inner.split_once('/') {
+                    if let (Ok(want), Ok(have)) = (want.parse::<u32>(), have.parse::<u32>()) {
+                        status.degraded |= have < want;
+                    }
+                } else if inner.chars().all(|c| c == 'U' || c == '_') {
+                    status.degraded |= inner.contains('_');
+                }
+            }
+        }
+        // 只有 recovery、resync 和 reshape 是在重建数据，check 只是校验
+        for action in ["recovery", "resync", "reshape"] {
+            let percent = line
+                .split_once(action)
+                .and_then(|(_, rest)| rest.trim_start().strip_prefix('='))
+                .and_then(|rest| rest.trim_start().split_once('%'))
+                .and_then(|(percent, _)| percent.parse::<f64>().ok());
+            if percent.is_some() {
+                status.sync_percent = percent;
+            }
+        }
+    }
+    arrays
+}
+
+// 分区所在的整个设备，例如 sda1 -> sda、nvme0n1p2 -> nvme0n1
+fn whole_disk(block: &str) -> String {
+    let sys = Path::new("/sys/class/block").join(block);
+    if !sys.join("partition").exists() {
+        return block.to_owned();
+    }
+    fs::canonicalize(&sys)
+        .ok()
+        .and_then(|path| Some(path.parent()?.file_name()?.to_string_lossy().into_owned()))
+        .unwrap_or_else(|| block.to_owned())
+}
+
+// SATA 硬盘需要 drivetemp 驱动，传感器位于 SCSI 设备下；NVMe 的传感器位于控制器下，
+// 不同内核版本中可能在控制器目录或其 PCI 设备目录中
+fn drive_temp_input(disk: &str) -> Option<PathBuf> {
+    let device = Path::new("/sys/class/block").join(disk).join("device");
+    [
+        device.join("hwmon"),
+        device.clone(),
+        device.join("device").join("hwmon"),
+    ]
+    .iter()
+    .flat_map(|dir| fs::read_dir(dir).into_iter().flatten().flatten())
+    .filter(|entry| entry.file_name().to_string_lossy().starts_with("hwmon"))
+    .map(|entry| entry.path().join("temp1_input"))
+    .find(|path| path.exists())
+}
+
+// 块设备所在的 md 阵列的状态，分区按整个设备查找，device-mapper 设备（LVM、LUKS）
+// 取其下各设备状态的合并，并检查自身是否被挂起
+fn raid_status(block: &str, arrays: &HashMap<String, RaidStatus>) -> Option<RaidStatus> {
+    let sys = Path::new("/sys/class/block").join(block);
+    let disk = whole_disk(block);
+    if let Some(status) = arrays.get(&disk) {
+        return Some(status.clone());
+    }
+    let suspended = fs::read_to_string(sys.join("dm/suspended")).ok()?;
+    let status = RaidStatus {
+        suspended: suspended.trim() == "1",
+        ..Default::default()
+    };
+    let status = fs::read_dir(sys.join("slaves"))
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|slave| raid_status(&slave.file_name().to_string_lossy(), arrays))
+        .fold(status, RaidStatus::merge);
+    Some(status)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mdstat;
+
+    // (降级, 进度)，阵列不存在时为 None
+    fn status(text: &str, name: &str) -> Option<(bool, Option<f64>)> {
+        mdstat(text)
+            .get(name)
+            .map(|status| (status.degraded, status.sync_percent))
+    }
+
+    #[test]
+    fn healthy_and_degraded_arrays() {
+        let text = "\
+Personalities : [raid1] [raid6] [raid5] [raid4]
+md0 : active raid1 sdb1[1] sda1[0]
+      1046528 blocks super 1.2 [2/2] [UU]
+
+md1 : active raid5 sdd1[3] sdc1[1] sde1[0](F)
+      2093056 blocks super 1.2 level 5, 512k chunk, algorithm 2 [3/2] [UU_]
+      bitmap: 0/1 pages [0KB], 65536KB chunk
+
+md2 : active raid0 sdf[1] sdg[0]
+      2093056 blocks super 1.2 512k chunks
+
+unused devices: <none>
+";
+        assert_eq!(mdstat(text).len(), 3);
+        assert_eq!(status(text, "md0"), Some((false, None)));
+        assert_eq!(status(text, "md1"), Some((true, None)));
+        assert_eq!(status(text, "md2"), Some((false, None)));
+        assert_eq!(status(text, "Personalities"), None);
+    }
+
+    #[test]
+    fn rebuild_progress() {
+        let text = "\
+md0 : active raid1 sdb1[2] sda1[0]
+      1046528 blocks super 1.2 [2/1] [U_]
+      [=>...................]  recovery =  8.5% (89344/1046528) finish=0.5min speed=29781K/sec
+
+md1 : active raid1 sdd1[1] sdc1[0]
+      1046528 blocks super 1.2 [2/2] [UU]
+      [====>................]  resync = 22.1% (231424/1046528) finish=0.4min speed=33060K/sec
+
+md2 : active raid5 sdg1[3] sdf1[1] sde1[0]
+      2093056 blocks super 1.2 level 5, 512k chunk, algorithm 2 [4/4] [UUUU]
+      [======>..............]  reshape = 33.0% (345600/1046528) finish=1.1min speed=10472K/sec
+
+md3 : active raid1 sdi1[1] sdh1[0]
+      1046528 blocks super 1.2 [2/2] [UU]
+      [==========>..........]  check = 50.0% (523264/1046528) finish=0.2min speed=41861K/sec
+";
+        assert_eq!(status(text, "md0"), Some((true, Some(8.5))));
+        assert_eq!(status(text, "md1"), Some((false, Some(22.1))));
+        assert_eq!(status(text, "md2"), Some((false, Some(33.0))));
+        // check 只是校验数据，不算重建
+        assert_eq!(status(text, "md3"), Some((false, None)));
+    }
+
+    #[test]
+    fn inactive_and_pending_arrays() {
+        let text = "\
+md127 : inactive sdb[0](S)
+      1046528 blocks super 1.2
+
+md0 : active (auto-read-only) raid1 sdd1[1] sdc1[0]
+      1046528 blocks super 1.2 [2/2] [UU]
+        resync=PENDING
+
+md1 : active raid1 sdf1[1] sde1[0]
+      1046528 blocks super 1.2 [2/2] [UU]
+        resync=DELAYED
+";
+        assert_eq!(status(text, "md127"), Some((true, None)));
+        assert_eq!(status(text, "md0"), Some((false, None)));
+        assert_eq!(status(text, "md1"), Some((false, None)));
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert!(mdstat("").is_empty());
+        assert!(mdstat("\n\n   \n").is_empty());
+        // 缺少 " : " 的行和不属于任何阵列的缩进行被忽略
+        assert!(mdstat("md0 active raid1 sda1[0]\n      [2/1] [U_]\n").is_empty());
+        assert!(mdstat("      [=>...]  recovery = 8.5%\n").is_empty());
+        let text = "\
+md0 : active raid1 sdb1[1] sda1[0]
+      1046528 blocks super 1.2 [x/y] [] [UX]
+      [=>...]  recovery = abc% recovery=
+";
+        assert_eq!(status(text, "md0"), Some((false, None)));
+        // 成员数无法解析时仍按 [U_] 判断
+        let text = "md0 : active raid1 sda1[0]\n      [2/?] [U_]\n";
+        assert_eq!(status(text, "md0"), Some((true, None)));
+        // 空行结束当前阵列，之后的缩进行不属于它
+        let text = "md0 : active raid1 sda1[0]\n\n      [2/1] [U_]\n";
+        assert_eq!(status(text, "md0"), Some((false, None)));
+    }
+}
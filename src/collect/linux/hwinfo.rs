@@ -0,0 +1,208 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use crate::shared::hwinfo::{CacheInfo, HwInfo};
+
+const PCI_IDS: &[&str] = &[
+    "/usr/share/hwdata/pci.ids",
+    "/usr/share/misc/pci.ids",
+    "/usr/share/pci.ids",
+];
+
+fn read_trim(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_owned())
+}
+
+// 解析 "32K" / "8M" 这类缓存大小
+fn parse_size(size: &str) -> u64 {
+    let (num, unit) = size.split_at(size.trim_end_matches(char::is_alphabetic).len());
+    let num: u64 = num.parse().unwrap_or(0);
+    match unit {
+        "K" => num << 10,
+        "M" => num << 20,
+        "G" => num << 30,
+        _ => num,
+    }
+}
+
+fn cpu_dirs() -> Vec<std::path::PathBuf> {
+    let Ok(entries) = fs::read_dir("/sys/devices/system/cpu") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.starts_with("cpu") && name[3..].parse::<u32>().is_ok()
+        })
+        .map(|e| e.path())
+        .filter(|p| read_trim(&p.join("online")).is_none_or(|v| v == "1"))
+        .collect()
+}
+
+fn collect_topology(info: &mut HwInfo) {
+    let mut packages = HashSet::new();
+    let mut cores = HashSet::new();
+    // (level, type) -> (size, 不同 shared_cpu_list 的集合)
+    let mut caches: BTreeMap<(u32, String), (u64, HashSet<String>)> = BTreeMap::new();
+
+    let cpus = cpu_dirs();
+    info.threads = cpus.len();
+    for cpu in cpus {
+        let topology = cpu.join("topology");
+        let package = read_trim(&topology.join("physical_package_id")).unwrap_or_default();
+        let core = read_trim(&topology.join("core_id")).unwrap_or_default();
+        packages.insert(package.clone());
+        cores.insert((package, core));
+
+        let Ok(indexes) = fs::read_dir(cpu.join("cache")) else {
+            continue;
+        };
+        for index in indexes.flatten().map(|e| e.path()) {
+            let level = read_trim(&index.join("level")).and_then(|l| l.parse().ok());
+            let cache_type = read_trim(&index.join("type"));
+            if let (Some(level), Some(cache_type)) = (level, cache_type) {
+                let size = read_trim(&index.join("size")).map_or(0, |s| parse_size(&s));
+                let shared = read_trim(&index.join("shared_cpu_list")).unwrap_or_default();
+                let entry = caches
+                    .entry((level, cache_type))
+                    .or_insert_with(|| (size, HashSet::new()));
+                entry.1.insert(shared);
+            }
+        }
+    }
+
+    info.sockets = packages.len();
+    info.cores = cores.len();
+    info.caches = caches
+        .into_iter()
+        .map(|((level, cache_type), (size, shared))| CacheInfo {
+            level,
+            cache_type,
+            size,
+            instances: shared.len(),
+        })
+        .collect();
+}
+
+// SMBIOS type 17 (Memory Device) 需要 root 权限读取
+fn collect_dimms(info: &mut HwInfo) {
+    let Ok(entries) = fs::read_dir("/sys/firmware/dmi/entries") else {
+        return;
+    };
+    for entry in entries.flatten() {
+        if !entry.file_name().to_string_lossy().starts_with("17-") {
+            continue;
+        }
+        let Ok(raw) = fs::read(entry.path().join("raw")) else {
+            continue;
+        };
+        if raw.len() < 0x0e {
+            continue;
+        }
+        let size = u16::from_le_bytes([raw[0x0c], raw[0x0d]]);
+        let bytes = match size {
+            0 | 0xffff => continue,
+            0x7fff if raw.len() >= 0x20 => {
+                (u32::from_le_bytes([raw[0x1c], raw[0x1d], raw[0x1e], raw[0x1f]]) as u64) << 20
+            }
+            0x7fff => continue,
+            s if s & 0x8000 != 0 => ((s & 0x7fff) as u64) << 10,
+            s => (s as u64) << 20,
+        };
+        info.dimm_count += 1;
+        info.mem_total += bytes;
+    }
+}
+
+fn collect_mem_blocks(info: &mut HwInfo) {
+    let block_size = read_trim(Path::new("/sys/devices/system/memory/block_size_bytes"))
+        .and_then(|s| u64::from_str_radix(&s, 16).ok());
+    if let (Some(block_size), Ok(entries)) =
+        (block_size, fs::read_dir("/sys/devices/system/memory"))
+    {
+        let online = entries
+            .flatten()
+            .filter(|e| e.file_name().to_string_lossy().starts_with("memory"))
+            .filter(|e| read_trim(&e.path().join("online")).is_some_and(|v| v == "1"))
+            .count() as u64;
+        info.mem_total = online * block_size;
+    }
+
+    if info.mem_total == 0 {
+        if let Ok(mem) = super::mem::collect() {
+            info.mem_total = mem.total;
+        }
+    }
+}
+
+fn pci_name(vendor: &str, device: &str) -> Option<String> {
+    let db = PCI_IDS.iter().find_map(|p| fs::read_to_string(p).ok())?;
+    let mut vendor_name = None;
+    for line in db.lines() {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+        if !line.starts_with('\t') {
+            if vendor_name.is_some() {
+                break;
+            }
+            if let Some(name) = line.strip_prefix(vendor) {
+                vendor_name = Some(name.trim().to_owned());
+            }
+        } else if let Some(vendor_name) = &vendor_name {
+            if let Some(name) = line.strip_prefix('\t').and_then(|l| l.strip_prefix(device)) {
+                return Some(format!("{} {}", vendor_name, name.trim()));
+            }
+        }
+    }
+    vendor_name
+}
+
+fn collect_gpus(info: &mut HwInfo) {
+    let Ok(entries) = fs::read_dir("/sys/class/drm") else {
+        return;
+    };
+    let mut cards: Vec<_> = entries
+        .flatten()
+        .filter(|e| {
+            let name = e.file_name().to_string_lossy().into_owned();
+            name.starts_with("card") && name[4..].parse::<u32>().is_ok()
+        })
+        .map(|e| e.path().join("device"))
+        .collect();
+    cards.sort();
+
+    for device in cards {
+        let vendor = read_trim(&device.join("vendor")).unwrap_or_default();
+        let device_id = read_trim(&device.join("device")).unwrap_or_default();
+        let vendor = vendor.trim_start_matches("0x");
+        let device_id = device_id.trim_start_matches("0x");
+        let driver = fs::read_link(device.join("driver"))
+            .ok()
+            .and_then(|p| p.file_name().map(|n| n.to_string_lossy().into_owned()))
+            .unwrap_or_default();
+
+        let name =
+            pci_name(vendor, device_id).unwrap_or_else(|| format!("[{}:{}]", vendor, device_id));
+        info.gpus.push(if driver.is_empty() {
+            name
+        } else {
+            format!("{} ({})", name, driver)
+        });
+    }
+}
+
+pub fn collect() -> HwInfo {
+    let mut info = HwInfo::default();
+    collect_topology(&mut info);
+    collect_dimms(&mut info);
+    if info.dimm_count == 0 {
+        collect_mem_blocks(&mut info);
+    }
+    collect_gpus(&mut info);
+    info
+}
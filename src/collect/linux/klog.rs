@@ -0,0 +1,79 @@
+use std::{
+    collections::VecDeque,
+    fs::{File, OpenOptions},
+    io::{self, Read},
+    os::unix::fs::OpenOptionsExt,
+};
+
+use crate::shared::klog::{parse_kmsg, KlogEntry, KlogInfo, KLOG_LINES};
+
+// 每次 read 返回 /dev/kmsg 中的一条记录，单条记录不超过 8 KiB
+const RECORD_SIZE: usize = 8192;
+
+// 以非阻塞方式读取 /dev/kmsg，第一次打开时从缓冲区中最早的记录开始。
+// 读取到的记录保存在这里，暂停后丢弃的那次采集不会丢失日志
+pub struct KlogReader {
+    file: Option<File>,
+    entries: VecDeque<KlogEntry>,
+    error: Option<String>,
+    buf: Vec<u8>,
+}
+
+impl Default for KlogReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl KlogReader {
+    pub fn new() -> Self {
+        KlogReader {
+            file: None,
+            entries: VecDeque::new(),
+            error: None,
+            buf: vec![0; RECORD_SIZE],
+        }
+    }
+
+    // 没有权限时不再重试，kernel.dmesg_restrict 为 1 时需要 CAP_SYSLOG
+    pub fn collect(&mut self) -> KlogInfo {
+        if self.file.is_none() && self.error.is_none() {
+            match OpenOptions::new()
+                .read(true)
+                .custom_flags(libc::O_NONBLOCK)
+                .open("/dev/kmsg")
+            {
+                Ok(file) => self.file = Some(file),
+                Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                    self.error = Some("permission denied, see kernel.dmesg_restrict".to_owned())
+                }
+                Err(err) => self.error = Some(err.to_string()),
+            }
+        }
+        while let Some(file) = self.file.as_mut() {
+            match file.read(&mut self.buf) {
+                Ok(0) => break,
+                Ok(len) => {
+                    if let Some(entry) = parse_kmsg(&String::from_utf8_lossy(&self.buf[..len])) {
+                        if self.entries.len() >= KLOG_LINES {
+                            self.entries.pop_front();
+                        }
+                        self.entries.push_back(entry);
+                    }
+                }
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => break,
+                // 还没读到的记录已经被覆盖，从下一条仍然存在的记录继续
+                Err(err) if err.raw_os_error() == Some(libc::EPIPE) => continue,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => continue,
+                Err(err) => {
+                    self.error = Some(err.to_string());
+                    self.file = None;
+                }
+            }
+        }
+        KlogInfo {
+            entries: self.entries.iter().cloned().collect(),
+            error: self.error.clone(),
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use std::fs;
+
+use super::procfs::{self, ProcFile};
+use crate::shared::limits::{Limit, SysLimits};
+
+fn read_u64(file: &mut ProcFile, path: &str) -> Option<u64> {
+    let data = file.read(format_args!("{}", path)).ok()?;
+    procfs::fields(data).next().map(procfs::parse_u64)
+}
+
+// 当前进程所在的 cgroup v2 目录，pids 控制器的计数和上限在其中
+fn cgroup_dir() -> Option<String> {
+    let cgroup = fs::read_to_string("/proc/self/cgroup").ok()?;
+    let path = cgroup.lines().find_map(|line| line.strip_prefix("0::"))?;
+    Some(format!("/sys/fs/cgroup{}", path.trim_end_matches('/')))
+}
+
+pub fn collect() -> SysLimits {
+    let mut file = ProcFile::new();
+    let mut limits = Vec::new();
+
+    // file-nr: 已分配的句柄数、其中未使用的数量、上限
+    if let Ok(data) = file.read(format_args!("/proc/sys/fs/file-nr")) {
+        let mut fields = procfs::fields(data).map(procfs::parse_u64);
+        if let (Some(allocated), Some(unused), Some(max)) =
+            (fields.next(), fields.next(), fields.next())
+        {
+            limits.push(Limit::new(
+                "Open files",
+                allocated.saturating_sub(unused),
+                max,
+            ));
+        }
+    }
+
+    // loadavg 第四列为 "运行中/总数"，总数包含所有线程
+    if let Ok(data) = file.read(format_args!("/proc/loadavg")) {
+        let tasks = procfs::fields(data)
+            .nth(3)
+            .and_then(|field| field.split(|&b| b == b'/').nth(1))
+            .map(procfs::parse_u64);
+        let pid_max = read_u64(&mut file, "/proc/sys/kernel/pid_max").unwrap_or(0);
+        let threads_max = read_u64(&mut file, "/proc/sys/kernel/threads-max").unwrap_or(0);
+        let max = match (pid_max, threads_max) {
+            (0, max) | (max, 0) => max,
+            (a, b) => a.min(b),
+        };
+        if let Some(tasks) = tasks {
+            limits.push(Limit::new("Tasks", tasks, max));
+        }
+    }
+
+    if let Some(dir) = cgroup_dir() {
+        if let Some(current) = read_u64(&mut file, &format!("{}/pids.current", dir)) {
+            // pids.max 为 "max" 时没有上限，解析结果为 0
+            let max = read_u64(&mut file, &format!("{}/pids.max", dir)).unwrap_or(0);
+            limits.push(Limit::new("Cgroup pids", current, max));
+        }
+    }
+
+    if let (Some(count), Some(max)) = (
+        read_u64(&mut file, "/proc/sys/net/netfilter/nf_conntrack_count"),
+        read_u64(&mut file, "/proc/sys/net/netfilter/nf_conntrack_max"),
+    ) {
+        limits.push(Limit::new("Conntrack", count, max));
+    }
+
+    if let Some(avail) = read_u64(&mut file, "/proc/sys/kernel/random/entropy_avail") {
+        let pool = read_u64(&mut file, "/proc/sys/kernel/random/poolsize").unwrap_or(0);
+        limits.push(Limit::new("Entropy", avail, pool));
+    }
+
+    SysLimits { limits }
+}
@@ -0,0 +1,227 @@
+use std::{cell::RefCell, fs, io, time::Instant};
+
+use super::procfs::{self, ProcFile};
+use crate::shared::mem::{MemInfo, NumaNode, PagingRates, SwapDevice};
+
+thread_local! {
+    // mem::collect 没有采集器状态，读取缓冲区按线程复用
+    static FILE: RefCell<ProcFile> = RefCell::new(ProcFile::new());
+    // 上一次读取的 /proc/vmstat 累计值，用于计算速率
+    static LAST_VMSTAT: RefCell<Option<(PagingRates, Instant)>> = const { RefCell::new(None) };
+}
+
+pub fn collect() -> io::Result<MemInfo> {
+    FILE.with_borrow_mut(|file| {
+        let mut info = MemInfo::default();
+        let meminfo = file.read(format_args!("/proc/meminfo"))?;
+        let (mut hugepages, mut hugepage_size) = ((0, 0), 0);
+
+        for line in procfs::lines(meminfo) {
+            let mut fields = procfs::fields(line);
+            let (Some(key), Some(value)) = (fields.next(), fields.next()) else {
+                continue;
+            };
+            // /proc/meminfo 的单位是 kB，只有大页的数量是页数
+            let bytes = procfs::parse_u64(value) << 10;
+            match key {
+                b"Shmem:" => info.shmem = bytes,
+                b"HugePages_Total:" => hugepages.0 = procfs::parse_u64(value),
+                b"HugePages_Free:" => hugepages.1 = procfs::parse_u64(value),
+                b"Hugepagesize:" => hugepage_size = bytes,
+                b"MemTotal:" => info.total = bytes,
+                b"MemFree:" => info.free = bytes,
+                b"MemAvailable:" => info.available = bytes,
+                b"Cached:" => info.cached = bytes,
+                b"SwapTotal:" => info.swap_total = bytes,
+                b"SwapFree:" => info.swap_free = bytes,
+                _ => {}
+            }
+        }
+
+        info.used = info.total.saturating_sub(info.available);
+        info.hugepages_total = hugepages.0 * hugepage_size;
+        info.hugepages_free = hugepages.1 * hugepage_size;
+        info.swap_used = info.swap_total.saturating_sub(info.swap_free);
+
+        // 没有启用交换空间或内核不支持时文件可能不存在
+        if let Ok(swaps) = file.read(format_args!("/proc/swaps")) {
+            info.swaps = parse_swaps(swaps);
+        }
+        info.numa = numa_nodes(file);
+        read_vmstat(file, &mut info);
+        Ok(info)
+    })
+}
+
+// 第一行为表头，大小和已用量的单位是 kB
+fn parse_swaps(swaps: &[u8]) -> Vec<SwapDevice> {
+    let mut devices = Vec::new();
+    for line in procfs::lines(swaps).skip(1) {
+        let mut fields = procfs::fields(line);
+        let (Some(name), Some(kind), Some(size), Some(used), Some(priority)) = (
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+            fields.next(),
+        ) else {
+            continue;
+        };
+        devices.push(SwapDevice {
+            name: procfs::unescape(name),
+            kind: String::from_utf8_lossy(kind).into_owned(),
+            size: procfs::parse_u64(size) << 10,
+            used: procfs::parse_u64(used) << 10,
+            priority: procfs::parse_i64(priority) as i32,
+        });
+    }
+    devices
+}
+
+fn read_vmstat(file: &mut ProcFile, info: &mut MemInfo) {
+    let Ok(vmstat) = file.read(format_args!("/proc/vmstat")) else {
+        return;
+    };
+    let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+    let (now, oom_kills) = parse_vmstat(vmstat, page_size);
+    info.oom_kills = oom_kills;
+    let time = Instant::now();
+    let Some((last, last_time)) = LAST_VMSTAT.replace(Some((now, time))) else {
+        return;
+    };
+    info.paging = now.rate(&last, time.duration_since(last_time).as_secs_f64());
+}
+
+// pswpin、pswpout 为页数，pgmajfault 和 oom_kill 为次数，都是开机以来的累计值
+fn parse_vmstat(vmstat: &[u8], page_size: u64) -> (PagingRates, Option<u64>) {
+    let (mut total, mut oom_kills) = (PagingRates::default(), None);
+    for line in procfs::lines(vmstat) {
+        let mut fields = procfs::fields(line);
+        let (Some(key), Some(value)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        match key {
+            b"pswpin" => total.swap_in = procfs::parse_u64(value) * page_size,
+            b"pswpout" => total.swap_out = procfs::parse_u64(value) * page_size,
+            b"pgmajfault" => total.major_faults = procfs::parse_u64(value),
+            b"oom_kill" => oom_kills = Some(procfs::parse_u64(value)),
+            _ => {}
+        }
+    }
+    (total, oom_kills)
+}
+
+// 每行的格式为 "Node 0 MemTotal:       32594356 kB"
+fn numa_nodes(file: &mut ProcFile) -> Vec<NumaNode> {
+    let Ok(entries) = fs::read_dir("/sys/devices/system/node") else {
+        return Vec::new();
+    };
+    let mut ids: Vec<u32> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name();
+            name.to_str()?.strip_prefix("node")?.parse().ok()
+        })
+        .collect();
+    // 单节点的机器上与整体的数值相同，不需要单独显示
+    if ids.len() < 2 {
+        return Vec::new();
+    }
+    ids.sort_unstable();
+    let mut nodes = Vec::new();
+    for id in ids {
+        let Ok(meminfo) = file.read(format_args!("/sys/devices/system/node/node{}/meminfo", id))
+        else {
+            continue;
+        };
+        nodes.push(parse_node(id, meminfo));
+    }
+    nodes
+}
+
+// 节点的已用量不包括空闲内存和页缓存
+fn parse_node(id: u32, meminfo: &[u8]) -> NumaNode {
+    let mut node = NumaNode {
+        id,
+        ..Default::default()
+    };
+    for line in procfs::lines(meminfo) {
+        let mut fields = procfs::fields(line).skip(2);
+        let (Some(key), Some(value)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let bytes = procfs::parse_u64(value) << 10;
+        match key {
+            b"MemTotal:" => node.total = bytes,
+            b"MemFree:" => node.free = bytes,
+            b"FilePages:" => node.cached = bytes,
+            _ => {}
+        }
+    }
+    node.used = node
+        .total
+        .saturating_sub(node.free)
+        .saturating_sub(node.cached);
+    node
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_node, parse_swaps, parse_vmstat};
+
+    #[test]
+    fn node_meminfo() {
+        let meminfo = b"Node 1 MemTotal:       8388608 kB\n\
+Node 1 MemFree:        2097152 kB\n\
+Node 1 MemUsed:        6291456 kB\n\
+Node 1 FilePages:      1048576 kB\n\
+Node 1 HugePages_Total:     0\n";
+        let node = parse_node(1, meminfo);
+        assert_eq!(node.id, 1);
+        assert_eq!(node.total, 8 << 30);
+        assert_eq!(node.free, 2 << 30);
+        assert_eq!(node.cached, 1 << 30);
+        // 已用不包括页缓存，不使用文件中的 MemUsed
+        assert_eq!(node.used, 5 << 30);
+    }
+
+    #[test]
+    fn node_used_saturates() {
+        let node = parse_node(0, b"Node 0 MemTotal: 1024 kB\nNode 0 MemFree: 2048 kB\n");
+        assert_eq!(node.used, 0);
+        assert_eq!(parse_node(0, b"").total, 0);
+    }
+
+    #[test]
+    fn vmstat_totals() {
+        let vmstat = b"nr_free_pages 123\npswpin 10\npswpout 3\npgmajfault 42\noom_kill 2\n";
+        let (total, oom_kills) = parse_vmstat(vmstat, 4096);
+        assert_eq!(total.swap_in, 10 * 4096);
+        assert_eq!(total.swap_out, 3 * 4096);
+        assert_eq!(total.major_faults, 42);
+        assert_eq!(oom_kills, Some(2));
+        // 老内核没有 oom_kill 一项
+        assert_eq!(parse_vmstat(b"pswpin 1\n", 4096).1, None);
+    }
+
+    #[test]
+    fn swap_devices() {
+        let swaps = b"Filename\t\t\t\tType\t\tSize\t\tUsed\t\tPriority\n\
+/dev/nvme0n1p3                          partition\t8388604\t\t262144\t\t-2\n\
+/swap\\040file                           file\t\t2097148\t\t0\t\t10\n\
+/broken\n";
+        let devices = parse_swaps(swaps);
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].name, "/dev/nvme0n1p3");
+        assert_eq!(devices[0].kind, "partition");
+        assert_eq!(devices[0].size, 8388604 << 10);
+        assert_eq!(devices[0].used, 256 << 20);
+        assert_eq!(devices[0].priority, -2);
+        // 路径中的空格被转义为 \040
+        assert_eq!(devices[1].name, "/swap file");
+        assert_eq!(devices[1].kind, "file");
+        assert_eq!(devices[1].priority, 10);
+        // 没有启用交换空间时只有表头
+        assert!(parse_swaps(b"Filename Type Size Used Priority\n").is_empty());
+    }
+}
@@ -0,0 +1,13 @@
+pub mod battery;
+pub mod cpu;
+pub mod disk;
+pub mod hwinfo;
+pub mod klog;
+pub mod limits;
+pub mod mem;
+pub mod net;
+pub mod proc;
+pub mod procfs;
+pub mod sensors;
+pub mod socket;
+pub mod wifi;
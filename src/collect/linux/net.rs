@@ -0,0 +1,113 @@
+use std::{io, time::Instant};
+
+use super::{
+    procfs::{self, ProcFile},
+    wifi,
+};
+use crate::shared::net::{NetInfo, NetPackets, NetStat};
+
+pub struct NetCollector {
+    last: NetInfo,
+    last_time: Option<Instant>,
+    file: ProcFile,
+}
+
+impl Default for NetCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetCollector {
+    pub fn new() -> Self {
+        NetCollector {
+            last: NetInfo::default(),
+            last_time: None,
+            file: ProcFile::new(),
+        }
+    }
+
+    pub fn collect(&mut self) -> io::Result<NetInfo> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_time
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut info = NetInfo {
+            interfaces: Vec::with_capacity(self.last.interfaces.len()),
+        };
+        // 前两行为表头
+        for line in procfs::lines(self.file.read(format_args!("/proc/net/dev"))?).skip(2) {
+            let Some(colon) = line.iter().position(|&b| b == b':') else {
+                continue;
+            };
+            let name = String::from_utf8_lossy(line[..colon].trim_ascii());
+            let mut fields = [0u64; 16];
+            let mut count = 0;
+            for (v, field) in fields.iter_mut().zip(procfs::fields(&line[colon + 1..])) {
+                *v = procfs::parse_u64(field);
+                count += 1;
+            }
+            if count < 16 {
+                continue;
+            }
+
+            let mut stat = NetStat {
+                name: name.into_owned(),
+                rx_bytes: fields[0],
+                tx_bytes: fields[8],
+                ..Default::default()
+            };
+            // 接收和发送各 8 列：字节、包、错误、丢弃、fifo、帧或冲突、压缩、组播或载波
+            let mut packets = NetPackets {
+                rx_packets: fields[1],
+                tx_packets: fields[9],
+                rx_errors: fields[2],
+                tx_errors: fields[10],
+                rx_drops: fields[3],
+                tx_drops: fields[11],
+                ..Default::default()
+            };
+
+            if let Some(last) = self.last.interfaces.iter().find(|s| s.name == stat.name) {
+                if elapsed > 0.0 {
+                    let rate =
+                        |now: u64, last: u64| (now.saturating_sub(last) as f64 / elapsed) as u64;
+                    stat.rx_rate = rate(stat.rx_bytes, last.rx_bytes);
+                    stat.tx_rate = rate(stat.tx_bytes, last.tx_bytes);
+                    if let Some(last) = last.packets {
+                        packets.rx_rate = rate(packets.rx_packets, last.rx_packets);
+                        packets.tx_rate = rate(packets.tx_packets, last.tx_packets);
+                    }
+                }
+            }
+            stat.packets = Some(packets);
+            info.interfaces.push(stat);
+        }
+
+        // 没有无线接口时 /proc/net/wireless 只有表头，不会发出 ioctl
+        let mut wireless = wifi::read_wireless(&mut self.file);
+        let _ = wifi::read_link(&mut wireless);
+        for stat in &mut info.interfaces {
+            stat.wifi = wireless.remove(&stat.name);
+        }
+
+        // 逐项覆盖上一次的结果，接口名复用已有的 String 容量
+        let last = &mut self.last.interfaces;
+        last.truncate(info.interfaces.len());
+        for (i, stat) in info.interfaces.iter().enumerate() {
+            match last.get_mut(i) {
+                Some(prev) => {
+                    prev.name.clone_from(&stat.name);
+                    prev.rx_bytes = stat.rx_bytes;
+                    prev.tx_bytes = stat.tx_bytes;
+                    prev.packets = stat.packets;
+                }
+                None => last.push(stat.clone()),
+            }
+        }
+        self.last_time = Some(now);
+        Ok(info)
+    }
+}
@@ -0,0 +1,517 @@
+use std::{collections::HashMap, fs, io, os::unix::fs::MetadataExt, time::Instant};
+
+use super::{
+    procfs::{self, ProcFile},
+    socket,
+};
+use crate::{
+    helper,
+    shared::{
+        proc::{
+            parse_environ, FdKind, IoPriority, ProcFd, ProcInfo, ProcLimit, ProcStack, ProcThread,
+        },
+        socket::SocketProto,
+    },
+};
+
+// cgroup 路径中出现这些片段时认为进程属于容器
+const CONTAINER_MARKERS: &[&str] = &[
+    "/docker",
+    "docker-",
+    "/containerd",
+    "cri-containerd",
+    "/kubepods",
+    "/libpod",
+    "libpod-",
+    "/lxc",
+    "/machine.slice",
+    "/crio",
+    "crio-",
+];
+
+pub fn is_container_cgroup(cgroup: &str) -> bool {
+    cgroup.lines().any(|line| {
+        let path = line.splitn(3, ':').nth(2).unwrap_or("");
+        CONTAINER_MARKERS.iter().any(|marker| path.contains(marker))
+    })
+}
+
+fn read_users() -> HashMap<u32, String> {
+    let mut users = HashMap::new();
+    if let Ok(passwd) = fs::read_to_string("/etc/passwd") {
+        for line in passwd.lines() {
+            let mut fields = line.split(':');
+            if let (Some(name), Some(uid)) = (fields.next(), fields.nth(1)) {
+                if let Ok(uid) = uid.parse() {
+                    users.insert(uid, name.to_string());
+                }
+            }
+        }
+    }
+    users
+}
+
+// 进程启动后基本不会变化的信息，只在第一次见到该进程（或进程名变化）时读取
+struct ProcStatic {
+    name: String,
+    cmd: String,
+    container: bool,
+}
+
+pub struct ProcCollector {
+    last_times: HashMap<i32, u64>,
+    times: HashMap<i32, u64>,
+    last_io: HashMap<i32, (u64, u64)>,
+    io: HashMap<i32, (u64, u64)>,
+    cache: HashMap<i32, ProcStatic>,
+    pids: Vec<i32>,
+    file: ProcFile,
+    last_collect: Option<Instant>,
+    users: Option<HashMap<u32, String>>,
+    page_size: u64,
+    clk_tck: f64,
+    core_count: usize,
+}
+
+impl Default for ProcCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcCollector {
+    pub fn new() -> Self {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64;
+        let clk_tck = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+        let core_count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) }.max(1) as usize;
+        ProcCollector {
+            last_times: HashMap::new(),
+            times: HashMap::new(),
+            last_io: HashMap::new(),
+            io: HashMap::new(),
+            cache: HashMap::new(),
+            pids: Vec::new(),
+            file: ProcFile::new(),
+            last_collect: None,
+            users: None,
+            page_size,
+            clk_tck,
+            core_count,
+        }
+    }
+
+    fn read_static(&mut self, pid: i32, name: &str) -> ProcStatic {
+        let mut cmd = String::new();
+        if let Ok(raw) = self.file.read(format_args!("/proc/{}/cmdline", pid)) {
+            for arg in raw.split(|&b| b == 0).filter(|arg| !arg.is_empty()) {
+                if !cmd.is_empty() {
+                    cmd.push(' ');
+                }
+                cmd.push_str(&String::from_utf8_lossy(arg));
+            }
+        }
+
+        let container = self
+            .file
+            .read(format_args!("/proc/{}/cgroup", pid))
+            .map(|cgroup| is_container_cgroup(&String::from_utf8_lossy(cgroup)))
+            .unwrap_or(false);
+
+        ProcStatic {
+            name: name.to_owned(),
+            cmd,
+            container,
+        }
+    }
+
+    // 直接读取被拒绝时交给辅助程序，辅助程序只允许读取属于当前用户的进程
+    fn read_io(&mut self, pid: i32) -> Option<(u64, u64)> {
+        let helper_data;
+        let data = match self.file.read(format_args!("/proc/{}/io", pid)) {
+            Ok(data) => data,
+            Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                helper_data = helper::read(&format!("/proc/{}/io", pid)).ok()?;
+                &helper_data
+            }
+            Err(_) => return None,
+        };
+        let (mut read, mut write) = (None, None);
+        for line in procfs::lines(data) {
+            if let Some(value) = line.strip_prefix(b"read_bytes:") {
+                read = Some(procfs::parse_u64(value.trim_ascii_start()));
+            } else if let Some(value) = line.strip_prefix(b"write_bytes:") {
+                write = Some(procfs::parse_u64(value.trim_ascii_start()));
+            }
+        }
+        Some((read?, write?))
+    }
+
+    fn read_proc(&mut self, pid: i32) -> Option<ProcInfo> {
+        let stat = self.file.read(format_args!("/proc/{}/stat", pid)).ok()?;
+        // 进程名可能包含空格和括号，以最后一个 ')' 为界
+        let name_start = stat.iter().position(|&b| b == b'(')?;
+        let name_end = stat.iter().rposition(|&b| b == b')')?;
+        let name = String::from_utf8_lossy(&stat[name_start + 1..name_end]).into_owned();
+
+        // 只保留需要的字段：状态、ppid、utime、stime、nice、线程数、rss
+        let mut state = '?';
+        let mut field = [0i64; 22];
+        let mut count = 0;
+        for (i, value) in procfs::fields(&stat[name_end + 1..]).take(22).enumerate() {
+            if i == 0 {
+                state = value.first().map(|&b| b as char).unwrap_or('?');
+            } else {
+                field[i] = procfs::parse_i64(value);
+            }
+            count += 1;
+        }
+        if count < 22 {
+            return None;
+        }
+
+        let uid = self
+            .file
+            .metadata(format_args!("/proc/{}", pid))
+            .map(|md| md.uid())
+            .unwrap_or(0);
+        // 用户表在第一次需要时才读取
+        let user = match self.users.get_or_insert_with(read_users).get(&uid) {
+            Some(name) => name.clone(),
+            None => uid.to_string(),
+        };
+
+        let cached = self.cache.get(&pid).is_some_and(|info| info.name == name);
+        if !cached {
+            let info = self.read_static(pid, &name);
+            self.cache.insert(pid, info);
+        }
+        let info = &self.cache[&pid];
+
+        Some(ProcInfo {
+            pid,
+            ppid: field[1] as i32,
+            name,
+            cmd: info.cmd.clone(),
+            user,
+            uid,
+            state,
+            threads: field[17] as u32,
+            nice: field[16] as i32,
+            mem: field[21].max(0) as u64 * self.page_size,
+            cpu_p: 0.0,
+            cpu_time: (field[11] + field[12]).max(0) as u64,
+            container: info.container,
+            io_bytes: None,
+            io_rate: None,
+        })
+    }
+
+    // per_core 为 true 时 cpu 占用率以单核为 100%
+    pub fn collect(&mut self, per_core: bool) -> io::Result<Vec<ProcInfo>> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_collect
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut pids = std::mem::take(&mut self.pids);
+        procfs::read_pids(&mut pids)?;
+        let mut procs = Vec::with_capacity(pids.len());
+        self.times.clear();
+        self.io.clear();
+        for &pid in &pids {
+            // 进程可能在读取过程中退出，直接跳过
+            let Some(mut proc) = self.read_proc(pid) else {
+                continue;
+            };
+
+            if let Some(last) = self.last_times.get(&pid) {
+                if elapsed > 0.0 {
+                    let ticks = proc.cpu_time.saturating_sub(*last) as f64;
+                    proc.cpu_p = ticks / self.clk_tck / elapsed * 100.0;
+                    if !per_core {
+                        proc.cpu_p /= self.core_count as f64;
+                    }
+                }
+            }
+            self.times.insert(pid, proc.cpu_time);
+
+            proc.io_bytes = self.read_io(pid);
+            if let Some((read, write)) = proc.io_bytes {
+                if let Some((last_read, last_write)) = self.last_io.get(&pid) {
+                    if elapsed > 0.0 {
+                        let rate = |now: u64, last: u64| {
+                            (now.saturating_sub(last) as f64 / elapsed) as u64
+                        };
+                        proc.io_rate = Some((rate(read, *last_read), rate(write, *last_write)));
+                    }
+                }
+                self.io.insert(pid, (read, write));
+            }
+            procs.push(proc);
+        }
+        self.pids = pids;
+
+        // 已退出进程的缓存随之清理
+        let times = &self.times;
+        self.cache.retain(|pid, _| times.contains_key(pid));
+        std::mem::swap(&mut self.last_times, &mut self.times);
+        std::mem::swap(&mut self.last_io, &mut self.io);
+        self.last_collect = Some(now);
+        Ok(procs)
+    }
+}
+
+const IOPRIO_WHO_PROCESS: libc::c_long = 1;
+
+pub fn get_ioprio(pid: i32) -> io::Result<IoPriority> {
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_ioprio_get,
+            IOPRIO_WHO_PROCESS,
+            pid as libc::c_long,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(IoPriority::from_raw(ret as i32))
+}
+
+pub fn set_ioprio(pid: i32, prio: IoPriority) -> io::Result<()> {
+    let ret = unsafe {
+        libc::syscall(
+            libc::SYS_ioprio_set,
+            IOPRIO_WHO_PROCESS,
+            pid as libc::c_long,
+            prio.to_raw() as libc::c_long,
+        )
+    };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// 降低 nice 值（提高优先级）需要 root 或 CAP_SYS_NICE，修改其它用户的进程返回 EPERM
+pub fn set_nice(pid: i32, nice: i32) -> io::Result<()> {
+    let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// 向进程发送信号，其它用户的进程需要 root 权限
+pub fn send_signal(pid: i32, signal: i32) -> io::Result<()> {
+    if unsafe { libc::kill(pid, signal) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// smaps_rollup 中的按比例分摊内存，直接读取被拒绝时交给辅助程序
+pub fn get_pss(pid: i32) -> io::Result<u64> {
+    let data = helper::read(&format!("/proc/{}/smaps_rollup", pid))?;
+    let pss = procfs::lines(&data)
+        .find_map(|line| line.strip_prefix(b"Pss:"))
+        .map(|value| procfs::parse_u64(value.trim_ascii()) * 1024);
+    pss.ok_or_else(|| io::ErrorKind::InvalidData.into())
+}
+
+// 各列的起始位置取自表头中 "Soft Limit"、"Hard Limit" 和 "Units" 的位置
+pub fn get_limits(pid: i32) -> io::Result<Vec<ProcLimit>> {
+    let data = helper::read(&format!("/proc/{}/limits", pid))?;
+    let text = String::from_utf8_lossy(&data);
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or_default();
+    let (Some(soft), Some(hard), Some(unit)) = (
+        header.find("Soft Limit"),
+        header.find("Hard Limit"),
+        header.find("Units"),
+    ) else {
+        return Err(io::ErrorKind::InvalidData.into());
+    };
+    let limits = lines
+        .map(|line| {
+            let col = |from: usize, to: usize| {
+                line.get(from.min(line.len())..to.min(line.len()))
+                    .unwrap_or_default()
+                    .trim()
+                    .to_owned()
+            };
+            ProcLimit {
+                name: col(0, soft),
+                soft: col(soft, hard),
+                hard: col(hard, unit),
+                unit: col(unit, line.len()),
+            }
+        })
+        .filter(|limit| !limit.name.is_empty())
+        .collect();
+    Ok(limits)
+}
+
+// wchan 所有用户可读，内核栈通常只有 root 可读。
+// 栈中每行形如 "[<0>] do_select+0x6b5/0x7a0"，只保留函数名
+pub fn get_stack(pid: i32) -> io::Result<ProcStack> {
+    let wchan = fs::read_to_string(format!("/proc/{}/wchan", pid))?;
+    let wchan = match wchan.trim() {
+        "0" => String::new(),
+        name => name.to_owned(),
+    };
+    let frames = fs::read_to_string(format!("/proc/{}/stack", pid))
+        .ok()
+        .map(|stack| {
+            stack
+                .lines()
+                .filter_map(|line| {
+                    let symbol = line.split_once("] ").map_or(line, |(_, symbol)| symbol);
+                    let symbol = symbol.split('+').next().unwrap_or_default().trim();
+                    (!symbol.is_empty()).then(|| symbol.to_owned())
+                })
+                .collect()
+        });
+    Ok(ProcStack { wchan, frames })
+}
+
+// 从进程所在网络命名空间的 /proc/[pid]/net 表中建立 inode 到套接字描述的映射
+fn read_sockets(pid: i32) -> HashMap<u64, String> {
+    let mut sockets = HashMap::new();
+    let dir = format!("/proc/{}/net", pid);
+    for proto in SocketProto::ALL {
+        for socket in socket::read_table(&dir, proto) {
+            let (name, local, remote) = (proto.name(), socket.local, socket.remote);
+            let desc = match (socket.is_listening(), proto.is_tcp()) {
+                (true, true) => format!("{} {} LISTEN", name, local),
+                (true, false) => format!("{} {}", name, local),
+                (false, true) => format!("{} {} -> {} {}", name, local, remote, socket.state),
+                (false, false) => format!("{} {} -> {}", name, local, remote),
+            };
+            sockets.insert(socket.inode, desc);
+        }
+    }
+    // unix 表的第 7 列为 inode，之后是可选的路径
+    if let Ok(table) = fs::read_to_string(format!("/proc/{}/net/unix", pid)) {
+        for line in table.lines().skip(1) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if let Some(Ok(inode)) = fields.get(6).map(|inode| inode.parse::<u64>()) {
+                let desc = match fields.get(7) {
+                    Some(path) => format!("unix {}", path),
+                    None => "unix".to_owned(),
+                };
+                sockets.insert(inode, desc);
+            }
+        }
+    }
+    sockets
+}
+
+// 读取 /proc/[pid]/fd 中各个链接的目标，其它用户的进程需要 root 权限
+pub fn get_fds(pid: i32) -> io::Result<Vec<ProcFd>> {
+    let mut fds: Vec<ProcFd> = fs::read_dir(format!("/proc/{}/fd", pid))?
+        .flatten()
+        .filter_map(|entry| {
+            let fd = entry.file_name().to_str()?.parse().ok()?;
+            // 读取期间关闭的描述符直接跳过
+            let target = fs::read_link(entry.path()).ok()?;
+            let target = target.to_string_lossy().into_owned();
+            let kind = if target.starts_with("socket:[") {
+                FdKind::Socket
+            } else if target.starts_with("pipe:[") {
+                FdKind::Pipe
+            } else if target.starts_with('/') {
+                FdKind::File
+            } else {
+                FdKind::Other
+            };
+            Some(ProcFd { fd, kind, target })
+        })
+        .collect();
+    fds.sort_by_key(|fd| fd.fd);
+
+    if fds.iter().any(|fd| fd.kind == FdKind::Socket) {
+        let sockets = read_sockets(pid);
+        for fd in fds.iter_mut().filter(|fd| fd.kind == FdKind::Socket) {
+            let inode = fd
+                .target
+                .trim_start_matches("socket:[")
+                .trim_end_matches(']')
+                .parse::<u64>();
+            if let Some(desc) = inode.ok().and_then(|inode| sockets.get(&inode)) {
+                fd.target = desc.clone();
+            }
+        }
+    }
+    Ok(fds)
+}
+
+// 环境变量可能包含密钥，只读取自己有权限的进程，不经过辅助程序
+pub fn get_environ(pid: i32) -> io::Result<Vec<String>> {
+    Ok(parse_environ(&fs::read(format!("/proc/{}/environ", pid))?))
+}
+
+// /proc/[pid]/task/[tid]/stat 与进程的 stat 格式相同
+pub fn get_threads(pid: i32) -> io::Result<Vec<ProcThread>> {
+    let clk_tck = unsafe { libc::sysconf(libc::_SC_CLK_TCK) }.max(1) as f64;
+    let mut threads: Vec<ProcThread> = fs::read_dir(format!("/proc/{}/task", pid))?
+        .flatten()
+        .filter_map(|entry| {
+            let tid = entry.file_name().to_str()?.parse().ok()?;
+            // 读取期间退出的线程直接跳过
+            let stat = fs::read(entry.path().join("stat")).ok()?;
+            let name_start = stat.iter().position(|&b| b == b'(')?;
+            let name_end = stat.iter().rposition(|&b| b == b')')?;
+            let name = String::from_utf8_lossy(&stat[name_start + 1..name_end]).into_owned();
+            let fields: Vec<&[u8]> = procfs::fields(&stat[name_end + 1..]).take(13).collect();
+            if fields.len() < 13 {
+                return None;
+            }
+            let ticks = procfs::parse_u64(fields[11]) + procfs::parse_u64(fields[12]);
+            Some(ProcThread {
+                tid,
+                name,
+                state: fields[0].first().map_or('?', |&b| b as char),
+                cpu_time: ticks as f64 / clk_tck,
+                cpu_p: None,
+            })
+        })
+        .collect();
+    threads.sort_by_key(|thread| thread.tid);
+    Ok(threads)
+}
+
+// 返回进程主线程允许运行的 cpu 编号
+pub fn get_affinity(pid: i32) -> io::Result<Vec<usize>> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    if unsafe { libc::sched_getaffinity(pid, size, &mut set) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok((0..libc::CPU_SETSIZE as usize)
+        .filter(|&cpu| unsafe { libc::CPU_ISSET(cpu, &set) })
+        .collect())
+}
+
+// sched_setaffinity 只作用于单个线程，这里与 taskset -a 一样设置进程的所有线程
+pub fn set_affinity(pid: i32, cpus: &[usize]) -> io::Result<()> {
+    let mut set: libc::cpu_set_t = unsafe { std::mem::zeroed() };
+    for &cpu in cpus {
+        unsafe { libc::CPU_SET(cpu, &mut set) };
+    }
+    let size = std::mem::size_of::<libc::cpu_set_t>();
+    let tids: Vec<i32> = fs::read_dir(format!("/proc/{}/task", pid))?
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse().ok())
+        .collect();
+    for tid in tids {
+        if unsafe { libc::sched_setaffinity(tid, size, &set) } < 0 {
+            let err = io::Error::last_os_error();
+            // 设置期间退出的线程直接跳过
+            if err.raw_os_error() != Some(libc::ESRCH) {
+                return Err(err);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,120 @@
+use std::{
+    ffi::CStr,
+    fmt::{self, Write as _},
+    fs::{self, File, Metadata},
+    io::{self, Read},
+};
+
+// 可复用的 /proc 读取缓冲区：路径和文件内容在多次读取之间保留容量，
+// 稳定运行后读取文件不再分配内存
+pub struct ProcFile {
+    path: String,
+    data: Vec<u8>,
+}
+
+impl Default for ProcFile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcFile {
+    pub fn new() -> Self {
+        ProcFile {
+            path: String::with_capacity(64),
+            data: Vec::with_capacity(4096),
+        }
+    }
+
+    // 路径使用 format_args! 传入，避免拼接路径时分配
+    pub fn read(&mut self, path: fmt::Arguments) -> io::Result<&[u8]> {
+        self.path.clear();
+        let _ = self.path.write_fmt(path);
+        self.data.clear();
+        File::open(&self.path)?.read_to_end(&mut self.data)?;
+        Ok(&self.data)
+    }
+
+    pub fn metadata(&mut self, path: fmt::Arguments) -> io::Result<Metadata> {
+        self.path.clear();
+        let _ = self.path.write_fmt(path);
+        fs::metadata(&self.path)
+    }
+}
+
+pub fn lines(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    data.split(|&b| b == b'\n')
+}
+
+// 按空白字符切分，跳过连续空白产生的空字段
+pub fn fields(data: &[u8]) -> impl Iterator<Item = &[u8]> {
+    data.split(|b| b.is_ascii_whitespace())
+        .filter(|field| !field.is_empty())
+}
+
+// 解析开头的十进制数字，遇到非数字字符停止，没有数字时返回 0
+pub fn parse_u64(field: &[u8]) -> u64 {
+    field
+        .iter()
+        .take_while(|b| b.is_ascii_digit())
+        .fold(0u64, |acc, b| {
+            acc.wrapping_mul(10).wrapping_add((b - b'0') as u64)
+        })
+}
+
+pub fn parse_i64(field: &[u8]) -> i64 {
+    match field.split_first() {
+        Some((b'-', rest)) => -(parse_u64(rest) as i64),
+        _ => parse_u64(field) as i64,
+    }
+}
+
+pub fn parse_f64(field: &[u8]) -> f64 {
+    std::str::from_utf8(field)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(0.0)
+}
+
+// /proc/swaps 和 /proc/self/mounts 中路径的空白字符被转义为 "\040" 这样的八进制序列
+pub fn unescape(raw: &[u8]) -> String {
+    let mut out = Vec::with_capacity(raw.len());
+    let mut i = 0;
+    while i < raw.len() {
+        let octal = raw
+            .get(i + 1..i + 4)
+            .filter(|digits| raw[i] == b'\\' && digits.iter().all(|d| (b'0'..=b'7').contains(d)));
+        match octal {
+            Some(digits) => {
+                out.push(digits.iter().fold(0u8, |acc, d| (acc << 3) | (d - b'0')));
+                i += 4;
+            }
+            None => {
+                out.push(raw[i]);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+// 把 /proc 下的进程目录写入 pids，直接使用 readdir 避免为每个目录项分配内存
+pub fn read_pids(pids: &mut Vec<i32>) -> io::Result<()> {
+    pids.clear();
+    let dir = unsafe { libc::opendir(c"/proc".as_ptr()) };
+    if dir.is_null() {
+        return Err(io::Error::last_os_error());
+    }
+    loop {
+        let entry = unsafe { libc::readdir(dir) };
+        if entry.is_null() {
+            break;
+        }
+        let name = unsafe { CStr::from_ptr((*entry).d_name.as_ptr()) }.to_bytes();
+        if !name.is_empty() && name.iter().all(|b| b.is_ascii_digit()) {
+            pids.push(parse_u64(name) as i32);
+        }
+    }
+    unsafe { libc::closedir(dir) };
+    Ok(())
+}
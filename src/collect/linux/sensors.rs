@@ -0,0 +1,78 @@
+use std::{fs, path::Path};
+
+use crate::shared::sensors::{Sensor, SensorKind, SensorList};
+
+fn read_trim(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_owned())
+}
+
+// "fan2_input" -> 2，prefix 为 "fan" 或 "in"
+fn input_index(file: &str, prefix: &str) -> Option<u32> {
+    file.strip_prefix(prefix)?
+        .strip_suffix("_input")?
+        .parse()
+        .ok()
+}
+
+// 枚举 /sys/class/hwmon 下各驱动的 fanN_input（RPM）和 inN_input（毫伏），
+// 较老的内核把这些属性放在 device 子目录中
+pub fn collect() -> SensorList {
+    let mut list = SensorList::default();
+    let Ok(entries) = fs::read_dir("/sys/class/hwmon") else {
+        return list;
+    };
+    let mut hwmons: Vec<_> = entries.flatten().map(|entry| entry.path()).collect();
+    hwmons.sort_by_key(|path| {
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        name.trim_start_matches("hwmon")
+            .parse::<u32>()
+            .unwrap_or(u32::MAX)
+    });
+    for hwmon in hwmons {
+        let dir = match hwmon.join("name").exists() {
+            true => hwmon,
+            false => hwmon.join("device"),
+        };
+        let Some(chip) = read_trim(&dir.join("name")) else {
+            continue;
+        };
+        let Ok(files) = fs::read_dir(&dir) else {
+            continue;
+        };
+        let mut inputs: Vec<(SensorKind, u32)> = files
+            .flatten()
+            .filter_map(|file| {
+                let file = file.file_name().to_string_lossy().into_owned();
+                match input_index(&file, "fan") {
+                    Some(index) => Some((SensorKind::Fan, index)),
+                    None => Some((SensorKind::Voltage, input_index(&file, "in")?)),
+                }
+            })
+            .collect();
+        inputs.sort_by_key(|&(kind, index)| (kind == SensorKind::Voltage, index));
+        for (kind, index) in inputs {
+            let prefix = match kind {
+                SensorKind::Fan => "fan",
+                SensorKind::Voltage => "in",
+            };
+            let Some(raw) = read_trim(&dir.join(format!("{}{}_input", prefix, index)))
+                .and_then(|value| value.parse::<f64>().ok())
+            else {
+                continue;
+            };
+            let label = read_trim(&dir.join(format!("{}{}_label", prefix, index)))
+                .filter(|label| !label.is_empty())
+                .unwrap_or_else(|| format!("{}{}", prefix, index));
+            list.sensors.push(Sensor {
+                chip: chip.clone(),
+                label,
+                kind,
+                value: match kind {
+                    SensorKind::Fan => raw,
+                    SensorKind::Voltage => raw / 1000.0,
+                },
+            });
+        }
+    }
+    list
+}
@@ -0,0 +1,270 @@
+use std::{
+    collections::HashMap,
+    fs, io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+};
+
+use super::procfs;
+use crate::shared::socket::{SocketInfo, SocketList, SocketProto};
+
+// /proc/net/tcp 中的连接状态编号
+const TCP_STATES: &[&str] = &[
+    "",
+    "ESTABLISHED",
+    "SYN_SENT",
+    "SYN_RECV",
+    "FIN_WAIT1",
+    "FIN_WAIT2",
+    "TIME_WAIT",
+    "CLOSE",
+    "CLOSE_WAIT",
+    "LAST_ACK",
+    "LISTEN",
+    "CLOSING",
+];
+
+// 地址按 32 位一组以主机字节序写成十六进制，例如 "0100007F:0035"。
+// IPv4 为 8 位、IPv6 为 32 位十六进制数字，端口为 4 位
+fn parse_net_addr(addr: &str) -> Option<SocketAddr> {
+    let (ip, port) = addr.split_once(':')?;
+    let hex = |text: &str| text.bytes().all(|b| b.is_ascii_hexdigit());
+    if !matches!(ip.len(), 8 | 32) || port.len() != 4 || !hex(ip) || !hex(port) {
+        return None;
+    }
+    let port = u16::from_str_radix(port, 16).ok()?;
+    let bytes: Vec<u8> = (0..ip.len() / 8)
+        .map(|i| u32::from_str_radix(ip.get(i * 8..i * 8 + 8)?, 16).ok())
+        .collect::<Option<Vec<u32>>>()?
+        .iter()
+        .flat_map(|word| word.to_ne_bytes())
+        .collect();
+    let ip = match bytes.len() {
+        4 => Ipv4Addr::from(<[u8; 4]>::try_from(bytes).ok()?).into(),
+        16 => Ipv6Addr::from(<[u8; 16]>::try_from(bytes).ok()?).into(),
+        _ => return None,
+    };
+    Some(SocketAddr::new(ip, port))
+}
+
+// 读取 dir（/proc/net 或 /proc/[pid]/net）中 proto 对应的表，第 4 列为状态，第 10 列为 inode
+pub fn read_table(dir: &str, proto: SocketProto) -> Vec<SocketInfo> {
+    match fs::read_to_string(format!("{}/{}", dir, proto.name())) {
+        Ok(table) => parse_table(&table, proto),
+        Err(_) => Vec::new(),
+    }
+}
+
+// 第一行是表头，无法解析的行跳过
+fn parse_table(table: &str, proto: SocketProto) -> Vec<SocketInfo> {
+    table
+        .lines()
+        .skip(1)
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let state = usize::from_str_radix(fields.get(3)?, 16).ok()?;
+            let state = match (proto.is_tcp(), state) {
+                (true, state) => TCP_STATES.get(state).copied().unwrap_or_default(),
+                // UDP 只使用 ESTABLISHED 和 CLOSE 两个状态
+                (false, 1) => "ESTABLISHED",
+                (false, _) => "UNCONN",
+            };
+            Some(SocketInfo {
+                proto,
+                local: parse_net_addr(fields.get(1)?)?,
+                remote: parse_net_addr(fields.get(2)?)?,
+                state,
+                inode: fields.get(9)?.parse().ok()?,
+                owner: None,
+            })
+        })
+        .collect()
+}
+
+// 扫描所有进程的描述符建立 inode 到 (pid, 名称) 的映射，只包含有权限读取的进程
+fn socket_owners() -> io::Result<HashMap<u64, (i32, String)>> {
+    let mut pids = Vec::new();
+    procfs::read_pids(&mut pids)?;
+    let mut owners = HashMap::new();
+    for pid in pids {
+        let Ok(dir) = fs::read_dir(format!("/proc/{}/fd", pid)) else {
+            continue;
+        };
+        let mut name = None;
+        for entry in dir.flatten() {
+            let Ok(target) = fs::read_link(entry.path()) else {
+                continue;
+            };
+            let inode = target
+                .to_str()
+                .and_then(|target| target.strip_prefix("socket:["))
+                .and_then(|target| target.strip_suffix(']'))
+                .and_then(|inode| inode.parse::<u64>().ok());
+            let Some(inode) = inode else {
+                continue;
+            };
+            // 多个进程共享的套接字（fork 后继承）归属于 pid 最小的进程
+            let name = name.get_or_insert_with(|| {
+                fs::read_to_string(format!("/proc/{}/comm", pid))
+                    .map(|comm| comm.trim_end().to_owned())
+                    .unwrap_or_default()
+            });
+            owners.entry(inode).or_insert_with(|| (pid, name.clone()));
+        }
+    }
+    Ok(owners)
+}
+
+// btop-rs 所在网络命名空间中的 TCP 和 UDP 套接字
+pub fn collect() -> io::Result<SocketList> {
+    let mut list = SocketList {
+        sockets: SocketProto::ALL
+            .iter()
+            .flat_map(|&proto| read_table("/proc/net", proto))
+            .collect(),
+    };
+    if !list.sockets.is_empty() {
+        let owners = socket_owners()?;
+        for socket in &mut list.sockets {
+            socket.owner = owners.get(&socket.inode).cloned();
+        }
+    }
+    list.sort();
+    Ok(list)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_net_addr, parse_table};
+    use crate::shared::socket::SocketProto;
+
+    const HEADER: &str = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode";
+
+    // (本地地址, 远端地址, 状态, inode)
+    fn parse(proto: SocketProto, rows: &[&str]) -> Vec<(String, String, &'static str, u64)> {
+        let table = std::iter::once(HEADER)
+            .chain(rows.iter().copied())
+            .collect::<Vec<_>>()
+            .join("\n");
+        parse_table(&table, proto)
+            .iter()
+            .map(|socket| {
+                (
+                    socket.local.to_string(),
+                    socket.remote.to_string(),
+                    socket.state,
+                    socket.inode,
+                )
+            })
+            .collect()
+    }
+
+    // 地址以主机字节序写出，下面的数据来自小端机器
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn addresses() {
+        let addr = |text| parse_net_addr(text).map(|addr| addr.to_string());
+        assert_eq!(addr("0100007F:0035").as_deref(), Some("127.0.0.1:53"));
+        assert_eq!(addr("00000000:0016").as_deref(), Some("0.0.0.0:22"));
+        assert_eq!(
+            addr("00000000000000000000000001000000:1F90").as_deref(),
+            Some("[::1]:8080")
+        );
+        assert_eq!(
+            addr("0000000000000000FFFF00000100007F:0050").as_deref(),
+            Some("[::ffff:127.0.0.1]:80")
+        );
+        assert_eq!(addr("0100007f:ffff").as_deref(), Some("127.0.0.1:65535"));
+    }
+
+    #[test]
+    fn malformed_addresses() {
+        for text in [
+            "",
+            ":",
+            "0100007F",
+            "0100007F:",
+            ":0035",
+            "0100007:0035",
+            "0100007F0:0035",
+            "0100007G:0035",
+            "+100007F:0035",
+            "0100007F:+035",
+            "0100007F:00035",
+            "0100007F:35",
+            "0000000000000000000000000100000:0050",
+            "0100007F:0035:0035",
+        ] {
+            assert!(parse_net_addr(text).is_none(), "{:?}", text);
+        }
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn tcp_table() {
+        let rows = [
+            "   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 21339 1 0000000000000000 100 0 0 10 0",
+            "   1: 0100007F:0277 0100007F:A1C4 01 00000000:00000000 00:00000000 00000000   101        0 33012 1 0000000000000000 20 4 30 10 -1",
+            "   2: 0100007F:A1C4 0100007F:0277 06 00000000:00000000 03:000016B8 00000000     0        0 0 3 0000000000000000",
+        ];
+        assert_eq!(
+            parse(SocketProto::Tcp, &rows),
+            [
+                (
+                    "0.0.0.0:22".to_owned(),
+                    "0.0.0.0:0".to_owned(),
+                    "LISTEN",
+                    21339
+                ),
+                (
+                    "127.0.0.1:631".to_owned(),
+                    "127.0.0.1:41412".to_owned(),
+                    "ESTABLISHED",
+                    33012
+                ),
+                (
+                    "127.0.0.1:41412".to_owned(),
+                    "127.0.0.1:631".to_owned(),
+                    "TIME_WAIT",
+                    0
+                ),
+            ]
+        );
+    }
+
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn udp_states() {
+        let rows = [
+            "  10: 3500007F:0035 00000000:0000 07 00000000:00000000 00:00000000 00000000   102        0 18543 2 0000000000000000 0",
+            "  11: 0100007F:D431 0100007F:0035 01 00000000:00000000 00:00000000 00000000  1000        0 90412 2 0000000000000000 0",
+        ];
+        let states: Vec<_> = parse(SocketProto::Udp, &rows)
+            .into_iter()
+            .map(|(_, _, state, _)| state)
+            .collect();
+        assert_eq!(states, ["UNCONN", "ESTABLISHED"]);
+    }
+
+    #[test]
+    fn malformed_rows() {
+        let rows = [
+            "",
+            "garbage",
+            // 缺少 inode 列
+            "   0: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0",
+            // 状态不是十六进制
+            "   1: 00000000:0016 00000000:0000 ZZ 00000000:00000000 00:00000000 00000000     0        0 1 1",
+            // 地址无法解析
+            "   2: 0000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 1 1",
+            // inode 不是数字
+            "   3: 00000000:0016 00000000:0000 0A 00000000:00000000 00:00000000 00000000     0        0 x 1",
+        ];
+        assert!(parse(SocketProto::Tcp, &rows).is_empty());
+        // 没有表头以外的行，或者整个表为空
+        assert!(parse(SocketProto::Tcp6, &[]).is_empty());
+        assert!(parse_table("", SocketProto::Udp6).is_empty());
+        // 未知的 TCP 状态编号不会越界
+        let rows = ["   0: 00000000:0016 00000000:0000 FF 00000000:00000000 00:00000000 00000000     0        0 7 1"];
+        assert_eq!(parse(SocketProto::Tcp, &rows)[0].2, "");
+    }
+}
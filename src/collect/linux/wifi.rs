@@ -0,0 +1,129 @@
+use std::{collections::HashMap, io, ptr};
+
+use libc::{c_int, c_ulong, c_void, iwreq, IW_ESSID_MAX_SIZE, SIOCGIWESSID, SIOCGIWRATE};
+
+use super::procfs::{self, ProcFile};
+use crate::shared::net::WifiInfo;
+
+// 大多数驱动报告的链路质量上限，iwconfig 中显示为 "Link Quality=54/70"
+const MAX_QUALITY: f64 = 70.0;
+
+// /proc/net/wireless 中各无线接口的链路质量和信号强度，前两行为表头：
+//   wlan0: 0000   54.  -56.  -256        0      0      0      0     12        0
+pub fn read_wireless(file: &mut ProcFile) -> HashMap<String, WifiInfo> {
+    match file.read(format_args!("/proc/net/wireless")) {
+        Ok(data) => parse_wireless(data),
+        Err(_) => HashMap::new(),
+    }
+}
+
+fn parse_wireless(data: &[u8]) -> HashMap<String, WifiInfo> {
+    let mut interfaces = HashMap::new();
+    for line in procfs::lines(data).skip(2) {
+        let Some(colon) = line.iter().position(|&b| b == b':') else {
+            continue;
+        };
+        let name = String::from_utf8_lossy(line[..colon].trim_ascii()).into_owned();
+        let mut fields = procfs::fields(&line[colon + 1..]).skip(1);
+        let (Some(link), Some(level)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        // 数值后面带有表示已更新的 "."
+        let number = |field: &[u8]| {
+            String::from_utf8_lossy(field)
+                .trim_end_matches('.')
+                .parse::<f64>()
+                .ok()
+        };
+        let quality = number(link).map(|link| (link / MAX_QUALITY * 100.0).clamp(0.0, 100.0));
+        // 旧驱动以 0-255 表示 dBm，需要减去 256
+        let signal = number(level).map(|level| match level {
+            level if level > 0.0 => level as i32 - 256,
+            level => level as i32,
+        });
+        interfaces.insert(
+            name,
+            WifiInfo {
+                quality,
+                signal,
+                ..Default::default()
+            },
+        );
+    }
+    interfaces
+}
+
+// 通过无线扩展的 ioctl 读取 SSID 和发送速率，cfg80211 驱动同样支持
+pub fn read_link(interfaces: &mut HashMap<String, WifiInfo>) -> io::Result<()> {
+    if interfaces.is_empty() {
+        return Ok(());
+    }
+    let sock = unsafe { libc::socket(libc::AF_INET, libc::SOCK_DGRAM | libc::SOCK_CLOEXEC, 0) };
+    if sock < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    for (name, info) in interfaces.iter_mut() {
+        let mut ssid = [0u8; IW_ESSID_MAX_SIZE + 1];
+        let mut req = request(name);
+        req.u.essid.pointer = ssid.as_mut_ptr() as *mut c_void;
+        req.u.essid.length = ssid.len() as u16;
+        if ioctl(sock, SIOCGIWESSID, &mut req) {
+            let len = unsafe { req.u.essid.length } as usize;
+            let ssid = String::from_utf8_lossy(&ssid[..len.min(IW_ESSID_MAX_SIZE)]);
+            info.ssid = Some(ssid.trim_end_matches('\0').to_owned()).filter(|s| !s.is_empty());
+        }
+        let mut req = request(name);
+        if ioctl(sock, SIOCGIWRATE, &mut req) {
+            let rate = unsafe { req.u.bitrate.value };
+            info.bitrate = (rate > 0).then_some(rate as u64);
+        }
+    }
+    unsafe { libc::close(sock) };
+    Ok(())
+}
+
+fn request(name: &str) -> iwreq {
+    let mut req: iwreq = unsafe { std::mem::zeroed() };
+    let len = name.len().min(libc::IFNAMSIZ - 1);
+    unsafe {
+        ptr::copy_nonoverlapping(
+            name.as_ptr(),
+            req.ifr_ifrn.ifrn_name.as_mut_ptr() as *mut u8,
+            len,
+        )
+    };
+    req
+}
+
+fn ioctl(sock: c_int, request: c_ulong, req: &mut iwreq) -> bool {
+    unsafe { libc::ioctl(sock, request as _, req as *mut iwreq) == 0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_wireless;
+
+    #[test]
+    fn wireless_table() {
+        let data = b"\
+Inter-| sta-|   Quality        |   Discarded packets               | Missed | WE
+ face | tus | link level noise |  nwid  crypt   frag  retry   misc | beacon | 22
+ wlan0: 0000   54.  -56.  -256        0      0      0      0     12        0
+wlp3s0: 0000   80    200  0           0      0      0      0      0        0
+  bad0: 0000
+";
+        let interfaces = parse_wireless(data);
+        assert_eq!(interfaces.len(), 2);
+        let wlan0 = &interfaces["wlan0"];
+        assert_eq!(wlan0.quality.map(f64::round), Some(77.0));
+        assert_eq!(wlan0.signal, Some(-56));
+        assert_eq!(wlan0.ssid, None);
+        // 质量超出上限时按 100% 显示，旧驱动的信号强度减去 256
+        let old = &interfaces["wlp3s0"];
+        assert_eq!(old.quality, Some(100.0));
+        assert_eq!(old.signal, Some(-56));
+        // 只有表头时没有无线接口
+        let header: Vec<&[u8]> = data.split_inclusive(|&b| b == b'\n').take(2).collect();
+        assert!(parse_wireless(&header.concat()).is_empty());
+    }
+}
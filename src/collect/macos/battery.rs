@@ -0,0 +1,50 @@
+use std::io;
+
+use super::iokit::PowerSources;
+use crate::shared::battery::{BatteryReading, BatteryStatus};
+
+// IOKit 电源列表中的内置电池，容量为百分比，剩余时间为分钟，系统还在估算时为 -1
+pub fn read() -> io::Result<Option<BatteryReading>> {
+    let Some(sources) = PowerSources::copy() else {
+        return Ok(None);
+    };
+    let Some(battery) = sources
+        .descriptions()
+        .into_iter()
+        .find(|desc| desc.string("Type").as_deref() == Some("InternalBattery"))
+    else {
+        return Ok(None);
+    };
+    let (Some(current), Some(max)) = (
+        battery.number("Current Capacity"),
+        battery.number("Max Capacity"),
+    ) else {
+        return Ok(None);
+    };
+    let percent = match max {
+        max if max > 0 => (current as f64 * 100.0 / max as f64).clamp(0.0, 100.0),
+        _ => 0.0,
+    };
+    let on_ac = battery.string("Power Source State").as_deref() == Some("AC Power");
+    let status = if battery.boolean("Is Charging") == Some(true) {
+        BatteryStatus::Charging
+    } else if !on_ac {
+        BatteryStatus::Discharging
+    } else if battery.boolean("Is Charged") == Some(true) || percent >= 100.0 {
+        BatteryStatus::Full
+    } else {
+        BatteryStatus::Unknown
+    };
+    let seconds = battery
+        .number("Time to Empty")
+        .filter(|minutes| *minutes > 0 && status == BatteryStatus::Discharging)
+        .map(|minutes| minutes as u64 * 60);
+
+    Ok(Some(BatteryReading {
+        percent,
+        status,
+        watts: None,
+        energy: None,
+        seconds,
+    }))
+}
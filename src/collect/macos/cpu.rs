@@ -0,0 +1,146 @@
+use std::{io, ptr};
+
+use libc::{
+    host_processor_info, mach_host_self, mach_msg_type_number_t, mach_task_self, natural_t,
+    processor_cpu_load_info, processor_info_array_t, vm_deallocate, CPU_STATE_IDLE, CPU_STATE_MAX,
+    CPU_STATE_NICE, CPU_STATE_SYSTEM, CPU_STATE_USER, KERN_SUCCESS, PROCESSOR_CPU_LOAD_INFO,
+};
+
+use super::{sysctl_by_name, sysctl_string};
+use crate::{
+    shared::cpu::{clean_model, state_stats, CpuInfo},
+    time_s,
+};
+
+// host_processor_info 返回的每个核心的节拍数：user, system, idle, nice
+type Ticks = [u64; CPU_STATE_MAX as usize];
+const STATE_NAMES: [&str; CPU_STATE_MAX as usize] = ["user", "system", "idle", "nice"];
+
+fn usage(last: &Ticks, now: &Ticks) -> f64 {
+    let total: u64 = now.iter().sum::<u64>().saturating_sub(last.iter().sum());
+    let idle = now[CPU_STATE_IDLE as usize].saturating_sub(last[CPU_STATE_IDLE as usize]);
+    if total == 0 {
+        return 0.0;
+    }
+    (total.saturating_sub(idle) as f64 * 100.0 / total as f64).clamp(0.0, 100.0)
+}
+
+fn read_ticks() -> io::Result<Vec<Ticks>> {
+    let mut cpu_count: natural_t = 0;
+    let mut info: processor_info_array_t = ptr::null_mut();
+    let mut info_count: mach_msg_type_number_t = 0;
+
+    let ret = unsafe {
+        host_processor_info(
+            mach_host_self(),
+            PROCESSOR_CPU_LOAD_INFO,
+            &mut cpu_count,
+            &mut info,
+            &mut info_count,
+        )
+    };
+    if ret != KERN_SUCCESS {
+        return Err(io::Error::other("host_processor_info failed"));
+    }
+
+    let loads = unsafe {
+        std::slice::from_raw_parts(info as *const processor_cpu_load_info, cpu_count as usize)
+    };
+    let ticks = loads
+        .iter()
+        .map(|load| {
+            let mut t: Ticks = [0; CPU_STATE_MAX as usize];
+            for state in [
+                CPU_STATE_USER,
+                CPU_STATE_SYSTEM,
+                CPU_STATE_IDLE,
+                CPU_STATE_NICE,
+            ] {
+                t[state as usize] = load.cpu_ticks[state as usize] as u64;
+            }
+            t
+        })
+        .collect();
+
+    unsafe {
+        vm_deallocate(
+            mach_task_self(),
+            info as libc::vm_address_t,
+            info_count as libc::vm_size_t * std::mem::size_of::<libc::integer_t>(),
+        )
+    };
+    Ok(ticks)
+}
+
+pub struct CpuCollector {
+    model: String,
+    last_total: Ticks,
+    last_cores: Vec<Ticks>,
+}
+
+impl Default for CpuCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuCollector {
+    pub fn new() -> Self {
+        CpuCollector {
+            model: sysctl_string("machdep.cpu.brand_string")
+                .map(|model| clean_model(&model))
+                .unwrap_or_default(),
+            last_total: [0; CPU_STATE_MAX as usize],
+            last_cores: Vec::new(),
+        }
+    }
+
+    pub fn collect(&mut self) -> io::Result<CpuInfo> {
+        let mut info = CpuInfo {
+            model: self.model.clone(),
+            ..Default::default()
+        };
+        let cores = read_ticks()?;
+
+        let mut total: Ticks = [0; CPU_STATE_MAX as usize];
+        for core in &cores {
+            for (sum, t) in total.iter_mut().zip(core.iter()) {
+                *sum += t;
+            }
+        }
+        info.cpu_percent = usage(&self.last_total, &total);
+        let delta = total
+            .iter()
+            .sum::<u64>()
+            .saturating_sub(self.last_total.iter().sum());
+        info.stats = state_stats(
+            info.cpu_percent,
+            &STATE_NAMES,
+            &self.last_total,
+            &total,
+            delta,
+        );
+        self.last_total = total;
+
+        info.core_percent = cores
+            .iter()
+            .enumerate()
+            .map(|(i, now)| match self.last_cores.get(i) {
+                Some(last) => usage(last, now),
+                None => usage(&[0; CPU_STATE_MAX as usize], now),
+            })
+            .collect();
+        self.last_cores = cores;
+
+        unsafe { libc::getloadavg(info.load_avg.as_mut_ptr(), 3) };
+        Ok(info)
+    }
+}
+
+// 系统启动以来的秒数
+pub fn uptime() -> u64 {
+    match sysctl_by_name::<libc::timeval>("kern.boottime") {
+        Some(boot) => time_s().saturating_sub(boot.tv_sec.max(0) as u64),
+        None => 0,
+    }
+}
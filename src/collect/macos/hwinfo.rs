@@ -0,0 +1,43 @@
+use libc::c_int;
+
+use super::sysctl_by_name;
+use crate::shared::hwinfo::{CacheInfo, HwInfo};
+
+fn cache(level: u32, cache_type: &str, name: &str, threads: usize) -> Option<CacheInfo> {
+    let size = sysctl_by_name::<u64>(name).filter(|&size| size > 0)?;
+    // hw.cacheconfig 给出共享每级缓存的逻辑 cpu 数
+    let shared = sysctl_by_name::<[u64; 10]>("hw.cacheconfig")
+        .and_then(|config| config.get(level as usize).copied())
+        .filter(|&n| n > 0)
+        .unwrap_or(1) as usize;
+    Some(CacheInfo {
+        level,
+        cache_type: cache_type.to_owned(),
+        size,
+        instances: (threads / shared).max(1),
+    })
+}
+
+pub fn collect() -> HwInfo {
+    let mut info = HwInfo {
+        sockets: sysctl_by_name::<c_int>("hw.packages").unwrap_or(1).max(1) as usize,
+        cores: sysctl_by_name::<c_int>("hw.physicalcpu")
+            .unwrap_or(0)
+            .max(0) as usize,
+        threads: sysctl_by_name::<c_int>("hw.logicalcpu").unwrap_or(0).max(0) as usize,
+        mem_total: sysctl_by_name::<u64>("hw.memsize").unwrap_or(0),
+        ..Default::default()
+    };
+
+    info.caches = [
+        (1, "Data", "hw.l1dcachesize"),
+        (1, "Instruction", "hw.l1icachesize"),
+        (2, "Unified", "hw.l2cachesize"),
+        (3, "Unified", "hw.l3cachesize"),
+    ]
+    .into_iter()
+    .filter_map(|(level, cache_type, name)| cache(level, cache_type, name, info.threads))
+    .collect();
+
+    info
+}
@@ -0,0 +1,285 @@
+// 电池和 SMC 传感器用到的少量 IOKit 和 CoreFoundation 接口，libc 中没有这些声明
+use std::ffi::{CStr, CString};
+
+use libc::{c_char, c_void, kern_return_t, mach_port_t, mach_task_self};
+
+type CFTypeRef = *const c_void;
+type CFIndex = isize;
+type IoObject = mach_port_t;
+
+const KCF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+const KCF_NUMBER_SINT64_TYPE: CFIndex = 4;
+// kIOMainPortDefault，老版本中称为 kIOMasterPortDefault
+const KIO_MAIN_PORT_DEFAULT: mach_port_t = 0;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFRelease(cf: CFTypeRef);
+    fn CFGetTypeID(cf: CFTypeRef) -> usize;
+    fn CFStringGetTypeID() -> usize;
+    fn CFNumberGetTypeID() -> usize;
+    fn CFBooleanGetTypeID() -> usize;
+    fn CFStringCreateWithCString(alloc: CFTypeRef, s: *const c_char, encoding: u32) -> CFTypeRef;
+    fn CFStringGetCString(s: CFTypeRef, buf: *mut c_char, size: CFIndex, encoding: u32) -> u8;
+    fn CFNumberGetValue(number: CFTypeRef, kind: CFIndex, value: *mut c_void) -> u8;
+    fn CFBooleanGetValue(boolean: CFTypeRef) -> u8;
+    fn CFDictionaryGetValue(dict: CFTypeRef, key: CFTypeRef) -> CFTypeRef;
+    fn CFArrayGetCount(array: CFTypeRef) -> CFIndex;
+    fn CFArrayGetValueAtIndex(array: CFTypeRef, index: CFIndex) -> CFTypeRef;
+}
+
+#[link(name = "IOKit", kind = "framework")]
+extern "C" {
+    fn IOPSCopyPowerSourcesInfo() -> CFTypeRef;
+    fn IOPSCopyPowerSourcesList(blob: CFTypeRef) -> CFTypeRef;
+    fn IOPSGetPowerSourceDescription(blob: CFTypeRef, source: CFTypeRef) -> CFTypeRef;
+    fn IOServiceMatching(name: *const c_char) -> CFTypeRef;
+    fn IOServiceGetMatchingService(main_port: mach_port_t, matching: CFTypeRef) -> IoObject;
+    fn IOServiceOpen(
+        service: IoObject,
+        task: mach_port_t,
+        kind: u32,
+        conn: *mut IoObject,
+    ) -> kern_return_t;
+    fn IOServiceClose(conn: IoObject) -> kern_return_t;
+    fn IOObjectRelease(object: IoObject) -> kern_return_t;
+    fn IOConnectCallStructMethod(
+        conn: IoObject,
+        selector: u32,
+        input: *const c_void,
+        input_size: usize,
+        output: *mut c_void,
+        output_size: *mut usize,
+    ) -> kern_return_t;
+}
+
+// 持有一个 Copy/Create 得到的 CF 对象，离开作用域时释放
+struct Owned(CFTypeRef);
+
+impl Owned {
+    fn new(cf: CFTypeRef) -> Option<Self> {
+        (!cf.is_null()).then_some(Owned(cf))
+    }
+}
+
+impl Drop for Owned {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.0) };
+    }
+}
+
+fn cf_string(text: &str) -> Option<Owned> {
+    let text = CString::new(text).ok()?;
+    Owned::new(unsafe {
+        CFStringCreateWithCString(std::ptr::null(), text.as_ptr(), KCF_STRING_ENCODING_UTF8)
+    })
+}
+
+// 电源描述字典中的一项，字典本身不归调用者所有
+#[derive(Clone, Copy)]
+pub struct Dict(CFTypeRef);
+
+impl Dict {
+    fn get(&self, key: &str) -> Option<CFTypeRef> {
+        let key = cf_string(key)?;
+        let value = unsafe { CFDictionaryGetValue(self.0, key.0) };
+        (!value.is_null()).then_some(value)
+    }
+
+    pub fn string(&self, key: &str) -> Option<String> {
+        let value = self.get(key)?;
+        if unsafe { CFGetTypeID(value) != CFStringGetTypeID() } {
+            return None;
+        }
+        let mut buf = [0 as c_char; 128];
+        let ok = unsafe {
+            CFStringGetCString(
+                value,
+                buf.as_mut_ptr(),
+                buf.len() as CFIndex,
+                KCF_STRING_ENCODING_UTF8,
+            )
+        };
+        (ok != 0).then(|| {
+            unsafe { CStr::from_ptr(buf.as_ptr()) }
+                .to_string_lossy()
+                .into_owned()
+        })
+    }
+
+    pub fn number(&self, key: &str) -> Option<i64> {
+        let value = self.get(key)?;
+        if unsafe { CFGetTypeID(value) != CFNumberGetTypeID() } {
+            return None;
+        }
+        let mut number = 0i64;
+        let ok = unsafe {
+            CFNumberGetValue(
+                value,
+                KCF_NUMBER_SINT64_TYPE,
+                &mut number as *mut i64 as *mut c_void,
+            )
+        };
+        (ok != 0).then_some(number)
+    }
+
+    pub fn boolean(&self, key: &str) -> Option<bool> {
+        let value = self.get(key)?;
+        if unsafe { CFGetTypeID(value) != CFBooleanGetTypeID() } {
+            return None;
+        }
+        Some(unsafe { CFBooleanGetValue(value) } != 0)
+    }
+}
+
+// IOPSCopyPowerSourcesInfo 返回的快照，描述字典的生命周期跟随快照
+pub struct PowerSources {
+    blob: Owned,
+    list: Owned,
+}
+
+impl PowerSources {
+    pub fn copy() -> Option<Self> {
+        let blob = Owned::new(unsafe { IOPSCopyPowerSourcesInfo() })?;
+        let list = Owned::new(unsafe { IOPSCopyPowerSourcesList(blob.0) })?;
+        Some(PowerSources { blob, list })
+    }
+
+    pub fn descriptions(&self) -> Vec<Dict> {
+        let count = unsafe { CFArrayGetCount(self.list.0) };
+        (0..count)
+            .filter_map(|i| {
+                let source = unsafe { CFArrayGetValueAtIndex(self.list.0, i) };
+                let desc = unsafe { IOPSGetPowerSourceDescription(self.blob.0, source) };
+                (!desc.is_null()).then_some(Dict(desc))
+            })
+            .collect()
+    }
+}
+
+// AppleSMC 用户客户端的请求和应答结构，与内核中的 SMCParamStruct 布局一致
+#[repr(C)]
+#[derive(Default)]
+struct SmcVersion {
+    major: u8,
+    minor: u8,
+    build: u8,
+    reserved: u8,
+    release: u16,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct SmcLimitData {
+    version: u16,
+    length: u16,
+    cpu_limit: u32,
+    gpu_limit: u32,
+    mem_limit: u32,
+}
+
+#[repr(C)]
+#[derive(Default, Clone, Copy)]
+struct SmcKeyInfo {
+    size: u32,
+    kind: u32,
+    attributes: u8,
+}
+
+#[repr(C)]
+#[derive(Default)]
+struct SmcParam {
+    key: u32,
+    version: SmcVersion,
+    limit: SmcLimitData,
+    info: SmcKeyInfo,
+    result: u8,
+    status: u8,
+    command: u8,
+    data32: u32,
+    bytes: [u8; 32],
+}
+
+// kSMCHandleYPCEvent，以及其中读取键信息和键值的命令
+const SMC_SELECTOR: u32 = 2;
+const SMC_READ_BYTES: u8 = 5;
+const SMC_READ_INFO: u8 = 9;
+
+// 四个字符的键名和类型按大端序组成一个整数
+fn four_cc(text: &str) -> u32 {
+    text.bytes().take(4).fold(0, |acc, b| (acc << 8) | b as u32)
+}
+
+// 打开的 AppleSMC 连接，没有 SMC 的机器上打开失败
+pub struct Smc {
+    conn: IoObject,
+}
+
+impl Smc {
+    pub fn open() -> Option<Self> {
+        let name = CString::new("AppleSMC").ok()?;
+        // IOServiceGetMatchingService 会消耗 matching 字典的引用
+        let service = unsafe {
+            IOServiceGetMatchingService(KIO_MAIN_PORT_DEFAULT, IOServiceMatching(name.as_ptr()))
+        };
+        if service == 0 {
+            return None;
+        }
+        let mut conn = 0;
+        let ret = unsafe { IOServiceOpen(service, mach_task_self(), 0, &mut conn) };
+        unsafe { IOObjectRelease(service) };
+        (ret == 0).then_some(Smc { conn })
+    }
+
+    fn call(&self, input: &SmcParam) -> Option<SmcParam> {
+        let mut output = SmcParam::default();
+        let mut size = std::mem::size_of::<SmcParam>();
+        let ret = unsafe {
+            IOConnectCallStructMethod(
+                self.conn,
+                SMC_SELECTOR,
+                input as *const SmcParam as *const c_void,
+                size,
+                &mut output as *mut SmcParam as *mut c_void,
+                &mut size,
+            )
+        };
+        (ret == 0 && output.result == 0).then_some(output)
+    }
+
+    // 按键的类型解码为数值，只支持风扇和计数用到的几种类型
+    pub fn read(&self, key: &str) -> Option<f64> {
+        let mut param = SmcParam {
+            key: four_cc(key),
+            command: SMC_READ_INFO,
+            ..Default::default()
+        };
+        let info = self.call(&param)?.info;
+        param.info = info;
+        param.command = SMC_READ_BYTES;
+        let bytes = self.call(&param)?.bytes;
+        let size = (info.size as usize).min(bytes.len());
+        let bytes = &bytes[..size];
+        match info.kind {
+            kind if kind == four_cc("ui8 ") => bytes.first().map(|&b| b as f64),
+            kind if kind == four_cc("ui16") => {
+                Some(u16::from_be_bytes(bytes.try_into().ok()?) as f64)
+            }
+            // 无符号定点数，低 2 位为小数
+            kind if kind == four_cc("fpe2") => {
+                Some(u16::from_be_bytes(bytes.try_into().ok()?) as f64 / 4.0)
+            }
+            // Apple Silicon 上为小端序的单精度浮点数
+            kind if kind == four_cc("flt ") => {
+                Some(f32::from_le_bytes(bytes.try_into().ok()?) as f64)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Drop for Smc {
+    fn drop(&mut self) {
+        unsafe { IOServiceClose(self.conn) };
+    }
+}
@@ -0,0 +1,47 @@
+use std::io;
+
+use libc::{
+    host_statistics64, mach_host_self, mach_msg_type_number_t, vm_statistics64, xsw_usage,
+    HOST_VM_INFO64, HOST_VM_INFO64_COUNT, KERN_SUCCESS,
+};
+
+use super::sysctl_by_name;
+use crate::shared::mem::MemInfo;
+
+pub fn collect() -> io::Result<MemInfo> {
+    let mut info = MemInfo {
+        total: sysctl_by_name::<u64>("hw.memsize").unwrap_or(0),
+        ..Default::default()
+    };
+    let page_size = sysctl_by_name::<u32>("hw.pagesize").unwrap_or(4096) as u64;
+
+    let mut stats: vm_statistics64 = unsafe { std::mem::zeroed() };
+    let mut count: mach_msg_type_number_t = HOST_VM_INFO64_COUNT;
+    let ret = unsafe {
+        host_statistics64(
+            mach_host_self(),
+            HOST_VM_INFO64,
+            &mut stats as *mut vm_statistics64 as *mut libc::integer_t,
+            &mut count,
+        )
+    };
+    if ret != KERN_SUCCESS {
+        return Err(io::Error::other("host_statistics64 failed"));
+    }
+
+    // 与活动监视器一致：已用 = 活跃 + 联动 + 压缩
+    info.used =
+        (stats.active_count as u64 + stats.wire_count as u64 + stats.compressor_page_count as u64)
+            * page_size;
+    info.free = stats.free_count as u64 * page_size;
+    info.cached = (stats.purgeable_count as u64 + stats.external_page_count as u64) * page_size;
+    info.available = info.total.saturating_sub(info.used);
+
+    if let Some(swap) = sysctl_by_name::<xsw_usage>("vm.swapusage") {
+        info.swap_total = swap.xsu_total;
+        info.swap_used = swap.xsu_used;
+        info.swap_free = swap.xsu_avail;
+    }
+
+    Ok(info)
+}
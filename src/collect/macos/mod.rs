@@ -0,0 +1,60 @@
+use std::{ffi::CString, ptr};
+
+pub mod battery;
+pub mod cpu;
+pub mod disk;
+pub mod hwinfo;
+mod iokit;
+pub mod klog;
+pub mod limits;
+pub mod mem;
+pub mod net;
+pub mod proc;
+pub mod sensors;
+pub mod socket;
+
+// 读取定长的 sysctl 值，T 必须是可以按位初始化为 0 的 C 结构体或整数
+pub fn sysctl_by_name<T: Copy>(name: &str) -> Option<T> {
+    let c_name = CString::new(name).ok()?;
+    let mut value: T = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<T>();
+    let ret = unsafe {
+        libc::sysctlbyname(
+            c_name.as_ptr(),
+            &mut value as *mut T as *mut libc::c_void,
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    (ret == 0).then_some(value)
+}
+
+// 读取字符串类型的 sysctl 值，先查询长度再读取
+pub fn sysctl_string(name: &str) -> Option<String> {
+    let c_name = CString::new(name).ok()?;
+    let mut len = 0;
+    let ret = unsafe {
+        libc::sysctlbyname(
+            c_name.as_ptr(),
+            ptr::null_mut(),
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 {
+        return None;
+    }
+    let mut buf = vec![0u8; len];
+    let ret = unsafe {
+        libc::sysctlbyname(
+            c_name.as_ptr(),
+            buf.as_mut_ptr() as *mut libc::c_void,
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    (ret == 0).then(|| String::from_utf8_lossy(&buf[..len]).into_owned())
+}
@@ -0,0 +1,92 @@
+use std::{collections::HashMap, ffi::CStr, io, ptr, time::Instant};
+
+use libc::{freeifaddrs, getifaddrs, if_data, ifaddrs, AF_LINK};
+
+use crate::shared::net::{NetInfo, NetStat};
+
+pub struct NetCollector {
+    // if_data 中的计数器只有 32 位，这里记录上次的原始值并自行累加
+    last_raw: HashMap<String, (u32, u32)>,
+    totals: HashMap<String, (u64, u64)>,
+    last_time: Option<Instant>,
+}
+
+impl Default for NetCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetCollector {
+    pub fn new() -> Self {
+        NetCollector {
+            last_raw: HashMap::new(),
+            totals: HashMap::new(),
+            last_time: None,
+        }
+    }
+
+    pub fn collect(&mut self) -> io::Result<NetInfo> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_time
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut addrs: *mut ifaddrs = ptr::null_mut();
+        if unsafe { getifaddrs(&mut addrs) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut info = NetInfo::default();
+        let mut cur = addrs;
+        while !cur.is_null() {
+            let ifa = unsafe { &*cur };
+            cur = ifa.ifa_next;
+            if ifa.ifa_addr.is_null()
+                || ifa.ifa_data.is_null()
+                || unsafe { (*ifa.ifa_addr).sa_family } as i32 != AF_LINK
+            {
+                continue;
+            }
+
+            let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
+                .to_string_lossy()
+                .into_owned();
+            let data = unsafe { &*(ifa.ifa_data as *const if_data) };
+            let raw = (data.ifi_ibytes, data.ifi_obytes);
+
+            let (rx_delta, tx_delta) = match self.last_raw.get(&name) {
+                Some(last) => (
+                    raw.0.wrapping_sub(last.0) as u64,
+                    raw.1.wrapping_sub(last.1) as u64,
+                ),
+                None => (0, 0),
+            };
+            self.last_raw.insert(name.clone(), raw);
+
+            let total = self
+                .totals
+                .entry(name.clone())
+                .or_insert((raw.0 as u64, raw.1 as u64));
+            total.0 += rx_delta;
+            total.1 += tx_delta;
+
+            let mut stat = NetStat {
+                name,
+                rx_bytes: total.0,
+                tx_bytes: total.1,
+                ..Default::default()
+            };
+            if elapsed > 0.0 {
+                stat.rx_rate = (rx_delta as f64 / elapsed) as u64;
+                stat.tx_rate = (tx_delta as f64 / elapsed) as u64;
+            }
+            info.interfaces.push(stat);
+        }
+        unsafe { freeifaddrs(addrs) };
+
+        self.last_time = Some(now);
+        Ok(info)
+    }
+}
@@ -0,0 +1,298 @@
+use std::{collections::HashMap, ffi::CStr, io, mem, ptr, time::Instant};
+
+use libc::{
+    c_int, c_void, mach_timebase_info, proc_listallpids, proc_pidinfo, proc_taskallinfo, CTL_KERN,
+    KERN_ARGMAX, KERN_PROCARGS2, PROC_PIDTASKALLINFO,
+};
+
+use super::sysctl_by_name;
+use crate::shared::proc::{IoPriority, ProcFd, ProcInfo, ProcLimit, ProcStack, ProcThread};
+
+// 与 Linux 保持一致，cpu_time 以 1/100 秒为单位
+const TICKS_PER_SEC: u64 = 100;
+
+fn user_name(uid: u32) -> Option<String> {
+    let pw = unsafe { libc::getpwuid(uid) };
+    if pw.is_null() {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr((*pw).pw_name) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+// kern.argmax 是 KERN_PROCARGS2 返回数据的上限
+fn arg_max() -> usize {
+    let mut mib = [CTL_KERN, KERN_ARGMAX];
+    let mut arg_max: c_int = 0;
+    let mut size = mem::size_of::<c_int>();
+    unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            2,
+            &mut arg_max as *mut c_int as *mut c_void,
+            &mut size,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    arg_max.max(4096) as usize
+}
+
+// KERN_PROCARGS2 的布局：argc，可执行文件路径，若干 '\0' 填充，然后是 argv 和环境变量
+fn read_procargs(pid: i32, arg_max: usize) -> io::Result<(usize, Vec<u8>)> {
+    let mut buf = vec![0u8; arg_max];
+    let mut size = buf.len();
+    let mut mib = [CTL_KERN, KERN_PROCARGS2, pid];
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            3,
+            buf.as_mut_ptr() as *mut c_void,
+            &mut size,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if size < mem::size_of::<c_int>() {
+        return Err(io::ErrorKind::InvalidData.into());
+    }
+    let argc = i32::from_ne_bytes([buf[0], buf[1], buf[2], buf[3]]).max(0) as usize;
+    buf.truncate(size);
+    buf.drain(..mem::size_of::<c_int>());
+    Ok((argc, buf))
+}
+
+fn read_cmdline(pid: i32, arg_max: usize) -> String {
+    let Ok((argc, buf)) = read_procargs(pid, arg_max) else {
+        return String::new();
+    };
+    let mut rest = buf.split(|&b| b == 0);
+    // 跳过可执行文件路径
+    rest.next();
+    rest.filter(|arg| !arg.is_empty())
+        .take(argc)
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn state_char(status: u32) -> char {
+    match status {
+        1 => 'I',
+        2 => 'R',
+        3 => 'S',
+        4 => 'T',
+        5 => 'Z',
+        _ => '?',
+    }
+}
+
+fn c_name(raw: &[libc::c_char]) -> String {
+    let bytes: Vec<u8> = raw
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+pub struct ProcCollector {
+    last_times: HashMap<i32, u64>,
+    last_collect: Option<Instant>,
+    users: HashMap<u32, String>,
+    // mach 绝对时间到纳秒的换算比例
+    timebase: (u32, u32),
+    arg_max: usize,
+    core_count: usize,
+}
+
+impl Default for ProcCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcCollector {
+    pub fn new() -> Self {
+        let mut timebase: mach_timebase_info = unsafe { mem::zeroed() };
+        unsafe { libc::mach_timebase_info(&mut timebase) };
+        ProcCollector {
+            last_times: HashMap::new(),
+            last_collect: None,
+            users: HashMap::new(),
+            timebase: (timebase.numer.max(1), timebase.denom.max(1)),
+            arg_max: arg_max(),
+            core_count: sysctl_by_name::<c_int>("hw.logicalcpu").unwrap_or(1).max(1) as usize,
+        }
+    }
+
+    fn read_proc(&mut self, pid: i32) -> Option<ProcInfo> {
+        let mut task: proc_taskallinfo = unsafe { mem::zeroed() };
+        let size = mem::size_of::<proc_taskallinfo>() as c_int;
+        let ret = unsafe {
+            proc_pidinfo(
+                pid,
+                PROC_PIDTASKALLINFO,
+                0,
+                &mut task as *mut proc_taskallinfo as *mut c_void,
+                size,
+            )
+        };
+        if ret != size {
+            return None;
+        }
+
+        let uid = task.pbsd.pbi_uid;
+        let user = self
+            .users
+            .entry(uid)
+            .or_insert_with(|| user_name(uid).unwrap_or_else(|| uid.to_string()))
+            .clone();
+
+        let name = match c_name(&task.pbsd.pbi_name) {
+            name if name.is_empty() => c_name(&task.pbsd.pbi_comm),
+            name => name,
+        };
+        let cpu_ns = (task.ptinfo.pti_total_user + task.ptinfo.pti_total_system)
+            * self.timebase.0 as u64
+            / self.timebase.1 as u64;
+
+        Some(ProcInfo {
+            pid,
+            ppid: task.pbsd.pbi_ppid as i32,
+            name,
+            cmd: read_cmdline(pid, self.arg_max),
+            user,
+            uid,
+            state: state_char(task.pbsd.pbi_status),
+            threads: task.ptinfo.pti_threadnum.max(0) as u32,
+            nice: task.pbsd.pbi_nice,
+            mem: task.ptinfo.pti_resident_size,
+            cpu_p: 0.0,
+            cpu_time: cpu_ns / (1_000_000_000 / TICKS_PER_SEC),
+            container: false,
+            io_bytes: None,
+            io_rate: None,
+        })
+    }
+
+    pub fn collect(&mut self, per_core: bool) -> io::Result<Vec<ProcInfo>> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_collect
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let count = unsafe { proc_listallpids(ptr::null_mut(), 0) };
+        if count <= 0 {
+            return Err(io::Error::last_os_error());
+        }
+        // 预留一些空间给两次调用之间新建的进程
+        let mut pids = vec![0 as c_int; count as usize + 32];
+        let count = unsafe {
+            proc_listallpids(
+                pids.as_mut_ptr() as *mut c_void,
+                (pids.len() * mem::size_of::<c_int>()) as c_int,
+            )
+        };
+        pids.truncate(count.max(0) as usize);
+
+        let mut procs = Vec::new();
+        let mut times = HashMap::new();
+        for pid in pids.into_iter().filter(|&pid| pid > 0) {
+            let Some(mut proc) = self.read_proc(pid) else {
+                continue;
+            };
+            if let Some(last) = self.last_times.get(&pid) {
+                if elapsed > 0.0 {
+                    let ticks = proc.cpu_time.saturating_sub(*last) as f64;
+                    proc.cpu_p = ticks / TICKS_PER_SEC as f64 / elapsed * 100.0;
+                    if !per_core {
+                        proc.cpu_p /= self.core_count as f64;
+                    }
+                }
+            }
+            times.insert(pid, proc.cpu_time);
+            procs.push(proc);
+        }
+
+        self.last_times = times;
+        self.last_collect = Some(now);
+        Ok(procs)
+    }
+}
+
+// I/O 调度优先级是 Linux 特有的接口
+pub fn get_ioprio(_pid: i32) -> io::Result<IoPriority> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn set_ioprio(_pid: i32, _prio: IoPriority) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+// 降低 nice 值（提高优先级）需要 root，修改其它用户的进程返回 EPERM
+pub fn set_nice(pid: i32, nice: i32) -> io::Result<()> {
+    let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// 向进程发送信号，其它用户的进程需要 root 权限
+pub fn send_signal(pid: i32, signal: i32) -> io::Result<()> {
+    if unsafe { libc::kill(pid, signal) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub fn get_pss(_pid: i32) -> io::Result<u64> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_limits(_pid: i32) -> io::Result<Vec<ProcLimit>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_stack(_pid: i32) -> io::Result<ProcStack> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_fds(_pid: i32) -> io::Result<Vec<ProcFd>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+// 环境变量紧跟在 argv 之后，其它用户的进程需要 root 权限
+pub fn get_environ(pid: i32) -> io::Result<Vec<String>> {
+    let (argc, buf) = read_procargs(pid, arg_max())?;
+    let mut rest = buf.split(|&b| b == 0);
+    // 跳过可执行文件路径
+    rest.next();
+    let mut vars: Vec<String> = rest
+        .filter(|arg| !arg.is_empty())
+        .skip(argc)
+        .map(|var| String::from_utf8_lossy(var).into_owned())
+        .collect();
+    vars.sort();
+    Ok(vars)
+}
+
+pub fn get_threads(_pid: i32) -> io::Result<Vec<ProcThread>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_affinity(_pid: i32) -> io::Result<Vec<usize>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn set_affinity(_pid: i32, _cpus: &[usize]) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,22 @@
+use super::iokit::Smc;
+use crate::shared::sensors::{Sensor, SensorKind, SensorList};
+
+// SMC 中风扇的个数为 FNum，第 N 个风扇的转速为 FNAc。电压的键名因机型而异，不读取
+pub fn collect() -> SensorList {
+    let mut list = SensorList::default();
+    let Some(smc) = Smc::open() else {
+        return list;
+    };
+    let fans = smc.read("FNum").unwrap_or(0.0) as usize;
+    for index in 0..fans {
+        if let Some(rpm) = smc.read(&format!("F{}Ac", index)) {
+            list.sensors.push(Sensor {
+                chip: "smc".to_owned(),
+                label: format!("fan{}", index + 1),
+                kind: SensorKind::Fan,
+                value: rpm,
+            });
+        }
+    }
+    list
+}
@@ -0,0 +1,8 @@
+use std::io;
+
+use crate::shared::socket::SocketList;
+
+// 套接字列表目前只在 Linux 上从 /proc/net 读取
+pub fn collect() -> io::Result<SocketList> {
+    Err(io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,35 @@
+// 平台无关的采集接口，由各平台的采集器实现
+pub mod collector;
+
+// 每个平台实现相同的模块接口：cpu、mem、disk、net、proc、socket、sensors、klog、hwinfo、limits
+#[cfg(target_os = "linux")]
+pub mod linux;
+
+#[cfg(target_os = "linux")]
+pub use linux::*;
+
+// libc 将 mach 接口标记为弃用（建议使用 mach2），这里只用到其中很少的几个
+#[cfg(target_os = "macos")]
+#[allow(deprecated)]
+pub mod macos;
+
+#[cfg(target_os = "macos")]
+pub use macos::*;
+
+#[cfg(target_os = "freebsd")]
+pub mod freebsd;
+
+#[cfg(target_os = "freebsd")]
+pub use freebsd::*;
+
+#[cfg(target_os = "netbsd")]
+pub mod netbsd;
+
+#[cfg(target_os = "netbsd")]
+pub use netbsd::*;
+
+#[cfg(target_os = "openbsd")]
+pub mod openbsd;
+
+#[cfg(target_os = "openbsd")]
+pub use openbsd::*;
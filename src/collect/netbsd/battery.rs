@@ -0,0 +1,8 @@
+use std::io;
+
+use crate::shared::battery::BatteryReading;
+
+// NetBSD 需要通过 envsys(4) 的 proplib 接口读取电池信息，暂不提供
+pub fn read() -> io::Result<Option<BatteryReading>> {
+    Ok(None)
+}
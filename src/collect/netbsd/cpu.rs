@@ -0,0 +1,117 @@
+use std::io;
+
+use libc::{c_int, CTL_HW, CTL_KERN, KERN_CP_TIME};
+
+use super::{sysctl_by_name, sysctl_vec};
+use crate::{
+    shared::cpu::{clean_model, state_stats, CpuInfo},
+    time_s,
+};
+
+// kern.cp_time.<n> 中每个核心的节拍数：user, nice, sys, intr, idle
+const CPUSTATES: usize = 5;
+const CP_IDLE: usize = 4;
+const STATE_NAMES: [&str; CPUSTATES] = ["user", "nice", "system", "irq", "idle"];
+type Ticks = [u64; CPUSTATES];
+// sys/sysctl.h 中的 hw.model，libc 未提供
+const HW_MODEL: c_int = 2;
+
+fn usage(last: &Ticks, now: &Ticks) -> f64 {
+    let total: u64 = now.iter().sum::<u64>().saturating_sub(last.iter().sum());
+    let idle = now[CP_IDLE].saturating_sub(last[CP_IDLE]);
+    if total == 0 {
+        return 0.0;
+    }
+    (total.saturating_sub(idle) as f64 * 100.0 / total as f64).clamp(0.0, 100.0)
+}
+
+fn read_ticks(core_count: usize) -> io::Result<Vec<Ticks>> {
+    let cores: Vec<Ticks> = (0..core_count as c_int)
+        .filter_map(|i| sysctl_vec::<u64>(&[CTL_KERN, KERN_CP_TIME, i]))
+        .filter(|raw| raw.len() >= CPUSTATES)
+        .map(|raw| {
+            let mut t: Ticks = [0; CPUSTATES];
+            t.copy_from_slice(&raw[..CPUSTATES]);
+            t
+        })
+        .collect();
+    if cores.is_empty() {
+        return Err(io::Error::other("sysctl kern.cp_time failed"));
+    }
+    Ok(cores)
+}
+
+pub struct CpuCollector {
+    model: String,
+    core_count: usize,
+    last_total: Ticks,
+    last_cores: Vec<Ticks>,
+}
+
+impl Default for CpuCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuCollector {
+    pub fn new() -> Self {
+        CpuCollector {
+            model: sysctl_vec::<u8>(&[CTL_HW, HW_MODEL])
+                .map(|model| clean_model(&String::from_utf8_lossy(&model)))
+                .unwrap_or_default(),
+            core_count: sysctl_by_name::<c_int>("hw.ncpu").unwrap_or(1).max(1) as usize,
+            last_total: [0; CPUSTATES],
+            last_cores: Vec::new(),
+        }
+    }
+
+    pub fn collect(&mut self) -> io::Result<CpuInfo> {
+        let mut info = CpuInfo {
+            model: self.model.clone(),
+            ..Default::default()
+        };
+        let cores = read_ticks(self.core_count)?;
+
+        let mut total: Ticks = [0; CPUSTATES];
+        for core in &cores {
+            for (sum, t) in total.iter_mut().zip(core.iter()) {
+                *sum += t;
+            }
+        }
+        info.cpu_percent = usage(&self.last_total, &total);
+        let delta = total
+            .iter()
+            .sum::<u64>()
+            .saturating_sub(self.last_total.iter().sum());
+        info.stats = state_stats(
+            info.cpu_percent,
+            &STATE_NAMES,
+            &self.last_total,
+            &total,
+            delta,
+        );
+        self.last_total = total;
+
+        info.core_percent = cores
+            .iter()
+            .enumerate()
+            .map(|(i, now)| match self.last_cores.get(i) {
+                Some(last) => usage(last, now),
+                None => usage(&[0; CPUSTATES], now),
+            })
+            .collect();
+        self.last_cores = cores;
+
+        unsafe { libc::getloadavg(info.load_avg.as_mut_ptr(), 3) };
+        Ok(info)
+    }
+}
+
+// 系统启动以来的秒数
+pub fn uptime() -> u64 {
+    match sysctl_by_name::<libc::timeval>("kern.boottime") {
+        Some(boot) => time_s().saturating_sub(boot.tv_sec.max(0) as u64),
+        None => 0,
+    }
+}
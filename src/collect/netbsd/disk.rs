@@ -0,0 +1,46 @@
+use std::{ffi::CStr, io};
+
+use crate::shared::disk::{self, DiskInfo, DiskStat};
+
+// 读写速率需要 hw.iostats，这里只提供容量
+#[derive(Default)]
+pub struct DiskCollector;
+
+impl DiskCollector {
+    pub fn new() -> Self {
+        DiskCollector
+    }
+
+    // 硬盘温度目前只在 Linux 上读取
+    pub fn collect(&mut self, only_physical: bool, _temps: bool) -> io::Result<DiskInfo> {
+        let mut mounts: *mut libc::statvfs = std::ptr::null_mut();
+        // 结果由 libc 管理，下次调用时复用，不需要释放
+        let count = unsafe { libc::getmntinfo(&mut mounts, libc::MNT_NOWAIT) };
+        if count <= 0 {
+            return Err(io::Error::last_os_error());
+        }
+        let mounts = unsafe { std::slice::from_raw_parts(mounts, count as usize) };
+        let mut info = DiskInfo::default();
+        for st in mounts {
+            let text = |raw: &[libc::c_char]| {
+                unsafe { CStr::from_ptr(raw.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned()
+            };
+            let (device, mount) = (text(&st.f_mntfromname), text(&st.f_mntonname));
+            if only_physical && !disk::is_physical(&device, &text(&st.f_fstypename)) {
+                continue;
+            }
+            let bsize = st.f_frsize;
+            info.push(DiskStat::new(
+                device,
+                mount,
+                st.f_blocks * bsize,
+                st.f_blocks.saturating_sub(st.f_bfree) * bsize,
+                st.f_bavail * bsize,
+            ));
+        }
+        info.sort();
+        Ok(info)
+    }
+}
@@ -0,0 +1,19 @@
+use libc::c_int;
+
+use super::sysctl_by_name;
+use crate::shared::hwinfo::HwInfo;
+
+// NetBSD 没有统一的拓扑和缓存大小接口，这里只给出 cpu 数量和内存总量
+pub fn collect() -> HwInfo {
+    let threads = sysctl_by_name::<c_int>("hw.ncpuonline")
+        .or_else(|| sysctl_by_name::<c_int>("hw.ncpu"))
+        .unwrap_or(0)
+        .max(0) as usize;
+    HwInfo {
+        sockets: 1,
+        cores: threads,
+        threads,
+        mem_total: sysctl_by_name::<u64>("hw.physmem64").unwrap_or(0),
+        ..Default::default()
+    }
+}
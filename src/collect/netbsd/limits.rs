@@ -0,0 +1,6 @@
+use crate::shared::limits::SysLimits;
+
+// NetBSD 只能通过遍历 kern.file2 统计打开的文件数，暂不提供
+pub fn collect() -> SysLimits {
+    SysLimits::default()
+}
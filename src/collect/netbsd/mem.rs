@@ -0,0 +1,52 @@
+use std::io;
+
+use super::sysctl_by_name;
+use crate::shared::mem::MemInfo;
+
+// vm.uvmexp2 返回的 struct uvmexp_sysctl 全部由 int64_t 组成，这里只取用到的前缀字段
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UvmExp {
+    pagesize: i64,
+    pagemask: i64,
+    pageshift: i64,
+    npages: i64,
+    free: i64,
+    active: i64,
+    inactive: i64,
+    paging: i64,
+    wired: i64,
+    zeropages: i64,
+    reserve_pagedaemon: i64,
+    reserve_kernel: i64,
+    freemin: i64,
+    freetarg: i64,
+    inactarg: i64,
+    wiredmax: i64,
+    nswapdev: i64,
+    swpages: i64,
+    swpginuse: i64,
+    // 为结构体后续字段预留空间，避免内核因缓冲区过小返回 ENOMEM
+    rest: [i64; 128],
+}
+
+pub fn collect() -> io::Result<MemInfo> {
+    let uvm = sysctl_by_name::<UvmExp>("vm.uvmexp2")
+        .ok_or_else(|| io::Error::other("sysctl vm.uvmexp2 failed"))?;
+    let page_size = uvm.pagesize.max(0) as u64;
+    let pages = |n: i64| n.max(0) as u64 * page_size;
+
+    let mut info = MemInfo {
+        total: sysctl_by_name::<u64>("hw.physmem64").unwrap_or_else(|| pages(uvm.npages)),
+        // 与 top(1) 一致：已用 = 活跃 + 联动，非活跃页可以被回收
+        used: pages(uvm.active) + pages(uvm.wired),
+        free: pages(uvm.free),
+        cached: pages(uvm.inactive),
+        swap_total: pages(uvm.swpages),
+        swap_used: pages(uvm.swpginuse),
+        ..Default::default()
+    };
+    info.available = info.total.saturating_sub(info.used);
+    info.swap_free = info.swap_total.saturating_sub(info.swap_used);
+    Ok(info)
+}
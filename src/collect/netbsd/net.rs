@@ -0,0 +1,100 @@
+use std::{collections::HashMap, ffi::CStr, io, ptr, time::Instant};
+
+use libc::{freeifaddrs, getifaddrs, ifaddrs, timespec, AF_LINK};
+
+use crate::shared::net::{NetInfo, NetStat};
+
+// net/if.h 中的 struct if_data，libc 未提供 NetBSD 版本
+#[repr(C)]
+struct IfData {
+    ifi_type: u8,
+    ifi_addrlen: u8,
+    ifi_hdrlen: u8,
+    ifi_link_state: i32,
+    ifi_mtu: u64,
+    ifi_metric: u64,
+    ifi_baudrate: u64,
+    ifi_ipackets: u64,
+    ifi_ierrors: u64,
+    ifi_opackets: u64,
+    ifi_oerrors: u64,
+    ifi_collisions: u64,
+    ifi_ibytes: u64,
+    ifi_obytes: u64,
+    ifi_imcasts: u64,
+    ifi_omcasts: u64,
+    ifi_iqdrops: u64,
+    ifi_noproto: u64,
+    ifi_lastchange: timespec,
+}
+
+pub struct NetCollector {
+    last: HashMap<String, (u64, u64)>,
+    last_time: Option<Instant>,
+}
+
+impl Default for NetCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl NetCollector {
+    pub fn new() -> Self {
+        NetCollector {
+            last: HashMap::new(),
+            last_time: None,
+        }
+    }
+
+    pub fn collect(&mut self) -> io::Result<NetInfo> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_time
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        let mut addrs: *mut ifaddrs = ptr::null_mut();
+        if unsafe { getifaddrs(&mut addrs) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        let mut info = NetInfo::default();
+        let mut current = HashMap::new();
+        let mut cur = addrs;
+        while !cur.is_null() {
+            let ifa = unsafe { &*cur };
+            cur = ifa.ifa_next;
+            if ifa.ifa_addr.is_null()
+                || ifa.ifa_data.is_null()
+                || unsafe { (*ifa.ifa_addr).sa_family } as i32 != AF_LINK
+            {
+                continue;
+            }
+
+            let name = unsafe { CStr::from_ptr(ifa.ifa_name) }
+                .to_string_lossy()
+                .into_owned();
+            let data = unsafe { &*(ifa.ifa_data as *const IfData) };
+            let mut stat = NetStat {
+                name: name.clone(),
+                rx_bytes: data.ifi_ibytes,
+                tx_bytes: data.ifi_obytes,
+                ..Default::default()
+            };
+            if let Some(&(rx, tx)) = self.last.get(&name) {
+                if elapsed > 0.0 {
+                    stat.rx_rate = (stat.rx_bytes.saturating_sub(rx) as f64 / elapsed) as u64;
+                    stat.tx_rate = (stat.tx_bytes.saturating_sub(tx) as f64 / elapsed) as u64;
+                }
+            }
+            current.insert(name, (stat.rx_bytes, stat.tx_bytes));
+            info.interfaces.push(stat);
+        }
+        unsafe { freeifaddrs(addrs) };
+
+        self.last = current;
+        self.last_time = Some(now);
+        Ok(info)
+    }
+}
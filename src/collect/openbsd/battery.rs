@@ -0,0 +1,8 @@
+use std::io;
+
+use crate::shared::battery::BatteryReading;
+
+// OpenBSD 需要通过 /dev/apm 的 ioctl 读取电池信息，暂不提供
+pub fn read() -> io::Result<Option<BatteryReading>> {
+    Ok(None)
+}
@@ -0,0 +1,107 @@
+use std::io;
+
+use libc::{c_int, CTL_HW, CTL_KERN, KERN_BOOTTIME, KERN_CPTIME2};
+
+use super::{ncpu, sysctl_value, sysctl_vec, HW_MODEL};
+use crate::{
+    shared::cpu::{clean_model, state_stats, CpuInfo},
+    time_s,
+};
+
+// kern.cptime2.<n> 中每个核心的节拍数：user, nice, sys, spin, intr, idle
+const CPUSTATES: usize = 6;
+const CP_IDLE: usize = 5;
+const STATE_NAMES: [&str; CPUSTATES] = ["user", "nice", "system", "spin", "irq", "idle"];
+type Ticks = [u64; CPUSTATES];
+
+fn usage(last: &Ticks, now: &Ticks) -> f64 {
+    let total: u64 = now.iter().sum::<u64>().saturating_sub(last.iter().sum());
+    let idle = now[CP_IDLE].saturating_sub(last[CP_IDLE]);
+    if total == 0 {
+        return 0.0;
+    }
+    (total.saturating_sub(idle) as f64 * 100.0 / total as f64).clamp(0.0, 100.0)
+}
+
+pub struct CpuCollector {
+    model: String,
+    core_count: usize,
+    last_total: Ticks,
+    last_cores: Vec<Ticks>,
+}
+
+impl Default for CpuCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CpuCollector {
+    pub fn new() -> Self {
+        CpuCollector {
+            model: sysctl_vec::<u8>(&[CTL_HW, HW_MODEL])
+                .map(|model| clean_model(&String::from_utf8_lossy(&model)))
+                .unwrap_or_default(),
+            core_count: ncpu(),
+            last_total: [0; CPUSTATES],
+            last_cores: Vec::new(),
+        }
+    }
+
+    pub fn collect(&mut self) -> io::Result<CpuInfo> {
+        let mut info = CpuInfo {
+            model: self.model.clone(),
+            ..Default::default()
+        };
+        // 被禁用的核心（例如 hw.smt=0 时的超线程）返回 ENODEV，按全 0 处理以保持编号
+        let cores: Vec<Option<Ticks>> = (0..self.core_count as c_int)
+            .map(|i| sysctl_value::<Ticks>(&[CTL_KERN, KERN_CPTIME2, i]))
+            .collect();
+        info.offline = (0..cores.len()).filter(|&i| cores[i].is_none()).collect();
+        let cores: Vec<Ticks> = cores.into_iter().map(Option::unwrap_or_default).collect();
+        if cores.iter().all(|t| t.iter().all(|&v| v == 0)) {
+            return Err(io::Error::other("sysctl kern.cptime2 failed"));
+        }
+
+        let mut total: Ticks = [0; CPUSTATES];
+        for core in &cores {
+            for (sum, t) in total.iter_mut().zip(core.iter()) {
+                *sum += t;
+            }
+        }
+        info.cpu_percent = usage(&self.last_total, &total);
+        let delta = total
+            .iter()
+            .sum::<u64>()
+            .saturating_sub(self.last_total.iter().sum());
+        info.stats = state_stats(
+            info.cpu_percent,
+            &STATE_NAMES,
+            &self.last_total,
+            &total,
+            delta,
+        );
+        self.last_total = total;
+
+        info.core_percent = cores
+            .iter()
+            .enumerate()
+            .map(|(i, now)| match self.last_cores.get(i) {
+                Some(last) => usage(last, now),
+                None => usage(&[0; CPUSTATES], now),
+            })
+            .collect();
+        self.last_cores = cores;
+
+        unsafe { libc::getloadavg(info.load_avg.as_mut_ptr(), 3) };
+        Ok(info)
+    }
+}
+
+// 系统启动以来的秒数
+pub fn uptime() -> u64 {
+    match sysctl_value::<libc::timeval>(&[CTL_KERN, KERN_BOOTTIME]) {
+        Some(boot) => time_s().saturating_sub(boot.tv_sec.max(0) as u64),
+        None => 0,
+    }
+}
@@ -0,0 +1,18 @@
+use libc::{c_int, CTL_HW};
+
+use super::{ncpu, sysctl_value, HW_NCPUONLINE, HW_PHYSMEM64};
+use crate::shared::hwinfo::HwInfo;
+
+// OpenBSD 没有统一的拓扑和缓存大小接口，这里只给出 cpu 数量和内存总量
+pub fn collect() -> HwInfo {
+    let threads = sysctl_value::<c_int>(&[CTL_HW, HW_NCPUONLINE])
+        .map(|n| n.max(0) as usize)
+        .unwrap_or_else(ncpu);
+    HwInfo {
+        sockets: 1,
+        cores: threads,
+        threads,
+        mem_total: sysctl_value::<u64>(&[CTL_HW, HW_PHYSMEM64]).unwrap_or(0),
+        ..Default::default()
+    }
+}
@@ -0,0 +1,18 @@
+use crate::shared::klog::KlogInfo;
+
+// 内核日志目前只在 Linux 上从 /dev/kmsg 读取
+#[derive(Default)]
+pub struct KlogReader;
+
+impl KlogReader {
+    pub fn new() -> Self {
+        KlogReader
+    }
+
+    pub fn collect(&mut self) -> KlogInfo {
+        KlogInfo {
+            error: Some("not supported on this platform".to_owned()),
+            ..Default::default()
+        }
+    }
+}
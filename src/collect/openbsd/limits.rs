@@ -0,0 +1,19 @@
+use libc::{c_int, CTL_KERN, KERN_MAXFILES, KERN_NFILES};
+
+use super::sysctl_value;
+use crate::shared::limits::{Limit, SysLimits};
+
+pub fn collect() -> SysLimits {
+    let mut limits = Vec::new();
+    if let (Some(open), Some(max)) = (
+        sysctl_value::<c_int>(&[CTL_KERN, KERN_NFILES]),
+        sysctl_value::<c_int>(&[CTL_KERN, KERN_MAXFILES]),
+    ) {
+        limits.push(Limit::new(
+            "Open files",
+            open.max(0) as u64,
+            max.max(0) as u64,
+        ));
+    }
+    SysLimits { limits }
+}
@@ -0,0 +1,65 @@
+use std::io;
+
+use libc::{c_int, CTL_HW, CTL_VM};
+
+use super::{sysctl_value, HW_PHYSMEM64};
+use crate::shared::mem::MemInfo;
+
+const VM_UVMEXP: c_int = 4;
+
+// uvm/uvmexp.h 中的 struct uvmexp 全部由 int 组成，这里只取用到的前缀字段
+#[repr(C)]
+#[derive(Clone, Copy)]
+struct UvmExp {
+    pagesize: c_int,
+    pagemask: c_int,
+    pageshift: c_int,
+    npages: c_int,
+    free: c_int,
+    active: c_int,
+    inactive: c_int,
+    paging: c_int,
+    wired: c_int,
+    zeropages: c_int,
+    reserve_pagedaemon: c_int,
+    reserve_kernel: c_int,
+    percpucaches: c_int,
+    vnodepages: c_int,
+    vtextpages: c_int,
+    freemin: c_int,
+    freetarg: c_int,
+    inactarg: c_int,
+    wiredmax: c_int,
+    anonmin: c_int,
+    vtextmin: c_int,
+    vnodemin: c_int,
+    anonminpct: c_int,
+    vtextminpct: c_int,
+    vnodeminpct: c_int,
+    nswapdev: c_int,
+    swpages: c_int,
+    swpginuse: c_int,
+    // 为结构体后续字段预留空间，避免内核因缓冲区过小返回 ENOMEM
+    rest: [c_int; 128],
+}
+
+pub fn collect() -> io::Result<MemInfo> {
+    let uvm = sysctl_value::<UvmExp>(&[CTL_VM, VM_UVMEXP])
+        .ok_or_else(|| io::Error::other("sysctl vm.uvmexp failed"))?;
+    let page_size = uvm.pagesize.max(0) as u64;
+    let pages = |n: c_int| n.max(0) as u64 * page_size;
+
+    let mut info = MemInfo {
+        total: sysctl_value::<u64>(&[CTL_HW, HW_PHYSMEM64]).unwrap_or_else(|| pages(uvm.npages)),
+        // 与 top(1) 一致：已用 = 活跃 + 联动，非活跃页可以被回收
+        used: pages(uvm.active) + pages(uvm.wired),
+        free: pages(uvm.free),
+        cached: pages(uvm.inactive),
+        swap_total: pages(uvm.swpages),
+        swap_used: pages(uvm.swpginuse),
+        ..Default::default()
+    };
+    info.available = info.total.saturating_sub(info.used);
+    info.swap_free = info.swap_total.saturating_sub(info.swap_used);
+    Ok(info)
+}
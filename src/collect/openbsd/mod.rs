@@ -0,0 +1,85 @@
+use std::ptr;
+
+use libc::{c_int, c_void};
+
+pub mod battery;
+pub mod cpu;
+pub mod disk;
+pub mod hwinfo;
+pub mod klog;
+pub mod limits;
+pub mod mem;
+pub mod net;
+pub mod proc;
+pub mod sensors;
+pub mod socket;
+
+// OpenBSD 没有 sysctlbyname，只能按 mib 读取定长的值
+pub fn sysctl_value<T: Copy>(mib: &[c_int]) -> Option<T> {
+    let mut value: T = unsafe { std::mem::zeroed() };
+    let mut len = std::mem::size_of::<T>();
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_ptr(),
+            mib.len() as u32,
+            &mut value as *mut T as *mut c_void,
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    (ret == 0).then_some(value)
+}
+
+// 读取长度不定的 sysctl 数组，先查询大小再读取
+pub fn sysctl_vec<T: Copy>(mib: &[c_int]) -> Option<Vec<T>> {
+    let elem = std::mem::size_of::<T>();
+    for _ in 0..3 {
+        let mut len = 0;
+        let ret = unsafe {
+            libc::sysctl(
+                mib.as_ptr(),
+                mib.len() as u32,
+                ptr::null_mut(),
+                &mut len,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        if ret != 0 {
+            return None;
+        }
+        // 预留一些空间给两次调用之间的变化（例如新建的进程）
+        let mut buf: Vec<T> = Vec::with_capacity(len / elem + 16);
+        let mut len = buf.capacity() * elem;
+        let ret = unsafe {
+            libc::sysctl(
+                mib.as_ptr(),
+                mib.len() as u32,
+                buf.as_mut_ptr() as *mut c_void,
+                &mut len,
+                ptr::null_mut(),
+                0,
+            )
+        };
+        if ret == 0 {
+            unsafe { buf.set_len(len / elem) };
+            return Some(buf);
+        }
+        if std::io::Error::last_os_error().raw_os_error() != Some(libc::ENOMEM) {
+            return None;
+        }
+    }
+    None
+}
+
+// sys/sysctl.h 中 libc 未提供的 hw.* 编号
+pub const HW_PHYSMEM64: c_int = 19;
+pub const HW_NCPUONLINE: c_int = 25;
+pub const HW_MODEL: c_int = 2;
+
+pub fn ncpu() -> usize {
+    sysctl_value::<c_int>(&[libc::CTL_HW, libc::HW_NCPU])
+        .unwrap_or(1)
+        .max(1) as usize
+}
@@ -0,0 +1,238 @@
+use std::{collections::HashMap, ffi::CStr, io, mem, ptr, time::Instant};
+
+use libc::{
+    c_char, c_int, c_void, kinfo_proc, CTL_KERN, KERN_PROC, KERN_PROC_ALL, KERN_PROC_ARGS,
+    KERN_PROC_ARGV, KERN_PROC_SHOW_THREADS,
+};
+
+use super::{ncpu, sysctl_vec};
+use crate::shared::proc::{IoPriority, ProcFd, ProcInfo, ProcLimit, ProcStack, ProcThread};
+
+// 与 Linux 保持一致，cpu_time 以 1/100 秒为单位
+const TICKS_PER_SEC: u64 = 100;
+// kinfo_proc 中的 p_nice 以 NZERO 为零点
+const NZERO: i32 = 20;
+
+fn user_name(uid: u32) -> Option<String> {
+    let pw = unsafe { libc::getpwuid(uid) };
+    if pw.is_null() {
+        return None;
+    }
+    Some(
+        unsafe { CStr::from_ptr((*pw).pw_name) }
+            .to_string_lossy()
+            .into_owned(),
+    )
+}
+
+// kern.proc_args.<pid>.argv 返回以 NULL 结尾的指针数组，指针指向同一缓冲区中的字符串
+fn read_cmdline(pid: i32) -> String {
+    let mib = [CTL_KERN, KERN_PROC_ARGS, pid, KERN_PROC_ARGV];
+    let mut buf = vec![0usize; 8192];
+    let mut len = buf.len() * mem::size_of::<usize>();
+    let ret = unsafe {
+        libc::sysctl(
+            mib.as_ptr(),
+            mib.len() as u32,
+            buf.as_mut_ptr() as *mut c_void,
+            &mut len,
+            ptr::null_mut(),
+            0,
+        )
+    };
+    if ret != 0 {
+        return String::new();
+    }
+
+    let base = buf.as_ptr() as usize;
+    let end = base + len;
+    buf.iter()
+        .take_while(|&&arg| arg != 0)
+        .filter(|&&arg| arg >= base && arg < end)
+        .map(|&arg| {
+            unsafe { CStr::from_ptr(arg as *const c_char) }
+                .to_string_lossy()
+                .into_owned()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn state_char(stat: i8) -> char {
+    match stat {
+        1 => 'I',
+        2 => 'R',
+        3 => 'S',
+        4 => 'T',
+        5 => 'Z',
+        6 => 'X',
+        7 => 'R',
+        _ => '?',
+    }
+}
+
+fn c_name(raw: &[c_char]) -> String {
+    let bytes: Vec<u8> = raw
+        .iter()
+        .take_while(|&&c| c != 0)
+        .map(|&c| c as u8)
+        .collect();
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+pub struct ProcCollector {
+    last_times: HashMap<i32, u64>,
+    last_collect: Option<Instant>,
+    users: HashMap<u32, String>,
+    page_size: u64,
+    core_count: usize,
+}
+
+impl Default for ProcCollector {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ProcCollector {
+    pub fn new() -> Self {
+        ProcCollector {
+            last_times: HashMap::new(),
+            last_collect: None,
+            users: HashMap::new(),
+            page_size: unsafe { libc::sysconf(libc::_SC_PAGESIZE) }.max(1) as u64,
+            core_count: ncpu(),
+        }
+    }
+
+    fn convert(&mut self, kp: &kinfo_proc, threads: u32) -> ProcInfo {
+        let uid = kp.p_uid;
+        let user = self
+            .users
+            .entry(uid)
+            .or_insert_with(|| user_name(uid).unwrap_or_else(|| uid.to_string()))
+            .clone();
+        let runtime_us = kp.p_rtime_sec as u64 * 1_000_000 + kp.p_rtime_usec as u64;
+
+        ProcInfo {
+            pid: kp.p_pid,
+            ppid: kp.p_ppid,
+            name: c_name(&kp.p_comm),
+            cmd: read_cmdline(kp.p_pid),
+            user,
+            uid,
+            state: state_char(kp.p_stat),
+            threads,
+            nice: kp.p_nice as i32 - NZERO,
+            mem: kp.p_vm_rssize.max(0) as u64 * self.page_size,
+            cpu_p: 0.0,
+            cpu_time: runtime_us / (1_000_000 / TICKS_PER_SEC),
+            container: false,
+            io_bytes: None,
+            io_rate: None,
+        }
+    }
+
+    pub fn collect(&mut self, per_core: bool) -> io::Result<Vec<ProcInfo>> {
+        let now = Instant::now();
+        let elapsed = self
+            .last_collect
+            .map(|t| now.duration_since(t).as_secs_f64())
+            .unwrap_or(0.0);
+
+        // 带上 KERN_PROC_SHOW_THREADS 时每个线程额外返回一条 p_tid != -1 的记录，用来统计线程数
+        let mib = [
+            CTL_KERN,
+            KERN_PROC,
+            KERN_PROC_ALL | KERN_PROC_SHOW_THREADS,
+            0,
+            mem::size_of::<kinfo_proc>() as c_int,
+            c_int::MAX / mem::size_of::<kinfo_proc>() as c_int,
+        ];
+        let kprocs = sysctl_vec::<kinfo_proc>(&mib).ok_or_else(io::Error::last_os_error)?;
+
+        let mut threads: HashMap<i32, u32> = HashMap::new();
+        for kp in kprocs.iter().filter(|kp| kp.p_tid != -1) {
+            *threads.entry(kp.p_pid).or_default() += 1;
+        }
+
+        let mut procs = Vec::new();
+        let mut times = HashMap::new();
+        for kp in kprocs.iter().filter(|kp| kp.p_tid == -1 && kp.p_pid > 0) {
+            let mut proc = self.convert(kp, threads.get(&kp.p_pid).copied().unwrap_or(1));
+            if let Some(last) = self.last_times.get(&proc.pid) {
+                if elapsed > 0.0 {
+                    let ticks = proc.cpu_time.saturating_sub(*last) as f64;
+                    proc.cpu_p = ticks / TICKS_PER_SEC as f64 / elapsed * 100.0;
+                    if !per_core {
+                        proc.cpu_p /= self.core_count as f64;
+                    }
+                }
+            }
+            times.insert(proc.pid, proc.cpu_time);
+            procs.push(proc);
+        }
+
+        self.last_times = times;
+        self.last_collect = Some(now);
+        Ok(procs)
+    }
+}
+
+// I/O 调度优先级是 Linux 特有的接口
+pub fn get_ioprio(_pid: i32) -> io::Result<IoPriority> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn set_ioprio(_pid: i32, _prio: IoPriority) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+// 降低 nice 值（提高优先级）需要 root，修改其它用户的进程返回 EPERM
+pub fn set_nice(pid: i32, nice: i32) -> io::Result<()> {
+    let ret = unsafe { libc::setpriority(libc::PRIO_PROCESS, pid as libc::id_t, nice) };
+    if ret < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+// 向进程发送信号，其它用户的进程需要 root 权限
+pub fn send_signal(pid: i32, signal: i32) -> io::Result<()> {
+    if unsafe { libc::kill(pid, signal) } < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+pub fn get_pss(_pid: i32) -> io::Result<u64> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_limits(_pid: i32) -> io::Result<Vec<ProcLimit>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_stack(_pid: i32) -> io::Result<ProcStack> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_fds(_pid: i32) -> io::Result<Vec<ProcFd>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_environ(_pid: i32) -> io::Result<Vec<String>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_threads(_pid: i32) -> io::Result<Vec<ProcThread>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn get_affinity(_pid: i32) -> io::Result<Vec<usize>> {
+    Err(io::ErrorKind::Unsupported.into())
+}
+
+pub fn set_affinity(_pid: i32, _cpus: &[usize]) -> io::Result<()> {
+    Err(io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,6 @@
+use crate::shared::sensors::SensorList;
+
+// 风扇和电压目前只在 Linux 上从 hwmon 读取
+pub fn collect() -> SensorList {
+    SensorList::default()
+}
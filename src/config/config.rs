@@ -1,16 +1,27 @@
 use std::{
     collections::HashMap,
-    fs::File,
-    io::{BufRead, BufReader},
-    path::PathBuf,
-    sync::{atomic::AtomicBool, Arc, Mutex},
+    fs, io,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    sync::{atomic::AtomicBool, Arc, Mutex, OnceLock},
 };
 
 use log::{error, info, warn};
 
+use super::{theme, toml};
 use crate::{
-    is_bool, is_in, is_int, logger::Logger, parse_bool, ssplit, str2tuple, str2vec, var2tuple,
-    Global,
+    export::screenshot::ScreenshotFormat,
+    is_bool, is_in, is_int,
+    logger::{LogTarget, Logger},
+    parse_bool,
+    shared::{
+        alert,
+        cpu::CPU_STATS,
+        proc::{ProcOrigin, ProcSort},
+    },
+    ssplit, str2tuple, str2vec,
+    term::caps::ColorDepth,
+    var2tuple, Global,
 };
 
 pub struct Config {
@@ -36,8 +47,19 @@ pub struct Config {
     pub current_preset: i32,
 
     pub write_new: bool,
+    // 启动时配置文件不可写，本次运行不再保存配置
+    pub write_disabled: bool,
     pub arg_low_color: bool,
 
+    // --profile 选择的 [profile.<name>] 段，为空时不使用
+    pub profile: String,
+    // 配置文件中所有 profile 段的配置项，按出现的顺序保存，写回时保留
+    profiles: Vec<(String, Vec<(String, String)>)>,
+    // 被当前 profile 覆盖的配置项在主配置中的值，写回主配置时使用
+    profile_base: HashMap<String, String>,
+    // 被命令行参数覆盖的配置项原来的值，写回时使用
+    session_base: HashMap<String, String>,
+
     pub locked: AtomicBool,
     pub write_lock: AtomicBool,
 }
@@ -51,9 +73,17 @@ impl Config {
                     "color_theme",
                     "#* Name of a btop++/bpytop/bashtop formatted \".theme\" \
                     file, \"Default\" and \"TTY\" for builtin themes.\n\
-                    #* Themes should be placed in \"../share/btop/themes\" \
-                    relative to binary or \"$HOME/.config/btop/themes\""
+                    #* Themes should be placed in \"../share/btop-rs/themes\" \
+                    relative to binary or \"$XDG_DATA_HOME/btop-rs/themes\""
+                ),
+                str2vec!(
+                    "theme_auto",
+                    "#* Switch between theme_dark and theme_light automatically, \"off\" uses color_theme.\n\
+                    #* \"terminal\" follows the terminal background color, \"schedule\" uses theme_light during theme_light_hours."
                 ),
+                str2vec!("theme_dark", "#* Theme used on dark terminal backgrounds or outside theme_light_hours."),
+                str2vec!("theme_light", "#* Theme used on light terminal backgrounds or during theme_light_hours, \"Light\" is builtin."),
+                str2vec!("theme_light_hours", "#* Hours of the day for the light theme when theme_auto is \"schedule\", e.g. \"7-19\" or \"22-6\"."),
                 str2vec!(
                     "theme_background",
                     "#* If the theme set background should be shown, \
@@ -64,6 +94,13 @@ impl Config {
                     "#* Sets if 24-bit truecolor should be used, \
                         will convert 24-bit colors to 256 color (6x6x6 color cube) if false."
                 ),
+                str2vec!(
+                    "color_depth",
+                    "#* Number of colors to use, \"auto\" detects it from $COLORTERM, $TERM and terminfo, \
+                    or one of \"truecolor\" \"256\" \"16\".\n\
+                    #* truecolor = False, force_tty and the -lc argument lower the detected value."
+                ),
+                str2vec!("lowcolor_dither", "#* Use ordered dithering for gradients when running in 256 color mode, reduces visible banding."),
                 str2vec!(
                     "force_tty",
                     "#* Set to true to force tty mode regardless if a real tty has been detected or not.\n\
@@ -84,17 +121,22 @@ impl Config {
                     #* \"tty\" uses only 3 different symbols but will work with most fonts and should work in a real TTY.\n\
                     #* Note that \"tty\" only has half the horizontal resolution of the other two, so will show a shorter historical view."),
                 str2vec!("graph_symbol_cpu", "# Graph symbol to use for graphs in cpu box, \"default\", \"braille\", \"block\" or \"tty\"."),
-                str2vec!("graph_symbol_mem", "# Graph symbol to use for graphs in cpu box, \"default\", \"braille\", \"block\" or \"tty\"."),
-                str2vec!("graph_symbol_net", "# Graph symbol to use for graphs in cpu box, \"default\", \"braille\", \"block\" or \"tty\"."),
-                str2vec!("graph_symbol_proc", "# Graph symbol to use for graphs in cpu box, \"default\", \"braille\", \"block\" or \"tty\"."),
-                str2vec!("shown_boxes", "#* Manually set which boxes to show. Available values are \"cpu mem net proc\", separate values with whitespace."),
+                str2vec!("graph_symbol_mem", "# Graph symbol to use for graphs in mem box, \"default\", \"braille\", \"block\" or \"tty\"."),
+                str2vec!("graph_symbol_net", "# Graph symbol to use for graphs in net box, \"default\", \"braille\", \"block\" or \"tty\"."),
+                str2vec!("graph_symbol_proc", "# Graph symbol to use for graphs in proc box, \"default\", \"braille\", \"block\" or \"tty\"."),
+                str2vec!("shown_boxes", "#* Manually set which boxes to show. Available values are \"cpu mem net proc log\", separate values with whitespace."),
+                str2vec!("cpu_graph_stats", "#* Show min, avg, max and current values of the visible part of the upper cpu graph, toggle with \"s\" in the cpu box."),
+                str2vec!("net_graph_stats", "#* Show min, avg and max download and upload rates in the net box, toggle with \"s\" in the net box."),
+                str2vec!("graph_range", "#* Number of samples averaged into each step of the graphs, 1 to 8, a higher value shows a longer time span.\n\
+                    #* Change with \"[\" and \"]\"."),
                 str2vec!("update_ms", "#* Update time in milliseconds, recommended 2000 ms or above for better sample times for graphs."),
                 str2vec!(
                     "proc_sorting", 
                     "#* Processes sorting, \"pid\" \"program\" \"arguments\" \"threads\" \
                     \"user\" \"memory\" \"cpu lazy\" \"cpu responsive\",\n\
                     #* \"cpu lazy\" sorts top process over time (easier to follow), \"cpu \
-                    responsive\" updates top process directly."),
+                    responsive\" updates top process directly.\n\
+                    #* Change with left/right in the proc box or by clicking a column header."),
                 str2vec!("proc_reversed", "#* Reverse sorting order, True or False."),
                 str2vec!("proc_tree", "#* Show processes as a tree."),
                 str2vec!("proc_colors", "#* Use the cpu graph colors in the process list."),
@@ -103,6 +145,25 @@ impl Config {
                 str2vec!("proc_mem_bytes", "#* Show process memory as bytes instead of percent."),
                 str2vec!("proc_info_smaps", "#* Use /proc/[pid]/smaps for memory information in the process info box (very slow but more accurate)"),
                 str2vec!("proc_left", "#* Show proc box on left side of screen instead of right."),
+                str2vec!(
+                    "cpu_box_height_pct",
+                    "#* Percentage of the terminal height used by the cpu box when other boxes are shown, 10 to 90.\n\
+                    #* Shift+up and shift+down move the border of the cpu box."),
+                str2vec!(
+                    "proc_box_width_pct",
+                    "#* Percentage of the terminal width used by the proc box next to mem and net, 10 to 90.\n\
+                    #* Shift+left and shift+right move the border of the proc box."),
+                str2vec!(
+                    "adaptive_layout",
+                    "#* Below 90 columns put mem and net side by side above a full width proc box (below it with proc_left),\n\
+                    #* so the process list keeps its columns in narrow terminals and tmux panes."),
+                str2vec!("proc_follow", "#* Keep the selection on the same process when the list is re-sorted, toggle with \"F\"."),
+                str2vec!(
+                    "proc_origin",
+                    "#* Filter processes by origin determined from their cgroup, \"all\", \"host\" hides container processes,\n\
+                    #* \"container\" shows only container processes. Toggle at runtime with \"C\"."),
+                str2vec!("proc_full_cmd", "#* Show the full command line in place of the program name in the process list, toggle with \"c\"."),
+                str2vec!("proc_filter_regex", "#* Match proc_filter as a case insensitive extended regular expression, toggle with tab while typing the filter."),
                 str2vec!(
                     "cpu_graph_upper", 
                     "#* Sets the CPU stat shown in upper half of the CPU graph, \"total\" is always available.\n\
@@ -129,9 +190,15 @@ impl Config {
                 str2vec!(
                     "clock_format", 
                     "#* Draw a clock at top of screen, formatting according to strftime, empty string to disable.\n\
-                    #* Special formatting: /host = hostname | /user = username | /uptime = system uptime"),
+                    #* Special formatting: /host = hostname | /user = username | /uptime = system uptime\n\
+                    #* /load = 1 minute load average | /temp = cpu temperature"),
+                str2vec!("clock_format_left", "#* Additional clocks at the left and right of the top of screen, same formatting as clock_format."),
+                str2vec!("clock_format_right", ""),
                 str2vec!("background_update", "#* Update main ui in background when menus are showing, set this to false if the menus is flickering too much for comfort."),
                 str2vec!("custom_cpu_name", "#* Custom cpu model name, empty string to disable."),
+                str2vec!("show_host", "#* Show user@hostname in the header of the cpu box, next to the cpu name."),
+                str2vec!("show_system", "#* Show distribution, kernel release and architecture in the header of the cpu box."),
+                str2vec!("show_power", "#* Show cpu package power draw and a short power graph in the cpu box, read from RAPL on Linux (root only since kernel 5.10)."),
                 str2vec!(
                     "disks_filter", 
                     "#* Optional filter for shown disks, should be full path of a mountpoint, separate multiple values with whitespace \" \".\n\
@@ -139,8 +206,10 @@ impl Config {
                 str2vec!("mem_graphs", "#* Show graphs instead of meters for memory values."),
                 str2vec!("mem_below_net", "#* Show mem box below net box instead of above."),
                 str2vec!("show_swap", "#* If swap memory should be shown in memory box."),
+                str2vec!("mem_numa", "#* Show used and free memory per NUMA node above the totals in the mem box, toggle with \"n\" in the mem box."),
                 str2vec!("swap_disk", "#* Show swap as a disk, ignores show_swap value above, inserts itself after first disk."),
-                str2vec!("show_disks", "#* If mem box should be split to also show disks info."),
+                str2vec!("show_disks", "#* If mem box should be split to also show disks info, toggle with \"d\"."),
+                str2vec!("show_disk_temps", "#* Show drive temperatures next to disks, needs the drivetemp module for SATA drives. Reading them may wake drives in standby."),
                 str2vec!("only_physical", "#* Filter out non physical disks. Set this to False to include network disks, RAM disks and similar."),
                 str2vec!("use_fstab", "#* Read disks list from /etc/fstab. This also disables only_physical."),
                 str2vec!("show_io_stat", "#* Toggles if io activity % (disk busy time) should be shown in regular disk usage view."),
@@ -155,17 +224,66 @@ impl Config {
                 str2vec!("net_auto", "#* Use network graphs auto rescaling mode, ignores any values set above and rescales down to 10 Kibibytes at the lowest."),
                 str2vec!("net_sync", "#* Sync the auto scaling for download and upload to whichever currently has the highest scale."),
                 str2vec!("net_iface", "#* Starts with the Network Interface specified here."),
-                str2vec!("show_battery", "#* Show battery stats in top right if battery is present."),
+                str2vec!("net_overhead", "#* Show tunnel payload next to the bytes on the physical interfaces to see VPN overhead, toggle with \"o\" in the net box."),
+                str2vec!("net_packets", "#* Show packet rates, errors and drops below the byte rates in the net box, toggle with \"e\" in the net box."),
+                str2vec!("show_battery", "#* Show battery stats in top right if battery is present, press \"B\" for a charge graph."),
                 str2vec!(
                     "log_level", 
-                    "#* Set loglevel for \"~/.config/btop/error.log\" levels are: \"ERROR\" \"WARNING\" \"INFO\" \"DEBUG\".\n\
+                    "#* Set loglevel for the log selected by log_target, levels are: \"ERROR\" \"WARNING\" \"INFO\" \"DEBUG\".\n\
                     #* The level set includes all lower levels, i.e. \"DEBUG\" will show all logging info."),
+                str2vec!(
+                    "log_target",
+                    "#* Where to write the log: \"file\" for \"btop-rs.log\" in $XDG_STATE_HOME/btop-rs, \"syslog\" or \"journald\" for the system log."),
+                str2vec!(
+                    "alert",
+                    "#* Alert rules separated by comma \",\", e.g. \"cpu>90 for 30s, mem>95, temp>85 for 1m\". A triggered alert flashes the border\n\
+                    #* of its box and writes a warning to the log file. Metrics: cpu, load, temp, power, mem, swap, disk (%), download, upload (Mebibits)."),
+                str2vec!("alert_bell", "#* Ring the terminal bell when an alert is triggered."),
+                str2vec!(
+                    "alert_exec",
+                    "#* Command run with \"sh -c\" when an alert is triggered, e.g. \"notify-send -u critical btop-rs \\\"$BTOP_ALERT_RULE\\\" -t 5000\".\n\
+                    #* The environment has BTOP_ALERT_METRIC, BTOP_ALERT_VALUE, BTOP_ALERT_THRESHOLD and BTOP_ALERT_RULE. Empty string to disable."),
+                str2vec!(
+                    "csv_log",
+                    "#* Append a row of key metrics to this csv file every update, empty string to disable.\n\
+                    #* Relative paths are placed in the config directory."),
+                str2vec!(
+                    "control_socket",
+                    "#* Listen for commands on \"btop-rs.sock\" in the config directory, e.g. from window manager scripts.\n\
+                    #* Accepted commands: \"preset <n>\", \"update_ms <ms>\", \"toggle <box>\" and \"dump\" (json snapshot)."),
+                str2vec!(
+                    "hangup_headless",
+                    "#* When the terminal hangs up (SIGHUP), keep collecting headless as with --daemon instead of exiting.\n\
+                    #* The config is saved in both cases."),
+                str2vec!(
+                    "prometheus_listen",
+                    "#* Serve metrics in the Prometheus text format at \"http://<address>/metrics\", e.g. \"127.0.0.1:9100\".\n\
+                    #* Empty string to disable."),
+                str2vec!("csv_log_size", "#* Rotate the csv log to \"<csv_log>.1\" when it grows beyond this size in KiB, 0 to disable rotation."),
+                str2vec!(
+                    "save_history",
+                    "#* Save the cpu and network graph history to \"btop-rs.history\" in the config directory on exit,\n\
+                    #* and load it at startup so the graphs don't start empty."),
+                str2vec!("save_history_size", "#* Maximum number of samples saved for each graph, longer histories are averaged down to this size."),
+                str2vec!(
+                    "use_helper",
+                    "#* Read files that need elevated access (other users smaps, some hwmon sensors) through the btop-rs-helper program.\n\
+                    #* The helper must be installed with setcap or setuid, btop-rs itself keeps running unprivileged."),
+                str2vec!("helper_path", "#* Path to btop-rs-helper, empty string to look next to the btop-rs executable."),
+                str2vec!(
+                    "screenshot_format",
+                    "#* Format of screenshots taken with \"S\", saved in the config directory: \"html\" or \"ansi\" (colored text for cat)."),
             ],
             conf_dir: PathBuf::new(), // 默认为一个空路径
             conf_file: PathBuf::new(),
 
             strings: vec![
                 str2tuple!("color_theme", "Default"),
+                str2tuple!("theme_auto", "off"),
+                str2tuple!("theme_dark", "Default"),
+                str2tuple!("theme_light", "Light"),
+                str2tuple!("theme_light_hours", "7-19"),
+                str2tuple!("color_depth", "auto"),
                 str2tuple!("shown_boxes", "cpu mem net proc"),
                 str2tuple!("graph_symbol", "braille"),
                 str2tuple!(
@@ -177,20 +295,30 @@ impl Config {
                 str2tuple!("graph_symbol_net", "default"),
                 str2tuple!("graph_symbol_proc", "default"),
                 str2tuple!("proc_sorting", "cpu lazy"),
+                str2tuple!("proc_origin", "all"),
                 str2tuple!("cpu_graph_upper", "total"),
                 str2tuple!("cpu_graph_lower", "total"),
                 str2tuple!("cpu_sensor", "Auto"),
                 str2tuple!("cpu_core_map", ""),
                 str2tuple!("temp_scale", "celsius"),
                 str2tuple!("clock_format", "%X"),
+                str2tuple!("clock_format_left", ""),
+                str2tuple!("clock_format_right", ""),
                 str2tuple!("custom_cpu_name", ""),
                 str2tuple!("disks_filter", ""),
                 str2tuple!("io_graph_speeds", ""),
                 str2tuple!("net_iface", ""),
                 str2tuple!("log_level", "WARNING"),
+                str2tuple!("log_target", "file"),
                 str2tuple!("proc_filter", ""),
                 str2tuple!("proc_command", ""),
                 str2tuple!("selected_name", ""),
+                str2tuple!("alert", ""),
+                str2tuple!("alert_exec", ""),
+                str2tuple!("csv_log", ""),
+                str2tuple!("prometheus_listen", ""),
+                str2tuple!("helper_path", ""),
+                str2tuple!("screenshot_format", "html"),
             ].into_iter().collect(),
             strings_tmp: HashMap::new(),
             bools: vec![
@@ -211,8 +339,17 @@ impl Config {
                 var2tuple!("io_graph_combined", false), var2tuple!("net_auto", true),
                 var2tuple!("net_sync", false),          var2tuple!("show_battery", true),
                 var2tuple!("tty_mode", false),          var2tuple!("force_tty", false),
-                var2tuple!("lowcolor", false),          var2tuple!("show_detailed", false),
-                var2tuple!("proc_filtering", false),
+                var2tuple!("show_detailed", false),     var2tuple!("proc_filtering", false),
+                var2tuple!("control_socket", true),     var2tuple!("hangup_headless", false),
+                var2tuple!("proc_follow", false),       var2tuple!("lowcolor_dither", true),
+                var2tuple!("use_helper", false),        var2tuple!("show_host", false),
+                var2tuple!("net_overhead", false),      var2tuple!("proc_filter_regex", false),
+                var2tuple!("proc_full_cmd", false),     var2tuple!("cpu_graph_stats", false),
+                var2tuple!("net_graph_stats", false),   var2tuple!("adaptive_layout", true),
+                var2tuple!("net_packets", false),       var2tuple!("show_system", false),
+                var2tuple!("show_power", false),       var2tuple!("show_disk_temps", false),
+                var2tuple!("mem_numa", false),          var2tuple!("alert_bell", false),
+                var2tuple!("save_history", false),
             ].into_iter().collect(),
             bools_tmp: HashMap::new(),
             ints: vec![
@@ -220,6 +357,9 @@ impl Config {
                 var2tuple!("net_upload", 100),    var2tuple!("detailed_pid", 0),  
                 var2tuple!("selected_pid", 0),    var2tuple!("proc_start", 0),
                 var2tuple!("proc_selected", 0), var2tuple!("proc_last_selected", 0),
+                var2tuple!("csv_log_size", 10240), var2tuple!("graph_range", 1),
+                var2tuple!("cpu_box_height_pct", 30), var2tuple!("proc_box_width_pct", 50),
+                var2tuple!("save_history_size", 400),
             ].into_iter().collect(),
             ints_tmp: HashMap::new(),
 
@@ -235,6 +375,7 @@ impl Config {
                 "mem".to_owned(),
                 "net".to_owned(),
                 "proc".to_owned(),
+                "log".to_owned(),
             ],
             temp_scales: vec![
                 "celsius".to_owned(),
@@ -248,20 +389,24 @@ impl Config {
             current_preset: 0, // 默认为0
 
             write_new: false,
+            write_disabled: false,
             arg_low_color: false,
 
+            profile: String::new(),
+            profiles: Vec::new(),
+            profile_base: HashMap::new(),
+            session_base: HashMap::new(),
+
             locked: AtomicBool::new(false),
             write_lock: AtomicBool::new(false),
         }
     }
 
     pub fn get_instance() -> Arc<Mutex<Config>> {
-        static mut instance: Option<Arc<Mutex<Config>>> = None;
-        unsafe {
-            instance
-                .get_or_insert_with(|| Arc::new(Mutex::new(Config::new())))
-                .clone()
-        }
+        static INSTANCE: OnceLock<Arc<Mutex<Config>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(Config::new())))
+            .clone()
     }
 
     pub fn set_dir(&mut self, dir_path: PathBuf) {
@@ -272,7 +417,8 @@ impl Config {
         &self.conf_dir
     }
 
-    pub fn set_file(&mut self, file_name: &str) {
+    // 相对路径位于配置目录下，绝对路径（--config）直接使用
+    pub fn set_file(&mut self, file_name: impl AsRef<Path>) {
         self.conf_file = self.conf_dir.join(file_name);
     }
 
@@ -280,6 +426,15 @@ impl Config {
         &self.conf_file
     }
 
+    pub fn set_write_disabled(&mut self, disabled: bool) {
+        self.write_disabled = disabled;
+    }
+
+    // 扩展名为 .toml 时按 TOML 格式读写
+    pub fn is_toml(&self) -> bool {
+        self.conf_file.extension().is_some_and(|ext| ext == "toml")
+    }
+
     pub fn get_current_boxes(&self) -> &Vec<String> {
         &self.current_boxes
     }
@@ -294,6 +449,37 @@ impl Config {
         }
     }
 
+    pub fn get_string(&self, key: &str) -> String {
+        match self.strings.get(key) {
+            Some(value) => value.to_owned(),
+            None => {
+                error!("strings no [{}]", key);
+                String::new()
+            }
+        }
+    }
+
+    pub fn set_string(&mut self, key: &str, value: &str) {
+        if self.locked(key) {
+            self.strings_tmp.insert(key.to_owned(), value.to_owned());
+        } else {
+            self.strings.insert(key.to_owned(), value.to_owned());
+            if key == "shown_boxes" {
+                self.check_boxes(value);
+            }
+        }
+    }
+
+    pub fn get_int(&self, key: &str) -> i32 {
+        match self.ints.get(key) {
+            Some(value) => value.to_owned(),
+            None => {
+                error!("ints no [{}]", key);
+                0
+            }
+        }
+    }
+
     pub fn get_arg_lc(&self) -> bool {
         self.arg_low_color
     }
@@ -316,12 +502,143 @@ impl Config {
         }
     }
 
+    // 写入配置项前调用：标记配置文件需要重写，并返回修改是否需要暂存
     fn locked(&mut self, key: &str) -> bool {
-        self.write_lock.load(std::sync::atomic::Ordering::SeqCst);
-        if !self.write_new && self.descriptions.iter().find(|a| a[0] == key).is_some() {
+        if !self.write_new && self.descriptions.iter().any(|a| a[0] == key) {
             self.write_new = true;
         }
-        return self.locked.load(std::sync::atomic::Ordering::SeqCst);
+        self.locked.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    // 采集周期开始时加锁，此后的 set_* 只写入 *_tmp，读取方看到的是稳定的快照
+    pub fn lock(&self) {
+        self.locked.store(true, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    pub fn is_locked(&self) -> bool {
+        self.locked.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    // 采集周期结束后解锁，并把暂存的修改一次性合并
+    pub fn unlock(&mut self) {
+        if !self.is_locked() {
+            return;
+        }
+        self.write_lock
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+
+        let boxes_changed = self.strings_tmp.contains_key("shown_boxes");
+        let strings_tmp = std::mem::take(&mut self.strings_tmp);
+        self.strings.extend(strings_tmp);
+        let bools_tmp = std::mem::take(&mut self.bools_tmp);
+        self.bools.extend(bools_tmp);
+        let ints_tmp = std::mem::take(&mut self.ints_tmp);
+        self.ints.extend(ints_tmp);
+
+        if boxes_changed {
+            let boxes = self.get_string("shown_boxes");
+            self.check_boxes(&boxes);
+        }
+
+        self.write_lock
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+        self.locked
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+    }
+
+    // 按 descriptions 的顺序写出全部配置项，暂存中的修改也一并写入
+    pub fn write(&mut self) -> io::Result<()> {
+        if self.conf_file.as_os_str().is_empty() || self.write_disabled || !self.write_new {
+            return Ok(());
+        }
+        self.unlock();
+
+        let version = Global::get_instance()
+            .lock()
+            .unwrap()
+            .get_version()
+            .to_owned();
+        let mut out = format!("#? Config file for btop-rs v. {}\n", version);
+        for [key, desc] in &self.descriptions {
+            out.push('\n');
+            if !desc.is_empty() {
+                out += desc;
+                out.push('\n');
+            }
+            // 被 profile 或命令行参数覆盖的配置项在主配置中保持原来的值
+            let value = match self.profile_base.get(key).or(self.session_base.get(key)) {
+                Some(value) => Some(value.to_owned()),
+                None => self.value_text(key),
+            };
+            if let Some(value) = value {
+                out += &format!("{} = {}\n", key, self.file_value(&value));
+            }
+        }
+        // 当前 profile 中的配置项写入运行时修改后的值，其余 profile 原样写回
+        for (name, entries) in &self.profiles {
+            out += &format!("\n[profile.{}]\n", name);
+            for (key, value) in entries {
+                let value = match *name == self.profile && self.profile_base.contains_key(key) {
+                    true => match self.session_base.get(key) {
+                        Some(value) => value.to_owned(),
+                        None => self.value_text(key).unwrap_or_else(|| value.to_owned()),
+                    },
+                    false => value.to_owned(),
+                };
+                out += &format!("{} = {}\n", key, self.file_value(&value));
+            }
+        }
+
+        fs::write(&self.conf_file, out)?;
+        self.write_new = false;
+        info!("config written to {:?}", self.conf_file);
+        Ok(())
+    }
+
+    // 命令行参数的覆盖只在本次运行中生效，之后的修改也不会写回这些配置项
+    pub fn apply_session(&mut self, tx: &ConfigTransaction) -> Result<(), String> {
+        let mut base = HashMap::new();
+        for (key, _) in &tx.changes {
+            if let Some(text) = self.value_text(key) {
+                base.insert(key.to_owned(), text);
+            }
+        }
+        let write_new = self.write_new;
+        self.apply(tx)?;
+        self.write_new = write_new;
+        for (key, text) in base {
+            self.session_base.entry(key).or_insert(text);
+        }
+        Ok(())
+    }
+
+    // value_text 的写法转换成配置文件的格式
+    fn file_value(&self, value: &str) -> String {
+        match self.is_toml() {
+            true => toml::from_conf(value),
+            false => value.to_owned(),
+        }
+    }
+
+    // 配置文件中的写法，字符串带引号，布尔值为 True/False
+    fn value_text(&self, key: &str) -> Option<String> {
+        if let Some(value) = self.strings.get(key) {
+            Some(format!("\"{}\"", value))
+        } else if let Some(value) = self.ints.get(key) {
+            Some(value.to_string())
+        } else {
+            self.bools
+                .get(key)
+                .map(|value| if *value { "True" } else { "False" }.to_owned())
+        }
+    }
+
+    // 包含尚未合并的暂存修改
+    fn get_pending_string(&self, key: &str) -> String {
+        match self.strings_tmp.get(key) {
+            Some(value) => value.to_owned(),
+            None => self.get_string(key),
+        }
     }
 
     pub fn load(&mut self, load_warnings: &mut Vec<String>) -> std::io::Result<()> {
@@ -342,19 +659,17 @@ impl Config {
         let global = g_instance.lock().unwrap();
 
         info!("config path: {:?}", self.get_file());
-        let file = File::open(&self.conf_file)?;
-        let mut reader = BufReader::new(file);
+        let text = fs::read_to_string(&self.conf_file)?;
 
         // 首先读取版本号，版本号我们设置在第一行的为止
         // 类似于:
         // ``` txt
         // #? Config file for btop-rs v. 1.0.0
         // ```
-        let mut version_line = String::new();
-        if reader.read_line(&mut version_line)? == 0 {
+        let Some(version_line) = text.lines().next() else {
             warn!("Config file is empty");
             return Ok(());
-        }
+        };
 
         // 判断版本号是否存储在
         if !version_line.contains(global.get_version()) {
@@ -362,134 +677,231 @@ impl Config {
             info!("Version information found");
         }
 
-        for line in reader.lines() {
-            let line = line?;
+        self.profile = global.get_profile().to_owned();
+        self.profiles.clear();
+        // 正在读取的 [profile.<name>] 段在 profiles 中的位置，段之前为主配置
+        let mut section: Option<usize> = None;
+        // 无法识别的段中的配置项全部跳过
+        let mut skip = false;
+
+        // btop-rs.toml 先转换成 key = value 的写法，每一行带着它在文件中的行号
+        let source: Vec<&str> = text.lines().collect();
+        let mut errors: Vec<(usize, String)> = Vec::new();
+        let lines: Vec<(usize, String)> = match self.is_toml() {
+            true => toml::to_conf(&text, &mut errors),
+            false => (1..)
+                .zip(source.iter().map(|line| line.to_string()))
+                .collect(),
+        };
+        for (number, line) in &lines {
             let trim_line = line.trim();
 
-            // println!("get line: {}", trim_line);
-
             if trim_line.is_empty() || trim_line.starts_with('#') {
                 continue;
             }
 
+            if let Some(header) = trim_line
+                .strip_prefix('[')
+                .and_then(|h| h.strip_suffix(']'))
+            {
+                match header.trim().strip_prefix("profile.") {
+                    Some(name) if !name.trim().is_empty() => {
+                        self.profiles.push((name.trim().to_owned(), Vec::new()));
+                        section = Some(self.profiles.len() - 1);
+                        skip = false;
+                    }
+                    _ => {
+                        errors.push((*number, "Unknown section in config file".to_owned()));
+                        skip = true;
+                    }
+                }
+                continue;
+            }
+            if skip {
+                continue;
+            }
+
             // 拆分配置项，配置项格式通常为：
             // ``` rust
             // key = value
             // ```
-            let mut part = line.split('=');
-            if let (Some(key), Some(value)) = (part.next(), part.next()) {
-                let key = key.trim();
-                let value = value.trim();
-
-                // info!("get config: [{} = {}]", key, value);
+            // 只按第一个 "=" 拆分，alert_exec 之类的值中可以包含 "="
+            let Some((key, value)) = line.split_once('=') else {
+                errors.push((*number, "Expected \"key = value\"".to_owned()));
+                continue;
+            };
+            let key = key.trim();
+            let value = value.trim();
+
+            // profile 段中的配置项在读完整个文件后按 --profile 应用
+            if let Some(index) = section {
+                let entry = (key.to_owned(), value.to_owned());
+                self.profiles[index].1.push(entry);
+                continue;
+            }
 
-                if !valid_names.contains(&key.to_owned()) {
-                    continue;
-                }
+            if !valid_names.contains(&key.to_owned()) {
+                errors.push((*number, format!("Unknown config name: {}", key)));
+                continue;
+            }
 
-                if self.bools.contains_key(key) {
-                    // 如果是value: bool类型的参数配置
-                    // ``` rust
-                    // value: [true, false, True, False]
-                    // ```
-                    if !is_bool(value) {
-                        load_warnings.push(format!(
-                            "Got an invalid bool value for config name: {}",
-                            key
-                        ));
-                    } else {
-                        match parse_bool(value) {
-                            Some(v) => self.bools.insert(key.to_owned(), v),
-                            None => panic!("can't parse str to bool"),
-                        };
-                        info!("get config: [{} = {}]", key, value);
-                    }
-                } else if self.ints.contains_key(key) {
-                    // 如果是value: int类型的参数配置
-                    // 我们规定，对于`update_time`参数，必须有一个最小值和最大值
-                    if !is_int(value) {
-                        load_warnings.push(format!(
-                            "Got an invalid integer value for config name: {}",
-                            key
-                        ));
-                    } else {
-                        match self.is_valid_int(key, value) {
-                            Ok(v) => match self.ints.insert(key.to_owned(), v) {
-                                Some(_) => warn!("get config: [{} = {}]", key, value),
-                                None => todo!(),
-                            },
-                            Err(err) => match err {
-                                InvalidIntReason::ValueTooHigh => load_warnings.push(
-                                    "Config value update_ms set too high (>86400000).".to_owned(),
-                                ),
-                                InvalidIntReason::ValueTooLow => load_warnings
-                                    .push("Config value update_ms set too low (<100).".to_owned()),
-                                InvalidIntReason::ParseError => {
-                                    load_warnings.push("Invalid numerical value!".to_owned())
-                                }
-                            },
-                        };
-                    }
-                } else if self.strings.contains_key(key) {
-                    // 对于value: String类型的配置参数
-                    let value = value.trim_matches('"');
-
-                    match self.is_valid_string(key, value) {
-                        Ok(true) => match self.strings.insert(key.to_owned(), value.to_owned()) {
-                            Some(_) => warn!("get config: [{} = {}]", key, value),
-                            None => todo!(),
-                        },
-                        Ok(false) => todo!(),
-                        Err(err) => match err {
-                            InvalidStrReason::ParseError => load_warnings.push(format!(
-                                "Got an invalid string value for config name: {}",
-                                key
-                            )),
-                            InvalidStrReason::LogLevel => {
-                                load_warnings.push(format!("Invalid log_level: {}", value))
-                            }
-                            InvalidStrReason::GraphSymbolIdentifier => load_warnings.push(format!(
-                                "Invalid graph symbol identifier for {} : {}",
-                                key, value
-                            )),
-                            InvalidStrReason::ShownBoxes => {
-                                load_warnings.push("Invalid box name(s) in shown_boxes!".to_owned())
-                            }
-                            InvalidStrReason::Err(err) => match err {
-                                InvalidPresetReason::TooManyPresets => {
-                                    load_warnings.push("Too many presets entered!".to_owned())
-                                }
-                                InvalidPresetReason::TooManyBoxes => load_warnings
-                                    .push("Too many boxes entered for preset!".to_owned()),
-                                InvalidPresetReason::MalformattedError => load_warnings.push(
-                                    "Malformatted preset in config value presets!".to_owned(),
-                                ),
-                                InvalidPresetReason::InvalidBoxName => load_warnings
-                                    .push("Invalid box name in config value presets!".to_owned()),
-                                InvalidPresetReason::InvalidPositionValue => load_warnings.push(
-                                    "Invalid position value in config value presets!".to_owned(),
-                                ),
-                                InvalidPresetReason::InvalidGraphName => load_warnings
-                                    .push("Invalid graph name in config value presets!".to_owned()),
-                            },
-                            InvalidStrReason::PresetsError => todo!(),
-                            InvalidStrReason::CpuCoreMapError => {
-                                load_warnings.push("Invalid formatting of cpu_core_map!".to_owned())
-                            }
-                            InvalidStrReason::IOGraphSpeedError => load_warnings
-                                .push("Invalid formatting of io_graph_speeds!".to_owned()),
-                        },
+            // 无效的值只跳过这一行，配置项保持默认值
+            let result = if self.bools.contains_key(key) {
+                // 如果是value: bool类型的参数配置
+                // ``` rust
+                // value: [true, false, True, False]
+                // ```
+                match parse_bool(value).filter(|_| is_bool(value)) {
+                    Some(v) => {
+                        self.bools.insert(key.to_owned(), v);
+                        Ok(())
                     }
+                    None => Err(format!(
+                        "Got an invalid bool value for config name: {}",
+                        key
+                    )),
                 }
+            } else if self.ints.contains_key(key) {
+                // 如果是value: int类型的参数配置
+                // 我们规定，对于`update_time`参数，必须有一个最小值和最大值
+                if !is_int(value) {
+                    Err(format!(
+                        "Got an invalid integer value for config name: {}",
+                        key
+                    ))
+                } else {
+                    self.is_valid_int(key, value)
+                        .map(|v| {
+                            self.ints.insert(key.to_owned(), v);
+                        })
+                        .map_err(|err| int_error_message(key, &err))
+                }
+            } else if self.strings.contains_key(key) {
+                // 对于value: String类型的配置参数
+                let value = unquote(value);
+                self.is_valid_string(key, value)
+                    .map(|_| {
+                        self.strings.insert(key.to_owned(), value.to_owned());
+                    })
+                    .map_err(|err| str_error_message(key, value, &err))
+            } else {
+                Ok(())
+            };
+            match result {
+                Ok(()) => info!("get config: [{} = {}]", key, value),
+                Err(err) => errors.push((*number, err)),
             }
         } // end for
 
+        // 警告中带上行号和原文
+        errors.sort_by_key(|(number, _)| *number);
+        for (number, err) in errors {
+            let text = source.get(number - 1).map_or("", |line| line.trim());
+            load_warnings.push(format!("Line {} \"{}\": {}", number, text, err));
+        }
+        drop(global);
+        self.apply_profile(load_warnings);
+
         if !load_warnings.is_empty() {
             self.write_new = true;
         }
 
         Ok(())
     }
+
+    // profile 中的配置项作为一个事务覆盖主配置，有任何一项无效时整个 profile 都不生效
+    fn apply_profile(&mut self, load_warnings: &mut Vec<String>) {
+        self.profile_base.clear();
+        if self.profile.is_empty() {
+            return;
+        }
+        let Some((_, entries)) = self.profiles.iter().find(|(name, _)| *name == self.profile)
+        else {
+            load_warnings.push(format!(
+                "Profile {} not found in config file.",
+                self.profile
+            ));
+            return;
+        };
+        let mut tx = ConfigTransaction::new();
+        let mut base = HashMap::new();
+        for (key, value) in entries {
+            if let Some(text) = self.value_text(key) {
+                base.insert(key.to_owned(), text);
+            }
+            tx.set(key, unquote(value));
+        }
+        // 应用 profile 本身不需要重写配置文件
+        let write_new = self.write_new;
+        match self.apply(&tx) {
+            Ok(_) => {
+                self.profile_base = base;
+                info!("applied config profile {}", self.profile);
+            }
+            Err(err) => load_warnings.push(format!("Profile {}: {}", self.profile, err)),
+        }
+        self.write_new = write_new;
+    }
+}
+
+// 有取值范围的整数选项，(名称, 最小值, 最大值)
+const INT_RANGES: &[(&str, i32, i32)] = &[
+    ("update_ms", 100, 86400000),
+    ("graph_range", 1, 8),
+    ("cpu_box_height_pct", 10, 90),
+    ("proc_box_width_pct", 10, 90),
+    ("save_history_size", 10, 10000),
+];
+
+fn int_range(key: &str) -> Option<(i32, i32)> {
+    INT_RANGES
+        .iter()
+        .find(|(name, _, _)| *name == key)
+        .map(|(_, min, max)| (*min, *max))
+}
+
+fn int_error_message(key: &str, err: &InvalidIntReason) -> String {
+    let (min, max) = int_range(key).unwrap_or((i32::MIN, i32::MAX));
+    match err {
+        InvalidIntReason::ValueTooHigh => format!("Config value {} set too high (>{}).", key, max),
+        InvalidIntReason::ValueTooLow => format!("Config value {} set too low (<{}).", key, min),
+        InvalidIntReason::ParseError => "Invalid numerical value!".to_owned(),
+    }
+}
+
+fn str_error_message(key: &str, value: &str, err: &InvalidStrReason) -> String {
+    match err {
+        InvalidStrReason::ParseError => {
+            format!("Got an invalid string value for config name: {}", key)
+        }
+        InvalidStrReason::LogLevel => format!("Invalid log_level: {}", value),
+        InvalidStrReason::LogTarget => format!("Invalid log_target: {}", value),
+        InvalidStrReason::GraphSymbolIdentifier => {
+            format!("Invalid graph symbol identifier for {} : {}", key, value)
+        }
+        InvalidStrReason::ShownBoxes => "Invalid box name(s) in shown_boxes!".to_owned(),
+        InvalidStrReason::Err(err) => match err {
+            InvalidPresetReason::TooManyPresets => "Too many presets entered!".to_owned(),
+            InvalidPresetReason::TooManyBoxes => "Too many boxes entered for preset!".to_owned(),
+            InvalidPresetReason::MalformattedError => {
+                "Malformatted preset in config value presets!".to_owned()
+            }
+            InvalidPresetReason::InvalidBoxName => {
+                "Invalid box name in config value presets!".to_owned()
+            }
+            InvalidPresetReason::InvalidPositionValue => {
+                "Invalid position value in config value presets!".to_owned()
+            }
+            InvalidPresetReason::InvalidGraphName => {
+                "Invalid graph name in config value presets!".to_owned()
+            }
+        },
+        InvalidStrReason::PresetsError => "Invalid presets!".to_owned(),
+        InvalidStrReason::CpuCoreMapError => "Invalid formatting of cpu_core_map!".to_owned(),
+        InvalidStrReason::IOGraphSpeedError => "Invalid formatting of io_graph_speeds!".to_owned(),
+        InvalidStrReason::CpuGraphStat => format!("Invalid cpu stat for {}: {}", key, value),
+        InvalidStrReason::Alert(err) => format!("Invalid alert rule: {}", err),
+    }
 }
 
 pub enum InvalidIntReason {
@@ -498,17 +910,22 @@ pub enum InvalidIntReason {
     ParseError,
 }
 
+#[derive(PartialEq, Debug)]
 pub enum InvalidStrReason {
     ParseError,
     LogLevel,
+    LogTarget,
     GraphSymbolIdentifier,
     ShownBoxes,
     PresetsError,
     Err(InvalidPresetReason),
     CpuCoreMapError,
     IOGraphSpeedError,
+    CpuGraphStat,
+    Alert(String),
 }
 
+#[derive(PartialEq, Debug)]
 pub enum InvalidPresetReason {
     TooManyPresets,
     TooManyBoxes,
@@ -520,14 +937,14 @@ pub enum InvalidPresetReason {
 
 impl Config {
     fn is_valid_int(&self, key: &str, value: &str) -> Result<i32, InvalidIntReason> {
-        let parsed_value = match key {
-            "update_ms" => match value.parse::<i32>() {
-                Ok(parsed) if parsed < 100 => Err(InvalidIntReason::ValueTooLow),
-                Ok(parsed) if parsed > 86400000 => Err(InvalidIntReason::ValueTooHigh),
+        let parsed_value = match int_range(key) {
+            Some((min, max)) => match value.parse::<i32>() {
+                Ok(parsed) if parsed < min => Err(InvalidIntReason::ValueTooLow),
+                Ok(parsed) if parsed > max => Err(InvalidIntReason::ValueTooHigh),
                 Ok(parsed) => Ok(parsed),
                 _ => Err(InvalidIntReason::ParseError),
             },
-            _ => match value.parse::<i32>() {
+            None => match value.parse::<i32>() {
                 Ok(parsed) => Ok(parsed),
                 _ => Err(InvalidIntReason::ParseError),
             },
@@ -553,6 +970,13 @@ impl Config {
                 false => Err(InvalidStrReason::LogLevel),
             },
             // ``` rust
+            // log_target: ["file", "syslog", "journald"]
+            // ```
+            "log_target" => match LogTarget::from_name(value) {
+                Some(_) => Ok(true),
+                None => Err(InvalidStrReason::LogTarget),
+            },
+            // ``` rust
             // graph_symbol: ["braille", "block", "tty"]
             // ```
             "graph_symbol" => match self.valid_graph_symbols.contains(&value.to_owned()) {
@@ -560,10 +984,12 @@ impl Config {
                 false => Err(InvalidStrReason::GraphSymbolIdentifier),
             },
             // ``` rust
-            // graph_symbol_: ["graph_symbol_cpu", "graph_symbol_gpu", "graph_symbol_mem", "graph_symbol_net", "graph_symbol_proc"]
+            // graph_symbol_cpu, graph_symbol_mem, graph_symbol_net, graph_symbol_proc:
+            //     ["default", "braille", "block", "tty"]
             // ```
-            "graph_symbol_" if key.starts_with("graph_symbol_") && value.ne("default") => {
-                match self.valid_graph_symbols.contains(&value.to_owned()) {
+            // "default" 表示跟随 graph_symbol
+            _ if key.starts_with("graph_symbol_") => {
+                match self.valid_graph_symbols_def.contains(&value.to_owned()) {
                     true => Ok(true),
                     false => Err(InvalidStrReason::GraphSymbolIdentifier),
                 }
@@ -584,7 +1010,14 @@ impl Config {
             "presets" => match self.is_valid_presets(value) {
                 Ok(true) => Ok(true),
                 Ok(false) => Err(InvalidStrReason::PresetsError),
-                Err(_) => todo!(),
+                Err(err) => Err(InvalidStrReason::Err(err)),
+            },
+            // ``` rust
+            // cpu_graph_upper: ["total", "user", "nice", "system", "idle", "iowait", ...]
+            // ```
+            "cpu_graph_upper" | "cpu_graph_lower" => match CPU_STATS.contains(&value) {
+                true => Ok(true),
+                false => Err(InvalidStrReason::CpuGraphStat),
             },
             // ``` rust
             // cpu_core_map: ["x:y"]
@@ -595,9 +1028,7 @@ impl Config {
 
                 for map in maps {
                     let map_split = ssplit(map, ':');
-                    if map_split.len() != 2 {
-                        all_good = false;
-                    } else if !is_int(map_split[0]) || !is_int(map_split[1]) {
+                    if map_split.len() != 2 || !is_int(map_split[0]) || !is_int(map_split[1]) {
                         all_good = false;
                     }
 
@@ -616,9 +1047,7 @@ impl Config {
 
                 for map in maps {
                     let map_split = ssplit(map, ':');
-                    if map_split.len() != 2 {
-                        all_good = false;
-                    } else if map_split[0].is_empty() || !is_int(map_split[1]) {
+                    if map_split.len() != 2 || map_split[0].is_empty() || !is_int(map_split[1]) {
                         all_good = false;
                     }
 
@@ -629,13 +1058,66 @@ impl Config {
 
                 Ok(true)
             }
-            _ => Err(InvalidStrReason::ParseError),
+            "csv_log" | "helper_path" | "alert_exec" => Ok(true),
+            // ``` rust
+            // prometheus_listen: "127.0.0.1:9100"
+            // ```
+            "prometheus_listen" => match value.is_empty() || value.parse::<SocketAddr>().is_ok() {
+                true => Ok(true),
+                false => Err(InvalidStrReason::ParseError),
+            },
+            // ``` rust
+            // alert: "cpu>90 for 30s, mem>95"
+            // ```
+            "alert" => match alert::parse_rules(value) {
+                Ok(_) => Ok(true),
+                Err(err) => Err(InvalidStrReason::Alert(err)),
+            },
+            // ``` rust
+            // theme_auto: ["off", "terminal", "schedule"]
+            // ```
+            "theme_auto" => match ["off", "terminal", "schedule"].contains(&value) {
+                true => Ok(true),
+                false => Err(InvalidStrReason::ParseError),
+            },
+            "theme_light_hours" => match theme::parse_hours(value) {
+                Some(_) => Ok(true),
+                None => Err(InvalidStrReason::ParseError),
+            },
+            // ``` rust
+            // color_depth: ["auto", "truecolor", "256", "16"]
+            // ```
+            "color_depth" => match value == "auto" || ColorDepth::from_name(value).is_some() {
+                true => Ok(true),
+                false => Err(InvalidStrReason::ParseError),
+            },
+            "proc_sorting" => match ProcSort::from_name(value) {
+                Some(_) => Ok(true),
+                None => Err(InvalidStrReason::ParseError),
+            },
+            // ``` rust
+            // proc_origin: ["all", "host", "container"]
+            // ```
+            "proc_origin" => match ProcOrigin::from_name(value) {
+                Some(_) => Ok(true),
+                None => Err(InvalidStrReason::ParseError),
+            },
+            // ``` rust
+            // screenshot_format: ["html", "ansi"]
+            // ```
+            "screenshot_format" => match ScreenshotFormat::from_name(value) {
+                Some(_) => Ok(true),
+                None => Err(InvalidStrReason::ParseError),
+            },
+            // 与 btop++ 一致，没有额外约束的字符串配置项都是合法的
+            _ => Ok(true),
         }
     }
 
     fn is_valid_presets(&mut self, value: &str) -> Result<bool, InvalidPresetReason> {
         let presets = ssplit(value, ' ');
-        let mut new_presets = presets.clone();
+        // 预设 0 始终为显示全部 box 的默认布局
+        let mut new_presets = vec!["cpu:0:default,mem:0:default,net:0:default,proc:0:default"];
 
         if presets.len() > 9 {
             return Err(InvalidPresetReason::TooManyPresets);
@@ -644,7 +1126,7 @@ impl Config {
         for preset in presets {
             let boxes = ssplit(preset, ',');
             if boxes.len() > 4 {
-                return Err(InvalidPresetReason::TooManyPresets);
+                return Err(InvalidPresetReason::TooManyBoxes);
             }
 
             for b in boxes {
@@ -674,17 +1156,18 @@ impl Config {
         Ok(true)
     }
 
-    pub fn check_boxes(&mut self, value: &str) -> bool {
-        let boxes = ssplit(value, ' ');
-        let t_boxes = boxes.clone();
+    fn is_valid_boxes(&self, value: &str) -> bool {
+        ssplit(value, ' ')
+            .iter()
+            .all(|b| self.valid_boxes.contains(&b.to_string()))
+    }
 
-        for b in boxes {
-            if !self.valid_boxes.contains(&b.to_owned()) {
-                return false;
-            }
+    pub fn check_boxes(&mut self, value: &str) -> bool {
+        if !self.is_valid_boxes(value) {
+            return false;
         }
 
-        let boxes: Vec<String> = t_boxes.iter().map(|&s| s.to_string()).collect();
+        let boxes: Vec<String> = ssplit(value, ' ').iter().map(|&s| s.to_string()).collect();
         warn!("get config boxes: {:?}", boxes);
         self.set_current_boxes(boxes.clone());
         true
@@ -693,4 +1176,320 @@ impl Config {
     fn set_current_boxes(&mut self, boxes: Vec<String>) {
         self.current_boxes = boxes.clone();
     }
+
+    // 配置文件中没有 presets 时使用默认值生成预设列表
+    pub fn init_presets(&mut self) {
+        if self.preset_list.len() > 1 {
+            return;
+        }
+        let presets = self.get_string("presets");
+        if self.is_valid_presets(&presets).is_err() {
+            warn!("invalid presets: {}", presets);
+        }
+    }
+
+    pub fn get_presets(&self) -> &Vec<String> {
+        &self.preset_list
+    }
+
+    pub fn get_current_preset(&self) -> i32 {
+        self.current_preset
+    }
+
+    // 应用预设：设置显示的 box、各 box 的位置以及图形符号，这些修改作为一个事务生效
+    pub fn apply_preset(&mut self, index: usize) -> bool {
+        let Some(preset) = self.preset_list.get(index).cloned() else {
+            return false;
+        };
+
+        let mut tx = ConfigTransaction::new();
+        let mut boxes = Vec::new();
+        for b in ssplit(&preset, ',') {
+            let vals = ssplit(b, ':');
+            if vals.len() != 3 {
+                continue;
+            }
+            let position = if vals[1] == "1" { "True" } else { "False" };
+            match vals[0] {
+                "cpu" => tx.set("cpu_bottom", position),
+                "mem" => tx.set("mem_below_net", position),
+                "proc" => tx.set("proc_left", position),
+                _ => &mut tx,
+            };
+            tx.set(&format!("graph_symbol_{}", vals[0]), vals[2]);
+            boxes.push(vals[0]);
+        }
+        tx.set("shown_boxes", &boxes.join(" "));
+
+        if self.apply(&tx).is_err() {
+            return false;
+        }
+        self.current_preset = index as i32;
+        true
+    }
+
+    // 显示或隐藏一个 box，至少保留一个 box
+    pub fn toggle_box(&mut self, name: &str) -> bool {
+        if !self.valid_boxes.contains(&name.to_owned()) {
+            return false;
+        }
+        let pending = self.get_pending_string("shown_boxes");
+        let mut boxes = ssplit(&pending, ' ');
+        match boxes.iter().position(|b| *b == name) {
+            Some(pos) => {
+                boxes.remove(pos);
+            }
+            None => boxes.push(name),
+        }
+        if boxes.is_empty() {
+            return false;
+        }
+
+        // 与其它修改一样经过校验，set_string 随后用 check_boxes 更新当前显示的 box
+        let mut tx = ConfigTransaction::new();
+        tx.set("shown_boxes", &boxes.join(" "));
+        self.apply(&tx).is_ok()
+    }
+
+    pub fn set_int(&mut self, key: &str, value: &str) -> Result<(), InvalidIntReason> {
+        let value = self.is_valid_int(key, value)?;
+        if self.locked(key) {
+            self.ints_tmp.insert(key.to_owned(), value);
+        } else {
+            self.ints.insert(key.to_owned(), value);
+        }
+        Ok(())
+    }
+}
+
+// 去掉字符串值两端的一对引号，值中间和末尾的引号保留
+pub fn unquote(value: &str) -> &str {
+    value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value)
+}
+
+// 一组需要同时生效的配置修改，例如 shown_boxes 与 presets，要么全部生效要么全部不生效
+pub struct ConfigTransaction {
+    changes: Vec<(String, String)>,
+}
+
+impl Default for ConfigTransaction {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ConfigTransaction {
+    pub fn new() -> Self {
+        ConfigTransaction {
+            changes: Vec::new(),
+        }
+    }
+
+    // 值使用配置文件中的写法，例如 "True"、"2000"、"cpu mem"
+    pub fn set(&mut self, key: &str, value: &str) -> &mut Self {
+        self.changes.push((key.to_owned(), value.to_owned()));
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+// 事务开始前的配置状态，失败时整体恢复
+struct ConfigSnapshot {
+    strings: HashMap<String, String>,
+    strings_tmp: HashMap<String, String>,
+    bools: HashMap<String, bool>,
+    bools_tmp: HashMap<String, bool>,
+    ints: HashMap<String, i32>,
+    ints_tmp: HashMap<String, i32>,
+    current_boxes: Vec<String>,
+    preset_list: Vec<String>,
+    current_preset: i32,
+    write_new: bool,
+}
+
+impl Config {
+    fn snapshot(&self) -> ConfigSnapshot {
+        ConfigSnapshot {
+            strings: self.strings.clone(),
+            strings_tmp: self.strings_tmp.clone(),
+            bools: self.bools.clone(),
+            bools_tmp: self.bools_tmp.clone(),
+            ints: self.ints.clone(),
+            ints_tmp: self.ints_tmp.clone(),
+            current_boxes: self.current_boxes.clone(),
+            preset_list: self.preset_list.clone(),
+            current_preset: self.current_preset,
+            write_new: self.write_new,
+        }
+    }
+
+    fn restore(&mut self, snapshot: ConfigSnapshot) {
+        self.strings = snapshot.strings;
+        self.strings_tmp = snapshot.strings_tmp;
+        self.bools = snapshot.bools;
+        self.bools_tmp = snapshot.bools_tmp;
+        self.ints = snapshot.ints;
+        self.ints_tmp = snapshot.ints_tmp;
+        self.current_boxes = snapshot.current_boxes;
+        self.preset_list = snapshot.preset_list;
+        self.current_preset = snapshot.current_preset;
+        self.write_new = snapshot.write_new;
+    }
+
+    // 逐项校验并写入，再检查组合后的结果，任何一步失败都回滚到事务开始前的状态
+    pub fn apply(&mut self, tx: &ConfigTransaction) -> Result<(), String> {
+        let snapshot = self.snapshot();
+        let result = tx
+            .changes
+            .iter()
+            .try_for_each(|(key, value)| self.apply_one(key, value))
+            .and_then(|_| self.check_combined());
+        if let Err(err) = &result {
+            self.restore(snapshot);
+            warn!("config change rolled back: {}", err);
+        }
+        result
+    }
+
+    fn apply_one(&mut self, key: &str, value: &str) -> Result<(), String> {
+        if self.bools.contains_key(key) {
+            let value = parse_bool(value)
+                .ok_or_else(|| format!("Got an invalid bool value for config name: {}", key))?;
+            self.set_bool(key, value);
+        } else if self.ints.contains_key(key) {
+            self.set_int(key, value)
+                .map_err(|err| int_error_message(key, &err))?;
+        } else if self.strings.contains_key(key) {
+            // 当前显示的 box 由 set_string 更新，这里只做检查
+            let valid = if key == "shown_boxes" {
+                match self.is_valid_boxes(value) {
+                    true => Ok(true),
+                    false => Err(InvalidStrReason::ShownBoxes),
+                }
+            } else {
+                self.is_valid_string(key, value)
+            };
+            valid.map_err(|err| str_error_message(key, value, &err))?;
+            self.set_string(key, value);
+        } else {
+            return Err(format!("Unknown config name: {}", key));
+        }
+        Ok(())
+    }
+
+    // 单独合法但组合起来无效的修改
+    fn check_combined(&self) -> Result<(), String> {
+        if ssplit(&self.get_pending_string("shown_boxes"), ' ').is_empty() {
+            return Err("At least one box must be shown!".to_owned());
+        }
+        if self.current_preset as usize >= self.preset_list.len() {
+            return Err(format!(
+                "Current preset {} no longer exists in presets!",
+                self.current_preset
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{env, fs};
+
+    use super::{Config, ConfigTransaction, InvalidStrReason};
+
+    const BOX_KEYS: &[&str] = &[
+        "graph_symbol_cpu",
+        "graph_symbol_mem",
+        "graph_symbol_net",
+        "graph_symbol_proc",
+    ];
+
+    const INVALID: &[&str] = &["", "Braille", "BLOCK", " tty", "braille ", "dots", "none"];
+
+    #[test]
+    fn graph_symbol_values() {
+        let mut config = Config::new();
+        for value in ["braille", "block", "tty"] {
+            assert_eq!(config.is_valid_string("graph_symbol", value), Ok(true));
+        }
+        // 只有单独的 box 可以使用 "default"
+        for value in INVALID.iter().chain(&["default"]) {
+            assert_eq!(
+                config.is_valid_string("graph_symbol", value),
+                Err(InvalidStrReason::GraphSymbolIdentifier),
+                "graph_symbol = {:?}",
+                value
+            );
+        }
+    }
+
+    #[test]
+    fn graph_symbol_box_values() {
+        let mut config = Config::new();
+        for key in BOX_KEYS {
+            for value in ["default", "braille", "block", "tty"] {
+                assert_eq!(
+                    config.is_valid_string(key, value),
+                    Ok(true),
+                    "{} = {:?}",
+                    key,
+                    value
+                );
+            }
+            for value in INVALID {
+                assert_eq!(
+                    config.is_valid_string(key, value),
+                    Err(InvalidStrReason::GraphSymbolIdentifier),
+                    "{} = {:?}",
+                    key,
+                    value
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn graph_symbol_box_apply() {
+        let mut config = Config::new();
+        for key in BOX_KEYS {
+            let mut tx = ConfigTransaction::new();
+            tx.set(key, "block");
+            assert_eq!(config.apply(&tx), Ok(()));
+            assert_eq!(config.get_string(key), "block");
+
+            // 无效的值被拒绝，保留原来的值
+            let mut tx = ConfigTransaction::new();
+            tx.set(key, "dots");
+            assert!(config.apply(&tx).is_err());
+            assert_eq!(config.get_string(key), "block");
+        }
+    }
+
+    // 启动检查发现配置文件不可写时，修改过的配置也不再写出
+    #[test]
+    fn write_disabled() {
+        let path = env::temp_dir().join(format!("btop-rs-write-{}.conf", std::process::id()));
+        let mut config = Config::new();
+        config.conf_file = path.clone();
+        let mut tx = ConfigTransaction::new();
+        tx.set("graph_symbol", "block");
+        assert_eq!(config.apply(&tx), Ok(()));
+
+        config.set_write_disabled(true);
+        config.write().unwrap();
+        assert!(!path.exists());
+
+        config.set_write_disabled(false);
+        config.write().unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(text.contains("graph_symbol = \"block\""));
+    }
 }
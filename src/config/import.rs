@@ -0,0 +1,159 @@
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+};
+
+use super::config::{unquote, Config, ConfigTransaction};
+
+// (程序名, $XDG_CONFIG_HOME 下的配置文件)，按顺序查找，btop++ 优先
+const SOURCES: &[(&str, &str)] = &[
+    ("btop++", "btop/btop.conf"),
+    ("bpytop", "bpytop/bpytop.conf"),
+];
+
+// 改过名字的配置项：(旧名称, btop-rs 中的名称)
+const RENAMED: &[(&str, &str)] = &[("draw_clock", "clock_format")];
+
+// 从 btop++ 或 bpytop 的配置文件中读到的配置项
+pub struct Import {
+    pub name: &'static str,
+    pub path: PathBuf,
+    // 已经换成 btop-rs 名称的 (配置项, 值)
+    pub values: Vec<(String, String)>,
+}
+
+// 查找 btop++ 和 bpytop 的配置文件，$XDG_CONFIG_HOME 未设置时使用 ~/.config
+pub fn find() -> Option<(&'static str, PathBuf)> {
+    let base = match env::var_os("XDG_CONFIG_HOME").map(PathBuf::from) {
+        Some(base) if base.is_absolute() => base,
+        _ => PathBuf::from(env::var_os("HOME")?).join(".config"),
+    };
+    SOURCES
+        .iter()
+        .map(|(name, file)| (*name, base.join(file)))
+        .find(|(_, path)| path.is_file())
+}
+
+// 两者的配置文件都是 key=value 格式，字符串值带引号。没有 "=" 或没有名称的行跳过
+fn parse(text: &str) -> Vec<(String, String)> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), unquote(value.trim())))
+        .filter(|(key, _)| !key.is_empty())
+        .map(|(key, value)| {
+            let key = RENAMED
+                .iter()
+                .find(|(old, _)| *old == key)
+                .map_or(key, |(_, new)| *new);
+            (key.to_owned(), value.to_owned())
+        })
+        .collect()
+}
+
+impl Import {
+    pub fn read(name: &'static str, path: &Path) -> io::Result<Self> {
+        let text = fs::read_to_string(path)?;
+        Ok(Import {
+            name,
+            path: path.to_owned(),
+            values: parse(&text),
+        })
+    }
+
+    // 逐项应用，一项无效不影响其它项。返回无法导入的配置项和原因
+    pub fn apply(&self, config: &mut Config) -> Vec<String> {
+        let mut failed = Vec::new();
+        for (key, value) in &self.values {
+            let mut tx = ConfigTransaction::new();
+            tx.set(key, value);
+            if let Err(err) = config.apply(&tx) {
+                failed.push(err);
+            }
+        }
+        failed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+
+    fn pair(key: &str, value: &str) -> (String, String) {
+        (key.to_owned(), value.to_owned())
+    }
+
+    #[test]
+    fn btop_config() {
+        let text = "\
+#? Config file for btop v. 1.2.13
+
+#* Name of a btop++/bpytop/bashtop formatted \".theme\" file.
+color_theme = \"Default\"
+
+theme_background = True
+update_ms = 2000
+shown_boxes = \"cpu mem net proc\"
+clock_format = \"%X\"
+";
+        assert_eq!(
+            parse(text),
+            [
+                pair("color_theme", "Default"),
+                pair("theme_background", "True"),
+                pair("update_ms", "2000"),
+                pair("shown_boxes", "cpu mem net proc"),
+                pair("clock_format", "%X"),
+            ]
+        );
+    }
+
+    #[test]
+    fn bpytop_config() {
+        // bpytop 的值和 "=" 之间没有空格，draw_clock 在 btop-rs 中叫 clock_format
+        let text = "\
+#? Config file for bpytop v. 1.0.68
+
+color_theme=\"+flat-remix\"
+draw_clock=\"%H:%M\"
+proc_sorting=\"cpu lazy\"
+";
+        assert_eq!(
+            parse(text),
+            [
+                pair("color_theme", "+flat-remix"),
+                pair("clock_format", "%H:%M"),
+                pair("proc_sorting", "cpu lazy"),
+            ]
+        );
+    }
+
+    #[test]
+    fn values_keep_inner_quotes() {
+        // 只去掉一对外层引号
+        assert_eq!(
+            parse("custom_cpu_name=\"\"quoted\"\"\nproc_filter=\"a=b\""),
+            [
+                pair("custom_cpu_name", "\"quoted\""),
+                pair("proc_filter", "a=b")
+            ]
+        );
+        assert_eq!(parse("disks_filter=\"\""), [pair("disks_filter", "")]);
+        assert_eq!(parse("net_iface=\"eth0"), [pair("net_iface", "\"eth0")]);
+        // 只有旧名称完全一致时才改名
+        assert_eq!(parse("draw_clock_x=1"), [pair("draw_clock_x", "1")]);
+    }
+
+    #[test]
+    fn malformed_lines() {
+        assert!(parse("").is_empty());
+        assert!(parse("\n   \n\t\n").is_empty());
+        assert!(parse("# update_ms = 100\n  #color_theme=x").is_empty());
+        assert!(parse("update_ms 2000\n[section]\n= value\n  =").is_empty());
+        assert_eq!(
+            parse("  update_ms   =   100  \r\nbroken\nlog_level=WARNING"),
+            [pair("update_ms", "100"), pair("log_level", "WARNING")]
+        );
+    }
+}
@@ -0,0 +1,139 @@
+use std::{
+    ffi::{c_int, CString},
+    fmt, fs,
+    os::unix::ffi::OsStrExt,
+    path::Path,
+};
+
+use libc::{R_OK, W_OK, X_OK};
+
+pub enum PathIssue {
+    Missing,
+    NotADirectory,
+    NotAFile,
+    NotReadable,
+    NotWritable,
+}
+
+impl fmt::Display for PathIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let reason = match self {
+            PathIssue::Missing => "does not exist",
+            PathIssue::NotADirectory => "is not a directory",
+            PathIssue::NotAFile => "is not a regular file",
+            PathIssue::NotReadable => "is not readable",
+            PathIssue::NotWritable => "is not writable",
+        };
+        write!(f, "{}", reason)
+    }
+}
+
+// 使用 access(2) 按当前用户的实际权限判断，而不是只看权限位
+fn access(path: &Path, mode: c_int) -> bool {
+    match CString::new(path.as_os_str().as_bytes()) {
+        Ok(c_path) => unsafe { libc::access(c_path.as_ptr(), mode) == 0 },
+        Err(_) => false,
+    }
+}
+
+pub fn check_dir(path: &Path, writable: bool) -> Result<(), PathIssue> {
+    let md = fs::metadata(path).map_err(|_| PathIssue::Missing)?;
+    if !md.is_dir() {
+        return Err(PathIssue::NotADirectory);
+    }
+    if !access(path, R_OK | X_OK) {
+        return Err(PathIssue::NotReadable);
+    }
+    if writable && !access(path, W_OK) {
+        return Err(PathIssue::NotWritable);
+    }
+    Ok(())
+}
+
+// 文件不存在时，只要父目录可写就认为可以创建
+pub fn check_file(path: &Path, writable: bool) -> Result<(), PathIssue> {
+    match fs::metadata(path) {
+        Ok(md) => {
+            if !md.is_file() {
+                return Err(PathIssue::NotAFile);
+            }
+            if !access(path, R_OK) {
+                return Err(PathIssue::NotReadable);
+            }
+            if writable && !access(path, W_OK) {
+                return Err(PathIssue::NotWritable);
+            }
+            Ok(())
+        }
+        Err(_) => match path.parent() {
+            Some(parent) if writable => check_dir(parent, true),
+            _ => Err(PathIssue::Missing),
+        },
+    }
+}
+
+pub fn describe(label: &str, path: &Path, issue: &PathIssue) -> String {
+    format!("{} {:?} {}", label, path, issue)
+}
+
+pub struct StartupPaths<'a> {
+    pub config_dir: &'a Path,
+    pub config_file: &'a Path,
+    pub log_file: &'a Path,
+    pub user_theme_dir: &'a Path,
+}
+
+// 启动时的检查结果，配置文件或日志文件不可写时调用者关闭对应的功能
+pub struct StartupCheck {
+    pub warnings: Vec<String>,
+    pub config_writable: bool,
+    pub log_writable: bool,
+}
+
+// 启动时统一检查所有路径，返回的警告会显示在警告浮层中。
+// 系统主题目录在第一次查找时检查，不在这里访问
+pub fn check_startup_paths(paths: &StartupPaths) -> StartupCheck {
+    let mut check = StartupCheck {
+        warnings: Vec::new(),
+        config_writable: true,
+        log_writable: true,
+    };
+
+    if !paths.config_dir.as_os_str().is_empty() {
+        if let Err(issue) = check_dir(paths.config_dir, true) {
+            check
+                .warnings
+                .push(describe("Config directory", paths.config_dir, &issue));
+        }
+    }
+
+    if !paths.config_file.as_os_str().is_empty() {
+        if let Err(issue) = check_file(paths.config_file, true) {
+            check.config_writable = false;
+            check.warnings.push(
+                describe("Config file", paths.config_file, &issue) + ", config saving disabled.",
+            );
+        }
+    }
+
+    if !paths.log_file.as_os_str().is_empty() {
+        if let Err(issue) = check_file(paths.log_file, true) {
+            check.log_writable = false;
+            check
+                .warnings
+                .push(describe("Log file", paths.log_file, &issue) + ", logging to file disabled.");
+        }
+    }
+
+    if !paths.user_theme_dir.as_os_str().is_empty() {
+        if let Err(issue) = check_dir(paths.user_theme_dir, false) {
+            check.warnings.push(describe(
+                "User theme directory",
+                paths.user_theme_dir,
+                &issue,
+            ));
+        }
+    }
+
+    check
+}
@@ -1,2 +1,6 @@
+#[allow(clippy::module_inception)]
 pub mod config;
+pub mod import;
+pub mod integrity;
 pub mod theme;
+pub mod toml;
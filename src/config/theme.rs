@@ -1,11 +1,262 @@
 use std::{
-    path::PathBuf,
-    sync::{Arc, Mutex},
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, OnceLock},
+    time::SystemTime,
 };
 
+use log::{info, warn};
+
+use super::{
+    config::Config,
+    integrity::{self, PathIssue},
+};
+
+// 与 btop++ 默认主题一致，主题文件中缺少的颜色也从这里取
+const DEFAULT_THEME: &[(&str, &str)] = &[
+    ("main_bg", "#000000"),
+    ("main_fg", "#cccccc"),
+    ("title", "#eeeeee"),
+    ("hi_fg", "#b54040"),
+    ("selected_bg", "#6a2f2f"),
+    ("selected_fg", "#eeeeee"),
+    ("inactive_fg", "#404040"),
+    ("graph_text", "#606060"),
+    ("meter_bg", "#404040"),
+    ("proc_misc", "#0de756"),
+    ("cpu_box", "#3d7b46"),
+    ("mem_box", "#8a882e"),
+    ("net_box", "#423ba5"),
+    ("proc_box", "#923535"),
+    ("div_line", "#303030"),
+    ("temp_start", "#4897d4"),
+    ("temp_mid", "#5474e8"),
+    ("temp_end", "#ff40b6"),
+    ("cpu_start", "#77ca9b"),
+    ("cpu_mid", "#cbc06c"),
+    ("cpu_end", "#dc4c4c"),
+    ("free_start", "#384f21"),
+    ("free_mid", "#b5e685"),
+    ("free_end", "#dcff85"),
+    ("cached_start", "#163350"),
+    ("cached_mid", "#74e6fc"),
+    ("cached_end", "#26c5ff"),
+    ("available_start", "#4e3f0e"),
+    ("available_mid", "#ffd77a"),
+    ("available_end", "#ffb814"),
+    ("used_start", "#592b26"),
+    ("used_mid", "#d9626d"),
+    ("used_end", "#ff4769"),
+    ("download_start", "#291f75"),
+    ("download_mid", "#4f43a3"),
+    ("download_end", "#b0a9de"),
+    ("upload_start", "#620665"),
+    ("upload_mid", "#7d4180"),
+    ("upload_end", "#dcafde"),
+    ("process_start", "#80d0a3"),
+    ("process_mid", "#dcd179"),
+    ("process_end", "#d45454"),
+    // btop++ 中没有的颜色：被暂停（SIGSTOP）的进程和 log box 的边框
+    ("proc_frozen", "#5fafd7"),
+    ("log_box", "#5f5f87"),
+];
+
+// 浅色背景下的内置主题，颜色整体加深以保证对比度
+const LIGHT_THEME: &[(&str, &str)] = &[
+    ("main_bg", "#f4f4f4"),
+    ("main_fg", "#303030"),
+    ("title", "#101010"),
+    ("hi_fg", "#b01010"),
+    ("selected_bg", "#d0b0b0"),
+    ("selected_fg", "#101010"),
+    ("inactive_fg", "#a0a0a0"),
+    ("graph_text", "#707070"),
+    ("meter_bg", "#c8c8c8"),
+    ("proc_misc", "#0a8a3a"),
+    ("cpu_box", "#2e6b37"),
+    ("mem_box", "#6e6c10"),
+    ("net_box", "#2f2890"),
+    ("proc_box", "#7a2020"),
+    ("div_line", "#b0b0b0"),
+    ("temp_start", "#1f6fb0"),
+    ("temp_mid", "#3050c0"),
+    ("temp_end", "#c01080"),
+    ("cpu_start", "#2f8f5b"),
+    ("cpu_mid", "#a08a10"),
+    ("cpu_end", "#c02020"),
+    ("free_start", "#4f7a20"),
+    ("free_mid", "#3f8f10"),
+    ("free_end", "#2a6a00"),
+    ("cached_start", "#3a7fa0"),
+    ("cached_mid", "#1f6f9f"),
+    ("cached_end", "#0a5080"),
+    ("available_start", "#a08020"),
+    ("available_mid", "#a06a00"),
+    ("available_end", "#804a00"),
+    ("used_start", "#a05050"),
+    ("used_mid", "#a02a3a"),
+    ("used_end", "#800020"),
+    ("download_start", "#6a60b0"),
+    ("download_mid", "#40349a"),
+    ("download_end", "#201870"),
+    ("upload_start", "#a060a0"),
+    ("upload_mid", "#803080"),
+    ("upload_end", "#5a0a5a"),
+    ("process_start", "#2f8f5b"),
+    ("process_mid", "#8a7a10"),
+    ("process_end", "#a02020"),
+    ("proc_frozen", "#1f5f9f"),
+    ("log_box", "#3f3f6f"),
+];
+
+const BUILTIN_THEMES: &[(&str, &[(&str, &str)])] =
+    &[("Default", DEFAULT_THEME), ("Light", LIGHT_THEME)];
+
+// 导出当前颜色表时使用的主题名称
+const EXPORT_NAME: &str = "exported";
+
+// 按 btop++ 的写法解析颜色："#RRGGBB"、灰阶 "#GG" 或十进制 "R G B"，统一为 "#rrggbb"
+fn parse_color(value: &str) -> Option<String> {
+    let value = value.trim();
+    if let Some(hex) = value.strip_prefix('#') {
+        let hex = match hex.len() {
+            2 => hex.repeat(3),
+            6 => hex.to_owned(),
+            _ => return None,
+        };
+        return u32::from_str_radix(&hex, 16)
+            .ok()
+            .map(|_| format!("#{}", hex.to_lowercase()));
+    }
+    let parts: Vec<u8> = value
+        .split_whitespace()
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [r, g, b] => Some(format!("#{:02x}{:02x}{:02x}", r, g, b)),
+        _ => None,
+    }
+}
+
+// bashtop 和早期 bpytop 的主题中还没有的键，从含义最接近的旧键取值（旧键, 新键），
+// 只在主题没有给出新键时使用。顺序与 btop++ 相同，先于中间色的计算
+const COMPAT_KEYS: &[(&str, &str)] = &[
+    ("inactive_fg", "meter_bg"),
+    ("inactive_fg", "graph_text"),
+    ("cpu_start", "process_start"),
+    ("cpu_mid", "process_mid"),
+    ("cpu_end", "process_end"),
+];
+
+// 带有 _start/_mid/_end 三种颜色的渐变
+const GRADIENTS: &[&str] = &[
+    "temp",
+    "cpu",
+    "free",
+    "cached",
+    "available",
+    "used",
+    "download",
+    "upload",
+    "process",
+];
+
+// 取出等号右边的值：引号中的内容，或没有引号时到空白或注释为止。
+// bashtop 的主题是 shell 脚本，等号两边可能有空格，行尾可能有注释
+fn theme_value(rest: &str) -> &str {
+    let rest = rest.trim_start();
+    match rest.chars().next() {
+        Some(quote @ ('"' | '\'')) => {
+            let rest = &rest[1..];
+            &rest[..rest.find(quote).unwrap_or(rest.len())]
+        }
+        // 颜色本身以 # 开头，行尾注释之前总有空白
+        _ => rest.split_whitespace().next().unwrap_or_default(),
+    }
+}
+
+fn midpoint(start: &str, end: &str) -> Option<String> {
+    let channel = |hex: &str, i: usize| u8::from_str_radix(hex.get(1 + i * 2..3 + i * 2)?, 16).ok();
+    let mut out = String::from("#");
+    for i in 0..3 {
+        let (a, b) = (channel(start, i)?, channel(end, i)?);
+        out += &format!("{:02x}", (a as u16 + b as u16) / 2);
+    }
+    Some(out)
+}
+
+// 主题文件每行为 theme[key]="value"，值为空或无法解析的颜色使用默认值。
+// 同时接受 bashtop/bpytop 的写法：旧的键名，以及留空中间色表示两段渐变
+pub fn parse_theme(text: &str) -> HashMap<String, String> {
+    let mut colors: HashMap<String, String> = text
+        .lines()
+        .filter_map(|line| {
+            let rest = line.trim().strip_prefix("theme[")?;
+            let (key, rest) = rest.split_once(']')?;
+            let value = theme_value(rest.trim_start().strip_prefix('=')?);
+            Some((key.trim().to_owned(), parse_color(value)?))
+        })
+        .collect();
+    for (old, key) in COMPAT_KEYS {
+        if let Some(color) = colors.get(*old).filter(|_| !colors.contains_key(*key)) {
+            colors.insert(key.to_string(), color.clone());
+        }
+    }
+    for name in GRADIENTS {
+        let key = format!("{}_mid", name);
+        if colors.contains_key(&key) {
+            continue;
+        }
+        let start = colors.get(&format!("{}_start", name));
+        let end = colors.get(&format!("{}_end", name));
+        if let Some(mid) = start.zip(end).and_then(|(start, end)| midpoint(start, end)) {
+            colors.insert(key, mid);
+        }
+    }
+    colors
+}
+
+// 按相对亮度判断背景是否为浅色
+pub fn is_light((r, g, b): (u8, u8, u8)) -> bool {
+    (0.2126 * r as f64 + 0.7152 * g as f64 + 0.0722 * b as f64) / 255.0 > 0.5
+}
+
+// theme_light_hours 的格式为 "起始-结束"，结束小于起始时跨过午夜
+pub fn parse_hours(value: &str) -> Option<(u32, u32)> {
+    let (start, end) = value.trim().split_once('-')?;
+    let (start, end) = (start.trim().parse().ok()?, end.trim().parse().ok()?);
+    (start < 24 && end <= 24).then_some((start, end))
+}
+
+fn modified(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+fn local_hour() -> u32 {
+    unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&now, &mut tm).is_null() {
+            return 12;
+        }
+        tm.tm_hour as u32
+    }
+}
+
 pub struct Theme {
     pub theme_dir: PathBuf,
     pub user_theme_dir: PathBuf,
+    pub exe_dir: PathBuf,
+    pub discovered: bool,
+    // 当前生效的主题名称和颜色
+    pub name: String,
+    pub colors: HashMap<String, String>,
+    // 从文件加载的主题记录路径和修改时间，文件被保存后重新加载
+    pub path: Option<PathBuf>,
+    pub modified: Option<SystemTime>,
+    // 启动时通过 OSC 11 查询到的终端背景色
+    pub background: Option<(u8, u8, u8)>,
 }
 
 impl Theme {
@@ -13,26 +264,71 @@ impl Theme {
         Theme {
             theme_dir: PathBuf::new(),
             user_theme_dir: PathBuf::new(),
+            exe_dir: PathBuf::new(),
+            discovered: false,
+            name: "Default".to_owned(),
+            colors: HashMap::new(),
+            path: None,
+            modified: None,
+            background: None,
         }
     }
 
     pub fn get_instance() -> Arc<Mutex<Theme>> {
-        static mut instance: Option<Arc<Mutex<Theme>>> = None;
-        unsafe {
-            instance
-                .get_or_insert_with(|| Arc::new(Mutex::new(Theme::new())))
-                .clone()
-        }
+        static INSTANCE: OnceLock<Arc<Mutex<Theme>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(Theme::new())))
+            .clone()
     }
 
     pub fn set_theme_dir(&mut self, theme_dir: PathBuf) {
         self.theme_dir = theme_dir;
+        self.discovered = true;
     }
 
-    pub fn get_theme_dir(&self) -> &PathBuf {
+    // 系统主题目录在第一次使用时才查找，避免启动时访问较慢的文件系统
+    pub fn get_theme_dir(&mut self) -> &PathBuf {
+        if !self.discovered {
+            self.discover_theme_dir();
+        }
         &self.theme_dir
     }
 
+    pub fn set_exe_dir(&mut self, exe_dir: PathBuf) {
+        self.exe_dir = exe_dir;
+    }
+
+    fn discover_theme_dir(&mut self) {
+        self.discovered = true;
+
+        let mut candidates = Vec::new();
+        if !self.exe_dir.as_os_str().is_empty() {
+            if let Ok(canon_path) = fs::canonicalize(self.exe_dir.join("../share/btop-rs/themes")) {
+                candidates.push(canon_path);
+            }
+        }
+        candidates.push(PathBuf::from("/usr/local/share/btop-rs/themes"));
+        candidates.push(PathBuf::from("/usr/share/btop-rs/themes"));
+
+        // 存在但无法读取的目录记录原因后跳过
+        match candidates
+            .into_iter()
+            .find(|path| match integrity::check_dir(path, false) {
+                Ok(()) => true,
+                Err(PathIssue::Missing) => false,
+                Err(issue) => {
+                    warn!("{}", integrity::describe("Theme directory", path, &issue));
+                    false
+                }
+            }) {
+            Some(path) => {
+                info!("theme dir: {:?}", path);
+                self.theme_dir = path;
+            }
+            None => warn!("no readable system theme directory found"),
+        }
+    }
+
     pub fn set_user_dir(&mut self, dir_path: PathBuf) {
         self.user_theme_dir = dir_path;
     }
@@ -48,4 +344,285 @@ impl Theme {
     pub fn clear_theme_dir(&mut self) {
         self.theme_dir.clear();
     }
+
+    pub fn get_name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn set_background(&mut self, background: Option<(u8, u8, u8)>) {
+        self.background = background;
+    }
+
+    // 主题中没有的颜色退回默认主题
+    pub fn color(&self, key: &str) -> &str {
+        self.colors
+            .get(key)
+            .map(String::as_str)
+            .or_else(|| {
+                DEFAULT_THEME
+                    .iter()
+                    .find(|(k, _)| *k == key)
+                    .map(|(_, v)| *v)
+            })
+            .unwrap_or("")
+    }
+
+    // 用户主题目录优先，与 btop++ 相同
+    fn find(&mut self, name: &str) -> Option<PathBuf> {
+        let file = format!("{}.theme", name);
+        let user_dir = self.user_theme_dir.clone();
+        [user_dir, self.get_theme_dir().clone()]
+            .into_iter()
+            .filter(|dir| !dir.as_os_str().is_empty())
+            .map(|dir| dir.join(&file))
+            .find(|path| path.is_file())
+    }
+
+    // 内置主题排在前面，之后是两个主题目录中按名称排序的 .theme 文件
+    pub fn list(&mut self) -> Vec<String> {
+        let mut files = Vec::new();
+        for dir in [self.get_theme_dir().clone(), self.user_theme_dir.clone()] {
+            let Ok(entries) = fs::read_dir(&dir) else {
+                continue;
+            };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().is_some_and(|ext| ext == "theme") {
+                    if let Some(name) = path.file_stem() {
+                        files.push(name.to_string_lossy().into_owned());
+                    }
+                }
+            }
+        }
+        files.sort();
+        let mut names: Vec<String> = BUILTIN_THEMES
+            .iter()
+            .map(|(name, _)| name.to_string())
+            .collect();
+        for name in files {
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+
+    pub fn load(&mut self, name: &str) -> Result<(), String> {
+        let (colors, path) = match BUILTIN_THEMES.iter().find(|(builtin, _)| *builtin == name) {
+            Some((_, colors)) => (
+                colors
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+                None,
+            ),
+            None => {
+                let path = self
+                    .find(name)
+                    .ok_or_else(|| format!("theme {:?} not found", name))?;
+                let text = fs::read_to_string(&path)
+                    .map_err(|err| format!("could not read theme {:?}: {}", path, err))?;
+                (parse_theme(&text), Some(path))
+            }
+        };
+        self.name = name.to_owned();
+        self.colors = colors;
+        self.modified = path.as_deref().and_then(modified);
+        self.path = path;
+        info!("theme loaded: {}", name);
+        Ok(())
+    }
+
+    // theme_auto 为 terminal 时按终端背景，为 schedule 时按 theme_light_hours 在
+    // theme_dark 和 theme_light 之间选择，否则使用 color_theme
+    pub fn wanted(&self, config: &Config) -> String {
+        let light = match config.get_string("theme_auto").as_str() {
+            "terminal" => self.background.map(is_light),
+            "schedule" => {
+                parse_hours(&config.get_string("theme_light_hours")).map(|(start, end)| {
+                    let hour = local_hour();
+                    match start <= end {
+                        true => (start..end).contains(&hour),
+                        false => hour >= start || hour < end,
+                    }
+                })
+            }
+            _ => None,
+        };
+        match light {
+            Some(true) => config.get_string("theme_light"),
+            Some(false) => config.get_string("theme_dark"),
+            None => config.get_string("color_theme"),
+        }
+    }
+
+    // 把当前生效的完整颜色表按 btop++ 格式写入用户主题目录，作为自定义主题的起点。
+    // 写出 btop++ 的全部键，主题中没有的按默认主题补全；写出的是主题中的 24 位颜色，
+    // 不经过低色模式的转换
+    pub fn export(&self) -> Result<PathBuf, String> {
+        if self.user_theme_dir.as_os_str().is_empty() {
+            return Err("no user theme directory".to_owned());
+        }
+        let mut text = format!(
+            "#Bashtop theme exported by btop-rs from \"{}\"\n\n",
+            self.name
+        );
+        for (key, _) in DEFAULT_THEME {
+            text += &format!("theme[{}]=\"{}\"\n", key, self.color(key));
+        }
+        let path = self.user_theme_dir.join(format!("{}.theme", EXPORT_NAME));
+        fs::write(&path, text).map_err(|err| format!("could not write {:?}: {}", path, err))?;
+        info!("theme exported to {:?}", path);
+        Ok(path)
+    }
+
+    // 当前主题文件的修改时间变化时重新读取，文件暂时不可读（例如编辑器正在替换文件）
+    // 时保留原来的颜色，下次再检查
+    fn reload(&mut self) -> bool {
+        let Some(path) = self.path.clone() else {
+            return false;
+        };
+        let time = modified(&path);
+        if time.is_none() || time == self.modified {
+            return false;
+        }
+        let Ok(text) = fs::read_to_string(&path) else {
+            return false;
+        };
+        self.colors = parse_theme(&text);
+        self.modified = time;
+        info!("theme file changed, reloaded: {:?}", path);
+        true
+    }
+
+    // 需要的主题与当前不同时切换，返回 true 表示颜色发生了变化。
+    // 加载失败时退回默认主题，并记住失败的名称避免每次都重试
+    pub fn update(&mut self, config: &Config) -> bool {
+        let wanted = self.wanted(config);
+        if wanted == self.name {
+            return self.reload();
+        }
+        if let Err(err) = self.load(&wanted) {
+            warn!("{}, using Default", err);
+            self.colors.clear();
+            self.path = None;
+            self.modified = None;
+            self.name = wanted;
+        }
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use super::*;
+
+    // 每个测试使用自己的临时目录，测试之间可以并行
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = env::temp_dir().join(format!("btop-rs-{}-{}", name, std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn export_writes_every_key() {
+        let dir = temp_dir("export");
+        let mut theme = Theme::new();
+        theme.set_user_dir(dir.clone());
+        theme.name = "partial".to_owned();
+        theme.colors = parse_theme("theme[hi_fg]=\"#ff0000\"\ntheme[used_end]=\"12 34 56\"");
+
+        let path = theme.export().unwrap();
+        let exported = parse_theme(&fs::read_to_string(&path).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        // 主题中没有的颜色按默认主题写出，完整覆盖 btop++ 的所有键
+        assert_eq!(exported.len(), DEFAULT_THEME.len());
+        for (key, value) in DEFAULT_THEME {
+            let expected = match *key {
+                "hi_fg" => "#ff0000",
+                "used_end" => "#0c2238",
+                _ => value,
+            };
+            assert_eq!(exported[*key], expected, "{}", key);
+        }
+    }
+
+    #[test]
+    fn builtin_themes_are_complete() {
+        for (name, colors) in BUILTIN_THEMES {
+            let keys: Vec<&str> = colors.iter().map(|(key, _)| *key).collect();
+            let default: Vec<&str> = DEFAULT_THEME.iter().map(|(key, _)| *key).collect();
+            assert_eq!(keys, default, "{}", name);
+            assert!(colors
+                .iter()
+                .all(|(_, value)| parse_color(value).as_deref() == Some(*value)));
+        }
+    }
+
+    // bashtop 时代的主题：shell 语法、单引号、行尾注释、留空的中间色，没有 bpytop 之后的键
+    const BASHTOP_THEME: &str = r##"#Bashtop theme with old key names
+theme[main_bg]=""
+theme[main_fg]="#cc"
+theme[inactive_fg] = "#30"   # 未启用的文字
+theme[cpu_start]='#50f095'
+theme[cpu_mid]=""
+theme[cpu_end]="#ff0000"
+theme[used_start]="#00 00 00"
+theme[used_end]="200 100 50"
+theme[download_start]="#000000"
+theme[download_mid]="#404040"
+theme[download_end]="#808080"
+"##;
+
+    #[test]
+    fn loads_bashtop_theme() {
+        let dir = temp_dir("bashtop");
+        fs::write(dir.join("old.theme"), BASHTOP_THEME).unwrap();
+        let mut theme = Theme::new();
+        theme.set_user_dir(dir.clone());
+        theme.set_theme_dir(PathBuf::new());
+        theme.load("old").unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(theme.get_name(), "old");
+        assert_eq!(theme.color("main_fg"), "#cccccc");
+        // 值为空的颜色使用默认值，无法解析的同样如此
+        assert_eq!(theme.color("main_bg"), "#000000");
+        assert_eq!(theme.color("used_start"), "#592b26");
+        assert_eq!(theme.color("used_end"), "#c86432");
+        // 新键从旧键取值
+        assert_eq!(theme.color("meter_bg"), "#303030");
+        assert_eq!(theme.color("graph_text"), "#303030");
+        assert_eq!(theme.color("process_start"), "#50f095");
+        assert_eq!(theme.color("process_end"), "#ff0000");
+        // 留空的中间色取两端的中点，给出的中间色保持不变
+        assert_eq!(theme.color("cpu_mid"), "#a7784a");
+        assert_eq!(theme.color("process_mid"), "#a7784a");
+        assert_eq!(theme.color("download_mid"), "#404040");
+        // 主题中完全没有的渐变使用默认主题
+        assert_eq!(theme.color("temp_mid"), "#5474e8");
+    }
+
+    #[test]
+    fn midpoints_for_every_gradient() {
+        let text: String = GRADIENTS
+            .iter()
+            .map(|name| {
+                format!(
+                    "theme[{0}_start]=\"#000000\"\ntheme[{0}_end]=\"#ffffff\"\n",
+                    name
+                )
+            })
+            .collect();
+        let colors = parse_theme(&text);
+        for name in GRADIENTS {
+            assert_eq!(colors[&format!("{}_mid", name)], "#7f7f7f", "{}", name);
+        }
+        // 新键已经给出时不使用旧键
+        let colors = parse_theme("theme[inactive_fg]=\"#30\"\ntheme[meter_bg]=\"#50\"");
+        assert_eq!(colors["meter_bg"], "#505050");
+        assert_eq!(colors["graph_text"], "#303030");
+    }
 }
@@ -0,0 +1,373 @@
+// btop-rs.toml 只支持配置需要的 TOML 子集：字符串、布尔值、整数、数组、[表] 和注释。
+// 读取时转换成 btop-rs.conf 的 key = value 写法，由 Config::load 统一解析
+
+// 数组拼接成原来的字符串，这些配置项用逗号分隔，其余（shown_boxes 等）用空格
+const COMMA_LISTS: &[&str] = &["alert"];
+
+enum Value {
+    Str(String),
+    Bool(bool),
+    Int(i64),
+    Array(Vec<Value>),
+}
+
+impl Value {
+    fn text(&self, separator: &str) -> String {
+        match self {
+            Value::Str(value) => value.to_owned(),
+            Value::Bool(value) => value.to_string(),
+            Value::Int(value) => value.to_string(),
+            Value::Array(items) => items
+                .iter()
+                .map(|item| item.text(separator))
+                .collect::<Vec<_>>()
+                .join(separator),
+        }
+    }
+}
+
+// 跳过空白、换行和注释
+fn skip_blank(mut text: &str) -> &str {
+    loop {
+        text = text.trim_start();
+        match text.strip_prefix('#') {
+            Some(comment) => text = comment.split_once('\n').map_or("", |(_, rest)| rest),
+            None => return text,
+        }
+    }
+}
+
+fn parse_string(text: &str) -> Result<(String, &str), String> {
+    let mut value = String::new();
+    let mut chars = text.char_indices();
+    while let Some((pos, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &text[pos + 1..])),
+            '\n' => break,
+            '\\' => match chars.next().map(|(_, c)| c) {
+                Some('"') => value.push('"'),
+                Some('\\') => value.push('\\'),
+                Some('n') => value.push('\n'),
+                Some('t') => value.push('\t'),
+                Some('r') => value.push('\r'),
+                Some(c) => return Err(format!("unsupported escape \"\\{}\"", c)),
+                None => break,
+            },
+            _ => value.push(c),
+        }
+    }
+    Err("unterminated string".to_owned())
+}
+
+// 返回解析出的值和剩余的文本
+fn parse_value(text: &str) -> Result<(Value, &str), String> {
+    if let Some(rest) = text.strip_prefix('"') {
+        let (value, rest) = parse_string(rest)?;
+        return Ok((Value::Str(value), rest));
+    }
+    if let Some(rest) = text.strip_prefix('\'') {
+        return match rest.split_once('\'') {
+            Some((value, rest)) if !value.contains('\n') => {
+                Ok((Value::Str(value.to_owned()), rest))
+            }
+            _ => Err("unterminated string".to_owned()),
+        };
+    }
+    if let Some(mut rest) = text.strip_prefix('[') {
+        // 数组可以跨行，元素之间和末尾可以有逗号和注释
+        let mut items = Vec::new();
+        loop {
+            rest = skip_blank(rest);
+            if let Some(after) = rest.strip_prefix(']') {
+                return Ok((Value::Array(items), after));
+            }
+            let (item, after) = parse_value(rest)?;
+            items.push(item);
+            rest = skip_blank(after);
+            match rest.strip_prefix(',') {
+                Some(after) => rest = after,
+                None if rest.starts_with(']') => {}
+                None => return Err("expected \",\" or \"]\" in array".to_owned()),
+            }
+        }
+    }
+    let end = text
+        .find(|c: char| c.is_whitespace() || matches!(c, ',' | ']' | '#'))
+        .unwrap_or(text.len());
+    let (token, rest) = text.split_at(end);
+    let value = match token {
+        "true" => Value::Bool(true),
+        "false" => Value::Bool(false),
+        _ => match token.replace('_', "").parse::<i64>() {
+            Ok(value) => Value::Int(value),
+            Err(_) => return Err(format!("invalid value \"{}\"", token)),
+        },
+    };
+    Ok((value, rest))
+}
+
+// 转换成 key = value 的写法，每一行带着它在文件中的行号。
+// 无法解析的行跳过，行号和原因加入 errors
+pub fn to_conf(text: &str, errors: &mut Vec<(usize, String)>) -> Vec<(usize, String)> {
+    let mut out = Vec::new();
+    let mut rest = skip_blank(text);
+    while !rest.is_empty() {
+        let number = text[..text.len() - rest.len()].matches('\n').count() + 1;
+        let (line, next) = rest.split_once('\n').unwrap_or((rest, ""));
+        if line.starts_with('[') {
+            // [profile.<name>] 原样保留，其它表由 Config::load 报告
+            let header = line.split_once('#').map_or(line, |(header, _)| header);
+            out.push((number, header.trim_end().to_owned()));
+            rest = skip_blank(next);
+            continue;
+        }
+        let Some(eq) = line.find('=') else {
+            errors.push((number, "Expected \"key = value\"".to_owned()));
+            rest = skip_blank(next);
+            continue;
+        };
+        let key = line[..eq].trim().trim_matches('"');
+        if key.contains('.') {
+            errors.push((number, "Dotted keys are not supported".to_owned()));
+            rest = skip_blank(next);
+            continue;
+        }
+        // 值从 "=" 之后开始解析，数组可以延续到后面的行
+        let value = match parse_value(rest[eq + 1..].trim_start_matches([' ', '\t'])) {
+            Ok((value, after)) => {
+                let after = after.trim_start_matches([' ', '\t', '\r']);
+                if !after.is_empty() && !after.starts_with(['\n', '#']) {
+                    errors.push((number, "Unexpected text after the value".to_owned()));
+                    rest = skip_blank(after.split_once('\n').map_or("", |(_, rest)| rest));
+                    continue;
+                }
+                rest = skip_blank(after);
+                value
+            }
+            Err(err) => {
+                errors.push((number, format!("Invalid value for {}: {}", key, err)));
+                rest = skip_blank(next);
+                continue;
+            }
+        };
+        let separator = if COMMA_LISTS.contains(&key) {
+            ", "
+        } else {
+            " "
+        };
+        let conf = match value {
+            Value::Bool(true) => "True".to_owned(),
+            Value::Bool(false) => "False".to_owned(),
+            Value::Int(value) => value.to_string(),
+            // 换行会被当作新的一行配置
+            value => format!("\"{}\"", value.text(separator).replace('\n', " ")),
+        };
+        out.push((number, format!("{} = {}", key, conf)));
+    }
+    out
+}
+
+// 把 key = value 写法中的值转换成 TOML，没有引号又不是整数或布尔值的按字符串处理
+pub fn from_conf(value: &str) -> String {
+    let value = value.trim();
+    match value {
+        "True" | "true" => return "true".to_owned(),
+        "False" | "false" => return "false".to_owned(),
+        _ => {}
+    }
+    if value.parse::<i64>().is_ok() {
+        return value.to_owned();
+    }
+    let inner = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    let mut out = String::from('"');
+    for c in inner.chars() {
+        match c {
+            '"' => out += "\\\"",
+            '\\' => out += "\\\\",
+            '\n' => out += "\\n",
+            '\t' => out += "\\t",
+            '\r' => out += "\\r",
+            _ => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{from_conf, to_conf};
+
+    // (行号, 内容)
+    type Lines = Vec<(usize, String)>;
+
+    // 转换后的行和错误
+    fn convert(text: &str) -> (Lines, Lines) {
+        let mut errors = Vec::new();
+        let lines = to_conf(text, &mut errors);
+        (lines, errors)
+    }
+
+    fn lines(text: &str) -> Vec<String> {
+        let (lines, errors) = convert(text);
+        assert!(errors.is_empty(), "{:?}: {:?}", text, errors);
+        lines.into_iter().map(|(_, line)| line).collect()
+    }
+
+    fn errors(text: &str) -> Lines {
+        convert(text).1
+    }
+
+    #[test]
+    fn round_trip() {
+        // btop-rs.conf 中的值 -> TOML -> btop-rs.conf 中的值
+        for value in [
+            "True",
+            "False",
+            "0",
+            "-42",
+            "2000",
+            "\"\"",
+            "\"Default\"",
+            "\"cpu mem net proc\"",
+            "\"2000\"",
+            "\"True\"",
+            "\"%X /user@/host\"",
+            "\"say \"hi\"\"",
+            "\"C:\\path\\to\"",
+            "\"tab\there\"",
+            "\"# not a comment\"",
+            "\"[brackets], commas\"",
+            "\"ünïcødé ░▒▓\"",
+        ] {
+            let toml = format!("key = {}", from_conf(value));
+            assert_eq!(lines(&toml), [format!("key = {}", value)], "{}", toml);
+        }
+    }
+
+    #[test]
+    fn from_conf_values() {
+        assert_eq!(from_conf("True"), "true");
+        assert_eq!(from_conf("false"), "false");
+        assert_eq!(from_conf(" 100 "), "100");
+        assert_eq!(from_conf("\"a\""), "\"a\"");
+        // 没有引号的文本按字符串处理
+        assert_eq!(from_conf("braille"), "\"braille\"");
+        assert_eq!(from_conf("\"a\\b\""), "\"a\\\\b\"");
+        assert_eq!(from_conf("\"line\nbreak\""), "\"line\\nbreak\"");
+        assert_eq!(from_conf("\"\""), "\"\"");
+        assert_eq!(from_conf("\""), "\"\\\"\"");
+    }
+
+    #[test]
+    fn toml_values() {
+        let text = r#"
+# 注释和空行被跳过
+color_theme = "Default"   # 行尾注释
+theme_background = true
+truecolor = false
+update_ms = 1_500
+proc_filter = 'C:\literal "string"'
+escapes = "a\"b\\c\td"
+"quoted_key" = 1
+shown_boxes = [
+    "cpu",  # 数组中的注释
+    "proc",
+]
+alert = ["cpu>90", "mem>80"]
+empty = []
+
+[profile.server]
+update_ms = -1 # 负数
+"#;
+        assert_eq!(
+            lines(text),
+            [
+                "color_theme = \"Default\"",
+                "theme_background = True",
+                "truecolor = False",
+                "update_ms = 1500",
+                "proc_filter = \"C:\\literal \"string\"\"",
+                "escapes = \"a\"b\\c\td\"",
+                "quoted_key = 1",
+                "shown_boxes = \"cpu proc\"",
+                "alert = \"cpu>90, mem>80\"",
+                "empty = \"\"",
+                "[profile.server]",
+                "update_ms = -1",
+            ]
+        );
+        // 行号是值开始的那一行
+        let numbers: Vec<usize> = convert(text).0.iter().map(|(line, _)| *line).collect();
+        assert_eq!(numbers, [3, 4, 5, 6, 7, 8, 9, 10, 14, 15, 17, 18]);
+    }
+
+    #[test]
+    fn malformed_input() {
+        assert!(convert("").0.is_empty());
+        assert!(convert("\n  \n# only a comment").0.is_empty());
+        assert_eq!(
+            errors("update_ms 2000"),
+            [(1, "Expected \"key = value\"".to_owned())]
+        );
+        assert_eq!(
+            errors("\nprofile.a.update_ms = 1"),
+            [(2, "Dotted keys are not supported".to_owned())]
+        );
+        assert_eq!(
+            errors("a = \"unterminated\nb = 1"),
+            [(1, "Invalid value for a: unterminated string".to_owned())]
+        );
+        assert_eq!(
+            errors("a = 'unterminated\nb = 1"),
+            [(1, "Invalid value for a: unterminated string".to_owned())]
+        );
+        assert_eq!(
+            errors("a = \"bad \\x escape\""),
+            [(
+                1,
+                "Invalid value for a: unsupported escape \"\\x\"".to_owned()
+            )]
+        );
+        assert_eq!(
+            errors("a = yes"),
+            [(1, "Invalid value for a: invalid value \"yes\"".to_owned())]
+        );
+        assert_eq!(
+            errors("a = 1.5"),
+            [(1, "Invalid value for a: invalid value \"1.5\"".to_owned())]
+        );
+        assert_eq!(
+            errors("a ="),
+            [(1, "Invalid value for a: invalid value \"\"".to_owned())]
+        );
+        assert_eq!(
+            errors("a = \"x\" \"y\""),
+            [(1, "Unexpected text after the value".to_owned())]
+        );
+        assert_eq!(
+            errors("a = [\"x\" \"y\"]"),
+            [(
+                1,
+                "Invalid value for a: expected \",\" or \"]\" in array".to_owned()
+            )]
+        );
+        assert_eq!(
+            errors("a = [\"x\",\n"),
+            [(1, "Invalid value for a: invalid value \"\"".to_owned())]
+        );
+        // 错误的行被跳过，其余的行照常转换
+        let (lines, errors) = convert("a = nope\nb = 2\nc\nd = \"ok\"");
+        assert_eq!(
+            lines,
+            [(2, "b = 2".to_owned()), (4, "d = \"ok\"".to_owned())]
+        );
+        assert_eq!(
+            errors.iter().map(|(line, _)| *line).collect::<Vec<_>>(),
+            [1, 3]
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use std::{
+    fs::{self, DirBuilder},
+    io::{self, BufRead, BufReader, Write},
+    os::unix::{
+        fs::{DirBuilderExt, PermissionsExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    process,
+    sync::{Arc, Mutex},
+    thread,
+};
+
+use log::{info, warn};
+
+use crate::{
+    collect::proc,
+    config::{
+        config::{Config, ConfigTransaction, InvalidIntReason},
+        theme::Theme,
+    },
+    export::json,
+    runner::Runner,
+    screen::Screen,
+    shared::{
+        host::HostInfo,
+        proc::{check_realtime, BoostCheck, IoClass, IoPriority, ProcInfo},
+    },
+};
+
+const HELP: &str = "commands: preset <0-9> | update_ms <ms> | toggle <cpu|mem|net|proc> | \
+    set <key>=<value>[; <key>=<value>...] | detail <pid> | ionice <pid> <none|idle|be/N|rt/N> [confirm] | refresh host | export theme | dump | help";
+
+// 配置目录中的 Unix 域套接字，供窗口管理器脚本等外部工具控制正在运行的实例
+pub struct ControlSocket {
+    path: PathBuf,
+    snapshot: Arc<Mutex<Snapshot>>,
+}
+
+// 最近一次采集的结果，进程列表用于检查实时优先级的请求
+#[derive(Default)]
+pub struct Snapshot {
+    json: String,
+    procs: Vec<ProcInfo>,
+    cores: usize,
+}
+
+impl ControlSocket {
+    pub fn start(path: PathBuf) -> io::Result<Self> {
+        if path.exists() {
+            // 还能连接上说明已有实例在运行，否则是上次遗留的文件
+            if UnixStream::connect(&path).is_ok() {
+                return Err(io::Error::new(
+                    io::ErrorKind::AddrInUse,
+                    "another instance is listening on the control socket",
+                ));
+            }
+            fs::remove_file(&path)?;
+        }
+
+        let listener = bind_private(&path)?;
+        info!("control socket listening on {:?}", path);
+
+        let snapshot = Arc::new(Mutex::new(Snapshot {
+            json: String::from("{}"),
+            ..Default::default()
+        }));
+        let t_snapshot = snapshot.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(err) = handle(stream, &t_snapshot) {
+                            warn!("control socket client error: {}", err);
+                        }
+                    }
+                    Err(err) => warn!("control socket accept failed: {}", err),
+                }
+            }
+        });
+
+        Ok(ControlSocket { path, snapshot })
+    }
+
+    pub fn get_path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn update(&self, runner: &Runner) {
+        *self.snapshot.lock().unwrap() = Snapshot {
+            json: json::snapshot(runner),
+            procs: runner.procs.clone(),
+            cores: runner
+                .cpu
+                .core_percent
+                .len()
+                .saturating_sub(runner.cpu.offline.len()),
+        };
+    }
+}
+
+// 先在只有自己可以访问的临时目录中创建套接字并改为 0600，再移动到 path，
+// 其它用户在任何时候都无法连接上。不修改进程的 umask，其它线程创建的文件不受影响
+fn bind_private(path: &Path) -> io::Result<UnixListener> {
+    let mut dir = path.as_os_str().to_owned();
+    dir.push(format!(".{}", process::id()));
+    let dir = PathBuf::from(dir);
+    let _ = fs::remove_dir_all(&dir);
+    DirBuilder::new().mode(0o700).create(&dir)?;
+    let tmp = dir.join("sock");
+    let listener = UnixListener::bind(&tmp).and_then(|listener| {
+        fs::set_permissions(&tmp, fs::Permissions::from_mode(0o600))?;
+        fs::rename(&tmp, path)?;
+        Ok(listener)
+    });
+    let _ = fs::remove_file(&tmp);
+    let _ = fs::remove_dir(&dir);
+    listener
+}
+
+fn handle(stream: UnixStream, snapshot: &Mutex<Snapshot>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let reply = execute(&line, snapshot);
+        writer.write_all(reply.as_bytes())?;
+        writer.write_all(b"\n")?;
+    }
+    Ok(())
+}
+
+// 修改了布局或显示的命令成功后请求重绘，调用时不能持有配置的锁
+fn request_redraw() {
+    Screen::get_instance().lock().unwrap().redraw = true;
+}
+
+// 每条命令返回一行结果，成功为 "ok"，失败以 "error:" 开头
+pub fn execute(line: &str, snapshot: &Mutex<Snapshot>) -> String {
+    let mut parts = line.split_whitespace();
+    let command = parts.next().unwrap_or("");
+    let arg = parts.next().unwrap_or("");
+
+    let instance = Config::get_instance();
+    match command {
+        "preset" => match arg.parse::<usize>() {
+            Ok(index) if instance.lock().unwrap().apply_preset(index) => {
+                request_redraw();
+                "ok".to_owned()
+            }
+            _ => format!("error: invalid preset: {}", arg),
+        },
+        "update_ms" => {
+            // 先释放配置的锁再请求重绘
+            let result = instance.lock().unwrap().set_int("update_ms", arg);
+            match result {
+                Ok(_) => {
+                    request_redraw();
+                    "ok".to_owned()
+                }
+                Err(InvalidIntReason::ValueTooLow) => "error: update_ms too low (<100)".to_owned(),
+                Err(InvalidIntReason::ValueTooHigh) => {
+                    "error: update_ms too high (>86400000)".to_owned()
+                }
+                Err(InvalidIntReason::ParseError) => format!("error: invalid number: {}", arg),
+            }
+        }
+        "toggle" => {
+            let toggled = instance.lock().unwrap().toggle_box(arg);
+            match toggled {
+                true => {
+                    request_redraw();
+                    "ok".to_owned()
+                }
+                false => format!("error: can't toggle box: {}", arg),
+            }
+        }
+        // 同一条 set 命令中的修改作为一个事务生效
+        "set" => {
+            let mut tx = ConfigTransaction::new();
+            let pairs = line.trim_start()[command.len()..].split(';');
+            for pair in pairs.map(str::trim).filter(|pair| !pair.is_empty()) {
+                match pair.split_once('=') {
+                    Some((key, value)) => tx.set(key.trim(), value.trim().trim_matches('"')),
+                    None => return format!("error: expected <key>=<value>: {}", pair),
+                };
+            }
+            if tx.is_empty() {
+                return "error: nothing to set".to_owned();
+            }
+            let result = instance.lock().unwrap().apply(&tx);
+            match result {
+                Ok(_) => {
+                    request_redraw();
+                    "ok".to_owned()
+                }
+                Err(err) => format!("error: {}", err),
+            }
+        }
+        "detail" => {
+            let mut config = instance.lock().unwrap();
+            match arg.parse::<i32>() {
+                Ok(pid) if pid > 0 => {
+                    let _ = config.set_int("detailed_pid", arg);
+                    config.set_bool("show_detailed", true);
+                    "ok".to_owned()
+                }
+                _ => format!("error: invalid pid: {}", arg),
+            }
+        }
+        "ionice" => {
+            let prio_arg = parts.next().unwrap_or("");
+            let confirmed = parts.next() == Some("confirm");
+            match (arg.parse::<i32>(), IoPriority::from_name(prio_arg)) {
+                (Ok(pid), Some(prio)) if pid > 0 && prio.class == IoClass::RealTime => {
+                    let snapshot = snapshot.lock().unwrap();
+                    let Some(target) = snapshot.procs.iter().find(|p| p.pid == pid) else {
+                        return format!("error: no such process: {}", pid);
+                    };
+                    let per_core = instance.lock().unwrap().get_bool("proc_per_core");
+                    match check_realtime(target, snapshot.cores, per_core) {
+                        BoostCheck::Refuse(reason) => format!("error: {}", reason),
+                        BoostCheck::Confirm(risk) if !confirmed => {
+                            format!("error: {}, repeat with confirm to apply", risk)
+                        }
+                        BoostCheck::Confirm(_) => match proc::set_ioprio(pid, prio) {
+                            Ok(_) => "ok".to_owned(),
+                            Err(err) => format!("error: {}", err),
+                        },
+                    }
+                }
+                (Ok(pid), Some(prio)) if pid > 0 => match proc::set_ioprio(pid, prio) {
+                    Ok(_) => "ok".to_owned(),
+                    Err(err) => format!("error: {}", err),
+                },
+                (Ok(pid), _) if pid > 0 => format!("error: invalid I/O priority: {}", prio_arg),
+                _ => format!("error: invalid pid: {}", arg),
+            }
+        }
+        // 主机名或登录用户在运行期间变化后重新读取
+        "refresh" => match arg {
+            "host" => {
+                HostInfo::get_instance().lock().unwrap().refresh();
+                "ok".to_owned()
+            }
+            _ => format!("error: can't refresh: {}", arg),
+        },
+        "export" => match arg {
+            "theme" => match Theme::get_instance().lock().unwrap().export() {
+                Ok(path) => format!("ok: {}", path.display()),
+                Err(err) => format!("error: {}", err),
+            },
+            _ => format!("error: can't export: {}", arg),
+        },
+        "dump" => snapshot.lock().unwrap().json.clone(),
+        "help" => HELP.to_owned(),
+        _ => format!("error: unknown command: {}, {}", command, HELP),
+    }
+}
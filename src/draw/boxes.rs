@@ -0,0 +1,1869 @@
+use std::collections::BTreeSet;
+
+use crate::{
+    human_bytes,
+    shared::{
+        battery::BatteryInfo,
+        cpu::CpuInfo,
+        disk::{DiskInfo, RaidStatus},
+        history::Stats,
+        klog::KlogInfo,
+        mem::MemInfo,
+        net::{NetInfo, Overhead, WifiInfo},
+        proc::{ProcCounts, ProcInfo, ProcSort},
+        socket::{addr_text, SocketInfo},
+    },
+    term::caps::ColorDepth,
+};
+
+use super::{
+    clock, create_box, fit, gradient_color, graph, hex_color, mv_to, parse_hex, rgb_color,
+    sanitize, strip_ansi,
+    symbols::{symbols, BoxSymbols, Symbols},
+    theme_color, Border, BOLD, RESET,
+};
+
+const REVERSE: &str = "\x1b[7m";
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BoxKind {
+    Cpu,
+    Mem,
+    Net,
+    Proc,
+    Log,
+}
+
+impl BoxKind {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cpu" => Some(BoxKind::Cpu),
+            "mem" => Some(BoxKind::Mem),
+            "net" => Some(BoxKind::Net),
+            "proc" => Some(BoxKind::Proc),
+            "log" => Some(BoxKind::Log),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BoxKind::Cpu => "cpu",
+            BoxKind::Mem => "mem",
+            BoxKind::Net => "net",
+            BoxKind::Proc => "proc",
+            BoxKind::Log => "log",
+        }
+    }
+
+    // 主题中边框颜色的名称
+    fn color_key(&self) -> &'static str {
+        match self {
+            BoxKind::Cpu => "cpu_box",
+            BoxKind::Mem => "mem_box",
+            BoxKind::Net => "net_box",
+            BoxKind::Proc => "proc_box",
+            BoxKind::Log => "log_box",
+        }
+    }
+}
+
+// 边框的颜色：普通时为 box 自己的颜色，获得焦点或告警闪烁时高亮
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BorderStyle {
+    Normal,
+    Focused,
+    Alert,
+}
+
+// cpu box 顶部边框上除标题以外的内容，不显示的项为空字符串
+pub struct CpuHeader<'a> {
+    pub clocks: &'a [String; 3],
+    pub host: &'a str,
+    // show_system 时的发行版、内核版本和架构
+    pub system: &'a str,
+    pub battery: &'a str,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct BoxRect {
+    pub kind: BoxKind,
+    pub x: u16,
+    pub y: u16,
+    pub width: u16,
+    pub height: u16,
+}
+
+impl BoxRect {
+    // 去掉边框后可用于内容的行数
+    pub fn inner_height(&self) -> usize {
+        self.height.saturating_sub(2) as usize
+    }
+
+    pub fn inner_width(&self) -> usize {
+        self.width.saturating_sub(2) as usize
+    }
+
+    // 获得焦点的 box 使用主题的 hi_fg，告警闪烁时使用 cpu_end
+    fn border_color(&self, style: BorderStyle, depth: ColorDepth) -> String {
+        match style {
+            BorderStyle::Normal => hex_color(&theme_color(self.kind.color_key()), false, depth),
+            BorderStyle::Focused => {
+                format!("{}{}", BOLD, hex_color(&theme_color("hi_fg"), false, depth))
+            }
+            BorderStyle::Alert => {
+                format!(
+                    "{}{}",
+                    BOLD,
+                    hex_color(&theme_color("cpu_end"), false, depth)
+                )
+            }
+        }
+    }
+
+    // 在顶部边框的左、中、右绘制时钟，与标题或前一个时钟重叠时不绘制。
+    // 电池状态放在最右侧，右侧时钟排在它的左边；user@host 紧跟在标题后面
+    pub fn draw_clocks(
+        &self,
+        symbols: &BoxSymbols,
+        style: BorderStyle,
+        depth: ColorDepth,
+        title: &str,
+        header: &CpuHeader,
+    ) -> String {
+        let CpuHeader {
+            clocks,
+            host,
+            system,
+            battery,
+        } = header;
+        let color = self.border_color(style, depth);
+        // 标题占据 "─┤title├"，右侧至少保留一段横线和边角
+        let mut free = self.x + 5 + title.chars().count() as u16;
+        let mut end = self.x + self.width.saturating_sub(2);
+        let mut out = String::new();
+        let slot = |start: u16, text: &str| {
+            format!(
+                "{}{}{}{}{}{}{}{}",
+                mv_to(self.y, start),
+                color,
+                symbols.title_left,
+                RESET,
+                text,
+                color,
+                symbols.title_right,
+                RESET
+            )
+        };
+        let width = battery.chars().count() as u16 + 2;
+        if !battery.is_empty() && width <= end.saturating_sub(free) {
+            end -= width;
+            out += &slot(end, battery);
+            end = end.saturating_sub(1);
+        }
+        for text in [host, system] {
+            let width = text.chars().count() as u16 + 2;
+            if !text.is_empty() && width <= end.saturating_sub(free) {
+                out += &slot(free, text);
+                free += width + 1;
+            }
+        }
+        for (i, clock) in clocks.iter().enumerate() {
+            let width = clock.chars().count() as u16 + 2;
+            if clock.is_empty() || width > end.saturating_sub(free) {
+                continue;
+            }
+            let start = match i {
+                0 => free,
+                1 => (self.x + self.width.saturating_sub(width) / 2).max(free),
+                _ => end - width,
+            };
+            if start + width > end {
+                continue;
+            }
+            out += &slot(start, clock);
+            free = start + width + 1;
+        }
+        out
+    }
+
+    pub fn draw_border(
+        &self,
+        symbols: &BoxSymbols,
+        style: BorderStyle,
+        depth: ColorDepth,
+        title: &str,
+        bottom_title: &str,
+    ) -> String {
+        let color = self.border_color(style, depth);
+        let border = Border {
+            symbols,
+            line_color: &color,
+            title,
+            bottom_title,
+        };
+        create_box(self.x, self.y, self.width, self.height, &border)
+    }
+
+    // 在顶部边框右侧绘制 texts 中第一个放得下的文本，与标题之间至少留一段横线
+    pub fn draw_title_right(
+        &self,
+        symbols: &BoxSymbols,
+        style: BorderStyle,
+        depth: ColorDepth,
+        title: &str,
+        texts: &[String],
+    ) -> String {
+        let color = self.border_color(style, depth);
+        let free = self.x + 5 + strip_ansi(title).chars().count() as u16;
+        let end = self.x + self.width.saturating_sub(2);
+        let Some(text) = texts
+            .iter()
+            .find(|text| text.chars().count() as u16 + 2 <= end.saturating_sub(free))
+        else {
+            return String::new();
+        };
+        format!(
+            "{}{}{}{}{}{}{}{}",
+            mv_to(self.y, end - text.chars().count() as u16 - 2),
+            color,
+            symbols.title_left,
+            RESET,
+            text,
+            color,
+            symbols.title_right,
+            RESET
+        )
+    }
+
+    // 在 box 内部第 line 行（从 0 开始）输出一行文本，超出宽度的部分截断
+    fn line(&self, line: usize, text: &str) -> String {
+        self.styled_line(line, "", text)
+    }
+
+    // 截断只按可见文本计算，style 在截断后再包裹上去
+    fn styled_line(&self, line: usize, style: &str, text: &str) -> String {
+        self.styled_at(line, 0, self.inner_width(), style, text)
+    }
+
+    // 已经着色的文本无法按字符截断，只在整段放得下时输出
+    fn meter_at(&self, line: usize, col: usize, width: usize, text: &str) -> String {
+        if line >= self.inner_height() || col + width > self.inner_width() {
+            return String::new();
+        }
+        format!(
+            "{}{}",
+            mv_to(self.y + 1 + line as u16, self.x + 1 + col as u16),
+            text
+        )
+    }
+
+    // 从内部第 col 列开始输出宽度为 width 的文本
+    fn styled_at(&self, line: usize, col: usize, width: usize, style: &str, text: &str) -> String {
+        if line >= self.inner_height() || col + width > self.inner_width() {
+            return String::new();
+        }
+        format!(
+            "{}{}{}{}",
+            mv_to(self.y + 1 + line as u16, self.x + 1 + col as u16),
+            style,
+            fit(text, width),
+            RESET
+        )
+    }
+}
+
+pub struct Layout {
+    pub cpu_bottom: bool,
+    pub mem_below_net: bool,
+    pub proc_left: bool,
+    // 终端较窄时把 proc 和 mem/net 上下排列
+    pub adaptive: bool,
+    // cpu_box_height_pct 和 proc_box_width_pct
+    pub cpu_height_pct: u16,
+    pub proc_width_pct: u16,
+}
+
+// adaptive_layout 时宽度小于这个值改为上下排列，proc 可以使用整行的宽度
+const NARROW_WIDTH: u16 = 90;
+// 上下排列时 mem/net 一行的高度，刚好放下 mem 的五行内容
+const STACKED_SIDE_ROWS: u16 = 7;
+
+impl Layout {
+    // 只有 proc 与 mem/net 同时显示时才需要改变排列
+    fn stacked(&self, boxes: &[BoxKind], width: u16) -> bool {
+        self.adaptive
+            && width < NARROW_WIDTH
+            && boxes.contains(&BoxKind::Proc)
+            && SIDE_BOXES.iter().any(|kind| boxes.contains(kind))
+    }
+}
+
+// 与 proc 相对的一侧从上到下排列的 box，mem_below_net 只交换 mem 和 net
+const SIDE_BOXES: [BoxKind; 3] = [BoxKind::Mem, BoxKind::Net, BoxKind::Log];
+
+// 与 btop++ 类似：cpu 占满整行，proc 占据一侧，mem、net 和 log 在另一侧上下排列。
+// 上下排列时 mem 和 net 并排占一行，proc 占满下面（proc_left 时为上面）的整行
+pub fn layout(boxes: &[BoxKind], opts: &Layout, width: u16, height: u16) -> Vec<BoxRect> {
+    let mut rects = Vec::new();
+    let has = |kind: BoxKind| boxes.contains(&kind);
+    let rect = |kind, x, y, width, height| BoxRect {
+        kind,
+        x,
+        y,
+        width,
+        height,
+    };
+
+    let mut top = 1;
+    let mut rest = height;
+    if has(BoxKind::Cpu) {
+        let cpu_h = if boxes.len() == 1 {
+            height
+        } else {
+            (height as u32 * opts.cpu_height_pct as u32 / 100) as u16
+        }
+        .max(min_box_size(BoxKind::Cpu).1)
+        .min(height);
+        rest = height - cpu_h;
+        let cpu_y = if opts.cpu_bottom {
+            rest + 1
+        } else {
+            top = cpu_h + 1;
+            1
+        };
+        rects.push(rect(BoxKind::Cpu, 1, cpu_y, width, cpu_h));
+    }
+    if rest == 0 {
+        return rects;
+    }
+
+    let mut side = SIDE_BOXES;
+    if opts.mem_below_net {
+        side.swap(0, 1);
+    }
+    let side: Vec<BoxKind> = side.into_iter().filter(|kind| has(*kind)).collect();
+
+    if opts.stacked(boxes, width) {
+        let (side_min, proc_min) = (min_box_size(BoxKind::Mem).1, min_box_size(BoxKind::Proc).1);
+        let side_h = STACKED_SIDE_ROWS
+            .min(rest.saturating_sub(proc_min))
+            .max(side_min)
+            .min(rest);
+        let (side_y, proc_y) = match opts.proc_left {
+            true => (top + rest - side_h, top),
+            false => (top, top + side_h),
+        };
+        rects.push(rect(BoxKind::Proc, 1, proc_y, width, rest - side_h));
+        let mut x = 1;
+        for (i, kind) in side.iter().enumerate() {
+            let w = if i + 1 == side.len() {
+                width + 1 - x
+            } else {
+                width / side.len() as u16
+            };
+            rects.push(rect(*kind, x, side_y, w, side_h));
+            x += w;
+        }
+        return rects;
+    }
+
+    let mut side_x = 1;
+    let mut side_w = width;
+    if has(BoxKind::Proc) {
+        let proc_w = match side.is_empty() {
+            true => width,
+            false => (width as u32 * opts.proc_width_pct as u32 / 100) as u16,
+        };
+        side_w = width - proc_w;
+        let proc_x = if opts.proc_left {
+            side_x = proc_w + 1;
+            1
+        } else {
+            side_w + 1
+        };
+        rects.push(rect(BoxKind::Proc, proc_x, top, proc_w, rest));
+    }
+
+    let mut y = top;
+    for (i, kind) in side.iter().enumerate() {
+        let h = if i + 1 == side.len() {
+            top + rest - y
+        } else {
+            rest / side.len() as u16
+        };
+        rects.push(rect(*kind, side_x, y, side_w, h));
+        y += h;
+    }
+    rects
+}
+
+// 各 box 能正常显示内容的最小尺寸（宽, 高）
+fn min_box_size(kind: BoxKind) -> (u16, u16) {
+    match kind {
+        BoxKind::Cpu => (40, 6),
+        BoxKind::Mem => (20, 4),
+        BoxKind::Net => (20, 4),
+        BoxKind::Proc => (30, 6),
+        BoxKind::Log => (20, 4),
+    }
+}
+
+// 按 layout 在这个宽度下的排列方式计算显示 boxes 需要的终端尺寸：
+// 左右排列时 proc 和 mem/net/log 各占一半宽度，mem、net 和 log 上下排列；
+// 上下排列时 mem、net 和 log 并排
+pub fn min_size(boxes: &[BoxKind], opts: &Layout, width: u16) -> (u16, u16) {
+    let has = |kind: BoxKind| boxes.contains(&kind);
+    let size = |kind: BoxKind| match has(kind) {
+        true => min_box_size(kind),
+        false => (0, 0),
+    };
+    let (cpu, proc) = (size(BoxKind::Cpu), size(BoxKind::Proc));
+    let sides = SIDE_BOXES.map(size);
+    // 按比例分配时，较小的一份也要达到最小尺寸
+    let share = |need: u16, pct: u16| (need as u32 * 100).div_ceil(pct.max(1) as u32) as u16;
+    let lower = if opts.stacked(boxes, width) {
+        let side_w: u16 = sides.iter().map(|side| side.0).sum();
+        let side_h = sides.iter().map(|side| side.1).max().unwrap_or(0);
+        (side_w.max(proc.0), side_h + proc.1)
+    } else {
+        let side = (
+            sides.iter().map(|side| side.0).max().unwrap_or(0),
+            sides.iter().map(|side| side.1).sum(),
+        );
+        match has(BoxKind::Proc) && side.0 > 0 {
+            true => (
+                share(proc.0, opts.proc_width_pct).max(share(side.0, 100 - opts.proc_width_pct)),
+                proc.1.max(side.1),
+            ),
+            false => (proc.0.max(side.0), proc.1.max(side.1)),
+        }
+    };
+    let height = match cpu.1 > 0 && lower.1 > 0 {
+        true => (cpu.1 + lower.1).max(share(lower.1, 100 - opts.cpu_height_pct)),
+        false => cpu.1 + lower.1,
+    };
+    (cpu.0.max(lower.0), height)
+}
+
+// 终端小于 min_size 时代替主界面显示的提示，居中绘制，不足的一项使用 hi_fg。
+// 连提示都放不下时截断且不带颜色
+pub fn draw_too_small(width: u16, height: u16, need: (u16, u16), depth: ColorDepth) -> String {
+    let warn = hex_color(&theme_color("hi_fg"), false, depth);
+    let color = |value: u16, need: u16| match value < need {
+        true => format!("{}{}{}", warn, value, RESET),
+        false => value.to_string(),
+    };
+    let lines = [
+        format!("{}Terminal too small{}", BOLD, RESET),
+        format!("need {}x{}", need.0, need.1),
+        format!("now {}x{}", color(width, need.0), color(height, need.1)),
+    ];
+    let top = (height as usize).saturating_sub(lines.len()) / 2;
+    let mut out = String::new();
+    for (i, line) in lines.iter().enumerate().take(height as usize) {
+        let plain = strip_ansi(line);
+        let len = plain.chars().count();
+        let text = match len > width as usize {
+            true => plain.chars().take(width as usize).collect(),
+            false => line.clone(),
+        };
+        let x = (width as usize).saturating_sub(len) / 2;
+        out += &mv_to((top + i + 1) as u16, x as u16 + 1);
+        out += &text;
+    }
+    out
+}
+
+// 启动时的警告横幅，铺满顶部，最多占半个屏幕，放不下的只显示数量
+pub fn draw_banner(
+    symbols: &BoxSymbols,
+    lines: &[String],
+    width: u16,
+    height: u16,
+    depth: ColorDepth,
+) -> String {
+    let max_lines = (height / 2).saturating_sub(2) as usize;
+    if lines.is_empty() || max_lines == 0 || width < 8 {
+        return String::new();
+    }
+    let mut shown: Vec<String> = lines
+        .iter()
+        .map(|line| sanitize(line).into_owned())
+        .collect();
+    if shown.len() > max_lines {
+        let hidden = shown.len() - max_lines + 1;
+        shown.truncate(max_lines - 1);
+        shown.push(format!("{} more, see the log", hidden));
+    }
+    let color = format!(
+        "{}{}",
+        BOLD,
+        hex_color(&theme_color("cpu_end"), false, depth)
+    );
+    let border = Border {
+        symbols,
+        line_color: &color,
+        title: "warnings",
+        bottom_title: "any key to dismiss",
+    };
+    let mut out = create_box(1, 1, width, shown.len() as u16 + 2, &border);
+    let inner = width as usize - 4;
+    for (i, line) in shown.iter().enumerate() {
+        out += &format!("{}{}", mv_to(i as u16 + 2, 3), fit(line, inner));
+    }
+    out
+}
+
+// net box 中的监听端口视图，每行为协议、本地地址和所属进程，age 为距上次读取的秒数
+pub fn draw_listening(
+    rect: &BoxRect,
+    sockets: &Result<Vec<SocketInfo>, String>,
+    age: u64,
+) -> String {
+    let mut lines = vec![format!(
+        " Listening, read {}s ago (r: refresh, l: close)",
+        age
+    )];
+    match sockets {
+        Err(err) => lines.push(format!(" Could not read sockets: {}", err)),
+        Ok(sockets) if sockets.is_empty() => lines.push(" No listening sockets".to_owned()),
+        Ok(sockets) => {
+            let rows = rect.inner_height().saturating_sub(1);
+            // 放不下时最后一行显示剩余的数量
+            let shown = match sockets.len() > rows {
+                true => rows.saturating_sub(1),
+                false => sockets.len(),
+            };
+            for socket in &sockets[..shown] {
+                let owner = match &socket.owner {
+                    Some((pid, name)) => format!("{}({})", sanitize(name), pid),
+                    None => "-".to_owned(),
+                };
+                lines.push(format!(
+                    " {:<4} {:<21} {}",
+                    socket.proto.name(),
+                    addr_text(&socket.local),
+                    owner
+                ));
+            }
+            if shown < sockets.len() {
+                lines.push(format!(" +{} more", sockets.len() - shown));
+            }
+        }
+    }
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| rect.line(i, line))
+        .collect()
+}
+
+// 选中无线接口时显示的 SSID、链路质量、信号强度和发送速率，没有的项省略
+fn wifi_line(wifi: &WifiInfo) -> String {
+    let mut parts = vec![match &wifi.ssid {
+        Some(ssid) => format!(" SSID: {}", sanitize(ssid)),
+        None => " SSID: -".to_owned(),
+    }];
+    if let Some(quality) = wifi.quality {
+        parts.push(format!("{:.0}%", quality));
+    }
+    if let Some(signal) = wifi.signal {
+        parts.push(format!("{} dBm", signal));
+    }
+    if let Some(bitrate) = wifi.bitrate {
+        parts.push(format!("{:.1} Mbit/s", bitrate as f64 / 1e6));
+    }
+    parts.join("  ")
+}
+
+// 字节速率正常时也可能在丢包，错误和丢弃为接口启动以来的累计值
+fn packet_lines(net: &NetInfo, iface: &str, symbols: &Symbols) -> Vec<String> {
+    let Some(p) = net.packets(iface) else {
+        return vec![" Packets: not available".to_owned()];
+    };
+    vec![
+        format!(
+            " Pkts  {} {:>7}/s {} {:>7}/s",
+            symbols.down,
+            human_count(p.rx_rate),
+            symbols.up,
+            human_count(p.tx_rate)
+        ),
+        format!(
+            " Errs  {} {:>9} {} {:>9}",
+            symbols.down,
+            human_count(p.rx_errors),
+            symbols.up,
+            human_count(p.tx_errors)
+        ),
+        format!(
+            " Drops {} {:>9} {} {:>9}",
+            symbols.down,
+            human_count(p.rx_drops),
+            symbols.up,
+            human_count(p.tx_drops)
+        ),
+    ]
+}
+
+// 较大的计数以 k/M 为单位显示
+fn human_count(count: u64) -> String {
+    match count {
+        0..1_000 => count.to_string(),
+        1_000..1_000_000 => format!("{:.1}k", count as f64 / 1e3),
+        _ => format!("{:.1}M", count as f64 / 1e6),
+    }
+}
+
+// cpu box 中的图形，lower 绘制在下半部分，cpu_single_graph 时为 None
+pub struct CpuGraph<'a> {
+    pub upper: &'a [f64],
+    pub lower: Option<&'a [f64]>,
+    pub invert_lower: bool,
+    pub symbol: &'a str,
+    // graph_range，图形中每一步平均的采样数
+    pub range: usize,
+    // cpu_graph_stats，在上半部分图形上显示统计
+    pub stats: bool,
+    // show_power 时的功耗历史（瓦）
+    pub power: Option<&'a [f64]>,
+    pub depth: ColorDepth,
+    pub dither: bool,
+}
+
+// 仪表使用的渐变起点、中点、终点（主题中的 <name>_start/_mid/_end），
+// 以及仪表中未填充部分和离线核心的颜色。每次绘制时重新从主题取出，切换主题后立即生效
+struct MeterColors {
+    gradient: [(u8, u8, u8); 3],
+    meter_bg: String,
+}
+
+impl MeterColors {
+    fn new(name: &str) -> Self {
+        let rgb = |part| parse_hex(&theme_color(&format!("{}_{}", name, part))).unwrap_or_default();
+        MeterColors {
+            gradient: [rgb("start"), rgb("mid"), rgb("end")],
+            meter_bg: theme_color("meter_bg"),
+        }
+    }
+
+    // 0.0-1.0 在渐变上的颜色
+    fn at(&self, t: f64) -> (u8, u8, u8) {
+        let [start, mid, end] = self.gradient;
+        let (from, to, t) = match t {
+            t if t < 0.5 => (start, mid, t * 2.0),
+            t => (mid, end, (t - 0.5) * 2.0),
+        };
+        let mix =
+            |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t.clamp(0.0, 1.0)).round() as u8;
+        (mix(from.0, to.0), mix(from.1, to.1), mix(from.2, to.2))
+    }
+}
+
+const METER_WIDTH: usize = 5;
+// 功耗图形的宽度，按图形中的最大功耗缩放
+const POWER_GRAPH_WIDTH: usize = 8;
+// 超过这些核心数时分多列显示，以及改用紧凑格式
+const WRAP_CORES: usize = 16;
+const COMPACT_CORES: usize = 64;
+// 图形的最小宽度，不足时核心列表占满整个 box
+const MIN_GRAPH_WIDTH: usize = 10;
+
+// 核心列表的排列方式，每列从上到下依次排列
+struct CoreLayout {
+    columns: usize,
+    // 每个核心占用的宽度
+    width: usize,
+    compact: bool,
+}
+
+impl CoreLayout {
+    fn new(rect: &BoxRect, cores: usize) -> Self {
+        let rows = rect.inner_height().saturating_sub(1).max(1);
+        let compact = cores > COMPACT_CORES;
+        // " C12 ■■■■■  45%" 或紧凑格式的 " 127▅"
+        let width = if compact { 5 } else { 10 + METER_WIDTH };
+        let wanted = match cores {
+            0..=WRAP_CORES => 1,
+            _ => cores.div_ceil(rows),
+        };
+        let inner = rect.inner_width();
+        let beside_graph = inner.saturating_sub(MIN_GRAPH_WIDTH + 1) / width;
+        let columns = match beside_graph {
+            0 => (inner / width).clamp(1, wanted),
+            fit => fit.min(wanted),
+        };
+        CoreLayout {
+            columns,
+            width,
+            compact,
+        }
+    }
+
+    fn area_width(&self) -> usize {
+        self.columns * self.width
+    }
+}
+
+// 核心列表一次能显示的核心数，用于限制滚动位置
+pub fn cpu_visible_cores(rect: &BoxRect, cores: usize) -> usize {
+    CoreLayout::new(rect, cores).columns * rect.inner_height().saturating_sub(1)
+}
+
+// 单个核心的仪表，(x, y) 为所在位置，用于抖动
+fn core_meter(
+    id: usize,
+    percent: Option<f64>,
+    layout: &CoreLayout,
+    graph: &CpuGraph,
+    colors: &MeterColors,
+    symbols: &Symbols,
+    (x, y): (usize, usize),
+) -> String {
+    let inactive = hex_color(&colors.meter_bg, false, graph.depth);
+    let Some(percent) = percent else {
+        return match layout.compact {
+            true => format!("{:>4}{}-{}", id, inactive, RESET),
+            false => format!(" C{:<3}{}{:<10}{}", id, inactive, "offline", RESET),
+        };
+    };
+    let t = percent.clamp(0.0, 100.0) / 100.0;
+    if layout.compact {
+        let color = gradient_color(colors.at(t), false, graph.depth, graph.dither, (x, y));
+        let level = symbols.levels[((t * 7.0).round() as usize).min(7)];
+        return format!("{:>4}{}{}{}", id, color, level, RESET);
+    }
+    let filled = (t * METER_WIDTH as f64).round() as usize;
+    let mut out = format!(" C{:<3}", id);
+    for i in 0..METER_WIDTH {
+        let color = match i < filled {
+            true => {
+                let pos = i as f64 / (METER_WIDTH - 1) as f64;
+                gradient_color(colors.at(pos), false, graph.depth, graph.dither, (x + i, y))
+            }
+            false => inactive.clone(),
+        };
+        out += &format!("{}{}{}", color, symbols.meter, RESET);
+    }
+    out + &format!(" {:>3.0}%", percent)
+}
+
+pub fn draw_cpu(rect: &BoxRect, cpu: &CpuInfo, offset: usize, graph: &CpuGraph) -> String {
+    let mut summary = format!(
+        " Total {:>5.1}%  Load {:.2} {:.2} {:.2}",
+        cpu.cpu_percent, cpu.load_avg[0], cpu.load_avg[1], cpu.load_avg[2]
+    );
+    // 降频原因以警告色画在预留的位置上
+    let throttled = cpu
+        .throttled
+        .map(|throttled| throttled.labels().join(","))
+        .filter(|labels| !labels.is_empty())
+        .map(|labels| {
+            let col = summary.chars().count() + 2;
+            summary += &" ".repeat(labels.chars().count() + 2);
+            (labels, col)
+        });
+    // 功耗图形画在功耗文本之后预留的位置上
+    let power = graph.power.zip(cpu.power).map(|(history, watts)| {
+        summary += &format!("  Power {:.1}W ", watts);
+        let col = summary.chars().count();
+        summary += &" ".repeat(POWER_GRAPH_WIDTH);
+        (history, col)
+    });
+    if let Some(events) = &cpu.events {
+        summary += &format!(
+            "  IRQ/s {}  Ctxt/s {}  SoftIRQ/s {}",
+            human_count(events.intr),
+            human_count(events.ctxt),
+            human_count(events.softirq)
+        );
+    }
+    let mut out = rect.line(0, &summary);
+    if let Some((labels, col)) = throttled {
+        let color = hex_color(&theme_color("hi_fg"), false, graph.depth);
+        out += &rect.styled_at(0, col, labels.chars().count(), &color, &labels);
+    }
+    if let Some((history, col)) = power {
+        let history = graph::compress(history, graph.range);
+        let recent = &history[history.len().saturating_sub(POWER_GRAPH_WIDTH * 2)..];
+        let max = recent.iter().copied().fold(1.0, f64::max);
+        let scaled: Vec<f64> = recent.iter().map(|watts| watts * 100.0 / max).collect();
+        let color = hex_color(&theme_color("cpu_end"), false, graph.depth);
+        let lines = graph::graph(&scaled, POWER_GRAPH_WIDTH, 1, graph.symbol, false);
+        out += &rect.styled_at(0, col, POWER_GRAPH_WIDTH, &color, &lines[0]);
+    }
+    let rows = rect.inner_height().saturating_sub(1);
+
+    // 宽度不够时只显示核心列表
+    let layout = CoreLayout::new(rect, cpu.core_percent.len());
+    let graph_width = rect.inner_width().saturating_sub(layout.area_width() + 1);
+    let core_col = if graph_width >= MIN_GRAPH_WIDTH {
+        graph_width + 1
+    } else {
+        0
+    };
+    if core_col > 0 {
+        let lower_rows = match graph.lower {
+            Some(_) if rows >= 2 => rows / 2,
+            _ => 0,
+        };
+        let upper_rows = rows - lower_rows;
+        let color = hex_color(&theme_color("cpu_start"), false, graph.depth);
+        let upper = graph::compress(graph.upper, graph.range);
+        let lines = graph::graph(&upper, graph_width, upper_rows, graph.symbol, false);
+        for (i, line) in lines.iter().enumerate() {
+            out += &rect.styled_at(1 + i, 0, graph_width, &color, line);
+        }
+        let visible = graph::visible(graph.upper, graph_width, graph.range);
+        if let Some(stats) = Stats::of(visible.iter().copied()).filter(|_| graph.stats) {
+            let text = format!(
+                " min {:.0}% avg {:.0}% max {:.0}% now {:.0}% ",
+                stats.min, stats.avg, stats.max, stats.now
+            );
+            let width = text.chars().count().min(graph_width);
+            out += &rect.styled_at(1, 0, width, BOLD, &text);
+        }
+        if let Some(lower) = graph.lower.filter(|_| lower_rows > 0) {
+            let color = hex_color(&theme_color("cpu_mid"), false, graph.depth);
+            let lower = graph::compress(lower, graph.range);
+            let lines = graph::graph(
+                &lower,
+                graph_width,
+                lower_rows,
+                graph.symbol,
+                graph.invert_lower,
+            );
+            for (i, line) in lines.iter().enumerate() {
+                out += &rect.styled_at(1 + upper_rows + i, 0, graph_width, &color, line);
+            }
+        }
+    }
+
+    let visible = layout.columns * rows;
+    let colors = MeterColors::new("cpu");
+    let symbols = symbols();
+    for (i, percent) in cpu
+        .core_percent
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(visible)
+    {
+        let (column, line) = ((i - offset) / rows, (i - offset) % rows);
+        let col = core_col + column * layout.width;
+        let percent = (!cpu.offline.contains(&i)).then_some(*percent);
+        let meter = core_meter(i, percent, &layout, graph, &colors, symbols, (col, line));
+        out += &rect.meter_at(1 + line, col, layout.width, &meter);
+    }
+    out
+}
+
+// mem_numa 时每个节点一行，显示在整体数值之前
+fn numa_lines(mem: &MemInfo) -> Vec<String> {
+    if mem.numa.is_empty() {
+        return vec![" NUMA: single node".to_owned()];
+    }
+    let mut lines = vec![format!(
+        " {:<5}{:>10} {:>10} {:>10}",
+        "Node", "Total", "Used", "Free"
+    )];
+    lines.extend(mem.numa.iter().map(|node| {
+        format!(
+            " {:<5}{:>10} {:>10} {:>10}",
+            node.id,
+            human_bytes(node.total),
+            human_bytes(node.used),
+            human_bytes(node.free)
+        )
+    }));
+    lines
+}
+
+// show_disks 时 mem box 分为左右两列，右侧为磁盘，宽度不够时只显示内存
+pub fn draw_mem(
+    rect: &BoxRect,
+    mem: &MemInfo,
+    show_swap: bool,
+    numa: bool,
+    disks: Option<&DiskInfo>,
+    temp_scale: &str,
+    depth: ColorDepth,
+) -> String {
+    let mut lines = match numa {
+        true => numa_lines(mem),
+        false => Vec::new(),
+    };
+    let width = rect.inner_width();
+    let disks = disks.filter(|_| width >= DISKS_MIN_WIDTH);
+    let mem_w = width / 2;
+    // 分出磁盘列后放不下完整格式时使用较短的名称和单位，数值靠右对齐
+    let short = disks.is_some() && mem_w < MEM_FULL_WIDTH;
+    let bytes = |bytes: u64| match short {
+        true => short_bytes(bytes),
+        false => human_bytes(bytes),
+    };
+    let row = |label: &str, short_label: &str, value: String| match short {
+        true => format!(" {:<7}{:>2$}", short_label, value, mem_w.saturating_sub(8)),
+        false => format!(" {:<11}{:>10}", label, value),
+    };
+    let pair = |used: u64, total: u64| match short {
+        true => format!("{}/{}", short_bytes(used), short_bytes(total)),
+        false => format!("{:>10} / {}", human_bytes(used), human_bytes(total)),
+    };
+    lines.extend([
+        row("Total:", "Total:", bytes(mem.total)),
+        row("Used:", "Used:", bytes(mem.used)),
+        row("Available:", "Avail:", bytes(mem.available)),
+        row("Cached:", "Cached:", bytes(mem.cached)),
+        row("Free:", "Free:", bytes(mem.free)),
+    ]);
+    // 平台不提供或没有预留大页时不显示
+    if mem.shmem > 0 {
+        lines.push(row("Shared:", "Shared:", bytes(mem.shmem)));
+    }
+    if mem.hugepages_total > 0 {
+        let used = mem.hugepages_total.saturating_sub(mem.hugepages_free);
+        lines.push(row("Huge:", "Huge:", pair(used, mem.hugepages_total)));
+    }
+    if show_swap {
+        lines.push(row("Swap:", "Swap:", pair(mem.swap_used, mem.swap_total)));
+        // 交换空间的使用量不代表内存紧张，持续的换入换出和缺页才是
+        if let Some(paging) = &mem.paging {
+            let symbols = symbols();
+            lines.push(match short {
+                true => format!(
+                    " I/O:   {}{} {}{}",
+                    symbols.down,
+                    short_bytes(paging.swap_in),
+                    symbols.up,
+                    short_bytes(paging.swap_out)
+                ),
+                false => format!(
+                    " Swap I/O:  {} {}/s {} {}/s",
+                    symbols.down,
+                    human_bytes(paging.swap_in),
+                    symbols.up,
+                    human_bytes(paging.swap_out)
+                ),
+            });
+            lines.push(row(
+                "Maj flt/s:",
+                "Maj flt:",
+                human_count(paging.major_faults),
+            ));
+        }
+        // 逐个列出交换分区和交换文件
+        for swap in &mem.swaps {
+            let kind = if swap.kind == "partition" {
+                "part"
+            } else {
+                "file"
+            };
+            lines.push(match short {
+                true => format!(
+                    "  {} {} {}",
+                    kind,
+                    pair(swap.used, swap.size),
+                    sanitize(&swap.name)
+                ),
+                false => format!(
+                    "  {} {} {} pri {}",
+                    kind,
+                    pair(swap.used, swap.size),
+                    sanitize(&swap.name),
+                    swap.priority
+                ),
+            });
+        }
+    }
+    let Some(disks) = disks else {
+        return lines
+            .iter()
+            .enumerate()
+            .map(|(i, line)| rect.line(i, line))
+            .collect();
+    };
+    let mut out: String = lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| rect.styled_at(i, 0, mem_w, "", line))
+        .collect();
+    out += &draw_disks(rect, disks, mem_w + 1, width - mem_w - 1, temp_scale, depth);
+    out
+}
+
+// 磁盘列的最小宽度，mem box 比两倍窄时不分列
+const DISKS_MIN_WIDTH: usize = 44;
+// 完整格式的 "Swap: 1023.9 MiB / 1023.9 GiB" 需要的宽度
+const MEM_FULL_WIDTH: usize = 32;
+
+// 分列时使用的短单位，例如 "15.6G"
+fn short_bytes(bytes: u64) -> String {
+    human_bytes(bytes).replace("iB", "").replace(' ', "")
+}
+
+// 每个磁盘占两行：名称和剩余空间，已用比例的仪表和 I/O 繁忙程度
+fn draw_disks(
+    rect: &BoxRect,
+    disks: &DiskInfo,
+    col: usize,
+    width: usize,
+    temp_scale: &str,
+    depth: ColorDepth,
+) -> String {
+    if disks.disks.is_empty() {
+        return rect.styled_at(0, col, width, "", "No disks");
+    }
+    let colors = MeterColors::new("used");
+    let symbols = symbols();
+    let inactive = hex_color(&colors.meter_bg, false, depth);
+    let mut out = String::new();
+    for (i, disk) in disks.disks.iter().enumerate() {
+        // md 阵列降级或重建时在名称后显示状态，降级和挂起用警告色，放不下时不显示剩余空间
+        let raid = disk
+            .raid
+            .as_ref()
+            .map(RaidStatus::label)
+            .unwrap_or_default();
+        let name = sanitize(&disk.name);
+        let mut free = format!("{} free", human_bytes(disk.free));
+        if let Some(temp) = disk.temp {
+            free = format!("{} {}", clock::temperature(temp, temp_scale), free);
+        }
+        let min_name = name.chars().count().min(4);
+        if !raid.is_empty() && min_name + raid.chars().count() + free.chars().count() + 2 > width {
+            free.clear();
+        }
+        out += &rect.styled_at(i * 2, col, width, "", &format!("{:>1$}", free, width));
+        let rest = width.saturating_sub(free.chars().count() + 1);
+        let raid: String = raid
+            .chars()
+            .take(rest.saturating_sub(min_name + 1))
+            .collect();
+        let name_w = match raid.is_empty() {
+            true => rest,
+            false => rest.saturating_sub(raid.chars().count() + 1),
+        };
+        let name: String = name.chars().take(name_w).collect();
+        out += &rect.styled_at(i * 2, col, name.chars().count(), BOLD, &name);
+        if !raid.is_empty() {
+            let style = match disk.raid.as_ref().is_some_and(RaidStatus::is_warning) {
+                true => hex_color(&theme_color("hi_fg"), false, depth),
+                false => String::new(),
+            };
+            let raid_col = col + name.chars().count() + 1;
+            out += &rect.styled_at(i * 2, raid_col, raid.chars().count(), &style, &raid);
+        }
+
+        let used = disk.used_percent();
+        let io = match disk.io_percent {
+            Some(io) => format!(" io{:>3.0}%", io),
+            None => String::new(),
+        };
+        let meter_w = width.saturating_sub(5 + io.chars().count());
+        let filled = (used / 100.0 * meter_w as f64).round() as usize;
+        let mut meter = String::new();
+        for cell in 0..meter_w {
+            let color = match cell < filled {
+                true => {
+                    let (r, g, b) = colors.at(cell as f64 / meter_w as f64);
+                    rgb_color(r, g, b, false, depth)
+                }
+                false => inactive.clone(),
+            };
+            meter += &format!("{}{}{}", color, symbols.meter, RESET);
+        }
+        out += &rect.meter_at(
+            i * 2 + 1,
+            col,
+            width,
+            &format!("{} {:>3.0}%{}", meter, used, io),
+        );
+    }
+    out
+}
+
+// overhead 模式下并排显示隧道上的有效载荷和物理接口上的字节。
+// history 为当前接口的速率历史，net_graph_stats 关闭时为 None，
+// 统计的范围与同样宽度的图形相同
+pub fn draw_net(
+    rect: &BoxRect,
+    net: &NetInfo,
+    iface: &str,
+    overhead: bool,
+    history: Option<&[(u64, u64)]>,
+    range: usize,
+    packets: bool,
+) -> String {
+    let symbols = symbols();
+    let lines = match net.overhead().filter(|_| overhead) {
+        Some(stats) => {
+            let row = |symbol: &str, payload: u64, wire: u64| {
+                let percent = Overhead::percent(payload, wire)
+                    .map(|percent| format!("{:+.1}%", percent))
+                    .unwrap_or_else(|| "-".to_owned());
+                format!(
+                    " {} {:>12} {:>12} {:>9}",
+                    symbol,
+                    format!("{}/s", human_bytes(payload)),
+                    format!("{}/s", human_bytes(wire)),
+                    percent
+                )
+            };
+            vec![
+                format!(
+                    " Tunnel: {} via {}",
+                    stats.tunnels.join(","),
+                    stats.physical.join(",")
+                ),
+                format!("   {:>12} {:>12} {:>9}", "Payload", "Wire", "Overhead"),
+                row(symbols.down, stats.payload.0, stats.wire.0),
+                row(symbols.up, stats.payload.1, stats.wire.1),
+            ]
+        }
+        None => {
+            let (rx, tx) = net.rates(iface);
+            let name = match (overhead, iface) {
+                (true, _) => "no active tunnel",
+                (false, "") => "all",
+                (false, iface) => iface,
+            };
+            let history = history.map(|history| graph::visible(history, rect.inner_width(), range));
+            let stats = |pick: fn(&(u64, u64)) -> u64| {
+                history.and_then(|history| Stats::of(history.iter().map(|r| pick(r) as f64)))
+            };
+            let mut lines = vec![format!(" Interface: {}", name)];
+            let wifi = net
+                .interfaces
+                .iter()
+                .find(|stat| !iface.is_empty() && stat.name == iface);
+            if let Some(wifi) = wifi.and_then(|stat| stat.wifi.as_ref()) {
+                lines.push(wifi_line(wifi));
+            }
+            for (symbol, rate, stats) in [
+                (symbols.down, rx, stats(|rates| rates.0)),
+                (symbols.up, tx, stats(|rates| rates.1)),
+            ] {
+                lines.push(format!(" {} {}/s", symbol, human_bytes(rate)));
+                if let Some(stats) = stats {
+                    lines.push(format!(
+                        "   min {} avg {} max {}",
+                        human_bytes(stats.min as u64),
+                        human_bytes(stats.avg as u64),
+                        human_bytes(stats.max as u64)
+                    ));
+                }
+            }
+            if packets {
+                lines.extend(packet_lines(net, iface, symbols));
+            }
+            lines
+        }
+    };
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| rect.line(i, line))
+        .collect()
+}
+
+// 内核日志，最新的在底部，offset 为向上滚动的行数。
+// err 及以上使用 hi_fg，warning 使用 cpu_mid，notice 加粗，debug 使用 meter_bg
+pub fn draw_log(rect: &BoxRect, klog: &KlogInfo, offset: usize, depth: ColorDepth) -> String {
+    if let Some(err) = &klog.error {
+        return rect.line(0, &format!(" Kernel log not readable: {}", err));
+    }
+    if klog.entries.is_empty() {
+        return rect.line(0, " No kernel messages");
+    }
+    let color = |key| hex_color(&theme_color(key), false, depth);
+    let styles = [
+        color("hi_fg"),
+        color("cpu_mid"),
+        BOLD.to_owned(),
+        color("meter_bg"),
+    ];
+    let end = klog.entries.len().saturating_sub(offset);
+    let start = end.saturating_sub(rect.inner_height());
+    klog.entries[start..end]
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| {
+            let style = match entry.level {
+                0..=3 => &styles[0],
+                4 => &styles[1],
+                5 => &styles[2],
+                7 => &styles[3],
+                _ => "",
+            };
+            let line = format!(" [{:>9.3}] {}", entry.time, sanitize(&entry.message));
+            rect.styled_line(i, style, &line)
+        })
+        .collect()
+}
+
+// log box 向上滚动时底部边框上显示下面还有多少条
+pub fn log_position(offset: usize) -> String {
+    match offset {
+        0 => String::new(),
+        offset => format!(" {} newer ", offset),
+    }
+}
+
+// cpu box 顶部边框右侧的电池状态，例如 "BAT▼ 87% 12.3W 2:31"
+pub fn battery_text(bat: &BatteryInfo) -> String {
+    let mut text = format!(
+        "BAT{} {:.0}%",
+        symbols().battery_status(&bat.status),
+        bat.percent
+    );
+    if let Some(watts) = bat.watts {
+        text += &format!(" {:.1}W", watts);
+    }
+    if let Some(seconds) = bat.seconds {
+        text += &format!(" {}:{:02}", seconds / 3600, seconds / 60 % 60);
+    }
+    text
+}
+
+// cpu box 右下角的采集间隔，"+" 和 "-" 键调整
+pub fn update_ms_text(update_ms: i32) -> String {
+    format!(" - {}ms + ", update_ms)
+}
+
+// 暂停采集时的提示，使用 hi_fg 颜色
+pub fn paused_text(depth: ColorDepth) -> String {
+    format!(
+        " {}PAUSED{}{} p to resume ",
+        hex_color(&theme_color("hi_fg"), false, depth),
+        RESET,
+        BOLD
+    )
+}
+
+// mem box 底部边框上启动以来的 OOM kill 次数，flash 时反色显示
+pub fn oom_text(kills: u64, flash: bool, depth: ColorDepth) -> String {
+    format!(
+        " {}{}OOM kills: {}{} ",
+        hex_color(&theme_color("hi_fg"), false, depth),
+        if flash { REVERSE } else { "" },
+        kills,
+        RESET
+    )
+}
+
+// proc box 底部边框上的选中位置、进程总数和标记的进程数
+// 有过滤条件或正在输入时显示在标题中，输入时末尾显示光标，过长时只保留末尾部分。
+// regex 为 (是否正则模式, 是否无法解析)，无法解析时 "filter" 或 "regex" 显示为 hi_fg 颜色
+pub fn proc_title(
+    filter: &str,
+    (regex, invalid): (bool, bool),
+    editing: bool,
+    width: usize,
+    depth: ColorDepth,
+) -> String {
+    if filter.is_empty() && !editing {
+        return "proc".to_owned();
+    }
+    let mode = if regex { "regex" } else { "filter" };
+    let cursor = if editing { "_" } else { "" };
+    let room = width.saturating_sub(4 + "proc : ".len() + mode.len() + cursor.len());
+    let len = filter.chars().count();
+    let shown: String = filter.chars().skip(len.saturating_sub(room)).collect();
+    let mode = match invalid {
+        true => format!(
+            "{}{}{}{}",
+            hex_color(&theme_color("hi_fg"), false, depth),
+            mode,
+            RESET,
+            BOLD
+        ),
+        false => mode.to_owned(),
+    };
+    format!("proc {}: {}{}", mode, shown, cursor)
+}
+
+// proc box 顶部的进程统计，宽度不够时依次换用更短的写法
+pub fn proc_summary(counts: &ProcCounts) -> Vec<String> {
+    let ProcCounts {
+        procs,
+        threads,
+        running,
+        zombie,
+    } = counts;
+    vec![
+        format!(
+            "{} procs, {} threads, {} running, {} zombie",
+            procs, threads, running, zombie
+        ),
+        format!(
+            "{} procs {} thr {} run {} zmb",
+            procs, threads, running, zombie
+        ),
+        format!("{}/{}", procs, threads),
+    ]
+}
+
+pub fn proc_counter(selected: usize, total: usize, follow: bool, tagged: usize) -> String {
+    let tagged = match tagged {
+        0 => String::new(),
+        count => format!("{} tagged ", count),
+    };
+    format!(
+        " {}{}{}/{} ",
+        if follow { "follow " } else { "" },
+        tagged,
+        selected,
+        total
+    )
+}
+
+// 显示线程数、nice 值和读写速率列需要的最小宽度，宽度不够时依次隐藏读写、nice 和线程数
+const PROC_THREADS_WIDTH: usize = 56;
+const PROC_NICE_WIDTH: usize = 60;
+const PROC_IO_WIDTH: usize = 82;
+// 其余的宽度留给命令行，不足这么多字符时不显示
+const PROC_CMD_MIN: usize = 10;
+// 程序名一列的宽度，放不下时先缩短程序名，短于最小宽度时再隐藏用户名，保证内存和 CPU 列完整显示
+const PROC_PROGRAM_WIDTH: usize = 15;
+const PROC_PROGRAM_MIN: usize = 8;
+
+#[derive(Clone, Copy, PartialEq)]
+enum ProcCol {
+    Pid,
+    Program,
+    Command,
+    Threads,
+    User,
+    Nice,
+    Read,
+    Write,
+    Mem,
+    Cpu,
+}
+
+impl ProcCol {
+    fn label(&self) -> &'static str {
+        match self {
+            ProcCol::Pid => "Pid:",
+            ProcCol::Program => "Program:",
+            ProcCol::Command => "Command:",
+            ProcCol::Threads => "Thr:",
+            ProcCol::User => "User:",
+            ProcCol::Nice => "Ni:",
+            ProcCol::Read => "Read/s",
+            ProcCol::Write => "Write/s",
+            ProcCol::Mem => "MemB",
+            ProcCol::Cpu => "Cpu%",
+        }
+    }
+
+    // 文本列左对齐，数值列右对齐
+    fn left(&self) -> bool {
+        matches!(self, ProcCol::Program | ProcCol::Command | ProcCol::User)
+    }
+
+    fn cell(&self, p: &ProcInfo) -> String {
+        match self {
+            ProcCol::Pid => p.pid.to_string(),
+            ProcCol::Program => sanitize(&p.name).into_owned(),
+            // 内核线程没有命令行，与 ps(1) 相同显示为 "[名称]"
+            ProcCol::Command if p.cmd.is_empty() => format!("[{}]", sanitize(&p.name)),
+            ProcCol::Command => sanitize(&p.cmd).into_owned(),
+            ProcCol::Threads => p.threads.to_string(),
+            ProcCol::User => sanitize(&p.user).into_owned(),
+            ProcCol::Nice => p.nice.to_string(),
+            ProcCol::Read => p
+                .io_rate
+                .map_or("-".to_owned(), |(read, _)| human_bytes(read)),
+            ProcCol::Write => p
+                .io_rate
+                .map_or("-".to_owned(), |(_, write)| human_bytes(write)),
+            ProcCol::Mem => human_bytes(p.mem),
+            ProcCol::Cpu => format!("{:.1}", p.cpu_p),
+        }
+    }
+}
+
+// proc box 中的一列，x 为在 box 内部的起始列
+pub struct ProcColumn {
+    col: ProcCol,
+    x: usize,
+    width: usize,
+}
+
+impl ProcColumn {
+    // 点击表头时切换到的排序字段，nice 和读写速率列不能排序
+    pub fn sort(&self) -> Option<ProcSort> {
+        match self.col {
+            ProcCol::Pid => Some(ProcSort::Pid),
+            ProcCol::Program => Some(ProcSort::Program),
+            ProcCol::Command => Some(ProcSort::Command),
+            ProcCol::Threads => Some(ProcSort::Threads),
+            ProcCol::User => Some(ProcSort::User),
+            ProcCol::Mem => Some(ProcSort::Memory),
+            ProcCol::Cpu => Some(ProcSort::Cpu),
+            ProcCol::Nice | ProcCol::Read | ProcCol::Write => None,
+        }
+    }
+
+    pub fn contains(&self, x: usize) -> bool {
+        x >= self.x && x < self.x + self.width
+    }
+
+    fn fit(&self, text: &str) -> String {
+        match self.col.left() {
+            true => fit(text, self.width),
+            false => format!("{:>1$}", text, self.width),
+        }
+    }
+}
+
+// 按 box 宽度决定显示哪些列，绘制和鼠标点击表头都使用这个布局。
+// full_cmd 时命令行代替程序名，占用程序名一列的宽度
+pub fn proc_columns(
+    rect: &BoxRect,
+    procs: &[ProcInfo],
+    start: usize,
+    full_cmd: bool,
+) -> Vec<ProcColumn> {
+    let width = rect.inner_width();
+    let rows = rect.inner_height().saturating_sub(1);
+    // 没有权限读取任何可见进程的 I/O 时隐藏读写列
+    let show_io = width >= PROC_IO_WIDTH
+        && procs
+            .iter()
+            .skip(start)
+            .take(rows)
+            .any(|p| p.io_rate.is_some());
+    let mut rest = Vec::new();
+    if width >= PROC_THREADS_WIDTH {
+        rest.push((ProcCol::Threads, 5));
+    }
+    rest.push((ProcCol::User, 9));
+    if width >= PROC_NICE_WIDTH {
+        rest.push((ProcCol::Nice, 3));
+    }
+    if show_io {
+        rest.extend([(ProcCol::Read, 10), (ProcCol::Write, 10)]);
+    }
+    rest.extend([(ProcCol::Mem, 10), (ProcCol::Cpu, 5)]);
+    // 行首保留一列显示标记
+    let used =
+        |rest: &[(ProcCol, usize)]| 1 + 7 + rest.iter().map(|(_, width)| width + 1).sum::<usize>();
+    if width.saturating_sub(used(&rest) + 1) < PROC_PROGRAM_MIN {
+        rest.retain(|(col, _)| *col != ProcCol::User);
+    }
+    let used = used(&rest);
+    let program = width.saturating_sub(used + 1);
+    let mut cols = vec![(ProcCol::Pid, 7)];
+    if full_cmd {
+        cols.push((ProcCol::Command, program));
+    } else {
+        let program = program.min(PROC_PROGRAM_WIDTH);
+        cols.push((ProcCol::Program, program));
+        let cmd = width.saturating_sub(used + 1 + program + 1);
+        if cmd >= PROC_CMD_MIN {
+            cols.push((ProcCol::Command, cmd));
+        }
+    }
+    cols.extend(rest);
+    let mut x = 1;
+    cols.into_iter()
+        .map(|(col, width)| {
+            let column = ProcColumn { col, x, width };
+            x += width + 1;
+            column
+        })
+        .collect()
+}
+
+// proc box 的滚动位置、选中行、标记的进程以及当前的排序方式
+pub struct ProcView<'a> {
+    pub start: usize,
+    // 从 1 开始计数，0 表示没有选中任何进程
+    pub selected: usize,
+    pub tagged: &'a BTreeSet<i32>,
+    pub sort: ProcSort,
+    pub reversed: bool,
+    pub full_cmd: bool,
+}
+
+// 当前排序的列在表头中带有箭头，被暂停的进程（状态为 T）使用 proc_frozen 颜色，
+// 标记的进程行首显示 "*"
+pub fn draw_proc(rect: &BoxRect, procs: &[ProcInfo], view: &ProcView, depth: ColorDepth) -> String {
+    let rows = rect.inner_height().saturating_sub(1);
+    let frozen = hex_color(&theme_color("proc_frozen"), false, depth);
+    let columns = proc_columns(rect, procs, view.start, view.full_cmd);
+    let row = |mark: char, cell: &dyn Fn(&ProcColumn) -> String| {
+        let cells: Vec<String> = columns
+            .iter()
+            .map(|column| column.fit(&cell(column)))
+            .collect();
+        format!("{}{}", mark, cells.join(" "))
+    };
+    let arrow = match view.sort.descending() != view.reversed {
+        true => symbols().down,
+        false => symbols().up,
+    };
+    let header = row(' ', &|column| match column.sort() == Some(view.sort) {
+        true => format!("{}{}", column.col.label(), arrow),
+        false => column.col.label().to_owned(),
+    });
+    let mut out = rect.styled_line(0, BOLD, &header);
+    for (i, p) in procs.iter().enumerate().skip(view.start).take(rows) {
+        let mark = if view.tagged.contains(&p.pid) {
+            '*'
+        } else {
+            ' '
+        };
+        let text = row(mark, &|column| column.col.cell(p));
+        let color = match p.state {
+            'T' => frozen.as_str(),
+            _ => "",
+        };
+        let style = match i + 1 == view.selected {
+            true => format!("{}{}", color, REVERSE),
+            false => color.to_owned(),
+        };
+        out += &rect.styled_line(1 + i - view.start, &style, &text);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::{
+        draw_cpu, draw_mem, draw_net, draw_proc, numa_lines, proc_columns, strip_ansi, wifi_line,
+        BoxKind, BoxRect, CpuGraph, ProcCol, ProcView,
+    };
+    use crate::{
+        draw::RESET,
+        shared::{
+            cpu::{CpuInfo, Throttled},
+            disk::{DiskInfo, DiskStat},
+            mem::{MemInfo, NumaNode, PagingRates, SwapDevice},
+            net::{NetInfo, NetStat, WifiInfo},
+            proc::{ProcInfo, ProcSort},
+        },
+        term::caps::ColorDepth,
+    };
+
+    fn procs() -> Vec<ProcInfo> {
+        (1..=3)
+            .map(|pid| ProcInfo {
+                pid,
+                ppid: 1,
+                name: format!("proc{}", pid),
+                user: "root".to_owned(),
+                state: 'S',
+                threads: 1,
+                mem: (pid as u64) << 20,
+                cpu_p: 12.5 * pid as f64,
+                ..Default::default()
+            })
+            .collect()
+    }
+
+    fn rect(inner: u16) -> BoxRect {
+        BoxRect {
+            kind: BoxKind::Proc,
+            x: 1,
+            y: 1,
+            width: inner + 2,
+            height: 10,
+        }
+    }
+
+    #[test]
+    fn columns_fit_box_width() {
+        for inner in [30, 40, 48, 52, 56, 60, 82, 120] {
+            for full_cmd in [false, true] {
+                let columns = proc_columns(&rect(inner), &procs(), 0, full_cmd);
+                let last = columns.last().unwrap();
+                assert!(last.col == ProcCol::Cpu, "width {}", inner);
+                assert!(last.x + last.width <= inner as usize, "width {}", inner);
+                assert!(columns.iter().any(|column| column.col == ProcCol::Mem));
+            }
+        }
+    }
+
+    // 默认 100x30 布局中 proc box 内部宽 48，缩短程序名后保留用户名
+    #[test]
+    fn cpu_column_shown_at_48_columns() {
+        let procs = procs();
+        let columns = proc_columns(&rect(48), &procs, 0, false);
+        let cols: Vec<_> = columns.iter().map(|column| column.col).collect();
+        assert!(cols.contains(&ProcCol::User));
+        assert!(!cols.contains(&ProcCol::Threads));
+
+        let tagged = BTreeSet::new();
+        let view = ProcView {
+            start: 0,
+            selected: 0,
+            tagged: &tagged,
+            sort: ProcSort::Cpu,
+            reversed: false,
+            full_cmd: false,
+        };
+        let out = strip_ansi(&draw_proc(&rect(48), &procs, &view, ColorDepth::TrueColor));
+        assert!(out.contains("Cpu%"), "{}", out);
+        for value in ["12.5", "25.0", "37.5"] {
+            assert!(out.contains(value), "{}", out);
+        }
+    }
+
+    // 分出磁盘列后 mem 一侧只剩 22 列，交换空间一行仍然显示总量
+    #[test]
+    fn mem_lines_fit_beside_disks() {
+        let mem = MemInfo {
+            total: 16 << 30,
+            available: 10 << 30,
+            swap_total: 2 << 30,
+            swap_used: 256 << 20,
+            ..Default::default()
+        };
+        let disks = DiskInfo {
+            disks: vec![DiskStat::new(
+                "/dev/sda1".to_owned(),
+                "/".to_owned(),
+                100 << 30,
+                42 << 30,
+                58 << 30,
+            )],
+        };
+        let rect = BoxRect {
+            kind: BoxKind::Mem,
+            x: 1,
+            y: 1,
+            width: 46,
+            height: 10,
+        };
+        let out = draw_mem(
+            &rect,
+            &mem,
+            true,
+            false,
+            Some(&disks),
+            "celsius",
+            ColorDepth::TrueColor,
+        );
+        let lines: Vec<String> = out.split(RESET).map(strip_ansi).collect();
+        let swap = lines
+            .iter()
+            .find(|line| line.starts_with(" Swap:"))
+            .unwrap();
+        assert!(swap.trim_end().ends_with("/2.0G"), "{:?}", swap);
+        assert_eq!(swap.chars().count(), 22);
+        let total = lines
+            .iter()
+            .find(|line| line.starts_with(" Total:"))
+            .unwrap();
+        assert!(total.ends_with("16.0G"), "{:?}", total);
+    }
+
+    #[test]
+    fn numa_lines_per_node() {
+        let mut mem = MemInfo::default();
+        assert_eq!(numa_lines(&mem), [" NUMA: single node"]);
+        mem.numa = (0..2)
+            .map(|id| NumaNode {
+                id,
+                total: 8 << 30,
+                used: (2 + id as u64) << 30,
+                free: (4 - id as u64) << 30,
+                cached: 2 << 30,
+            })
+            .collect();
+        let lines = numa_lines(&mem);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], " Node      Total       Used       Free");
+        assert_eq!(lines[2], " 1       8.0 GiB    3.0 GiB    3.0 GiB");
+        // 每行的各列对齐
+        assert!(lines.iter().all(|line| line.len() == lines[0].len()));
+    }
+
+    #[test]
+    fn paging_rates_shown() {
+        let mut mem = MemInfo {
+            total: 16 << 30,
+            ..Default::default()
+        };
+        let rect = BoxRect {
+            kind: BoxKind::Mem,
+            x: 1,
+            y: 1,
+            width: 50,
+            height: 15,
+        };
+        let draw = |mem: &MemInfo| {
+            strip_ansi(&draw_mem(
+                &rect,
+                mem,
+                true,
+                false,
+                None,
+                "celsius",
+                ColorDepth::TrueColor,
+            ))
+        };
+        // 还没有上一次采样时不显示
+        assert!(!draw(&mem).contains("Swap I/O"));
+        mem.paging = Some(PagingRates {
+            swap_in: 3 << 20,
+            swap_out: 512 << 10,
+            major_faults: 1234,
+        });
+        let out = draw(&mem);
+        // 箭头取决于终端是否支持 UTF-8，只比较数值
+        for text in [" Swap I/O:", " 3.0 MiB/s ", " 512.0 KiB/s"] {
+            assert!(out.contains(text), "{}", out);
+        }
+        assert!(out.contains(" Maj flt/s:       1.2k"), "{}", out);
+    }
+
+    // 降频原因显示在负载之后，没有降频时不占位置
+    #[test]
+    fn throttled_in_cpu_summary() {
+        let mut cpu = CpuInfo {
+            cpu_percent: 37.5,
+            core_percent: vec![10.0; 4],
+            load_avg: [1.0, 0.5, 0.25],
+            ..Default::default()
+        };
+        let history = [37.5; 8];
+        let graph = CpuGraph {
+            upper: &history,
+            lower: None,
+            invert_lower: false,
+            symbol: "braille",
+            range: 1,
+            stats: false,
+            power: None,
+            depth: ColorDepth::TrueColor,
+            dither: false,
+        };
+        let rect = BoxRect {
+            kind: BoxKind::Cpu,
+            x: 1,
+            y: 1,
+            width: 100,
+            height: 8,
+        };
+        let draw = |cpu: &CpuInfo| strip_ansi(&draw_cpu(&rect, cpu, 0, &graph));
+        let plain = draw(&cpu);
+        assert!(plain.starts_with(" Total  37.5%  Load 1.00 0.50 0.25 "));
+        cpu.throttled = Some(Throttled::from_bits(0x50005));
+        // 标签另外以警告色绘制，摘要中为它预留了空白
+        let out = draw(&cpu);
+        assert!(out.contains("under-voltage,throttled"), "{}", out);
+        assert_eq!(out.chars().count(), plain.chars().count() + 23);
+        cpu.throttled = Some(Throttled::from_bits(0x50000));
+        assert_eq!(draw(&cpu), plain);
+    }
+
+    // show_swap 时逐个列出交换设备，关闭后不显示
+    #[test]
+    fn swap_devices_listed() {
+        let mem = MemInfo {
+            total: 16 << 30,
+            swap_total: 10 << 30,
+            swap_used: 256 << 20,
+            swaps: vec![
+                SwapDevice {
+                    name: "/dev/sda2".to_owned(),
+                    kind: "partition".to_owned(),
+                    size: 8 << 30,
+                    used: 256 << 20,
+                    priority: -2,
+                },
+                SwapDevice {
+                    name: "/swap\x1bfile".to_owned(),
+                    kind: "file".to_owned(),
+                    size: 2 << 30,
+                    used: 0,
+                    priority: 10,
+                },
+            ],
+            ..Default::default()
+        };
+        let rect = BoxRect {
+            kind: BoxKind::Mem,
+            x: 1,
+            y: 1,
+            width: 60,
+            height: 15,
+        };
+        let draw = |show_swap| {
+            let out = draw_mem(
+                &rect,
+                &mem,
+                show_swap,
+                false,
+                None,
+                "celsius",
+                ColorDepth::TrueColor,
+            );
+            out.split(RESET)
+                .map(|line| strip_ansi(line).trim_end().to_owned())
+                .collect::<Vec<_>>()
+        };
+        let lines = draw(true);
+        let devices: Vec<_> = lines
+            .iter()
+            .filter(|line| line.starts_with("  part") || line.starts_with("  file"))
+            .collect();
+        assert_eq!(devices.len(), 2, "{:?}", lines);
+        assert_eq!(devices[0], "  part  256.0 MiB / 8.0 GiB /dev/sda2 pri -2");
+        assert_eq!(devices[1], "  file        0 B / 2.0 GiB /swap^[file pri 10");
+        assert!(!draw(false).iter().any(|line| line.contains("/dev/sda2")));
+    }
+
+    #[test]
+    fn wifi_line_skips_missing_values() {
+        let wifi = WifiInfo {
+            ssid: Some("home\x1b[2J".to_owned()),
+            quality: Some(77.1),
+            signal: Some(-56),
+            bitrate: Some(866_700_000),
+        };
+        // 控制字符不会原样输出到终端
+        assert_eq!(
+            wifi_line(&wifi),
+            " SSID: home^[[2J  77%  -56 dBm  866.7 Mbit/s"
+        );
+        let wifi = WifiInfo {
+            signal: Some(-70),
+            ..Default::default()
+        };
+        assert_eq!(wifi_line(&wifi), " SSID: -  -70 dBm");
+    }
+
+    // 只有选择了无线接口时才显示，合计（all）不显示
+    #[test]
+    fn wifi_shown_for_selected_interface() {
+        let net = NetInfo {
+            interfaces: vec![NetStat {
+                name: "wlan0".to_owned(),
+                wifi: Some(WifiInfo {
+                    ssid: Some("home".to_owned()),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+        };
+        let rect = BoxRect {
+            kind: BoxKind::Net,
+            x: 1,
+            y: 1,
+            width: 50,
+            height: 10,
+        };
+        let draw = |iface| strip_ansi(&draw_net(&rect, &net, iface, false, None, 1, false));
+        assert!(draw("wlan0").contains(" SSID: home"));
+        assert!(!draw("").contains("SSID"));
+    }
+}
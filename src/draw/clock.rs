@@ -0,0 +1,91 @@
+use std::{
+    ffi::CString,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use crate::{collect::cpu, runner::Runner, shared::host::HostInfo};
+
+use super::symbols::symbols;
+
+// 顶部边框上的三个时钟位置，对应 clock_format_left、clock_format 和 clock_format_right
+pub const SLOTS: [&str; 3] = ["clock_format_left", "clock_format", "clock_format_right"];
+
+pub fn strftime(format: &str) -> String {
+    let Ok(format) = CString::new(format) else {
+        return String::new();
+    };
+    let mut buf = [0u8; 256];
+    let len = unsafe {
+        let now = libc::time(std::ptr::null_mut());
+        let mut tm: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&now, &mut tm).is_null() {
+            return String::new();
+        }
+        libc::strftime(
+            buf.as_mut_ptr() as *mut libc::c_char,
+            buf.len(),
+            format.as_ptr(),
+            &tm,
+        )
+    };
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+fn uptime(seconds: u64) -> String {
+    let (days, hours, minutes) = (seconds / 86400, seconds / 3600 % 24, seconds / 60 % 60);
+    match days {
+        0 => format!("{:02}:{:02}:{:02}", hours, minutes, seconds % 60),
+        _ => format!("{}d {:02}:{:02}", days, hours, minutes),
+    }
+}
+
+// temp 为摄氏度，按 temp_scale 换算
+pub fn temperature(temp: f64, scale: &str) -> String {
+    let degree = symbols().degree;
+    match scale {
+        "fahrenheit" => format!("{:.0}{}F", temp * 9.0 / 5.0 + 32.0, degree),
+        "kelvin" => format!("{:.0}K", temp + 273.15),
+        "rankine" => format!("{:.0}{}R", (temp + 273.15) * 9.0 / 5.0, degree),
+        _ => format!("{:.0}{}C", temp, degree),
+    }
+}
+
+// 先替换特殊标记，再按 strftime 格式化
+pub fn format(format: &str, runner: &Runner, temp_scale: &str) -> String {
+    if format.is_empty() {
+        return String::new();
+    }
+    let mut format = format.to_owned();
+    if format.contains("/host") || format.contains("/user") {
+        let instance = HostInfo::get_instance();
+        let host = instance.lock().unwrap();
+        // 名称中的 % 不能被 strftime 当作格式
+        format = format
+            .replace("/host", &host.get_hostname().replace('%', "%%"))
+            .replace("/user", &host.get_user().replace('%', "%%"));
+    }
+    if format.contains("/uptime") {
+        format = format.replace("/uptime", &uptime(cpu::uptime()));
+    }
+    if format.contains("/load") {
+        format = format.replace("/load", &format!("{:.2}", runner.cpu.load_avg[0]));
+    }
+    if format.contains("/temp") {
+        let temp = runner
+            .cpu
+            .temp
+            .map(|temp| temperature(temp, temp_scale))
+            .unwrap_or_default();
+        format = format.replace("/temp", &temp);
+    }
+    strftime(format.trim())
+}
+
+// 距离下一个整秒的时间，用于在两次采集之间让时钟走动
+pub fn until_next_second() -> Duration {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|now| now.subsec_nanos())
+        .unwrap_or(0);
+    Duration::from_nanos(1_000_000_000 - nanos as u64)
+}
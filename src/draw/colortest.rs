@@ -0,0 +1,126 @@
+use std::env;
+
+use super::{fit, gradient_color, mv_to, symbols::GRAPH_SYMBOLS, BOLD, CLEAR, RESET};
+use crate::term::caps::{Capabilities, ColorDepth};
+
+const LABEL_WIDTH: usize = 9;
+
+// 把 0.0-1.0 的位置映射为颜色
+type RampColor = fn(f64) -> (u8, u8, u8);
+
+// 色相 0-360，饱和度和亮度取最大值
+fn hue_to_rgb(hue: f64) -> (u8, u8, u8) {
+    let h = (hue % 360.0) / 60.0;
+    let x = 1.0 - (h % 2.0 - 1.0).abs();
+    let (r, g, b) = match h as u32 {
+        0 => (1.0, x, 0.0),
+        1 => (x, 1.0, 0.0),
+        2 => (0.0, 1.0, x),
+        3 => (0.0, x, 1.0),
+        4 => (x, 0.0, 1.0),
+        _ => (1.0, 0.0, x),
+    };
+    let to_u8 = |v: f64| (v * 255.0).round() as u8;
+    (to_u8(r), to_u8(g), to_u8(b))
+}
+
+fn ramp(width: usize, line: usize, depth: ColorDepth, dither: bool, color: RampColor) -> String {
+    let mut out = String::new();
+    for i in 0..width {
+        let rgb = color(i as f64 / (width.max(2) - 1) as f64);
+        out += &gradient_color(rgb, true, depth, dither, (i, line));
+        out.push(' ');
+    }
+    out + RESET
+}
+
+// 用符号表画一段正弦波，每个字符包含相邻的两个采样点
+fn wave(symbols: &[&str; 25], width: usize) -> String {
+    let level = |i: usize| {
+        let v = ((i as f64 / 4.0).sin() + 1.0) / 2.0;
+        (v * 4.0).round() as usize
+    };
+    (0..width)
+        .map(|i| symbols[level(i * 2) * 5 + level(i * 2 + 1)])
+        .collect()
+}
+
+// 隐藏的诊断界面：以当前色彩模式绘制渐变和所有图形符号，便于在报告渲染问题前检查终端和字体
+pub fn draw(
+    width: u16,
+    height: u16,
+    caps: &Capabilities,
+    depth: ColorDepth,
+    dither: bool,
+) -> String {
+    let width = width as usize;
+    let ramp_width = width.saturating_sub(LABEL_WIDTH + 1);
+    let mode = match (depth, dither) {
+        (ColorDepth::Color256, true) => "256 colors, dithered",
+        (ColorDepth::Color256, false) => "256 colors",
+        (ColorDepth::Color16, _) => "16 colors",
+        (ColorDepth::TrueColor, _) => "truecolor",
+    };
+    let term = env::var("TERM").unwrap_or_default();
+    let colorterm = env::var("COLORTERM").unwrap_or_default();
+
+    let mut lines = vec![
+        format!(
+            "{}Color test{}  mode: {}  TERM={} COLORTERM={}",
+            BOLD, RESET, mode, term, colorterm
+        ),
+        format!(
+            "detected: {} colors, italics {}",
+            caps.colors.name(),
+            if caps.italics { "yes" } else { "no" }
+        ),
+        String::new(),
+    ];
+
+    let ramps: [(&str, RampColor); 5] = [
+        ("Red", |t| ((t * 255.0) as u8, 0, 0)),
+        ("Green", |t| (0, (t * 255.0) as u8, 0)),
+        ("Blue", |t| (0, 0, (t * 255.0) as u8)),
+        ("Gray", |t| {
+            let v = (t * 255.0) as u8;
+            (v, v, v)
+        }),
+        ("Hue", |t| hue_to_rgb(t * 360.0)),
+    ];
+    for (label, color) in ramps.iter() {
+        lines.push(format!(
+            "{}{}",
+            fit(label, LABEL_WIDTH + 1),
+            ramp(ramp_width, lines.len(), depth, dither, *color)
+        ));
+    }
+
+    // 16 色调色板不受色彩模式影响，由终端主题决定
+    let mut ansi = fit("ANSI 16", LABEL_WIDTH + 1);
+    for code in (40..48).chain(100..108) {
+        ansi += &format!("\x1b[{}m   ", code);
+    }
+    lines.push(ansi + RESET);
+    lines.push(String::new());
+
+    for (name, symbols) in GRAPH_SYMBOLS {
+        lines.push(format!(
+            "{}{}",
+            fit(name, LABEL_WIDTH + 1),
+            symbols.concat()
+        ));
+        lines.push(format!(
+            "{}{}",
+            " ".repeat(LABEL_WIDTH + 1),
+            wave(symbols, ramp_width)
+        ));
+    }
+    lines.push(String::new());
+    lines.push("Press q or Esc to return".to_owned());
+
+    let mut out = String::from(CLEAR);
+    for (i, line) in lines.iter().take(height as usize).enumerate() {
+        out += &format!("{}{}", mv_to(i as u16 + 1, 1), line);
+    }
+    out
+}
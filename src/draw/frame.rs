@@ -0,0 +1,165 @@
+use std::{fmt::Write, sync::Arc};
+
+use super::{mv_to, CLEAR, RESET};
+
+#[derive(Clone, PartialEq)]
+struct Cell {
+    ch: char,
+    // 生效中的 SGR 序列，RESET 之后为空
+    style: Arc<str>,
+}
+
+// 一帧屏幕内容。绘制代码输出的转义字符串先写入 Frame，
+// 再与上一帧比较，只输出发生变化的单元格
+pub struct Frame {
+    width: u16,
+    height: u16,
+    cells: Vec<Cell>,
+}
+
+impl Frame {
+    pub fn new(width: u16, height: u16) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![Frame::blank(); width as usize * height as usize],
+        }
+    }
+
+    fn blank() -> Cell {
+        Cell {
+            ch: ' ',
+            style: Arc::from(""),
+        }
+    }
+
+    pub fn get_size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    // 解释绘制代码使用的转义序列：光标定位、清屏和 SGR，其余序列忽略
+    pub fn apply(&mut self, text: &str) {
+        let (mut line, mut col) = (0usize, 0usize);
+        let mut style: Arc<str> = Arc::from("");
+        let mut chars = text.char_indices().peekable();
+
+        while let Some((start, ch)) = chars.next() {
+            if ch != '\x1b' {
+                if line < self.height as usize && col < self.width as usize {
+                    self.cells[line * self.width as usize + col] = Cell {
+                        ch,
+                        style: style.clone(),
+                    };
+                }
+                col += 1;
+                continue;
+            }
+            if chars.next_if(|(_, c)| *c == '[').is_none() {
+                continue;
+            }
+            // 参数字节之后的第一个字母为结束符
+            let mut end = start;
+            let mut last = '\0';
+            for (i, c) in chars.by_ref() {
+                end = i + c.len_utf8();
+                if c.is_ascii_alphabetic() || c == '~' {
+                    last = c;
+                    break;
+                }
+            }
+            let seq = &text[start..end];
+            let params = &seq[2..seq.len() - last.len_utf8()];
+            match last {
+                'm' if params.is_empty() || params == "0" => style = Arc::from(""),
+                'm' => style = Arc::from(format!("{}{}", style, seq)),
+                'f' | 'H' => {
+                    let mut nums = params.split(';').map(|n| n.parse::<usize>().unwrap_or(1));
+                    line = nums.next().unwrap_or(1).saturating_sub(1);
+                    col = nums.next().unwrap_or(1).saturating_sub(1);
+                }
+                'J' if params == "2" => self.cells.fill(Frame::blank()),
+                _ => {}
+            }
+        }
+    }
+
+    // 去掉样式后的纯文本，每行末尾的空格被去掉，用于比较画面内容
+    pub fn text(&self) -> String {
+        let mut out = String::new();
+        for line in self.cells.chunks(self.width.max(1) as usize) {
+            let text: String = line.iter().map(|cell| cell.ch).collect();
+            out += text.trim_end();
+            out.push('\n');
+        }
+        out
+    }
+
+    // 每行中样式相同的连续单元格合并为一段，每行末尾没有样式的空格被去掉
+    pub fn runs(&self) -> Vec<Vec<(&str, String)>> {
+        self.cells
+            .chunks(self.width.max(1) as usize)
+            .map(|line| {
+                let end = line
+                    .iter()
+                    .rposition(|cell| cell.ch != ' ' || !cell.style.is_empty())
+                    .map_or(0, |i| i + 1);
+                let mut runs: Vec<(&str, String)> = Vec::new();
+                for cell in &line[..end] {
+                    match runs.last_mut() {
+                        Some((style, text)) if *style == &*cell.style => text.push(cell.ch),
+                        _ => runs.push((&cell.style, cell.ch.to_string())),
+                    }
+                }
+                runs
+            })
+            .collect()
+    }
+
+    // 带颜色的文本，每行末尾恢复默认样式，可以直接 cat 到终端
+    pub fn ansi(&self) -> String {
+        let mut out = String::new();
+        for line in self.runs() {
+            for (style, text) in &line {
+                out += style;
+                out += text;
+                if !style.is_empty() {
+                    out += RESET;
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    // 输出把 prev 更新为当前帧所需的最少转义序列
+    pub fn diff(&self, prev: &Frame, out: &mut impl Write) {
+        let mut cursor: Option<(usize, usize)> = None;
+        let mut style: &str = "";
+        let width = self.width as usize;
+
+        for (i, cell) in self.cells.iter().enumerate() {
+            if prev.cells.get(i) == Some(cell) {
+                continue;
+            }
+            let (line, col) = (i / width, i % width);
+            if cursor != Some((line, col)) {
+                let _ = write!(out, "{}", mv_to(line as u16 + 1, col as u16 + 1));
+            }
+            if *cell.style != *style {
+                let _ = write!(out, "{}{}", RESET, cell.style);
+                style = &cell.style;
+            }
+            let _ = out.write_char(cell.ch);
+            cursor = Some((line, col + 1));
+        }
+        if !style.is_empty() {
+            let _ = out.write_str(RESET);
+        }
+    }
+
+    // 清屏后完整重绘，用于第一帧和终端尺寸变化之后
+    pub fn full(&self, out: &mut impl Write) {
+        let _ = out.write_str(CLEAR);
+        self.diff(&Frame::new(self.width, self.height), out);
+    }
+}
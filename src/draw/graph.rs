@@ -0,0 +1,58 @@
+use std::borrow::Cow;
+
+use super::symbols::graph_symbols;
+
+// 宽度为 width 的图形能够显示的最近的采样
+pub fn visible<T>(values: &[T], width: usize, range: usize) -> &[T] {
+    &values[values.len().saturating_sub(width * 2 * range.max(1))..]
+}
+
+// 每 range 个采样平均为一个，按最新的采样对齐，最早不足 range 个的一组丢弃
+pub fn compress(values: &[f64], range: usize) -> Cow<'_, [f64]> {
+    if range <= 1 {
+        return Cow::Borrowed(values);
+    }
+    Cow::Owned(
+        values[values.len() % range..]
+            .chunks(range)
+            .map(|chunk| chunk.iter().sum::<f64>() / range as f64)
+            .collect(),
+    )
+}
+
+// 把 0-100 的采样值绘制为 height 行的图形，每个字符显示两个采样，每行分为 4 级。
+// 最新的采样在最右侧，数据不足时左侧留空；invert 时图形从顶部向下绘制
+pub fn graph(
+    values: &[f64],
+    width: usize,
+    height: usize,
+    symbol: &str,
+    invert: bool,
+) -> Vec<String> {
+    let symbols = graph_symbols(symbol, invert);
+
+    let needed = width * 2;
+    let values = &values[values.len().saturating_sub(needed)..];
+    let pad = needed - values.len();
+    let levels: Vec<usize> = (0..needed)
+        .map(|i| match i.checked_sub(pad) {
+            Some(i) => (values[i].clamp(0.0, 100.0) / 100.0 * (height * 4) as f64).round() as usize,
+            None => 0,
+        })
+        .collect();
+
+    (0..height)
+        .map(|row| {
+            // 当前行之下（反转时为之上）已经填满的级数
+            let base = if invert { row } else { height - 1 - row } * 4;
+            levels
+                .chunks(2)
+                .map(|pair| {
+                    let left = pair[0].saturating_sub(base).min(4);
+                    let right = pair[1].saturating_sub(base).min(4);
+                    symbols[left * 5 + right]
+                })
+                .collect()
+        })
+        .collect()
+}
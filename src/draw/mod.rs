@@ -0,0 +1,311 @@
+use std::borrow::Cow;
+
+use crate::{config::theme::Theme, term::caps::ColorDepth};
+
+use symbols::BoxSymbols;
+
+pub mod boxes;
+pub mod clock;
+pub mod colortest;
+pub mod frame;
+pub mod graph;
+pub mod symbols;
+
+pub const RESET: &str = "\x1b[0m";
+pub const BOLD: &str = "\x1b[1m";
+pub const CLEAR: &str = "\x1b[2J\x1b[0;0f";
+pub const ALT_SCREEN: &str = "\x1b[?1049h";
+pub const NORMAL_SCREEN: &str = "\x1b[?1049l";
+pub const HIDE_CURSOR: &str = "\x1b[?25l";
+pub const SHOW_CURSOR: &str = "\x1b[?25h";
+// 报告鼠标按键，使用 SGR 格式以支持超过 223 列的坐标
+pub const MOUSE_ON: &str = "\x1b[?1000h\x1b[?1006h";
+pub const MOUSE_OFF: &str = "\x1b[?1006l\x1b[?1000l";
+
+// 终端坐标从 1 开始
+pub fn mv_to(line: u16, col: u16) -> String {
+    format!("\x1b[{};{}f", line, col)
+}
+
+pub fn parse_hex(hex: &str) -> Option<(u8, u8, u8)> {
+    let hex = hex.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    match (
+        u8::from_str_radix(&hex[0..2], 16),
+        u8::from_str_radix(&hex[2..4], 16),
+        u8::from_str_radix(&hex[4..6], 16),
+    ) {
+        (Ok(r), Ok(g), Ok(b)) => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+// 当前主题中的颜色，调用时不能持有主题的锁
+pub fn theme_color(key: &str) -> String {
+    Theme::get_instance().lock().unwrap().color(key).to_owned()
+}
+
+// "#RRGGBB" 格式的颜色转换为 24 位前景色转义序列
+pub fn fg_hex(hex: &str) -> String {
+    match parse_hex(hex) {
+        Some((r, g, b)) => format!("\x1b[38;2;{};{};{}m", r, g, b),
+        None => String::new(),
+    }
+}
+
+// 与 fg_hex 相同，但按当前色彩模式输出
+pub fn hex_color(hex: &str, background: bool, depth: ColorDepth) -> String {
+    match parse_hex(hex) {
+        Some((r, g, b)) => rgb_color(r, g, b, background, depth),
+        None => String::new(),
+    }
+}
+
+// 与 btop++ 相同：灰阶使用 232-255，其余映射到 6x6x6 色立方
+pub fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    let scale = |v: u8, step: f64| (v as f64 / step).round() as u8;
+    let gray = scale(r, 11.0);
+    if gray == scale(g, 11.0) && gray == scale(b, 11.0) {
+        return 232 + gray.min(23);
+    }
+    scale(r, 51.0) * 36 + scale(g, 51.0) * 6 + scale(b, 51.0) + 16
+}
+
+// xterm 默认的 16 色调色板，实际颜色由终端主题决定
+const ANSI_16: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+// rgb_to_256 和 rgb_to_16 的反向转换，256 色中的前 16 色使用 xterm 的默认值
+pub fn palette_rgb(index: u8) -> (u8, u8, u8) {
+    match index {
+        0..=15 => ANSI_16[index as usize],
+        16..=231 => {
+            let index = index - 16;
+            (index / 36 * 51, index / 6 % 6 * 51, index % 6 * 51)
+        }
+        _ => {
+            let gray = (index - 232) * 11;
+            (gray, gray, gray)
+        }
+    }
+}
+
+// 取调色板中距离最近的颜色，返回 0-15 的序号
+pub fn rgb_to_16(r: u8, g: u8, b: u8) -> u8 {
+    let dist = |(cr, cg, cb): (u8, u8, u8)| {
+        [(r, cr), (g, cg), (b, cb)]
+            .iter()
+            .map(|(a, b)| (*a as i32 - *b as i32).pow(2))
+            .sum::<i32>()
+    };
+    (0..16u8)
+        .min_by_key(|i| dist(ANSI_16[*i as usize]))
+        .unwrap_or(7)
+}
+
+// 4x4 Bayer 矩阵，值为 0-15 的阈值
+const BAYER: [[u8; 4]; 4] = [[0, 8, 2, 10], [12, 4, 14, 6], [3, 11, 1, 9], [15, 7, 13, 5]];
+
+// 与 rgb_to_256 相同，但按 (x, y) 处的阈值在相邻两级之间选择，
+// 渐变中相邻的单元格交替使用两级颜色，减少 256 色下明显的色带
+pub fn rgb_to_256_dithered(r: u8, g: u8, b: u8, x: usize, y: usize) -> u8 {
+    let threshold = (BAYER[y % 4][x % 4] as f64 + 0.5) / 16.0;
+    let scale = |v: u8, step: f64, max: u8| {
+        let level = v as f64 / step;
+        let up = level.fract() > threshold;
+        (level.floor() as u8 + up as u8).min(max)
+    };
+    let round = |v: u8| (v as f64 / 11.0).round() as u8;
+    if round(r) == round(g) && round(r) == round(b) {
+        return 232 + scale(r, 11.0, 23);
+    }
+    scale(r, 51.0, 5) * 36 + scale(g, 51.0, 5) * 6 + scale(b, 51.0, 5) + 16
+}
+
+// 渐变中位于 (x, y) 的颜色，256 色且开启 dither 时使用有序抖动
+pub fn gradient_color(
+    (r, g, b): (u8, u8, u8),
+    background: bool,
+    depth: ColorDepth,
+    dither: bool,
+    (x, y): (usize, usize),
+) -> String {
+    if !(depth == ColorDepth::Color256 && dither) {
+        return rgb_color(r, g, b, background, depth);
+    }
+    let layer = if background { 48 } else { 38 };
+    format!("\x1b[{};5;{}m", layer, rgb_to_256_dithered(r, g, b, x, y))
+}
+
+// 按当前色彩模式生成前景或背景色转义序列，不支持 24 位颜色时降级为 256 或 16 色
+pub fn rgb_color(r: u8, g: u8, b: u8, background: bool, depth: ColorDepth) -> String {
+    let layer = if background { 48 } else { 38 };
+    match depth {
+        ColorDepth::TrueColor => format!("\x1b[{};2;{};{};{}m", layer, r, g, b),
+        ColorDepth::Color256 => format!("\x1b[{};5;{}m", layer, rgb_to_256(r, g, b)),
+        ColorDepth::Color16 => {
+            let index = rgb_to_16(r, g, b);
+            let base = match (background, index < 8) {
+                (false, true) => 30,
+                (false, false) => 82,
+                (true, true) => 40,
+                (true, false) => 92,
+            };
+            format!("\x1b[{}m", base + index)
+        }
+    }
+}
+
+// 去掉 CSI 转义序列（颜色、光标移动等），只保留可见文本，用于计算显示宽度
+pub fn strip_ansi(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            out.push(c);
+            continue;
+        }
+        if chars.next_if_eq(&'[').is_some() {
+            // 参数字节之后的第一个字母或 '~' 为结束符
+            for c in chars.by_ref() {
+                if c.is_ascii_alphabetic() || c == '~' {
+                    break;
+                }
+            }
+        }
+    }
+    out
+}
+
+// 进程名和命令行由进程自己决定，绘制前替换其中的控制字符，避免 ANSI 转义序列
+// 破坏界面：C0 控制字符和 DEL 使用 ^X 表示，C1 控制字符使用 \xNN 表示，
+// 改变文字方向的 Unicode 控制字符替换为 U+FFFD。无效的 UTF-8 在采集时已被替换
+pub fn sanitize(text: &str) -> Cow<'_, str> {
+    let unsafe_char =
+        |c: char| c.is_control() || matches!(c, '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}');
+    if !text.chars().any(unsafe_char) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len() + 8);
+    for c in text.chars() {
+        match c {
+            '\0'..='\x1f' => {
+                out.push('^');
+                out.push((c as u8 + b'@') as char);
+            }
+            '\x7f' => out.push_str("^?"),
+            '\u{80}'..='\u{9f}' => out.push_str(&format!("\\x{:02x}", c as u32)),
+            c if unsafe_char(c) => out.push('\u{fffd}'),
+            c => out.push(c),
+        }
+    }
+    Cow::Owned(out)
+}
+
+// 按字符数截断或补齐到固定宽度
+pub fn fit(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    if len >= width {
+        text.chars().take(width).collect()
+    } else {
+        format!("{}{}", text, " ".repeat(width - len))
+    }
+}
+
+pub struct Border<'a> {
+    pub symbols: &'a BoxSymbols,
+    // 为空时使用终端默认颜色绘制边框
+    pub line_color: &'a str,
+    // 左上角的标题
+    pub title: &'a str,
+    // 右下角的标题，用于显示位置等状态
+    pub bottom_title: &'a str,
+}
+
+// 标题放在边框的一段中，放不下时只画横线
+fn border_line(
+    symbols: &BoxSymbols,
+    line_color: &str,
+    inner: usize,
+    title: &str,
+    style: &str,
+    right: bool,
+) -> String {
+    // 标题中可能带有颜色，只按可见字符计算宽度
+    let len = strip_ansi(title).chars().count();
+    if title.is_empty() || len + 4 > inner {
+        return symbols.horizontal.repeat(inner);
+    }
+    let slot = format!(
+        "{}{}{}{}{}{}",
+        symbols.title_left, style, title, RESET, line_color, symbols.title_right
+    );
+    let fill = symbols.horizontal.repeat(inner - 3 - len);
+    if right {
+        format!("{}{}{}", fill, slot, symbols.horizontal)
+    } else {
+        format!("{}{}{}", symbols.horizontal, slot, fill)
+    }
+}
+
+pub fn create_box(x: u16, y: u16, width: u16, height: u16, border: &Border) -> String {
+    let mut out = String::new();
+    if width < 2 || height < 2 {
+        return out;
+    }
+    let inner = width as usize - 2;
+    let symbols = border.symbols;
+    let color = border.line_color;
+
+    out += &format!(
+        "{}{}{}{}{}{}",
+        mv_to(y, x),
+        color,
+        symbols.top_left,
+        border_line(symbols, color, inner, border.title, BOLD, false),
+        symbols.top_right,
+        RESET
+    );
+
+    for line in 1..height - 1 {
+        out += &format!(
+            "{}{}{}{}{}{}{}{}",
+            mv_to(y + line, x),
+            color,
+            symbols.vertical,
+            RESET,
+            " ".repeat(inner),
+            color,
+            symbols.vertical,
+            RESET
+        );
+    }
+    out += &format!(
+        "{}{}{}{}{}{}",
+        mv_to(y + height - 1, x),
+        color,
+        symbols.bottom_left,
+        border_line(symbols, color, inner, border.bottom_title, "", true),
+        symbols.bottom_right,
+        RESET
+    );
+    out
+}
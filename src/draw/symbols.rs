@@ -0,0 +1,252 @@
+use crate::{config::config::Config, shared::battery::BatteryStatus, term::Term};
+
+// 终端能够显示的字符范围，linux 控制台的字体只有 cp437 中的字符
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Charset {
+    Unicode,
+    Tty,
+    Ascii,
+}
+
+// 没有检测到 UTF-8 时只使用 ASCII，tty_mode 或 force_tty 时避开控制台字体中没有的字符
+pub fn charset() -> Charset {
+    if !Term::get_instance().lock().unwrap().is_utf8() {
+        return Charset::Ascii;
+    }
+    let instance = Config::get_instance();
+    let config = instance.lock().unwrap();
+    match config.get_bool("tty_mode") || config.get_bool("force_tty") {
+        true => Charset::Tty,
+        false => Charset::Unicode,
+    }
+}
+
+// 边框使用的字符，title_left/title_right 包围边框上的标题
+pub struct BoxSymbols {
+    pub horizontal: &'static str,
+    pub vertical: &'static str,
+    pub top_left: &'static str,
+    pub top_right: &'static str,
+    pub bottom_left: &'static str,
+    pub bottom_right: &'static str,
+    pub title_left: &'static str,
+    pub title_right: &'static str,
+}
+
+pub const ROUNDED: BoxSymbols = BoxSymbols {
+    horizontal: "─",
+    vertical: "│",
+    top_left: "╭",
+    top_right: "╮",
+    bottom_left: "╰",
+    bottom_right: "╯",
+    title_left: "┤",
+    title_right: "├",
+};
+
+pub const SQUARE: BoxSymbols = BoxSymbols {
+    top_left: "┌",
+    top_right: "┐",
+    bottom_left: "└",
+    bottom_right: "┘",
+    ..ROUNDED
+};
+
+pub const ASCII: BoxSymbols = BoxSymbols {
+    horizontal: "-",
+    vertical: "|",
+    top_left: "+",
+    top_right: "+",
+    bottom_left: "+",
+    bottom_right: "+",
+    title_left: "[",
+    title_right: "]",
+};
+
+// tty 模式下忽略 rounded_corners
+pub fn box_symbols() -> &'static BoxSymbols {
+    match charset() {
+        Charset::Ascii => &ASCII,
+        Charset::Tty => &SQUARE,
+        Charset::Unicode => match Config::get_instance()
+            .lock()
+            .unwrap()
+            .get_bool("rounded_corners")
+        {
+            true => &ROUNDED,
+            false => &SQUARE,
+        },
+    }
+}
+
+// 边框和图形之外的符号：箭头、仪表、电池状态、温度单位和图形刻度的上下标数字
+pub struct Symbols {
+    pub up: &'static str,
+    pub down: &'static str,
+    // 仪表中的一格
+    pub meter: &'static str,
+    // 从低到高的 8 级，用于只有一个字符宽的仪表
+    pub levels: [&'static str; 8],
+    pub degree: &'static str,
+    // 电池充电、放电、充满和未知状态，与 btop++ 相同
+    pub battery: [&'static str; 4],
+}
+
+pub const UNICODE_SYMBOLS: Symbols = Symbols {
+    up: "▲",
+    down: "▼",
+    meter: "■",
+    levels: ["▁", "▂", "▃", "▄", "▅", "▆", "▇", "█"],
+    degree: "°",
+    battery: ["▲", "▼", "■", "○"],
+};
+
+pub const TTY_SYMBOLS: Symbols = Symbols {
+    levels: [" ", "░", "░", "▒", "▒", "▓", "▓", "█"],
+    ..UNICODE_SYMBOLS
+};
+
+pub const ASCII_SYMBOLS: Symbols = Symbols {
+    up: "^",
+    down: "v",
+    meter: "#",
+    levels: [" ", ".", ".", ":", ":", "=", "=", "#"],
+    degree: "",
+    battery: ["+", "-", "=", "?"],
+};
+
+pub fn symbols() -> &'static Symbols {
+    match charset() {
+        Charset::Unicode => &UNICODE_SYMBOLS,
+        Charset::Tty => &TTY_SYMBOLS,
+        Charset::Ascii => &ASCII_SYMBOLS,
+    }
+}
+
+impl Symbols {
+    pub fn battery_status(&self, status: &BatteryStatus) -> &'static str {
+        match status {
+            BatteryStatus::Charging => self.battery[0],
+            BatteryStatus::Discharging => self.battery[1],
+            BatteryStatus::Full => self.battery[2],
+            BatteryStatus::Unknown => self.battery[3],
+        }
+    }
+}
+
+// 图形符号表，按 左列高度 * 5 + 右列高度 索引，每列高度为 0-4
+pub const GRAPH_SYMBOLS: &[(&str, [&str; 25])] = &[
+    (
+        "braille",
+        [
+            " ", "⢀", "⢠", "⢰", "⢸", "⡀", "⣀", "⣠", "⣰", "⣸", "⡄", "⣄", "⣤", "⣴", "⣼", "⡆", "⣆",
+            "⣦", "⣶", "⣾", "⡇", "⣇", "⣧", "⣷", "⣿",
+        ],
+    ),
+    (
+        "block",
+        [
+            " ", "▗", "▗", "▐", "▐", "▖", "▄", "▄", "▟", "▟", "▖", "▄", "▄", "▟", "▟", "▌", "▙",
+            "▙", "█", "█", "▌", "▙", "▙", "█", "█",
+        ],
+    ),
+    (
+        "tty",
+        [
+            " ", "░", "░", "▒", "▒", "░", "░", "▒", "▒", "█", "░", "▒", "▒", "▒", "█", "▒", "▒",
+            "▒", "█", "█", "▒", "█", "█", "█", "█",
+        ],
+    ),
+];
+
+// 向下绘制的图形符号表，用于反转的图形，索引方式与 GRAPH_SYMBOLS 相同
+pub const GRAPH_SYMBOLS_DOWN: &[(&str, [&str; 25])] = &[
+    (
+        "braille",
+        [
+            " ", "⠈", "⠘", "⠸", "⢸", "⠁", "⠉", "⠙", "⠹", "⢹", "⠃", "⠋", "⠛", "⠻", "⢻", "⠇", "⠏",
+            "⠟", "⠿", "⢿", "⡇", "⡏", "⡟", "⡿", "⣿",
+        ],
+    ),
+    (
+        "block",
+        [
+            " ", "▝", "▝", "▐", "▐", "▘", "▀", "▀", "▜", "▜", "▘", "▀", "▀", "▜", "▜", "▌", "▛",
+            "▛", "█", "█", "▌", "▛", "▛", "█", "█",
+        ],
+    ),
+    (
+        "tty",
+        [
+            " ", "░", "░", "▒", "▒", "░", "░", "▒", "▒", "█", "░", "▒", "▒", "▒", "█", "▒", "▒",
+            "▒", "█", "█", "▒", "█", "█", "█", "█",
+        ],
+    ),
+];
+
+// 没有 UTF-8 时的图形，两列中较高的一列决定字符，向上和向下绘制时相同
+const GRAPH_ASCII: [&str; 25] = [
+    " ", ".", ":", "|", "#", ".", ".", ":", "|", "#", ":", ":", ":", "|", "#", "|", "|", "|", "|",
+    "#", "#", "#", "#", "#", "#",
+];
+
+// 按 graph_symbol 的名称查找符号表，未知的名称使用第一个
+pub fn graph_symbols(name: &str, invert: bool) -> &'static [&'static str; 25] {
+    if charset() == Charset::Ascii {
+        return &GRAPH_ASCII;
+    }
+    let table = match invert {
+        true => GRAPH_SYMBOLS_DOWN,
+        false => GRAPH_SYMBOLS,
+    };
+    &table
+        .iter()
+        .find(|(table_name, _)| *table_name == name)
+        .unwrap_or(&table[0])
+        .1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Symbols, ASCII, ASCII_SYMBOLS, GRAPH_ASCII};
+
+    // 没有 UTF-8 时终端只能可靠地显示 7 位 ASCII 中的可打印字符
+    fn assert_ascii(what: &str, symbols: &[&str]) {
+        for symbol in symbols {
+            assert!(
+                symbol.bytes().all(|b| (b' '..=b'~').contains(&b)),
+                "{} contains {:?}",
+                what,
+                symbol
+            );
+        }
+    }
+
+    #[test]
+    fn ascii_set_is_7bit() {
+        let boxes = [
+            ASCII.horizontal,
+            ASCII.vertical,
+            ASCII.top_left,
+            ASCII.top_right,
+            ASCII.bottom_left,
+            ASCII.bottom_right,
+            ASCII.title_left,
+            ASCII.title_right,
+        ];
+        assert_ascii("ASCII box symbols", &boxes);
+
+        let Symbols {
+            up,
+            down,
+            meter,
+            levels,
+            degree,
+            battery,
+        } = ASCII_SYMBOLS;
+        assert_ascii("ASCII symbols", &[up, down, meter, degree]);
+        assert_ascii("ASCII levels", &levels);
+        assert_ascii("ASCII battery", &battery);
+        assert_ascii("ASCII graph", &GRAPH_ASCII);
+    }
+}
@@ -0,0 +1,118 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+};
+
+use log::info;
+
+use crate::{config::config::Config, runner::Runner, time_s};
+
+const HEADER: &str = "timestamp,cpu_percent,load_1,load_5,load_15,\
+mem_total,mem_used,swap_total,swap_used,net_rx_rate,net_tx_rate";
+
+// 含有逗号、引号或换行的字段用双引号包裹，内部的引号写两次
+pub fn escape(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_owned()
+    }
+}
+
+pub struct CsvLogger {
+    path: PathBuf,
+    max_size: u64,
+    file: Option<File>,
+}
+
+impl CsvLogger {
+    pub fn new(path: PathBuf, max_size: u64) -> Self {
+        CsvLogger {
+            path,
+            max_size,
+            file: None,
+        }
+    }
+
+    // csv_log 为空时不启用；相对路径以配置目录为基准
+    pub fn from_config(config: &Config) -> Option<Self> {
+        let path = config.get_string("csv_log");
+        if path.is_empty() {
+            return None;
+        }
+
+        let mut path = PathBuf::from(path);
+        if path.is_relative() {
+            path = config.get_dir().join(path);
+        }
+        let max_size = config.get_int("csv_log_size").max(0) as u64 * 1024;
+        info!("csv log enabled: {:?}", path);
+        Some(CsvLogger::new(path, max_size))
+    }
+
+    // 外部轮转（例如 logrotate）之后重新打开文件
+    pub fn reopen(&mut self) {
+        self.file = None;
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file = None;
+        let mut rotated = self.path.clone().into_os_string();
+        rotated.push(".1");
+        fs::rename(&self.path, rotated)
+    }
+
+    fn open(&mut self) -> io::Result<&mut File> {
+        if self.file.is_none() {
+            // 已有文件的表头和当前列不一致时，先轮转旧文件再重新生成表头
+            if let Ok(file) = File::open(&self.path) {
+                let mut first_line = String::new();
+                BufReader::new(file).read_line(&mut first_line)?;
+                if !first_line.is_empty() && first_line.trim_end() != HEADER {
+                    self.rotate()?;
+                }
+            }
+
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?;
+            if file.metadata()?.len() == 0 {
+                writeln!(file, "{}", HEADER)?;
+            }
+            self.file = Some(file);
+        }
+
+        Ok(self.file.as_mut().unwrap())
+    }
+
+    pub fn write_row(&mut self, runner: &Runner, iface: &str) -> io::Result<()> {
+        if self.max_size > 0 {
+            if let Ok(md) = fs::metadata(&self.path) {
+                if md.len() >= self.max_size {
+                    self.rotate()?;
+                }
+            }
+        }
+
+        let (rx_rate, tx_rate) = runner.net.rates(iface);
+        let row = format!(
+            "{},{:.1},{:.2},{:.2},{:.2},{},{},{},{},{},{}",
+            time_s(),
+            runner.cpu.cpu_percent,
+            runner.cpu.load_avg[0],
+            runner.cpu.load_avg[1],
+            runner.cpu.load_avg[2],
+            runner.mem.total,
+            runner.mem.used,
+            runner.mem.swap_total,
+            runner.mem.swap_used,
+            rx_rate,
+            tx_rate,
+        );
+
+        let file = self.open()?;
+        writeln!(file, "{}", row)
+    }
+}
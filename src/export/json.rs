@@ -0,0 +1,134 @@
+use crate::{runner::Runner, time_s};
+
+pub fn escape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len() + 2);
+    out.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn float_list(values: &[f64]) -> String {
+    let values: Vec<String> = values.iter().map(|v| format!("{:.2}", v)).collect();
+    format!("[{}]", values.join(","))
+}
+
+// 生成当前采集结果的 JSON 快照，单行输出
+pub fn snapshot(runner: &Runner) -> String {
+    let events = match &runner.cpu.events {
+        Some(events) => format!(
+            "{{\"intr\":{},\"ctxt\":{},\"softirq\":{}}}",
+            events.intr, events.ctxt, events.softirq
+        ),
+        None => "null".to_owned(),
+    };
+    let cpu = format!(
+        "{{\"model\":{},\"percent\":{:.2},\"cores\":{},\"load_avg\":{},\"events\":{}}}",
+        escape(&runner.cpu.model),
+        runner.cpu.cpu_percent,
+        float_list(&runner.cpu.core_percent),
+        float_list(&runner.cpu.load_avg),
+        events
+    );
+
+    let swaps: Vec<String> = runner
+        .mem
+        .swaps
+        .iter()
+        .map(|swap| {
+            format!(
+                "{{\"name\":{},\"type\":{},\"size\":{},\"used\":{},\"priority\":{}}}",
+                escape(&swap.name),
+                escape(&swap.kind),
+                swap.size,
+                swap.used,
+                swap.priority
+            )
+        })
+        .collect();
+
+    let mem = format!(
+        "{{\"total\":{},\"used\":{},\"available\":{},\"free\":{},\"cached\":{},\
+        \"swap_total\":{},\"swap_used\":{},\"swaps\":[{}]}}",
+        runner.mem.total,
+        runner.mem.used,
+        runner.mem.available,
+        runner.mem.free,
+        runner.mem.cached,
+        runner.mem.swap_total,
+        runner.mem.swap_used,
+        swaps.join(",")
+    );
+
+    let interfaces: Vec<String> = runner
+        .net
+        .interfaces
+        .iter()
+        .map(|iface| {
+            format!(
+                "{{\"name\":{},\"rx_bytes\":{},\"tx_bytes\":{},\"rx_rate\":{},\"tx_rate\":{}}}",
+                escape(&iface.name),
+                iface.rx_bytes,
+                iface.tx_bytes,
+                iface.rx_rate,
+                iface.tx_rate
+            )
+        })
+        .collect();
+
+    let procs: Vec<String> = runner.procs.iter().map(proc_json).collect();
+
+    let battery = match &runner.battery {
+        Some(bat) => format!(
+            "{{\"percent\":{:.0},\"status\":{},\"watts\":{},\"seconds\":{}}}",
+            bat.percent,
+            escape(bat.status.name()),
+            bat.watts
+                .map_or("null".to_owned(), |watts| format!("{:.2}", watts)),
+            bat.seconds
+                .map_or("null".to_owned(), |seconds| seconds.to_string())
+        ),
+        None => "null".to_owned(),
+    };
+
+    format!(
+        "{{\"timestamp\":{},\"cpu\":{},\"mem\":{},\"net\":[{}],\"procs\":[{}],\"battery\":{}}}",
+        time_s(),
+        cpu,
+        mem,
+        interfaces.join(","),
+        procs.join(","),
+        battery
+    )
+}
+
+pub fn proc_json(proc: &crate::shared::proc::ProcInfo) -> String {
+    format!(
+        "{{\"pid\":{},\"ppid\":{},\"name\":{},\"cmd\":{},\"user\":{},\"state\":{},\
+        \"threads\":{},\"nice\":{},\"mem\":{},\"cpu\":{:.2},\"io_read\":{},\"io_write\":{}}}",
+        proc.pid,
+        proc.ppid,
+        escape(&proc.name),
+        escape(&proc.cmd),
+        escape(&proc.user),
+        escape(&proc.state.to_string()),
+        proc.threads,
+        proc.nice,
+        proc.mem,
+        proc.cpu_p,
+        proc.io_rate
+            .map_or("null".to_owned(), |(read, _)| read.to_string()),
+        proc.io_rate
+            .map_or("null".to_owned(), |(_, write)| write.to_string())
+    )
+}
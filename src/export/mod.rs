@@ -0,0 +1,5 @@
+pub mod csv;
+pub mod json;
+pub mod prometheus;
+pub mod screenshot;
+pub mod top;
@@ -0,0 +1,282 @@
+use std::{
+    fmt::Write as _,
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream},
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use log::{info, warn};
+
+use crate::{config::config::Config, runner::Runner};
+
+// 标签值中的反斜杠、双引号和换行需要转义
+pub fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+// 一个 gauge 指标：HELP、TYPE 和每组标签的取值
+fn gauge(out: &mut String, name: &str, help: &str, samples: &[(String, f64)]) {
+    if samples.is_empty() {
+        return;
+    }
+    let _ = writeln!(out, "# HELP btop_{} {}", name, help);
+    let _ = writeln!(out, "# TYPE btop_{} gauge", name);
+    for (labels, value) in samples {
+        let _ = writeln!(out, "btop_{}{} {}", name, labels, value);
+    }
+}
+
+fn label(name: &str, value: &str) -> String {
+    format!("{{{}=\"{}\"}}", name, escape(value))
+}
+
+// 当前采集结果的 Prometheus 文本格式
+pub fn metrics(runner: &Runner) -> String {
+    let mut out = String::new();
+    let cpu = &runner.cpu;
+    gauge(
+        &mut out,
+        "cpu_percent",
+        "Total cpu usage in percent.",
+        &[(String::new(), cpu.cpu_percent)],
+    );
+    let cores: Vec<_> = cpu
+        .core_percent
+        .iter()
+        .enumerate()
+        .filter(|(core, _)| !cpu.offline.contains(core))
+        .map(|(core, &percent)| (label("core", &core.to_string()), percent))
+        .collect();
+    gauge(
+        &mut out,
+        "cpu_core_percent",
+        "Cpu usage of each online core in percent.",
+        &cores,
+    );
+    let load: Vec<_> = ["1", "5", "15"]
+        .iter()
+        .zip(cpu.load_avg)
+        .map(|(minutes, load)| (label("minutes", minutes), load))
+        .collect();
+    gauge(&mut out, "load_average", "System load average.", &load);
+    let temp: Vec<_> = cpu
+        .temp
+        .map(|temp| (String::new(), temp))
+        .into_iter()
+        .collect();
+    gauge(
+        &mut out,
+        "cpu_temp_celsius",
+        "Cpu temperature in degrees Celsius.",
+        &temp,
+    );
+
+    let mem = &runner.mem;
+    for (name, help, value) in [
+        ("mem_total_bytes", "Total memory.", mem.total),
+        ("mem_used_bytes", "Used memory.", mem.used),
+        ("mem_available_bytes", "Available memory.", mem.available),
+        ("mem_cached_bytes", "Page cache.", mem.cached),
+        ("mem_free_bytes", "Free memory.", mem.free),
+        ("swap_total_bytes", "Total swap.", mem.swap_total),
+        ("swap_used_bytes", "Used swap.", mem.swap_used),
+    ] {
+        gauge(&mut out, name, help, &[(String::new(), value as f64)]);
+    }
+
+    let ifaces = &runner.net.interfaces;
+    let rates = |rate: fn(&_) -> u64| -> Vec<_> {
+        ifaces
+            .iter()
+            .map(|iface| (label("interface", &iface.name), rate(iface) as f64))
+            .collect()
+    };
+    gauge(
+        &mut out,
+        "net_receive_bytes_per_second",
+        "Download rate of each interface.",
+        &rates(|iface| iface.rx_rate),
+    );
+    gauge(
+        &mut out,
+        "net_transmit_bytes_per_second",
+        "Upload rate of each interface.",
+        &rates(|iface| iface.tx_rate),
+    );
+
+    let disks = &runner.disks.disks;
+    let space = |value: fn(&_) -> u64| -> Vec<_> {
+        disks
+            .iter()
+            .map(|disk| (label("mount", &disk.mount), value(disk) as f64))
+            .collect()
+    };
+    gauge(
+        &mut out,
+        "disk_total_bytes",
+        "Size of each mounted filesystem.",
+        &space(|disk| disk.total),
+    );
+    gauge(
+        &mut out,
+        "disk_used_bytes",
+        "Used space of each mounted filesystem.",
+        &space(|disk| disk.used),
+    );
+    out
+}
+
+// prometheus_listen 中的地址上提供 "GET /metrics"，内容为最近一次采集的结果
+pub struct PrometheusExporter {
+    addr: SocketAddr,
+    metrics: Arc<Mutex<String>>,
+}
+
+impl PrometheusExporter {
+    // prometheus_listen 为空时不启用
+    pub fn from_config(config: &Config) -> Option<io::Result<Self>> {
+        let listen = config.get_string("prometheus_listen");
+        if listen.is_empty() {
+            return None;
+        }
+        Some(PrometheusExporter::start(&listen))
+    }
+
+    pub fn start(listen: &str) -> io::Result<Self> {
+        let listener = TcpListener::bind(listen)?;
+        let addr = listener.local_addr()?;
+        info!("prometheus exporter listening on {}", addr);
+
+        let metrics = Arc::new(Mutex::new(String::new()));
+        let t_metrics = metrics.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => {
+                        if let Err(err) = serve(stream, &t_metrics) {
+                            warn!("prometheus client error: {}", err);
+                        }
+                    }
+                    Err(err) => warn!("prometheus accept failed: {}", err),
+                }
+            }
+        });
+
+        Ok(PrometheusExporter { addr, metrics })
+    }
+
+    pub fn get_addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    pub fn update(&self, runner: &Runner) {
+        *self.metrics.lock().unwrap() = metrics(runner);
+    }
+}
+
+// 只读取请求行，请求头的内容不影响应答
+fn serve(stream: TcpStream, metrics: &Mutex<String>) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+    let mut request = String::new();
+    reader.read_line(&mut request)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim().is_empty() {
+            break;
+        }
+    }
+
+    let mut parts = request.split_whitespace();
+    let (status, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", metrics.lock().unwrap().clone()),
+        (Some("GET"), _) => ("404 Not Found", "not found, try /metrics\n".to_owned()),
+        _ => ("405 Method Not Allowed", String::new()),
+    };
+    write!(
+        writer,
+        "HTTP/1.1 {}\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        status,
+        body.len(),
+        body
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{Read, Write},
+        net::TcpStream,
+    };
+
+    use super::{escape, metrics, PrometheusExporter};
+    use crate::{
+        runner::Runner,
+        shared::net::{NetInfo, NetStat},
+    };
+
+    fn runner() -> Runner {
+        let mut runner = Runner::new();
+        runner.cpu.cpu_percent = 37.5;
+        runner.cpu.core_percent = vec![10.0, 0.0, 65.0];
+        runner.cpu.offline = vec![1];
+        runner.cpu.load_avg = [1.0, 0.5, 0.25];
+        runner.mem.total = 16 << 30;
+        runner.net = NetInfo {
+            interfaces: vec![NetStat {
+                name: "eth\"0".to_owned(),
+                rx_rate: 1024,
+                ..Default::default()
+            }],
+        };
+        runner
+    }
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(escape(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape("a\nb"), "a\\nb");
+    }
+
+    #[test]
+    fn text_format() {
+        let text = metrics(&runner());
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines.contains(&"# TYPE btop_cpu_percent gauge"));
+        assert!(lines.contains(&"btop_cpu_percent 37.5"));
+        assert!(lines.contains(&"btop_cpu_core_percent{core=\"2\"} 65"));
+        // 离线的核心不输出
+        assert!(!text.contains("core=\"1\""));
+        assert!(lines.contains(&"btop_load_average{minutes=\"15\"} 0.25"));
+        assert!(lines.contains(&"btop_mem_total_bytes 17179869184"));
+        assert!(lines.contains(&"btop_net_receive_bytes_per_second{interface=\"eth\\\"0\"} 1024"));
+        // 没有温度和磁盘时不输出这些指标
+        assert!(!text.contains("cpu_temp_celsius"));
+        assert!(!text.contains("disk_used_bytes"));
+    }
+
+    #[test]
+    fn serves_metrics_over_http() {
+        let exporter = PrometheusExporter::start("127.0.0.1:0").unwrap();
+        exporter.update(&runner());
+        // 端口 0 由系统分配
+        let addr = exporter.get_addr();
+        let get = |path: &str| {
+            let mut stream = TcpStream::connect(addr).unwrap();
+            write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+            let mut reply = String::new();
+            stream.read_to_string(&mut reply).unwrap();
+            reply
+        };
+        let reply = get("/metrics");
+        assert!(reply.starts_with("HTTP/1.1 200 OK\r\n"), "{}", reply);
+        assert!(reply.contains("\r\n\r\n# HELP btop_cpu_percent"));
+        assert!(get("/").starts_with("HTTP/1.1 404"));
+    }
+}
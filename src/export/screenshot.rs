@@ -0,0 +1,241 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    thread,
+    time::Duration,
+};
+
+use log::info;
+
+use crate::{
+    config::{
+        config::Config,
+        theme::{is_light, Theme},
+    },
+    draw::{frame::Frame, palette_rgb, parse_hex},
+    runner::Runner,
+    screen,
+    term::Term,
+    time_s,
+};
+
+// cpu 占用按两次采集之间的差值计算，中间等待的时间
+const SAMPLE_MS: u64 = 1000;
+
+// 不在终端中运行（例如输出被重定向）时使用的画面尺寸
+const FALLBACK_SIZE: (u16, u16) = (120, 40);
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ScreenshotFormat {
+    Ansi,
+    Html,
+}
+
+impl ScreenshotFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "ansi" => Some(ScreenshotFormat::Ansi),
+            "html" => Some(ScreenshotFormat::Html),
+            _ => None,
+        }
+    }
+
+    // .html 和 .htm 文件写出 HTML，其余写出带颜色的文本
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("html") || ext.eq_ignore_ascii_case("htm") => {
+                ScreenshotFormat::Html
+            }
+            _ => ScreenshotFormat::Ansi,
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            ScreenshotFormat::Ansi => "ans",
+            ScreenshotFormat::Html => "html",
+        }
+    }
+}
+
+// 一段文本的样式，由单元格上累积的 SGR 序列解析而来
+#[derive(Default)]
+struct Style {
+    fg: Option<(u8, u8, u8)>,
+    bg: Option<(u8, u8, u8)>,
+    bold: bool,
+    dim: bool,
+    italic: bool,
+    underline: bool,
+    reverse: bool,
+}
+
+impl Style {
+    fn parse(seqs: &str) -> Self {
+        let mut style = Style::default();
+        for seq in seqs.split('\x1b') {
+            let Some(params) = seq.strip_prefix('[').and_then(|s| s.strip_suffix('m')) else {
+                continue;
+            };
+            let mut params = params.split(';').map(|n| n.parse::<u8>().unwrap_or(0));
+            while let Some(param) = params.next() {
+                match param {
+                    0 => style = Style::default(),
+                    1 => style.bold = true,
+                    2 => style.dim = true,
+                    3 => style.italic = true,
+                    4 => style.underline = true,
+                    7 => style.reverse = true,
+                    22 => (style.bold, style.dim) = (false, false),
+                    23 => style.italic = false,
+                    24 => style.underline = false,
+                    27 => style.reverse = false,
+                    30..=37 => style.fg = Some(palette_rgb(param - 30)),
+                    90..=97 => style.fg = Some(palette_rgb(param - 82)),
+                    40..=47 => style.bg = Some(palette_rgb(param - 40)),
+                    100..=107 => style.bg = Some(palette_rgb(param - 92)),
+                    39 => style.fg = None,
+                    49 => style.bg = None,
+                    38 | 48 => {
+                        let color = match params.next() {
+                            Some(2) => match (params.next(), params.next(), params.next()) {
+                                (Some(r), Some(g), Some(b)) => Some((r, g, b)),
+                                _ => None,
+                            },
+                            Some(5) => params.next().map(palette_rgb),
+                            _ => None,
+                        };
+                        match param {
+                            38 => style.fg = color,
+                            _ => style.bg = color,
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        style
+    }
+
+    // 反显时没有设置的颜色使用页面的前景色和背景色
+    fn css(&self, page: ((u8, u8, u8), (u8, u8, u8))) -> String {
+        let (mut fg, mut bg) = (self.fg, self.bg);
+        if self.reverse {
+            (fg, bg) = (Some(bg.unwrap_or(page.1)), Some(fg.unwrap_or(page.0)));
+        }
+        let mut css = Vec::new();
+        if let Some((r, g, b)) = fg {
+            css.push(format!("color:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if let Some((r, g, b)) = bg {
+            css.push(format!("background:#{:02x}{:02x}{:02x}", r, g, b));
+        }
+        if self.bold {
+            css.push("font-weight:bold".to_owned());
+        }
+        if self.dim {
+            css.push("opacity:0.6".to_owned());
+        }
+        if self.italic {
+            css.push("font-style:italic".to_owned());
+        }
+        if self.underline {
+            css.push("text-decoration:underline".to_owned());
+        }
+        css.join(";")
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+// 页面的前景色和背景色：主题中的 main_fg/main_bg，没有时按启动时查询到的终端背景选择
+fn page_colors() -> ((u8, u8, u8), (u8, u8, u8)) {
+    let instance = Theme::get_instance();
+    let theme = instance.lock().unwrap();
+    let bg = parse_hex(theme.color("main_bg"))
+        .or(theme.background)
+        .unwrap_or((0, 0, 0));
+    let fg = parse_hex(theme.color("main_fg")).unwrap_or(match bg {
+        bg if is_light(bg) => (0, 0, 0),
+        _ => (204, 204, 204),
+    });
+    (fg, bg)
+}
+
+// 独立的 HTML 页面，每段样式相同的文本为一个 span
+pub fn html(frame: &Frame) -> String {
+    let page = page_colors();
+    let ((fr, fg, fb), (br, bg, bb)) = page;
+    let mut body = String::new();
+    for line in frame.runs() {
+        for (style, text) in line {
+            match Style::parse(style).css(page) {
+                css if css.is_empty() => body += &escape(&text),
+                css => body += &format!("<span style=\"{}\">{}</span>", css, escape(&text)),
+            }
+        }
+        body.push('\n');
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>btop-rs</title>\n\
+        <style>body{{margin:0;background:#{:02x}{:02x}{:02x}}}\
+        pre{{margin:0;padding:1em;color:#{:02x}{:02x}{:02x};\
+        font-family:\"DejaVu Sans Mono\",Menlo,Consolas,monospace;line-height:1.2}}</style>\n\
+        </head>\n<body>\n<pre>\n{}</pre>\n</body>\n</html>\n",
+        br, bg, bb, fr, fg, fb, body
+    )
+}
+
+pub fn render(frame: &Frame, format: ScreenshotFormat) -> String {
+    match format {
+        ScreenshotFormat::Ansi => frame.ansi(),
+        ScreenshotFormat::Html => html(frame),
+    }
+}
+
+pub fn write(frame: &Frame, path: &Path, format: ScreenshotFormat) -> io::Result<()> {
+    fs::write(path, render(frame, format))?;
+    info!("screenshot written to {:?}", path);
+    Ok(())
+}
+
+// 界面中截图时的文件名，保存在配置目录中，没有配置目录时保存在当前目录
+pub fn default_path(config: &Config) -> PathBuf {
+    let format = ScreenshotFormat::from_name(&config.get_string("screenshot_format"))
+        .unwrap_or(ScreenshotFormat::Html);
+    let name = format!("btop-rs-{}.{}", time_s(), format.extension());
+    match config.get_dir() {
+        dir if dir.as_os_str().is_empty() => PathBuf::from(name),
+        dir => dir.join(name),
+    }
+}
+
+// --screenshot：采集两次后按当前终端尺寸绘制一帧写入 path，格式由扩展名决定
+pub fn run(path: &Path) -> io::Result<()> {
+    {
+        let instance = Config::get_instance();
+        let config = instance.lock().unwrap();
+        Theme::get_instance().lock().unwrap().update(&config);
+    }
+    let (width, height) = {
+        let instance = Term::get_instance();
+        let mut term = instance.lock().unwrap();
+        term.refresh();
+        match term.get_size() {
+            (0, _) | (_, 0) => FALLBACK_SIZE,
+            size => size,
+        }
+    };
+
+    let mut runner = Runner::new();
+    runner.collect();
+    thread::sleep(Duration::from_millis(SAMPLE_MS));
+    runner.collect();
+
+    let frame = screen::snapshot(&runner, width, height);
+    write(&frame, path, ScreenshotFormat::from_path(path))
+}
@@ -0,0 +1,84 @@
+use std::{thread, time::Duration};
+
+use super::{csv, json};
+use crate::{
+    runner::Runner,
+    shared::proc::{ProcInfo, ProcSort},
+};
+
+// cpu 占用按两次采集之间的差值计算，中间等待的时间
+const SAMPLE_MS: u64 = 1000;
+
+const CSV_HEADER: &str = "pid,ppid,name,cmd,user,state,threads,nice,mem,cpu,io_read,io_write";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum TopFormat {
+    Json,
+    Csv,
+}
+
+impl TopFormat {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "json" => Some(TopFormat::Json),
+            "csv" => Some(TopFormat::Csv),
+            _ => None,
+        }
+    }
+}
+
+// --top 的查询参数，排序和格式可以省略
+pub struct TopQuery {
+    pub count: usize,
+    pub sort: ProcSort,
+    pub format: TopFormat,
+}
+
+// 没有权限读取 I/O 的进程对应的两列留空
+fn csv_row(proc: &ProcInfo) -> String {
+    let (read, write) = match proc.io_rate {
+        Some((read, write)) => (read.to_string(), write.to_string()),
+        None => (String::new(), String::new()),
+    };
+    format!(
+        "{},{},{},{},{},{},{},{},{},{:.2},{},{}",
+        proc.pid,
+        proc.ppid,
+        csv::escape(&proc.name),
+        csv::escape(&proc.cmd),
+        csv::escape(&proc.user),
+        proc.state,
+        proc.threads,
+        proc.nice,
+        proc.mem,
+        proc.cpu_p,
+        read,
+        write
+    )
+}
+
+pub fn format(procs: &[ProcInfo], format: TopFormat) -> String {
+    match format {
+        TopFormat::Json => {
+            let procs: Vec<String> = procs.iter().map(json::proc_json).collect();
+            format!("[{}]", procs.join(","))
+        }
+        TopFormat::Csv => std::iter::once(CSV_HEADER.to_owned())
+            .chain(procs.iter().map(csv_row))
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+// 采集两次进程信息，按指定字段排序后输出前 count 个进程
+pub fn run(query: &TopQuery) {
+    let mut runner = Runner::new();
+    runner.collect();
+    thread::sleep(Duration::from_millis(SAMPLE_MS));
+    runner.collect();
+
+    let mut procs = std::mem::take(&mut runner.procs);
+    query.sort.sort(&mut procs);
+    procs.truncate(query.count);
+    println!("{}", format(&procs, query.format));
+}
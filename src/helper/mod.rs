@@ -0,0 +1,128 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    path::PathBuf,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{Arc, Mutex, OnceLock},
+};
+
+use log::{info, warn};
+
+use crate::config::config::Config;
+
+// 与 btop-rs-helper 通信的客户端。主程序自身不需要任何特权，
+// 直接读取失败时才把白名单内的文件交给辅助程序读取
+pub struct Helper {
+    child: Option<(Child, ChildStdin, BufReader<ChildStdout>)>,
+    // 启动或通信失败后不再重试，避免每次采集都启动新进程
+    failed: bool,
+}
+
+impl Helper {
+    fn new() -> Self {
+        Helper {
+            child: None,
+            failed: false,
+        }
+    }
+
+    pub fn get_instance() -> Arc<Mutex<Helper>> {
+        static INSTANCE: OnceLock<Arc<Mutex<Helper>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(Helper::new())))
+            .clone()
+    }
+
+    // helper_path 为空时使用与主程序相同目录下的 btop-rs-helper
+    fn path() -> Option<PathBuf> {
+        let instance = Config::get_instance();
+        let config = instance.lock().unwrap();
+        if !config.get_bool("use_helper") {
+            return None;
+        }
+        match config.get_string("helper_path") {
+            path if path.is_empty() => std::env::current_exe()
+                .ok()
+                .map(|exe| exe.with_file_name("btop-rs-helper")),
+            path => Some(PathBuf::from(path)),
+        }
+    }
+
+    fn start(&mut self) -> io::Result<()> {
+        let path = Helper::path().ok_or(io::ErrorKind::PermissionDenied)?;
+        let mut child = Command::new(&path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        };
+        info!("started privileged helper {:?}", path);
+        self.child = Some((child, stdin, BufReader::new(stdout)));
+        Ok(())
+    }
+
+    fn request(&mut self, path: &str) -> io::Result<Vec<u8>> {
+        let Some((_, stdin, stdout)) = self.child.as_mut() else {
+            return Err(io::ErrorKind::NotConnected.into());
+        };
+        writeln!(stdin, "read {}", path)?;
+        stdin.flush()?;
+
+        let mut line = String::new();
+        stdout.read_line(&mut line)?;
+        match line.trim_end().split_once(' ') {
+            Some(("ok", len)) => {
+                let len = len.parse().map_err(|_| io::ErrorKind::InvalidData)?;
+                let mut data = vec![0; len];
+                stdout.read_exact(&mut data)?;
+                Ok(data)
+            }
+            // 辅助程序拒绝或读取失败，通信本身正常
+            Some(("err", reason)) => Err(io::Error::other(reason.to_owned())),
+            _ => Err(io::ErrorKind::InvalidData.into()),
+        }
+    }
+
+    pub fn read(&mut self, path: &str) -> io::Result<Vec<u8>> {
+        if self.failed {
+            return Err(io::ErrorKind::PermissionDenied.into());
+        }
+        if self.child.is_none() {
+            if let Err(err) = self.start() {
+                if err.kind() != io::ErrorKind::PermissionDenied {
+                    warn!("could not start privileged helper: {}", err);
+                }
+                self.failed = true;
+                return Err(err);
+            }
+        }
+        let result = self.request(path);
+        if let Err(err) = &result {
+            if err.kind() != io::ErrorKind::Other {
+                warn!("privileged helper stopped responding: {}", err);
+                self.stop();
+                self.failed = true;
+            }
+        }
+        result
+    }
+
+    pub fn stop(&mut self) {
+        if let Some((mut child, stdin, _)) = self.child.take() {
+            // 关闭 stdin 后辅助程序自行退出
+            drop(stdin);
+            let _ = child.wait();
+        }
+    }
+}
+
+// 先直接读取，没有权限时交给辅助程序
+pub fn read(path: &str) -> io::Result<Vec<u8>> {
+    match std::fs::read(path) {
+        Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+            Helper::get_instance().lock().unwrap().read(path)
+        }
+        result => result,
+    }
+}
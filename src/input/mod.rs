@@ -0,0 +1,397 @@
+use std::{
+    io,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use libc::{pollfd, POLLIN, STDIN_FILENO};
+use log::{info, warn};
+
+pub mod record;
+
+use record::{KeyRecorder, KeyReplay};
+
+use crate::{
+    config::config::Config,
+    draw::clock,
+    menu::{self, Menu, MenuKind},
+    runner::Runner,
+    screen::{self, Screen},
+    shared::proc::ProcOrigin,
+};
+
+// shift+方向键每次移动 box 边框的百分比
+const LAYOUT_STEP: i32 = 5;
+
+// "+" 和 "-" 每次调整 update_ms 的毫秒数
+const UPDATE_STEP: i32 = 100;
+
+// 数字键 1-4 依次显示或隐藏的 box
+const TOGGLE_BOXES: &[&str] = &["cpu", "mem", "net", "proc"];
+
+// 转义序列与按键名称的对应关系
+const ESCAPES: &[(&str, &str)] = &[
+    ("\x1b", "escape"),
+    ("\x1b[A", "up"),
+    ("\x1bOA", "up"),
+    ("\x1b[B", "down"),
+    ("\x1bOB", "down"),
+    ("\x1b[C", "right"),
+    ("\x1bOC", "right"),
+    ("\x1b[D", "left"),
+    ("\x1bOD", "left"),
+    ("\x1b[2~", "insert"),
+    ("\x1b[3~", "delete"),
+    ("\x1b[H", "home"),
+    ("\x1bOH", "home"),
+    ("\x1b[1~", "home"),
+    ("\x1b[F", "end"),
+    ("\x1bOF", "end"),
+    ("\x1b[4~", "end"),
+    ("\x1b[5~", "page_up"),
+    ("\x1b[6~", "page_down"),
+    ("\x1b[Z", "shift_tab"),
+    ("\x1b[1;2A", "shift_up"),
+    ("\x1b[1;2B", "shift_down"),
+    ("\x1b[1;2C", "shift_right"),
+    ("\x1b[1;2D", "shift_left"),
+    ("\x1bOP", "f1"),
+    ("\x1bOQ", "f2"),
+    ("\x1bOR", "f3"),
+    ("\x1bOS", "f4"),
+    ("\x1b[15~", "f5"),
+    ("\x1b[17~", "f6"),
+    ("\x1b[18~", "f7"),
+    ("\x1b[19~", "f8"),
+    ("\x1b[20~", "f9"),
+    ("\x1b[21~", "f10"),
+    ("\x1b[23~", "f11"),
+    ("\x1b[24~", "f12"),
+    ("\n", "enter"),
+    ("\r", "enter"),
+    (" ", "space"),
+    ("\t", "tab"),
+    ("\x7f", "backspace"),
+    ("\x08", "backspace"),
+];
+
+pub fn translate(raw: &str) -> String {
+    if let Some(event) = raw.strip_prefix("\x1b[<") {
+        return mouse(event);
+    }
+    match ESCAPES.iter().find(|(seq, _)| *seq == raw) {
+        Some((_, name)) => name.to_string(),
+        None => raw.to_owned(),
+    }
+}
+
+// SGR 格式的鼠标事件 "按键;列;行" 加上 M（按下）或 m（松开），
+// 左键按下转换为 "click:列:行"，其余事件统一为 "mouse"。
+// 按下和松开可能在同一次读取中到达，只看第一个事件
+fn mouse(event: &str) -> String {
+    let Some(end) = event.find(['M', 'm']) else {
+        return "mouse".to_owned();
+    };
+    if &event[end..end + 1] == "m" {
+        return "mouse".to_owned();
+    }
+    match event[..end].split(';').collect::<Vec<_>>()[..] {
+        ["0", x, y] => format!("click:{}:{}", x, y),
+        _ => "mouse".to_owned(),
+    }
+}
+
+// 从 "click:列:行" 中取出终端坐标，坐标从 1 开始
+pub fn parse_click(key: &str) -> Option<(u16, u16)> {
+    let (x, y) = key.strip_prefix("click:")?.split_once(':')?;
+    Some((x.parse().ok()?, y.parse().ok()?))
+}
+
+// 等待标准输入可读，超时返回 false
+pub fn poll(timeout: Duration) -> bool {
+    let mut fds = pollfd {
+        fd: STDIN_FILENO,
+        events: POLLIN,
+        revents: 0,
+    };
+    let ret = unsafe {
+        libc::poll(
+            &mut fds,
+            1,
+            timeout.as_millis().min(i32::MAX as u128) as i32,
+        )
+    };
+    ret > 0 && fds.revents & POLLIN != 0
+}
+
+pub fn read_raw() -> String {
+    let mut buf = [0u8; 64];
+    let len = unsafe {
+        libc::read(
+            STDIN_FILENO,
+            buf.as_mut_ptr() as *mut libc::c_void,
+            buf.len(),
+        )
+    };
+    if len <= 0 {
+        return String::new();
+    }
+    String::from_utf8_lossy(&buf[..len as usize]).into_owned()
+}
+
+pub struct Input {
+    recorder: Option<KeyRecorder>,
+    replay: Option<KeyReplay>,
+    stdin_open: bool,
+}
+
+impl Default for Input {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Input {
+    pub fn new() -> Self {
+        Input {
+            recorder: None,
+            replay: None,
+            stdin_open: true,
+        }
+    }
+
+    pub fn record_to(&mut self, path: &Path) -> io::Result<()> {
+        self.recorder = Some(KeyRecorder::create(path)?);
+        info!("recording keys to {:?}", path);
+        Ok(())
+    }
+
+    pub fn replay_from(&mut self, path: &Path) -> io::Result<()> {
+        self.replay = Some(KeyReplay::load(path)?);
+        info!("replaying keys from {:?}", path);
+        Ok(())
+    }
+
+    // 在 timeout 内等待一个按键，脚本中的按键按其时间戳注入
+    pub fn wait(&mut self, timeout: Duration) -> Option<String> {
+        let mut timeout = timeout;
+        if let Some(replay) = self.replay.as_mut() {
+            match replay.time_to_next() {
+                Some(wait) if wait.is_zero() => return replay.next_key(),
+                Some(wait) => timeout = timeout.min(wait),
+                None => self.replay = None,
+            }
+        }
+
+        if !self.stdin_open {
+            std::thread::sleep(timeout);
+        } else if poll(timeout) {
+            let raw = read_raw();
+            if raw.is_empty() {
+                // 标准输入已关闭（例如重定向自 /dev/null），之后只等待
+                self.stdin_open = false;
+                return None;
+            }
+            let key = translate(&raw);
+            if let Some(recorder) = self.recorder.as_mut() {
+                if let Err(err) = recorder.write(&key) {
+                    warn!("failed to record key: {}", err);
+                    self.recorder = None;
+                }
+            }
+            return Some(key);
+        }
+
+        match self.replay.as_mut() {
+            Some(replay) if replay.time_to_next().is_some_and(|w| w.is_zero()) => replay.next_key(),
+            _ => None,
+        }
+    }
+
+    // 在截止时间之前持续处理按键
+    pub fn wait_until(&mut self, deadline: Instant, runner: &Runner) {
+        loop {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            // 至少每秒唤醒一次，让时钟可以走动
+            match self.wait((deadline - now).min(clock::until_next_second())) {
+                Some(key) => process(&key, runner),
+                None => screen::tick(runner),
+            }
+            screen::render(runner);
+            let global = crate::Global::get_instance();
+            let global = global.lock().unwrap();
+            // 修改了过滤条件等需要立即重新采集时提前结束等待
+            if global.get_quit_state()
+                || crate::Global::get_hangup()
+                || crate::Global::get_terminate()
+                || global.take_refresh()
+            {
+                break;
+            }
+        }
+    }
+}
+
+pub fn process(key: &str, runner: &Runner) {
+    info!("key: {:?}", key);
+    let instance = Menu::get_instance();
+    let mut menu = instance.lock().unwrap();
+    if menu.is_active() {
+        if menu.process(key) {
+            drop(menu);
+            crate::quit(runner);
+        }
+        return;
+    }
+    if Screen::get_instance().lock().unwrap().dismiss_banner() {
+        return;
+    }
+    // 输入过滤条件时所有按键都作为文本
+    if Screen::get_instance().lock().unwrap().is_filtering() {
+        drop(menu);
+        Screen::get_instance().lock().unwrap().filter_input(key);
+        return;
+    }
+
+    match key {
+        "q" => {
+            drop(menu);
+            crate::quit(runner);
+        }
+        "escape" | "m" => menu.show(MenuKind::Main),
+        "B" => {
+            menu.show(MenuKind::Battery);
+            drop(menu);
+            menu::update(runner);
+        }
+        // 隐藏的色彩测试界面，不在帮助中列出
+        "f12" => menu.show(MenuKind::ColorTest),
+        "C" => {
+            let instance = Config::get_instance();
+            let mut config = instance.lock().unwrap();
+            let origin = ProcOrigin::from_name(&config.get_string("proc_origin"))
+                .unwrap_or(ProcOrigin::All)
+                .next();
+            config.set_string("proc_origin", origin.name());
+            info!("proc origin filter: {}", origin.name());
+        }
+        "F" => {
+            let instance = Config::get_instance();
+            let mut config = instance.lock().unwrap();
+            let follow = !config.get_bool("proc_follow");
+            config.set_bool("proc_follow", follow);
+            info!("proc follow: {}", follow);
+            drop(config);
+            Screen::get_instance().lock().unwrap().redraw = true;
+        }
+        // 按 100ms 调整采集间隔，范围与 update_ms 的校验一致，退出时随配置一起保存
+        "+" | "-" => {
+            let instance = Config::get_instance();
+            let mut config = instance.lock().unwrap();
+            let step = if key == "+" {
+                UPDATE_STEP
+            } else {
+                -UPDATE_STEP
+            };
+            let update_ms = (config.get_int("update_ms") + step).clamp(100, 86400000);
+            let _ = config.set_int("update_ms", &update_ms.to_string());
+            info!("update_ms: {}", update_ms);
+            drop(config);
+            Screen::get_instance().lock().unwrap().redraw = true;
+        }
+        // 暂停或恢复采集，恢复时立即结束等待
+        "p" => {
+            let instance = crate::Global::get_instance();
+            let mut global = instance.lock().unwrap();
+            let paused = !global.get_paused();
+            global.set_paused(paused);
+            global.set_refresh();
+            info!("paused: {}", paused);
+            drop(global);
+            Screen::get_instance().lock().unwrap().redraw = true;
+        }
+        // 切换 mem box 中的磁盘列，关闭时不再采集磁盘，打开时立即采集一次
+        "d" => {
+            let instance = Config::get_instance();
+            let mut config = instance.lock().unwrap();
+            let show_disks = !config.get_bool("show_disks");
+            config.set_bool("show_disks", show_disks);
+            info!("show_disks: {}", show_disks);
+            drop(config);
+            crate::Global::get_instance().lock().unwrap().set_refresh();
+            Screen::get_instance().lock().unwrap().redraw = true;
+        }
+        // 显示或隐藏 box，布局在下次绘制时按新的 box 重新计算，退出时随配置一起保存
+        "1" | "2" | "3" | "4" => {
+            let name = TOGGLE_BOXES[key.parse::<usize>().unwrap() - 1];
+            let instance = Config::get_instance();
+            let mut config = instance.lock().unwrap();
+            if config.toggle_box(name) {
+                info!("shown_boxes: {}", config.get_string("shown_boxes"));
+                drop(config);
+                Screen::get_instance().lock().unwrap().redraw = true;
+            }
+        }
+        // 保存当前画面，格式由 screenshot_format 决定
+        "S" => {
+            drop(menu);
+            Screen::get_instance().lock().unwrap().screenshot();
+        }
+        // 图形每一步平均的采样数按 1、2、4、8 切换，历史在下次采集时扩容
+        "[" | "]" => {
+            let instance = Config::get_instance();
+            let mut config = instance.lock().unwrap();
+            let range = config.get_int("graph_range");
+            let range = match key {
+                "]" => range * 2,
+                _ => range / 2,
+            }
+            .clamp(1, 8);
+            let _ = config.set_int("graph_range", &range.to_string());
+            info!("graph_range: {}", range);
+            drop(config);
+            Screen::get_instance().lock().unwrap().redraw = true;
+        }
+        // 移动 box 之间的边框：上下调整 cpu box 的高度，左右调整 proc box 的宽度，
+        // 方向按边框在屏幕上移动的方向，与 cpu_bottom/proc_left 的位置有关
+        "shift_up" | "shift_down" | "shift_left" | "shift_right" => {
+            let instance = Config::get_instance();
+            let mut config = instance.lock().unwrap();
+            let (key, grow) = match key {
+                "shift_up" => ("cpu_box_height_pct", config.get_bool("cpu_bottom")),
+                "shift_down" => ("cpu_box_height_pct", !config.get_bool("cpu_bottom")),
+                "shift_left" => ("proc_box_width_pct", !config.get_bool("proc_left")),
+                _ => ("proc_box_width_pct", config.get_bool("proc_left")),
+            };
+            let step = if grow { LAYOUT_STEP } else { -LAYOUT_STEP };
+            let pct = (config.get_int(key) + step).clamp(10, 90);
+            let _ = config.set_int(key, &pct.to_string());
+            info!("{}: {}", key, pct);
+            drop(config);
+            Screen::get_instance().lock().unwrap().redraw = true;
+        }
+        // 向标记的进程（没有标记时为选中的进程）发送信号
+        "f9" => {
+            let targets = Screen::get_instance()
+                .lock()
+                .unwrap()
+                .signal_targets(runner);
+            if !targets.is_empty() {
+                menu.show_signals(targets);
+            }
+        }
+        "tab" | "shift_tab" => Screen::get_instance()
+            .lock()
+            .unwrap()
+            .cycle_focus(key == "tab"),
+        _ => {
+            drop(menu);
+            Screen::get_instance().lock().unwrap().process(key, runner);
+            // 在进程列表中按回车后立即打开详细视图
+            menu::update(runner);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use std::{
+    collections::VecDeque,
+    fs::{self, File},
+    io::{self, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use crate::{input::translate, ssplit};
+
+// 录制格式：每行 "<相对启动的毫秒数> <按键名>"，以 '#' 开头的行为注释
+pub struct KeyRecorder {
+    file: File,
+    start: Instant,
+}
+
+impl KeyRecorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut file = File::create(path)?;
+        writeln!(file, "# btop-rs key recording: <milliseconds> <key>")?;
+        Ok(KeyRecorder {
+            file,
+            start: Instant::now(),
+        })
+    }
+
+    pub fn write(&mut self, key: &str) -> io::Result<()> {
+        writeln!(
+            self.file,
+            "{} {}",
+            self.start.elapsed().as_millis(),
+            key.escape_default()
+        )
+    }
+}
+
+pub struct KeyReplay {
+    events: VecDeque<(Duration, String)>,
+    start: Instant,
+}
+
+impl KeyReplay {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let mut events = VecDeque::new();
+        for (nr, line) in fs::read_to_string(path)?.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parts = ssplit(line, ' ');
+            match (parts.first().map(|ms| ms.parse::<u64>()), parts.get(1)) {
+                (Some(Ok(ms)), Some(key)) => {
+                    events.push_back((Duration::from_millis(ms), unescape(key)))
+                }
+                _ => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("malformed key event on line {}: {}", nr + 1, line),
+                    ))
+                }
+            }
+        }
+
+        Ok(KeyReplay {
+            events,
+            start: Instant::now(),
+        })
+    }
+
+    // 距离下一个按键的时间，没有剩余按键时返回 None
+    pub fn time_to_next(&self) -> Option<Duration> {
+        self.events
+            .front()
+            .map(|(at, _)| at.saturating_sub(self.start.elapsed()))
+    }
+
+    pub fn next_key(&mut self) -> Option<String> {
+        self.events.pop_front().map(|(_, key)| key)
+    }
+}
+
+// 还原录制时 escape_default 转义的字符，未命名的转义序列再交给 translate
+fn unescape(key: &str) -> String {
+    let mut raw = String::new();
+    let mut chars = key.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            raw.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('t') => raw.push('\t'),
+            Some('n') => raw.push('\n'),
+            Some('r') => raw.push('\r'),
+            Some('u') => {
+                let hex: String = chars
+                    .by_ref()
+                    .skip_while(|&c| c == '{')
+                    .take_while(|&c| c != '}')
+                    .collect();
+                if let Some(c) = u32::from_str_radix(&hex, 16).ok().and_then(char::from_u32) {
+                    raw.push(c);
+                }
+            }
+            Some(c) => raw.push(c),
+            None => raw.push('\\'),
+        }
+    }
+    translate(&raw)
+}
@@ -5,52 +5,113 @@ use std::{
     path::{Path, PathBuf},
     process::exit,
     sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
 };
 
-use config::config::Config;
-use libc::{SIGCONT, SIGINT, SIGTSTP, SIGWINCH};
+use config::config::{Config, ConfigTransaction};
+use libc::{SIGCONT, SIGHUP, SIGINT, SIGTERM, SIGTSTP, SIGUSR1, SIGWINCH, SIG_IGN};
 use log::{info, warn};
 use shared::global::*;
 use util::*;
 
-use crate::{config::theme::Theme, logger::Logger};
+use crate::{
+    config::{
+        import::{self, Import},
+        integrity,
+        theme::Theme,
+    },
+    control::ControlSocket,
+    export::{csv::CsvLogger, prometheus::PrometheusExporter},
+    input::Input,
+    logger::Logger,
+    runner::Runner,
+    shared::host::HostInfo,
+    term::{caps::ColorDepth, Term},
+    util::args::{Action, Args, USAGE},
+};
 
+pub mod collect;
 pub mod config;
+pub mod control;
+pub mod draw;
+pub mod export;
+pub mod helper;
 pub mod include;
+pub mod input;
+pub mod menu;
+pub mod runner;
+pub mod screen;
 pub mod shared;
+pub mod term;
 pub mod util;
 
-fn argument_parser(args: Vec<String>) {
+#[cfg(feature = "alloc-count")]
+#[global_allocator]
+static ALLOC: util::alloc::CountingAlloc = util::alloc::CountingAlloc;
+
+// 暂停结束后重新建立采集基准，经过这段时间再采集
+const RESUME_MS: u64 = 500;
+
+// 解析出的参数写入 Global，-h、-v、--benchmark 和 --top 执行后直接退出
+fn apply_args(args: Args) {
     let instance = Global::get_instance();
-    for arg in args.iter().skip(1) {
-        if is_in(arg, &["-h".to_owned(), "--help".to_owned()]) {
+    match args.action {
+        Some(Action::Help) => {
+            println!("{}", USAGE);
+            exit(0);
+        }
+        Some(Action::Version) => {
             println!(
-          "usage: btop [-h] [-v] [-/+t] [--utf-foce] [--debug]\n\n\
-          optional arguments:\n\
-          \t-h, --help            show this help message and exit\n\
-          \t-v, --version         show version info and exit\n\
-          \t-lc, --low-color      disable truecolor, converts 24-bit colors to 256-color\n\
-          \t-t, --tty_on          force (ON) tty mode, max 16 colors and tty friendly graph symbols\n\
-          \t+t, --tty_off         force (OFF) tty mode\n\
-          \t-p --preset <id>      start with preset, integer value between 0-9\n\
-          \t--utf-foce            force start even if no UTF-8 locale was detected\n\
-          \t--debug               start in DEBUG mode: shows microsecond timer for information collect\n\
-          \t                      and screen draw functions and sets loglevel to DEBUG\n
-          "
-        );
+                "btop-rs version: {}",
+                instance.lock().unwrap().get_version()
+            );
             exit(0);
-        } else if is_in(arg, &["-v".to_owned(), "--version".to_owned()]) {
-            {
-                let v_instance = instance.lock().unwrap();
-                println!("btop-rs version: {}", v_instance.get_version());
-            }
-        } else if is_in(arg, &["-lc".to_owned(), "--low-color".to_owned()]) {
-            {
-                let mut v_instance = instance.lock().unwrap();
-                v_instance.set_arglc();
-            }
         }
-        // TODO
+        Some(Action::Benchmark(iterations)) => {
+            runner::bench::run(iterations);
+            exit(0);
+        }
+        Some(Action::Top(query)) => {
+            export::top::run(&query);
+            exit(0);
+        }
+        None => {}
+    }
+
+    let mut global = instance.lock().unwrap();
+    if args.low_color {
+        global.set_arglc();
+    }
+    if args.utf_force {
+        global.set_utf_force();
+    }
+    if args.daemon {
+        global.set_daemon();
+    }
+    if let Some(preset) = args.preset {
+        global.set_preset(preset);
+    }
+    if let Some(path) = args.keys_record {
+        global.set_keys_record(path);
+    }
+    if let Some(path) = args.keys_from {
+        global.set_keys_from(path);
+    }
+    if let Some(path) = args.screenshot {
+        global.set_screenshot(path);
+    }
+    if let Some(path) = args.config_file {
+        global.set_config_file(path);
+    }
+    if let Some(path) = args.config_dir {
+        global.set_config_dir(path);
+    }
+    if let Some(name) = args.profile {
+        global.set_profile(&name);
+    }
+    for (key, value) in &args.overrides {
+        global.add_override(key, value);
     }
 }
 
@@ -66,8 +127,28 @@ fn clean_quit(sig: i32) {
         return;
     }
     g_instance.set_quit_state();
+    if !g_instance.get_control_socket().as_os_str().is_empty() {
+        let _ = fs::remove_file(g_instance.get_control_socket());
+    }
+    drop(g_instance);
+    // 信号处理函数中调用时配置可能正被主循环持有，拿不到锁就放弃保存
+    if let Ok(mut config) = Config::get_instance().try_lock() {
+        if let Err(err) = config.write() {
+            warn!("failed to write config: {}", err);
+        }
+    }
+    Term::get_instance().lock().unwrap().restore();
     // TODO
-    exit(sig);
+    // sig 为 -1 时由 atexit 调用，此时不能再次调用 exit
+    if sig != -1 {
+        exit(sig);
+    }
+}
+
+// 从主循环或按键退出时 Runner 可用，先保存图形历史再退出
+pub fn quit(runner: &Runner) {
+    runner.save_history();
+    clean_quit(0);
 }
 
 fn _sleep() {}
@@ -76,12 +157,34 @@ fn _resume() {}
 
 fn term_resize() {}
 
+// $XDG_STATE_HOME 等环境变量下的 btop-rs 目录，变量未设置或不是绝对路径时使用 $HOME 下的默认位置
+fn xdg_dir(env: &str, fallback: &str) -> Option<PathBuf> {
+    let base = match env::var_os(env).map(PathBuf::from) {
+        Some(base) if base.is_absolute() => base,
+        _ => PathBuf::from(env::var_os("HOME")?).join(fallback),
+    };
+    Some(base.join("btop-rs"))
+}
+
+// 把旧版本放在配置目录中的文件移动到新位置，新位置已有同名文件时保留旧文件
+fn migrate(old: &Path, new: &Path) -> Result<(), String> {
+    if old == new || !old.exists() || new.exists() {
+        return Ok(());
+    }
+    // 跨文件系统时 rename 会失败，改为复制后删除
+    fs::rename(old, new)
+        .or_else(|_| fs::copy(old, new).and_then(|_| fs::remove_file(old)))
+        .map_err(|err| format!("Could not move {:?} to {:?}: {}", old, new, err))?;
+    info!("migrated {:?} to {:?}", old, new);
+    Ok(())
+}
+
 extern "C" fn signal_handler(signal: c_int) {
     match signal {
-        SIGINT => {
-            println!("SIGNAL SIGINT");
-            clean_quit(0);
-        }
+        // 信号可能在主循环或采集线程持有锁时到达，这里只设置标志，由主循环保存图形历史后退出
+        SIGINT | SIGTERM => Global::set_terminate(),
+        SIGUSR1 => Global::set_reopen(),
+        SIGHUP => Global::set_hangup(),
         SIGTSTP => {
             _sleep();
             println!("SIGNAL SIGTSTP");
@@ -93,7 +196,8 @@ extern "C" fn signal_handler(signal: c_int) {
 }
 
 fn main() {
-    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("trace")).init();
+    let startup = Instant::now();
+    logger::init();
 
     let g_instance: Arc<Mutex<Global>> = Global::get_instance();
     let c_instance: Arc<Mutex<Config>> = Config::get_instance();
@@ -111,37 +215,64 @@ fn main() {
     }
 
     let args: Vec<String> = std::env::args().collect();
-
-    if args.len() > 1 {
-        argument_parser(args);
+    match Args::parse(&args) {
+        Ok(args) => apply_args(args),
+        Err(err) => {
+            eprintln!(
+                "btop-rs: {}\nSee btop-rs --help for the accepted arguments.",
+                err
+            );
+            exit(1);
+        }
     }
 
     unsafe {
         libc::atexit(_exit_handler);
 
-        libc::signal(SIGINT, signal_handler as usize);
-        libc::signal(SIGTSTP, signal_handler as usize);
-        libc::signal(SIGCONT, signal_handler as usize);
-        libc::signal(SIGWINCH, signal_handler as usize);
-    }
-
-    // 设置启动配置文件、日志和主题路径
-    for env in ["XDG_CONFIG_HOME", "HOME"] {
-        if let Ok(env_val) = env::var(env) {
-            if let Ok(mut perms) = fs::metadata(&env_val).map(|md| md.permissions()) {
-                perms.set_readonly(false);
-                if !perms.readonly() {
-                    let dir = if env == "HOME" {
-                        Path::new(&env_val).join(".config/btop-rs")
-                    } else {
-                        Path::new(&env_val).join("btop-rs")
-                    };
-                    {
+        libc::signal(SIGINT, signal_handler as *const () as usize);
+        libc::signal(SIGTSTP, signal_handler as *const () as usize);
+        libc::signal(SIGCONT, signal_handler as *const () as usize);
+        libc::signal(SIGWINCH, signal_handler as *const () as usize);
+        libc::signal(SIGTERM, signal_handler as *const () as usize);
+        libc::signal(SIGUSR1, signal_handler as *const () as usize);
+        // 在 nohup 下启动时保持忽略 SIGHUP
+        if libc::signal(SIGHUP, signal_handler as *const () as usize) == SIG_IGN {
+            libc::signal(SIGHUP, SIG_IGN);
+        }
+    }
+
+    // 启动阶段收集的路径警告，最终统一放入警告浮层
+    let mut init_warnings: Vec<String> = Vec::new();
+
+    // 设置启动配置文件、日志和主题路径，--config-dir 优先于环境变量
+    let (arg_config_file, arg_config_dir) = {
+        let global = g_instance.lock().unwrap();
+        (
+            global.get_config_file().clone(),
+            global.get_config_dir().clone(),
+        )
+    };
+    if !arg_config_dir.as_os_str().is_empty() {
+        c_instance.lock().unwrap().set_dir(arg_config_dir.clone());
+    } else {
+        for env in ["XDG_CONFIG_HOME", "HOME"] {
+            if let Ok(env_val) = env::var(env) {
+                let base = Path::new(&env_val);
+                match integrity::check_dir(base, true) {
+                    Ok(_) => {
+                        let dir = if env == "HOME" {
+                            base.join(".config/btop-rs")
+                        } else {
+                            base.join("btop-rs")
+                        };
                         let mut config = c_instance.lock().unwrap();
                         config.set_dir(dir);
                         info!("set config file dir path: {:?}", config.get_dir());
+                        break;
+                    }
+                    Err(issue) => {
+                        init_warnings.push(integrity::describe(&format!("${}", env), base, &issue))
                     }
-                    break;
                 }
             }
         }
@@ -152,23 +283,86 @@ fn main() {
         let mut logger = l_instance.lock().unwrap();
         let mut theme = t_instance.lock().unwrap();
         if config.get_dir().as_os_str().is_empty() {
-            warn!("WARNING: Could not get path user HOME folder.");
-            warn!("Make sure $XDG_CONFIG_HOME or $HOME environment variables is correctly set to fix this.");
+            init_warnings.push(
+                "Could not get path user HOME folder, make sure $XDG_CONFIG_HOME or $HOME \
+                environment variables is correctly set to fix this."
+                    .to_owned(),
+            );
+        } else if !config.get_dir().is_dir() && fs::create_dir_all(config.get_dir()).is_err() {
+            init_warnings.push(format!(
+                "Could not create config directory {:?}, logging and config saving disabled.",
+                config.get_dir()
+            ));
+            config.set_dir(PathBuf::new());
         } else {
-            if !config.get_dir().is_dir() && !fs::create_dir(config.get_dir()).is_ok() {
-                warn!("WARNING: Could not create or access btop config directory. Logging and config saving disabled.");
-                warn!("Make sure $XDG_CONFIG_HOME or $HOME environment variables is correctly set to fix this.");
-            } else {
-                let config_dir = config.get_dir().clone();
-                config.set_file("btop-rs.conf");
-                logger.set_file(config_dir.join("btop-rs.log"));
-                theme.set_user_dir(config_dir.join("themes"));
-
-                if !theme.get_user_dir().exists() && !fs::create_dir(theme.get_user_dir()).is_ok() {
-                    theme.clear_user_dir();
+            // 只有 btop-rs.toml 时使用 TOML 格式，两者都存在时以 btop-rs.conf 为准
+            let toml = config.get_dir().join("btop-rs.toml");
+            let conf = config.get_dir().join("btop-rs.conf");
+            match toml.is_file() {
+                true if conf.is_file() => {
+                    init_warnings.push(format!(
+                        "Both {:?} and {:?} exist, ignoring {:?}.",
+                        conf, toml, toml
+                    ));
+                    config.set_file(conf);
                 }
+                true => config.set_file(toml),
+                false => config.set_file(conf),
+            }
+        }
+        // 没有可用的配置目录时 --config 仍然有效
+        if !arg_config_file.as_os_str().is_empty() {
+            config.set_file(&arg_config_file);
+        }
+        info!("set config path: {:?}", config.get_file());
+
+        // 日志放在 $XDG_STATE_HOME/btop-rs，用户主题放在 $XDG_DATA_HOME/btop-rs/themes，
+        // --config-dir 时全部放在指定目录中
+        let config_dir = config.get_dir().clone();
+        let (state_dir, data_dir) = match arg_config_dir.as_os_str().is_empty() {
+            true => (
+                xdg_dir("XDG_STATE_HOME", ".local/state"),
+                xdg_dir("XDG_DATA_HOME", ".local/share"),
+            ),
+            false => (Some(config_dir.clone()), Some(config_dir.clone())),
+        };
+
+        match state_dir {
+            Some(dir) if dir.is_dir() || fs::create_dir_all(&dir).is_ok() => {
+                logger.set_file(dir.join("btop-rs.log"));
+                if !config_dir.as_os_str().is_empty() {
+                    if let Err(err) = migrate(&config_dir.join("btop-rs.log"), logger.get_file()) {
+                        init_warnings.push(err);
+                    }
+                }
+            }
+            Some(dir) => init_warnings.push(format!(
+                "Could not create state directory {:?}, logging disabled.",
+                dir
+            )),
+            None => {}
+        }
 
-                info!("set config path: {:?}", config.get_file());
+        if let Some(dir) = data_dir {
+            theme.set_user_dir(dir.join("themes"));
+            if !theme.get_user_dir().is_dir() && fs::create_dir_all(theme.get_user_dir()).is_err() {
+                init_warnings.push(format!(
+                    "Could not create user theme directory {:?}.",
+                    theme.get_user_dir()
+                ));
+                theme.clear_user_dir();
+            } else if !config_dir.as_os_str().is_empty() {
+                let old_dir = config_dir.join("themes");
+                if old_dir != *theme.get_user_dir() {
+                    for entry in fs::read_dir(&old_dir).into_iter().flatten().flatten() {
+                        let new_path = theme.get_user_dir().join(entry.file_name());
+                        if let Err(err) = migrate(&entry.path(), &new_path) {
+                            init_warnings.push(err);
+                        }
+                    }
+                    // 只删除已经移空的旧目录
+                    let _ = fs::remove_dir(&old_dir);
+                }
             }
         }
     }
@@ -184,47 +378,28 @@ fn main() {
     {
         let global = g_instance.lock().unwrap();
         let mut theme = t_instance.lock().unwrap();
-
-        if !global.get_self().as_os_str().is_empty() {
-            match fs::canonicalize(global.get_self().join("../share/btop-rs/themes")) {
-                Ok(canon_path) => {
-                    info!("canno: {:?}", canon_path);
-                    theme.set_theme_dir(canon_path);
-                }
-                Err(err) => {
-                    warn!("Failed to get canonical path: {}", err);
-                }
-            };
-            if let Ok(perms) = fs::metadata(theme.get_theme_dir()) {
-                if !perms.is_dir() || perms.permissions().readonly() {
-                    theme.clear_theme_dir();
-                }
-            };
-        }
+        // 系统主题目录延迟到第一次使用时再查找，查找时需要可执行文件的位置
+        theme.set_exe_dir(global.get_self().clone());
+        info!("user theme dir: {:?}", theme.get_user_dir());
     }
 
     {
-        let mut theme = t_instance.lock().unwrap();
-
-        if theme.get_theme_dir().as_os_str().is_empty() {
-            for theme_path in [
-                "/usr/local/share/btop-rs/themes",
-                "/usr/share/btop-rs/themes",
-            ] {
-                if let Ok(perms) = fs::metadata(theme_path) {
-                    if perms.is_dir() && !perms.permissions().readonly() {
-                        theme.set_theme_dir(PathBuf::from(theme_path));
-                        break;
-                    }
-                }
-            }
-        }
-
-        info!("user theme dir: {:?}", theme.get_user_dir());
-        info!("theme dir: {:?}", theme.get_theme_dir());
+        let mut config = c_instance.lock().unwrap();
+        let mut logger = l_instance.lock().unwrap();
+        let theme = t_instance.lock().unwrap();
+        let check = integrity::check_startup_paths(&integrity::StartupPaths {
+            config_dir: config.get_dir(),
+            config_file: config.get_file(),
+            log_file: logger.get_file(),
+            user_theme_dir: theme.get_user_dir(),
+        });
+        init_warnings.extend(check.warnings);
+        config.set_write_disabled(!check.config_writable);
+        logger.set_file_disabled(!check.log_writable);
     }
 
     let mut load_warnings: Vec<String> = Vec::new();
+    let first_run = !c_instance.lock().unwrap().get_file().exists();
     {
         let mut config = c_instance.lock().unwrap();
         match config.load(&mut load_warnings) {
@@ -232,6 +407,31 @@ fn main() {
             Err(_) => warn!("config load failed"),
         }
 
+        config.init_presets();
+
+        // 命令行参数覆盖配置文件，只在本次运行中生效
+        let mut tx = ConfigTransaction::new();
+        for (key, value) in g_instance.lock().unwrap().get_overrides() {
+            tx.set(key, value);
+        }
+        if !tx.is_empty() {
+            if let Err(err) = config.apply_session(&tx) {
+                eprintln!("{}", err);
+                exit(1);
+            }
+        }
+
+        // -p 选择的预设在读取配置之后应用，预设列表来自配置文件
+        if let Some(preset) = g_instance.lock().unwrap().get_preset() {
+            if !config.apply_preset(preset) {
+                load_warnings.push(format!("Preset {} does not exist in presets!", preset));
+            }
+        }
+
+        if let Some(warning) = l_instance.lock().unwrap().update(&config) {
+            load_warnings.push(warning);
+        }
+
         if config.get_current_boxes().is_empty() {
             let default_v = config.get_boxes("shown_boxes");
             config.check_boxes(&default_v);
@@ -239,10 +439,239 @@ fn main() {
         }
 
         {
-            let v = config.get_bool("truecolor");
+            // color_depth 为 auto 时使用探测结果，truecolor = False、force_tty 和 -lc 只会降低色彩数量
             let global = g_instance.lock().unwrap();
-            config.set_bool("lowcolor", if global.get_arg_lc() { true } else { v });
+            let instance = Term::get_instance();
+            let mut term = instance.lock().unwrap();
+            let mut colors = ColorDepth::from_name(&config.get_string("color_depth"))
+                .unwrap_or(term.get_caps().colors);
+            if global.get_arg_lc() || !config.get_bool("truecolor") {
+                colors = colors.min(ColorDepth::Color256);
+            }
+            if config.get_bool("force_tty") {
+                colors = ColorDepth::Color16;
+            }
+            term.set_colors(colors);
+            info!(
+                "color depth: {} (detected {:?})",
+                colors.name(),
+                term.get_caps()
+            );
+        }
+    }
+
+    {
+        let mut global = g_instance.lock().unwrap();
+        for warning in init_warnings.iter().chain(load_warnings.iter()) {
+            warn!("{}", warning);
+        }
+        global.add_warnings(&init_warnings);
+        global.add_warnings(&load_warnings);
+        screen::Screen::get_instance()
+            .lock()
+            .unwrap()
+            .set_banner(global.get_warnings());
+    }
+
+    let screenshot = g_instance.lock().unwrap().get_screenshot().clone();
+    if !screenshot.as_os_str().is_empty() {
+        // 截图不接管终端，--utf-force 直接作用于字符集的判断
+        if g_instance.lock().unwrap().get_utf_force() {
+            Term::get_instance().lock().unwrap().set_utf8(true);
+        }
+        if let Err(err) = export::screenshot::run(&screenshot) {
+            eprintln!("could not write screenshot {:?}: {}", screenshot, err);
+            exit(1);
+        }
+        exit(0);
+    }
+
+    {
+        // 主机名和用户只在启动时读取，之后由控制套接字按需刷新
+        let instance = HostInfo::get_instance();
+        let host = instance.lock().unwrap();
+        info!("host: {}@{}", host.get_user(), host.get_hostname());
+    }
+
+    let mut runner = Runner::new();
+    let mut csv_logger = CsvLogger::from_config(&c_instance.lock().unwrap());
+    let prometheus = match PrometheusExporter::from_config(&c_instance.lock().unwrap()) {
+        Some(Ok(exporter)) => Some(exporter),
+        Some(Err(err)) => {
+            warn!("could not start prometheus exporter: {}", err);
+            None
+        }
+        None => None,
+    };
+
+    let control = {
+        let config = c_instance.lock().unwrap();
+        if config.get_bool("control_socket") && !config.get_dir().as_os_str().is_empty() {
+            match ControlSocket::start(config.get_dir().join("btop-rs.sock")) {
+                Ok(control) => {
+                    g_instance
+                        .lock()
+                        .unwrap()
+                        .set_control_socket(control.get_path().to_path_buf());
+                    Some(control)
+                }
+                Err(err) => {
+                    warn!("could not start control socket: {}", err);
+                    None
+                }
+            }
+        } else {
+            None
+        }
+    };
+
+    let mut daemon = g_instance.lock().unwrap().get_daemon();
+    let mut input = Input::new();
+    if daemon {
+        // 守护模式下不接管终端，只运行采集器和导出器
+        info!("running headless in daemon mode");
+        if csv_logger.is_none() && prometheus.is_none() && control.is_none() {
+            warn!("daemon mode without any exporter configured, set csv_log, prometheus_listen or control_socket");
+        }
+    } else {
+        let global = g_instance.lock().unwrap();
+        {
+            let instance = Term::get_instance();
+            let mut term = instance.lock().unwrap();
+            term.init();
+            if global.get_utf_force() {
+                term.set_utf8(true);
+            }
+            if c_instance.lock().unwrap().get_string("theme_auto") == "terminal" {
+                let background = term.query_background(Duration::from_millis(200));
+                info!("terminal background: {:?}", background);
+                t_instance.lock().unwrap().set_background(background);
+            }
+        }
+        if !global.get_keys_record().as_os_str().is_empty() {
+            if let Err(err) = input.record_to(global.get_keys_record()) {
+                warn!(
+                    "could not record keys to {:?}: {}",
+                    global.get_keys_record(),
+                    err
+                );
+            }
+        }
+        if !global.get_keys_from().as_os_str().is_empty() {
+            if let Err(err) = input.replay_from(global.get_keys_from()) {
+                eprintln!(
+                    "could not load keys from {:?}: {}",
+                    global.get_keys_from(),
+                    err
+                );
+                exit(1);
+            }
+        }
+    }
+
+    // 第一次运行时提示导入 btop++ 或 bpytop 的配置
+    if first_run && !daemon {
+        if let Some((name, path)) = import::find() {
+            match Import::read(name, &path) {
+                Ok(import) => menu::Menu::get_instance()
+                    .lock()
+                    .unwrap()
+                    .show_import(import),
+                Err(err) => warn!("could not read {:?}: {}", path, err),
+            }
+        }
+    }
+
+    runner.load_history();
+    info!("startup took {:?}", startup.elapsed());
+
+    let mut was_paused = false;
+    loop {
+        if g_instance.lock().unwrap().get_quit_state() {
+            break;
+        }
+
+        // 暂停期间只处理按键和重绘；恢复时先重建采集基准，稍后再采集
+        let paused = g_instance.lock().unwrap().get_paused();
+        let resumed = std::mem::replace(&mut was_paused, paused) && !paused;
+        if resumed {
+            runner.prime();
+        }
+
+        let update_ms = c_instance.lock().unwrap().get_int("update_ms");
+        if !paused && !resumed {
+            // 采集期间锁定配置，期间的修改在 unlock 时统一生效
+            c_instance.lock().unwrap().lock();
+            runner.collect();
+            if let Some(control) = control.as_ref() {
+                control.update(&runner);
+            }
+            if let Some(exporter) = prometheus.as_ref() {
+                exporter.update(&runner);
+            }
+
+            let net_iface = c_instance.lock().unwrap().get_string("net_iface");
+            let reopen = Global::take_reopen();
+            if reopen {
+                l_instance.lock().unwrap().reopen();
+            }
+            if let Some(logger) = csv_logger.as_mut() {
+                if reopen {
+                    logger.reopen();
+                }
+                if let Err(err) = logger.write_row(&runner, &net_iface) {
+                    warn!("failed to write csv log: {}", err);
+                }
+            }
+            c_instance.lock().unwrap().unlock();
+            {
+                // 主题可能随配置、theme_light_hours 或主题文件的修改而变化
+                let config = c_instance.lock().unwrap();
+                t_instance.lock().unwrap().update(&config);
+                if let Some(warning) = l_instance.lock().unwrap().update(&config) {
+                    warn!("{}", warning);
+                }
+            }
+
+            if !daemon {
+                menu::update(&runner);
+                screen::update(&runner);
+                screen::render(&runner);
+            }
+        }
+
+        let wait = match resumed {
+            true => RESUME_MS,
+            false => update_ms as u64,
+        };
+        let deadline = Instant::now() + Duration::from_millis(wait);
+        if daemon {
+            thread::sleep(deadline.saturating_duration_since(Instant::now()));
+        } else {
+            input.wait_until(deadline, &runner);
+        }
+
+        if Global::get_terminate() {
+            quit(&runner);
+        }
+
+        if Global::take_hangup() {
+            let headless = c_instance.lock().unwrap().get_bool("hangup_headless");
+            if let Err(err) = c_instance.lock().unwrap().write() {
+                warn!("failed to write config: {}", err);
+            }
+            if daemon {
+                info!("SIGHUP received, config saved");
+            } else if headless {
+                // 终端已断开，释放终端后按守护模式继续采集和导出
+                Term::get_instance().lock().unwrap().restore();
+                g_instance.lock().unwrap().set_daemon();
+                daemon = true;
+                info!("terminal hung up, continuing headless");
+            } else {
+                info!("terminal hung up, exiting");
+                quit(&runner);
+            }
         }
     }
-    // while true {}
 }
@@ -0,0 +1,1567 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, OnceLock},
+    time::Instant,
+};
+
+use log::warn;
+
+use crate::{
+    collect::{hwinfo, limits, proc, sensors, socket},
+    config::{
+        config::{Config, ConfigTransaction},
+        import::Import,
+        theme::Theme,
+    },
+    draw::{
+        boxes, colortest, create_box, fit, graph, mv_to, sanitize, strip_ansi,
+        symbols::box_symbols, Border, BOLD, RESET,
+    },
+    human_bytes,
+    runner::Runner,
+    shared::{
+        battery::{BatteryInfo, BatteryStatus},
+        host::HostInfo,
+        hwinfo::HwInfo,
+        limits::SysLimits,
+        proc::{
+            check_realtime, BoostCheck, FdKind, IoClass, IoPriority, ProcFd, ProcInfo, ProcLimit,
+            ProcStack, ProcThread,
+        },
+        sensors::{SensorKind, SensorList},
+        socket::{addr_text, SocketInfo, SocketList},
+    },
+    term::Term,
+};
+
+const REVERSE: &str = "\x1b[7m";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum MenuKind {
+    Main,
+    Options,
+    SysInfo,
+    Limits,
+    Sensors,
+    Sockets,
+    Battery,
+    ProcDetail,
+    Signal,
+    ColorTest,
+    Import,
+}
+
+const MAIN_ENTRIES: &[&str] = &[
+    "Options",
+    "System info",
+    "System limits",
+    "Sensors",
+    "Connections",
+    "Quit",
+];
+
+// 选项面板中的配置项和显示名称
+const OPTION_ENTRIES: &[(&str, &str)] = &[
+    ("color_theme", "Color theme"),
+    ("cpu_graph_upper", "CPU graph upper"),
+    ("cpu_graph_lower", "CPU graph lower"),
+    ("cpu_invert_lower", "Invert lower graph"),
+    ("cpu_single_graph", "Single cpu graph"),
+    ("net_overhead", "Net tunnel overhead"),
+];
+
+// 批量发送时可以选择的信号，第一项为默认
+const SIGNALS: &[(&str, i32)] = &[
+    ("SIGTERM", libc::SIGTERM),
+    ("SIGKILL", libc::SIGKILL),
+    ("SIGHUP", libc::SIGHUP),
+    ("SIGINT", libc::SIGINT),
+    ("SIGQUIT", libc::SIGQUIT),
+    ("SIGSTOP", libc::SIGSTOP),
+    ("SIGCONT", libc::SIGCONT),
+    ("SIGUSR1", libc::SIGUSR1),
+    ("SIGUSR2", libc::SIGUSR2),
+];
+
+// 信号面板中列出的进程数量，其余的只显示数量
+const SIGNAL_LIST: usize = 5;
+
+// 导入报告中列出的失败项数量，完整列表写入日志
+const IMPORT_LIST: usize = 10;
+
+// 线程、文件和环境变量标签页每页显示的行数
+const TAB_PAGE: usize = 15;
+
+// 详细视图中的标签页
+#[derive(Clone, Copy, PartialEq)]
+pub enum DetailTab {
+    Info,
+    Threads,
+    Affinity,
+    Limits,
+    Stack,
+    Files,
+    Env,
+}
+
+impl DetailTab {
+    const ALL: [DetailTab; 7] = [
+        DetailTab::Info,
+        DetailTab::Threads,
+        DetailTab::Affinity,
+        DetailTab::Limits,
+        DetailTab::Stack,
+        DetailTab::Files,
+        DetailTab::Env,
+    ];
+
+    fn name(&self) -> &'static str {
+        match self {
+            DetailTab::Info => "Info",
+            DetailTab::Threads => "Threads",
+            DetailTab::Affinity => "Affinity",
+            DetailTab::Limits => "Limits",
+            DetailTab::Stack => "Stack",
+            DetailTab::Files => "Files",
+            DetailTab::Env => "Env",
+        }
+    }
+
+    fn next(&self, forward: bool) -> Self {
+        let pos = DetailTab::ALL
+            .iter()
+            .position(|tab| tab == self)
+            .unwrap_or(0);
+        let len = DetailTab::ALL.len();
+        match forward {
+            true => DetailTab::ALL[(pos + 1) % len],
+            false => DetailTab::ALL[(pos + len - 1) % len],
+        }
+    }
+}
+
+pub struct Menu {
+    pub active: Option<MenuKind>,
+    pub selected: usize,
+    pub redraw: bool,
+    hwinfo: Option<HwInfo>,
+    // 系统限制面板打开期间每次采集后刷新
+    limits: Option<SysLimits>,
+    // 连接面板打开期间每次采集后刷新，读取失败时为错误信息
+    sensors: SensorList,
+    sockets: Result<SocketList, String>,
+    // 电池面板打开期间每次采集后刷新
+    battery: Option<BatteryInfo>,
+    // 当前平台检测到的 cpu 状态，供 cpu_graph_upper/cpu_graph_lower 选择
+    cpu_stats: Vec<&'static str>,
+    // 打开选项菜单时扫描到的主题
+    themes: Vec<String>,
+    // 详细视图中的进程，None 表示进程已退出
+    detail: Option<ProcInfo>,
+    ioprio: Option<IoPriority>,
+    detail_tab: DetailTab,
+    // 进程允许运行的 cpu，每次采集后重新读取，读取失败时为错误信息
+    allowed: Result<Vec<usize>, String>,
+    // 亲和性编辑器中各 cpu 是否选中，edited 为 true 时不再用读取的结果覆盖修改
+    affinity_edit: Vec<bool>,
+    affinity_cursor: usize,
+    affinity_edited: bool,
+    // 各核心的占用率，显示在亲和性编辑器中
+    core_percent: Vec<f64>,
+    // 当前标签页打开期间每次采集后重新读取，读取失败时为错误信息
+    proc_threads: Result<Vec<ProcThread>, String>,
+    proc_limits: Result<Vec<ProcLimit>, String>,
+    proc_stack: Result<ProcStack, String>,
+    proc_fds: Result<Vec<ProcFd>, String>,
+    proc_env: Result<Vec<String>, String>,
+    // 上一次读取线程的时间和各线程的 cpu_time，用于计算线程的 cpu 占用率
+    thread_times: Option<(Instant, HashMap<i32, f64>)>,
+    // 线程、文件、环境变量标签页和连接面板中第一行显示的序号
+    tab_offset: usize,
+    // 环境变量的搜索词，searching 为 true 时按键输入到搜索词中
+    env_search: String,
+    searching: bool,
+    // 信号面板打开时要发送信号的进程
+    signal_targets: Vec<(i32, String)>,
+    // 等待用户按 y 确认的实时 I/O 优先级
+    pending: Option<IoPriority>,
+    // 首次运行时找到的 btop++/bpytop 配置，确认前为 Some，导入后显示 import_report
+    import: Option<Import>,
+    import_report: Vec<String>,
+    // 在线核心数，用于判断进程是否占满了 cpu
+    cores: usize,
+    // proc_info_smaps 开启时读取的 PSS
+    pss: Option<u64>,
+    message: String,
+}
+
+impl Menu {
+    fn new() -> Self {
+        Menu {
+            active: None,
+            selected: 0,
+            redraw: false,
+            hwinfo: None,
+            limits: None,
+            sensors: SensorList::default(),
+            sockets: Ok(SocketList::default()),
+            battery: None,
+            cpu_stats: Vec::new(),
+            themes: Vec::new(),
+            detail: None,
+            ioprio: None,
+            pending: None,
+            import: None,
+            import_report: Vec::new(),
+            signal_targets: Vec::new(),
+            detail_tab: DetailTab::Info,
+            allowed: Ok(Vec::new()),
+            affinity_edit: Vec::new(),
+            affinity_cursor: 0,
+            affinity_edited: false,
+            core_percent: Vec::new(),
+            proc_threads: Ok(Vec::new()),
+            thread_times: None,
+            proc_limits: Ok(Vec::new()),
+            proc_stack: Ok(ProcStack::default()),
+            proc_fds: Ok(Vec::new()),
+            proc_env: Ok(Vec::new()),
+            tab_offset: 0,
+            env_search: String::new(),
+            searching: false,
+            cores: 1,
+            pss: None,
+            message: String::new(),
+        }
+    }
+
+    pub fn get_instance() -> Arc<Mutex<Menu>> {
+        static INSTANCE: OnceLock<Arc<Mutex<Menu>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(Menu::new())))
+            .clone()
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active.is_some()
+    }
+
+    pub fn show(&mut self, kind: MenuKind) {
+        if kind == MenuKind::SysInfo && self.hwinfo.is_none() {
+            self.hwinfo = Some(hwinfo::collect());
+        }
+        if kind == MenuKind::Limits {
+            self.limits = Some(limits::collect());
+        }
+        if kind == MenuKind::Sensors {
+            self.sensors = sensors::collect();
+            self.tab_offset = 0;
+        }
+        if kind == MenuKind::Sockets {
+            self.sockets = socket::collect().map_err(|err| err.to_string());
+            self.tab_offset = 0;
+        }
+        if kind == MenuKind::ProcDetail {
+            self.detail_tab = DetailTab::Info;
+            self.tab_offset = 0;
+            self.env_search.clear();
+            self.thread_times = None;
+            self.affinity_cursor = 0;
+            self.affinity_edited = false;
+        }
+        if kind == MenuKind::Options {
+            self.themes = Theme::get_instance().lock().unwrap().list();
+        }
+        self.active = Some(kind);
+        self.selected = 0;
+        self.pending = None;
+        self.searching = false;
+        self.message.clear();
+        self.redraw = true;
+    }
+
+    pub fn show_signals(&mut self, targets: Vec<(i32, String)>) {
+        self.signal_targets = targets;
+        self.show(MenuKind::Signal);
+    }
+
+    pub fn show_import(&mut self, import: Import) {
+        self.import = Some(import);
+        self.import_report.clear();
+        self.show(MenuKind::Import);
+    }
+
+    pub fn close(&mut self) {
+        self.active = None;
+        self.pending = None;
+        self.searching = false;
+        self.redraw = true;
+    }
+
+    // 返回 true 表示用户在菜单中选择了退出
+    pub fn process(&mut self, key: &str) -> bool {
+        // 等待确认时只有 y 会生效，其它按键取消
+        if let Some(prio) = self.pending.take() {
+            match key {
+                "y" => self.set_ioprio(prio),
+                _ => {
+                    self.message = "Cancelled".to_owned();
+                    self.redraw = true;
+                }
+            }
+            return false;
+        }
+        if self.searching {
+            self.search_input(key);
+            return false;
+        }
+        match self.active {
+            Some(MenuKind::Main) => match key {
+                "up" | "k" => {
+                    self.selected = self
+                        .selected
+                        .checked_sub(1)
+                        .unwrap_or(MAIN_ENTRIES.len() - 1);
+                    self.redraw = true;
+                }
+                "down" | "j" | "tab" => {
+                    self.selected = (self.selected + 1) % MAIN_ENTRIES.len();
+                    self.redraw = true;
+                }
+                "enter" | "space" => match MAIN_ENTRIES[self.selected] {
+                    "Options" => self.show(MenuKind::Options),
+                    "System info" => self.show(MenuKind::SysInfo),
+                    "System limits" => self.show(MenuKind::Limits),
+                    "Sensors" => self.show(MenuKind::Sensors),
+                    "Connections" => self.show(MenuKind::Sockets),
+                    "Quit" => return true,
+                    _ => {}
+                },
+                "escape" | "m" | "q" => self.close(),
+                _ => {}
+            },
+            Some(MenuKind::Options) => match key {
+                "up" | "k" => {
+                    self.selected = self
+                        .selected
+                        .checked_sub(1)
+                        .unwrap_or(OPTION_ENTRIES.len() - 1);
+                    self.redraw = true;
+                }
+                "down" | "j" | "tab" => {
+                    self.selected = (self.selected + 1) % OPTION_ENTRIES.len();
+                    self.redraw = true;
+                }
+                "left" | "h" => self.change_option(false),
+                "right" | "l" | "enter" | "space" => self.change_option(true),
+                "e" => {
+                    self.message = match Theme::get_instance().lock().unwrap().export() {
+                        Ok(path) => format!("Theme exported to {}", path.display()),
+                        Err(err) => err,
+                    };
+                    self.redraw = true;
+                }
+                "escape" | "backspace" | "q" => self.show(MenuKind::Main),
+                _ => {}
+            },
+            Some(MenuKind::SysInfo | MenuKind::Limits | MenuKind::Battery) => match key {
+                "escape" | "backspace" | "enter" | "q" => self.show(MenuKind::Main),
+                _ => {}
+            },
+            Some(MenuKind::Sensors | MenuKind::Sockets) => match key {
+                "up" | "down" | "k" | "j" | "page_up" | "page_down" | "home" | "end" => {
+                    self.scroll_tab(key)
+                }
+                "escape" | "backspace" | "enter" | "q" => self.show(MenuKind::Main),
+                _ => {}
+            },
+            Some(MenuKind::ProcDetail) => match key {
+                "tab" | "shift_tab" | "left" | "right" | "h" | "l" => {
+                    self.detail_tab = self.detail_tab.next(matches!(key, "tab" | "right" | "l"));
+                    self.tab_offset = 0;
+                    self.affinity_cursor = 0;
+                    self.read_tab();
+                    self.redraw = true;
+                }
+                "up" | "down" | "k" | "j" | "page_up" | "page_down" | "home" | "end"
+                    if self.detail_tab == DetailTab::Affinity =>
+                {
+                    self.move_affinity_cursor(key)
+                }
+                "space" | "a" | "r" | "enter" if self.detail_tab == DetailTab::Affinity => {
+                    self.edit_affinity(key)
+                }
+                "up" | "down" | "k" | "j" | "page_up" | "page_down" | "home" | "end"
+                    if matches!(
+                        self.detail_tab,
+                        DetailTab::Threads | DetailTab::Files | DetailTab::Env
+                    ) =>
+                {
+                    self.scroll_tab(key)
+                }
+                "/" | "f" if self.detail_tab == DetailTab::Env => {
+                    self.searching = true;
+                    self.redraw = true;
+                }
+                "i" => {
+                    if let Some(prio) = self.ioprio {
+                        self.request_ioprio(prio.next_class());
+                    }
+                }
+                "+" | "-" => match self.ioprio {
+                    Some(prio) if matches!(prio.class, IoClass::RealTime | IoClass::BestEffort) => {
+                        let level = match key {
+                            "+" => prio.level.saturating_sub(1),
+                            _ => (prio.level + 1).min(IoPriority::MAX_LEVEL),
+                        };
+                        self.request_ioprio(IoPriority { level, ..prio });
+                    }
+                    Some(_) => {
+                        self.message = "Level only applies to rt and be classes".to_owned();
+                        self.redraw = true;
+                    }
+                    None => {}
+                },
+                "escape" | "backspace" | "q" => {
+                    let instance = Config::get_instance();
+                    let mut config = instance.lock().unwrap();
+                    config.set_bool("show_detailed", false);
+                    // 恢复打开详细视图之前选中的进程，跟随模式下按 pid 恢复
+                    let last = config.get_int("proc_last_selected");
+                    let _ = config.set_int("proc_selected", &last.to_string());
+                    let pid = config.get_int("detailed_pid");
+                    let _ = config.set_int("selected_pid", &pid.to_string());
+                    self.close();
+                }
+                _ => {}
+            },
+            Some(MenuKind::Signal) => match key {
+                "up" | "k" => {
+                    self.selected = self.selected.checked_sub(1).unwrap_or(SIGNALS.len() - 1);
+                    self.redraw = true;
+                }
+                "down" | "j" | "tab" => {
+                    self.selected = (self.selected + 1) % SIGNALS.len();
+                    self.redraw = true;
+                }
+                "enter" => self.send_signals(),
+                "escape" | "backspace" | "q" => self.close(),
+                _ => {}
+            },
+            Some(MenuKind::ColorTest) => match key {
+                "escape" | "backspace" | "q" => self.close(),
+                _ => {}
+            },
+            Some(MenuKind::Import) => match key {
+                "y" if self.import.is_some() => self.import_config(),
+                "n" | "escape" | "backspace" | "q" | "enter" => {
+                    self.import = None;
+                    self.close();
+                }
+                _ => {}
+            },
+            None => {}
+        }
+        false
+    }
+
+    // 逐个发送并汇总结果，失败的进程列出 pid 和原因
+    fn send_signals(&mut self) {
+        let (name, signal) = SIGNALS[self.selected];
+        let failed: Vec<String> = self
+            .signal_targets
+            .iter()
+            .filter_map(|(pid, _)| match proc::send_signal(*pid, signal) {
+                Ok(_) => None,
+                Err(err) if err.kind() == std::io::ErrorKind::PermissionDenied => {
+                    Some(format!("{}: not permitted", pid))
+                }
+                Err(err) => Some(format!("{}: {}", pid, err)),
+            })
+            .collect();
+        let sent = self.signal_targets.len() - failed.len();
+        self.message = match failed.len() {
+            0 => format!("{} sent to {} processes", name, sent),
+            count => {
+                let mut shown = failed[..count.min(SIGNAL_LIST)].join(", ");
+                if count > SIGNAL_LIST {
+                    shown += &format!(", {} more", count - SIGNAL_LIST);
+                }
+                format!("{} sent to {}, failed {}: {}", name, sent, count, shown)
+            }
+        };
+        self.redraw = true;
+    }
+
+    fn import_config(&mut self) {
+        let Some(import) = self.import.take() else {
+            return;
+        };
+        let failed = import.apply(&mut Config::get_instance().lock().unwrap());
+        for err in &failed {
+            warn!("import from {:?}: {}", import.path, err);
+        }
+        self.import_report = vec![format!(
+            "Imported {} of {} settings from {}.",
+            import.values.len() - failed.len(),
+            import.values.len(),
+            import.name
+        )];
+        if !failed.is_empty() {
+            self.import_report.push(String::new());
+            self.import_report.push("Could not migrate:".to_owned());
+            self.import_report
+                .extend(failed.iter().take(IMPORT_LIST).cloned());
+            if failed.len() > IMPORT_LIST {
+                self.import_report.push(format!(
+                    "{} more, see the log for the full list",
+                    failed.len() - IMPORT_LIST
+                ));
+            }
+        }
+        self.import_report.push(String::new());
+        self.import_report.push("(enter: close)".to_owned());
+        self.redraw = true;
+    }
+
+    fn import_lines(&self) -> Vec<String> {
+        let Some(import) = &self.import else {
+            return self.import_report.clone();
+        };
+        vec![
+            format!("Found an existing {} config:", import.name),
+            import.path.display().to_string(),
+            String::new(),
+            format!(
+                "{} settings can be imported into btop-rs.conf.",
+                import.values.len()
+            ),
+            String::new(),
+            "Import them? (y/n)".to_owned(),
+        ]
+    }
+
+    fn signal_lines(&self) -> Vec<String> {
+        let count = self.signal_targets.len();
+        let mut names: Vec<String> = self
+            .signal_targets
+            .iter()
+            .take(SIGNAL_LIST)
+            .map(|(pid, name)| format!("{} ({})", sanitize(name), pid))
+            .collect();
+        if count > SIGNAL_LIST {
+            names.push(format!("{} more", count - SIGNAL_LIST));
+        }
+        let mut lines = vec![
+            format!("{}Send to {} processes{}", BOLD, count, RESET),
+            format!("  {}", names.join(", ")),
+            String::new(),
+        ];
+        lines.extend(SIGNALS.iter().enumerate().map(|(i, (name, signal))| {
+            let line = format!("{:<8} {:>2}", name, signal);
+            match i == self.selected {
+                true => format!("  {}{}{}", REVERSE, line, RESET),
+                false => format!("  {}", line),
+            }
+        }));
+        if !self.message.is_empty() {
+            lines.push(String::new());
+            lines.push(self.message.clone());
+        }
+        lines
+    }
+
+    // 字符串选项在检测到的状态或主题之间循环，布尔选项直接切换
+    fn change_option(&mut self, forward: bool) {
+        let (key, _) = OPTION_ENTRIES[self.selected];
+        let instance = Config::get_instance();
+        let mut config = instance.lock().unwrap();
+        let mut tx = ConfigTransaction::new();
+        if let Some(choices) = self.choices(key) {
+            let len = choices.len();
+            if len == 0 {
+                return;
+            }
+            let current = config.get_string(key);
+            let pos = choices.iter().position(|choice| *choice == current);
+            let next = match (pos, forward) {
+                (Some(pos), true) => (pos + 1) % len,
+                (Some(pos), false) => (pos + len - 1) % len,
+                (None, _) => 0,
+            };
+            tx.set(key, choices[next]);
+        } else {
+            tx.set(
+                key,
+                if config.get_bool(key) {
+                    "False"
+                } else {
+                    "True"
+                },
+            );
+        }
+        self.message = config.apply(&tx).err().unwrap_or_default();
+        // 立即切换主题，菜单重绘时主界面使用新的颜色预览
+        if key == "color_theme" && self.message.is_empty() {
+            Theme::get_instance().lock().unwrap().update(&config);
+            if config.get_string("theme_auto") != "off" {
+                self.message = "theme_auto is on, color_theme is not used".to_owned();
+            }
+        }
+        self.redraw = true;
+    }
+
+    // 字符串选项在这些值之间循环，布尔选项返回 None
+    fn choices(&self, key: &str) -> Option<Vec<&str>> {
+        match key {
+            "color_theme" => Some(self.themes.iter().map(String::as_str).collect()),
+            _ if key.starts_with("cpu_graph_") => Some(self.cpu_stats.clone()),
+            _ => None,
+        }
+    }
+
+    fn options_lines(&self) -> Vec<String> {
+        let instance = Config::get_instance();
+        let config = instance.lock().unwrap();
+        let mut lines: Vec<String> = OPTION_ENTRIES
+            .iter()
+            .enumerate()
+            .map(|(i, (key, name))| {
+                let value = if self.choices(key).is_some() {
+                    format!("< {} >", config.get_string(key))
+                } else if config.get_bool(key) {
+                    "On".to_owned()
+                } else {
+                    "Off".to_owned()
+                };
+                let line = format!("{:<20} {:<14}", name, value);
+                if i == self.selected {
+                    format!("{}{}{}", REVERSE, line, RESET)
+                } else {
+                    line
+                }
+            })
+            .collect();
+        lines.push("(e: export theme)".to_owned());
+        if !self.message.is_empty() {
+            lines.push(self.message.clone());
+        }
+        lines
+    }
+
+    // 进入实时类别前需要确认，会让系统得不到调度时直接拒绝
+    fn request_ioprio(&mut self, prio: IoPriority) {
+        let (Some(detail), Some(current)) = (&self.detail, self.ioprio) else {
+            return;
+        };
+        if prio.class == IoClass::RealTime {
+            let per_core = Config::get_instance()
+                .lock()
+                .unwrap()
+                .get_bool("proc_per_core");
+            match check_realtime(detail, self.cores, per_core) {
+                BoostCheck::Refuse(reason) => {
+                    self.message = format!("Refused: {}", reason);
+                    self.redraw = true;
+                    return;
+                }
+                BoostCheck::Confirm(risk) if current.class != IoClass::RealTime => {
+                    self.message = format!("{}, press y to set {}", risk, prio);
+                    self.pending = Some(prio);
+                    self.redraw = true;
+                    return;
+                }
+                BoostCheck::Confirm(_) => {}
+            }
+        }
+        self.set_ioprio(prio);
+    }
+
+    // 级别数值越小优先级越高，"+" 表示提高优先级
+    fn set_ioprio(&mut self, prio: IoPriority) {
+        let Some(detail) = &self.detail else {
+            return;
+        };
+        self.message = match proc::set_ioprio(detail.pid, prio) {
+            Ok(_) => {
+                self.ioprio = Some(prio);
+                format!("I/O priority set to {}", prio)
+            }
+            Err(err) => format!("Could not set I/O priority: {}", err),
+        };
+        self.redraw = true;
+    }
+
+    // 线程、限制、内核栈、打开的文件和环境变量只在对应的标签页打开时读取，
+    // 亲和性在编辑期间保留用户的修改
+    fn read_tab(&mut self) {
+        let Some(p) = &self.detail else {
+            return;
+        };
+        match self.detail_tab {
+            DetailTab::Info => {}
+            DetailTab::Threads => {
+                let now = Instant::now();
+                self.proc_threads = proc::get_threads(p.pid).map_err(|err| err.to_string());
+                if let Ok(threads) = &mut self.proc_threads {
+                    let per_core = Config::get_instance()
+                        .lock()
+                        .unwrap()
+                        .get_bool("proc_per_core");
+                    let cores = if per_core {
+                        1.0
+                    } else {
+                        self.cores.max(1) as f64
+                    };
+                    if let Some((last, times)) = &self.thread_times {
+                        let elapsed = now.duration_since(*last).as_secs_f64();
+                        for thread in threads.iter_mut().filter(|_| elapsed > 0.0) {
+                            thread.cpu_p = times.get(&thread.tid).map(|time| {
+                                (thread.cpu_time - time).max(0.0) / elapsed * 100.0 / cores
+                            });
+                        }
+                    }
+                    self.thread_times =
+                        Some((now, threads.iter().map(|t| (t.tid, t.cpu_time)).collect()));
+                    // 占用高的线程排在前面
+                    threads.sort_by(|a, b| {
+                        b.cpu_p
+                            .unwrap_or(0.0)
+                            .total_cmp(&a.cpu_p.unwrap_or(0.0))
+                            .then(a.tid.cmp(&b.tid))
+                    });
+                }
+            }
+            DetailTab::Affinity if !self.affinity_edited => {
+                let allowed = self.allowed.as_deref().unwrap_or_default();
+                // 离线的 cpu 也列出来，进程的掩码中可能包含编号更大的 cpu
+                let len = allowed
+                    .iter()
+                    .map(|cpu| cpu + 1)
+                    .max()
+                    .unwrap_or(0)
+                    .max(self.core_percent.len());
+                self.affinity_edit = (0..len).map(|cpu| allowed.contains(&cpu)).collect();
+                self.affinity_cursor = self.affinity_cursor.min(len.saturating_sub(1));
+            }
+            DetailTab::Affinity => {}
+            DetailTab::Limits => {
+                self.proc_limits = proc::get_limits(p.pid).map_err(|err| err.to_string())
+            }
+            DetailTab::Stack => {
+                self.proc_stack = proc::get_stack(p.pid).map_err(|err| err.to_string())
+            }
+            DetailTab::Files => self.proc_fds = proc::get_fds(p.pid).map_err(|err| err.to_string()),
+            DetailTab::Env => {
+                self.proc_env = proc::get_environ(p.pid).map_err(|err| err.to_string())
+            }
+        }
+        // 行数减少后保持在最后一页
+        self.tab_offset = self.tab_offset.min(self.tab_len().saturating_sub(TAB_PAGE));
+    }
+
+    // 可以滚动的标签页中的总行数，环境变量只计算匹配搜索词的部分
+    fn tab_len(&self) -> usize {
+        if self.active == Some(MenuKind::Sensors) {
+            return self.sensors.sensors.len();
+        }
+        if self.active == Some(MenuKind::Sockets) {
+            return self.sockets.as_ref().map_or(0, |list| list.sockets.len());
+        }
+        match self.detail_tab {
+            DetailTab::Threads => self.proc_threads.as_ref().map_or(0, Vec::len),
+            DetailTab::Files => self.proc_fds.as_ref().map_or(0, Vec::len),
+            DetailTab::Env => self.env_matches().len(),
+            _ => 0,
+        }
+    }
+
+    fn scroll_tab(&mut self, key: &str) {
+        let last = self.tab_len().saturating_sub(TAB_PAGE);
+        self.tab_offset = match key {
+            "up" | "k" => self.tab_offset.saturating_sub(1),
+            "down" | "j" => self.tab_offset + 1,
+            "page_up" => self.tab_offset.saturating_sub(TAB_PAGE),
+            "page_down" => self.tab_offset + TAB_PAGE,
+            "home" => 0,
+            _ => last,
+        }
+        .min(last);
+        self.redraw = true;
+    }
+
+    // 光标移出当前页时滚动
+    fn move_affinity_cursor(&mut self, key: &str) {
+        let last = self.affinity_edit.len().saturating_sub(1);
+        self.affinity_cursor = match key {
+            "up" | "k" => self.affinity_cursor.saturating_sub(1),
+            "down" | "j" => self.affinity_cursor + 1,
+            "page_up" => self.affinity_cursor.saturating_sub(TAB_PAGE),
+            "page_down" => self.affinity_cursor + TAB_PAGE,
+            "home" => 0,
+            _ => last,
+        }
+        .min(last);
+        if self.affinity_cursor < self.tab_offset {
+            self.tab_offset = self.affinity_cursor;
+        } else if self.affinity_cursor >= self.tab_offset + TAB_PAGE {
+            self.tab_offset = self.affinity_cursor + 1 - TAB_PAGE;
+        }
+        self.redraw = true;
+    }
+
+    // space 切换光标所在的 cpu，a 选中全部，r 放弃修改，回车应用到进程
+    fn edit_affinity(&mut self, key: &str) {
+        match key {
+            "space" => {
+                if let Some(on) = self.affinity_edit.get_mut(self.affinity_cursor) {
+                    *on = !*on;
+                    self.affinity_edited = true;
+                }
+            }
+            "a" => {
+                self.affinity_edit.fill(true);
+                self.affinity_edited = true;
+            }
+            "r" => {
+                self.affinity_edited = false;
+                self.read_tab();
+            }
+            _ => self.apply_affinity(),
+        }
+        self.redraw = true;
+    }
+
+    fn apply_affinity(&mut self) {
+        let Some(pid) = self.detail.as_ref().map(|p| p.pid) else {
+            return;
+        };
+        let cpus: Vec<usize> = self
+            .affinity_edit
+            .iter()
+            .enumerate()
+            .filter(|(_, on)| **on)
+            .map(|(cpu, _)| cpu)
+            .collect();
+        if cpus.is_empty() {
+            self.message = "Select at least one cpu".to_owned();
+            return;
+        }
+        self.message = match proc::set_affinity(pid, &cpus) {
+            Ok(_) => {
+                self.affinity_edited = false;
+                self.allowed = Ok(cpus.clone());
+                format!("Affinity set to {}", cpu_list(&cpus))
+            }
+            Err(err) => format!("Could not set affinity: {}", err),
+        };
+    }
+
+    // 回车结束输入并保留搜索词，escape 清空搜索词
+    fn search_input(&mut self, key: &str) {
+        match key {
+            "enter" => self.searching = false,
+            "escape" => {
+                self.searching = false;
+                self.env_search.clear();
+            }
+            "backspace" => {
+                self.env_search.pop();
+            }
+            "space" => self.env_search.push(' '),
+            _ if key.chars().count() == 1 => self.env_search.push_str(key),
+            _ => return,
+        }
+        self.tab_offset = 0;
+        self.redraw = true;
+    }
+
+    // 不区分大小写，同时匹配变量名和值
+    fn env_matches(&self) -> Vec<&String> {
+        let Ok(vars) = &self.proc_env else {
+            return Vec::new();
+        };
+        let search = self.env_search.to_lowercase();
+        vars.iter()
+            .filter(|var| var.to_lowercase().contains(&search))
+            .collect()
+    }
+
+    fn detail_tabs(&self) -> String {
+        let tabs: Vec<String> = DetailTab::ALL
+            .iter()
+            .map(|tab| match *tab == self.detail_tab {
+                true => format!("{} {} {}", REVERSE, tab.name(), RESET),
+                false => format!(" {} ", tab.name()),
+            })
+            .collect();
+        format!("  {}  (tab: switch)", tabs.join(""))
+    }
+
+    // 第一次读取时还没有占用率，显示为 "-"
+    fn threads_tab_lines(&self) -> Vec<String> {
+        let threads = match &self.proc_threads {
+            Ok(threads) if !threads.is_empty() => threads,
+            Ok(_) => return vec!["  No threads".to_owned()],
+            Err(err) => return vec![format!("  Could not read threads: {}", err)],
+        };
+        let running = threads.iter().filter(|t| t.state == 'R').count();
+        let mut lines = vec![
+            format!("  Threads: {}  Running: {}", threads.len(), running),
+            format!(
+                "{}  {:>7}  {:<16} {:>5} {:>7} {:>10}{}",
+                BOLD, "Tid", "Name", "State", "Cpu%", "Time", RESET
+            ),
+        ];
+        lines.extend(
+            threads
+                .iter()
+                .skip(self.tab_offset)
+                .take(TAB_PAGE)
+                .map(|thread| {
+                    let cpu = match thread.cpu_p {
+                        Some(cpu) => format!("{:.1}", cpu),
+                        None => "-".to_owned(),
+                    };
+                    format!(
+                        "  {:>7}  {:<16} {:>5} {:>7} {:>9.2}s",
+                        thread.tid,
+                        sanitize(&thread.name),
+                        thread.state,
+                        cpu,
+                        thread.cpu_time
+                    )
+                }),
+        );
+        if threads.len() > TAB_PAGE {
+            lines.push(self.page_footer(threads.len()));
+        }
+        lines
+    }
+
+    fn affinity_tab_lines(&self) -> Vec<String> {
+        let allowed = match &self.allowed {
+            Ok(allowed) => allowed,
+            Err(err) => return vec![format!("  Could not read affinity: {}", err)],
+        };
+        let mut lines = vec![format!("  Allowed: {}", cpu_list(allowed))];
+        if self.affinity_edited {
+            let selected: Vec<usize> = (0..self.affinity_edit.len())
+                .filter(|&cpu| self.affinity_edit[cpu])
+                .collect();
+            lines[0] += &format!("  Selected: {}", cpu_list(&selected));
+        }
+        lines.extend(
+            self.affinity_edit
+                .iter()
+                .enumerate()
+                .skip(self.tab_offset)
+                .take(TAB_PAGE)
+                .map(|(cpu, on)| {
+                    let usage = match self.core_percent.get(cpu) {
+                        Some(percent) => format!("{:>5.1}%", percent),
+                        None => "     -".to_owned(),
+                    };
+                    let line = format!("[{}] cpu{:<4} {}", if *on { "x" } else { " " }, cpu, usage);
+                    match cpu == self.affinity_cursor {
+                        true => format!("  {}{}{}", REVERSE, line, RESET),
+                        false => format!("  {}", line),
+                    }
+                }),
+        );
+        if self.affinity_edit.len() > TAB_PAGE {
+            lines.push(self.page_footer(self.affinity_edit.len()));
+        }
+        lines.push("  (space: toggle, a: all, r: reset, enter: apply)".to_owned());
+        lines
+    }
+
+    fn limits_tab_lines(&self) -> Vec<String> {
+        let limits = match &self.proc_limits {
+            Ok(limits) if !limits.is_empty() => limits,
+            Ok(_) => return vec!["  No limits available".to_owned()],
+            Err(err) => return vec![format!("  Could not read limits: {}", err)],
+        };
+        let mut lines = vec![format!(
+            "{}  {:<26} {:>12} {:>12} {:<8}{}",
+            BOLD, "Limit", "Soft", "Hard", "Units", RESET
+        )];
+        lines.extend(limits.iter().map(|limit| {
+            format!(
+                "  {:<26} {:>12} {:>12} {:<8}",
+                limit.name, limit.soft, limit.hard, limit.unit
+            )
+        }));
+        lines
+    }
+
+    // 描述符可能有数千个，按页显示，标题行给出各类别的数量
+    fn files_tab_lines(&self) -> Vec<String> {
+        let fds = match &self.proc_fds {
+            Ok(fds) if !fds.is_empty() => fds,
+            Ok(_) => return vec!["  No open files".to_owned()],
+            Err(err) => return vec![format!("  Could not read open files: {}", err)],
+        };
+        let counts: Vec<String> = FdKind::ALL
+            .iter()
+            .map(|kind| {
+                let count = fds.iter().filter(|fd| fd.kind == *kind).count();
+                format!("{}: {}", kind.name(), count)
+            })
+            .collect();
+        let mut lines = vec![format!("  {}", counts.join("  "))];
+        lines.extend(
+            fds.iter()
+                .skip(self.tab_offset)
+                .take(TAB_PAGE)
+                .map(|fd| format!("  {:>5}  {}", fd.fd, sanitize(&fd.target))),
+        );
+        if fds.len() > TAB_PAGE {
+            lines.push(self.page_footer(fds.len()));
+        }
+        lines
+    }
+
+    fn page_footer(&self, len: usize) -> String {
+        format!(
+            "  {}-{} of {}  (up/down, pgup/pgdn: scroll)",
+            self.tab_offset + 1,
+            (self.tab_offset + TAB_PAGE).min(len),
+            len
+        )
+    }
+
+    fn env_tab_lines(&self) -> Vec<String> {
+        let vars = match &self.proc_env {
+            Ok(vars) if !vars.is_empty() => vars,
+            Ok(_) => return vec!["  No environment variables".to_owned()],
+            Err(err) => return vec![format!("  Could not read environment: {}", err)],
+        };
+        let matches = self.env_matches();
+        let search = match (self.searching, self.env_search.is_empty()) {
+            (true, _) => format!(
+                "  Search: {}_  (enter: done, esc: clear)",
+                sanitize(&self.env_search)
+            ),
+            (false, true) => format!("  Variables: {}  (/: search)", vars.len()),
+            (false, false) => format!(
+                "  Search: {}  {} of {} match  (/: edit)",
+                sanitize(&self.env_search),
+                matches.len(),
+                vars.len()
+            ),
+        };
+        let mut lines = vec![search];
+        lines.extend(
+            matches
+                .iter()
+                .skip(self.tab_offset)
+                .take(TAB_PAGE)
+                .map(|var| format!("  {}", sanitize(var))),
+        );
+        if matches.len() > TAB_PAGE {
+            lines.push(self.page_footer(matches.len()));
+        }
+        lines
+    }
+
+    // 内核栈通常很深，只显示最上面的几层
+    fn stack_tab_lines(&self) -> Vec<String> {
+        const MAX_FRAMES: usize = 12;
+        let stack = match &self.proc_stack {
+            Ok(stack) => stack,
+            Err(err) => return vec![format!("  Could not read stack: {}", err)],
+        };
+        let wchan = match stack.wchan.as_str() {
+            "" => "running",
+            wchan => wchan,
+        };
+        let mut lines = vec![format!("  Waiting in: {}", wchan)];
+        match &stack.frames {
+            Some(frames) if frames.is_empty() => lines.push("  Kernel stack: empty".to_owned()),
+            Some(frames) => {
+                lines.push("  Kernel stack:".to_owned());
+                lines.extend(
+                    frames
+                        .iter()
+                        .take(MAX_FRAMES)
+                        .map(|frame| format!("    {}", frame)),
+                );
+                if frames.len() > MAX_FRAMES {
+                    lines.push(format!("    ... {} more", frames.len() - MAX_FRAMES));
+                }
+            }
+            None => lines.push("  Kernel stack: not readable, needs root".to_owned()),
+        }
+        lines
+    }
+
+    fn info_tab_lines(&self, p: &ProcInfo) -> Vec<String> {
+        let mut lines = vec![
+            format!("  Command: {}", sanitize(&p.cmd)),
+            format!(
+                "  User: {}  State: {}  Threads: {}  Nice: {}",
+                sanitize(&p.user),
+                p.state,
+                p.threads,
+                p.nice
+            ),
+            format!("  Memory: {}  CPU: {:.1}%", human_bytes(p.mem), p.cpu_p),
+        ];
+        if let Some(pss) = self.pss {
+            lines.push(format!("  PSS: {}", human_bytes(pss)));
+        }
+        // 第一次采集时还没有速率，只显示累计值
+        match (p.io_bytes, p.io_rate) {
+            (Some((read, write)), Some((read_rate, write_rate))) => lines.push(format!(
+                "  I/O read: {}/s ({})  write: {}/s ({})",
+                human_bytes(read_rate),
+                human_bytes(read),
+                human_bytes(write_rate),
+                human_bytes(write)
+            )),
+            (Some((read, write)), None) => lines.push(format!(
+                "  I/O read: {}  write: {}",
+                human_bytes(read),
+                human_bytes(write)
+            )),
+            (None, _) => lines.push("  I/O: not readable, needs root or use_helper".to_owned()),
+        }
+        if let Ok(allowed) = &self.allowed {
+            lines.push(format!("  CPU affinity: {}", cpu_list(allowed)));
+        }
+        match self.ioprio {
+            Some(prio) => lines.push(format!("  I/O priority: {}  (i: class, +/-: level)", prio)),
+            None => lines.push("  I/O priority: unavailable".to_owned()),
+        }
+        lines
+    }
+
+    fn detail_lines(&self) -> Vec<String> {
+        let Some(p) = &self.detail else {
+            return vec!["Process has exited".to_owned()];
+        };
+        let mut lines = vec![
+            format!("{}{}{} ({})", BOLD, sanitize(&p.name), RESET, p.pid),
+            self.detail_tabs(),
+        ];
+        lines.extend(match self.detail_tab {
+            DetailTab::Info => self.info_tab_lines(p),
+            DetailTab::Threads => self.threads_tab_lines(),
+            DetailTab::Affinity => self.affinity_tab_lines(),
+            DetailTab::Limits => self.limits_tab_lines(),
+            DetailTab::Stack => self.stack_tab_lines(),
+            DetailTab::Files => self.files_tab_lines(),
+            DetailTab::Env => self.env_tab_lines(),
+        });
+        if !self.message.is_empty() {
+            lines.push(format!("  {}", self.message));
+        }
+        lines
+    }
+
+    fn sysinfo_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let Some(info) = &self.hwinfo else {
+            return lines;
+        };
+
+        {
+            let instance = HostInfo::get_instance();
+            let host = instance.lock().unwrap();
+            lines.push(format!("{}System{}", BOLD, RESET));
+            lines.push(format!("  OS:     {}", sanitize(&host.distro)));
+            lines.push(format!("  Kernel: {}", sanitize(&host.kernel)));
+            lines.push(format!("  Arch:   {}", sanitize(&host.arch)));
+        }
+
+        lines.push(format!("{}CPU topology{}", BOLD, RESET));
+        lines.push(format!(
+            "  Sockets: {}  Cores: {}  Threads: {}",
+            info.sockets, info.cores, info.threads
+        ));
+        for cache in &info.caches {
+            let name = match cache.cache_type.as_str() {
+                "Data" => format!("L{}d", cache.level),
+                "Instruction" => format!("L{}i", cache.level),
+                _ => format!("L{}", cache.level),
+            };
+            lines.push(format!(
+                "  {:<4} {} x {}",
+                name,
+                human_bytes(cache.size),
+                cache.instances
+            ));
+        }
+
+        lines.push(format!("{}Memory{}", BOLD, RESET));
+        if info.dimm_count > 0 {
+            lines.push(format!(
+                "  {} DIMMs, {} total",
+                info.dimm_count,
+                human_bytes(info.mem_total)
+            ));
+        } else {
+            lines.push(format!("  {} total", human_bytes(info.mem_total)));
+        }
+
+        lines.push(format!("{}GPU{}", BOLD, RESET));
+        if info.gpus.is_empty() {
+            lines.push("  No GPU found".to_owned());
+        }
+        for gpu in &info.gpus {
+            lines.push(format!("  {}", gpu));
+        }
+        lines
+    }
+
+    fn limits_lines(&self) -> Vec<String> {
+        let Some(info) = &self.limits else {
+            return Vec::new();
+        };
+        if info.limits.is_empty() {
+            return vec!["No limits available on this system".to_owned()];
+        }
+        info.limits
+            .iter()
+            .map(|limit| {
+                let max = match limit.max {
+                    0 => "unlimited".to_owned(),
+                    max => max.to_string(),
+                };
+                let percent = match limit.percent() {
+                    Some(percent) => format!("{:>5.1}%", percent),
+                    None => String::new(),
+                };
+                format!(
+                    "  {:<12} {:>10} / {:<10} {}",
+                    limit.name, limit.used, max, percent
+                )
+            })
+            .collect()
+    }
+
+    // 驱动名称列的宽度按当前页中最长的名称计算
+    fn sensors_lines(&self) -> Vec<String> {
+        let list = &self.sensors;
+        if list.sensors.is_empty() {
+            return vec!["No fan or voltage sensors found".to_owned()];
+        }
+        let page: Vec<_> = list
+            .sensors
+            .iter()
+            .skip(self.tab_offset)
+            .take(TAB_PAGE)
+            .collect();
+        let chip_w = page
+            .iter()
+            .map(|sensor| sensor.chip.chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(4);
+        let mut lines = vec![
+            format!(
+                "  Fans: {}  Voltages: {}",
+                list.count(SensorKind::Fan),
+                list.count(SensorKind::Voltage)
+            ),
+            format!(
+                "{}  {:<chip_w$} {:<16} {:>12}{}",
+                BOLD, "Chip", "Sensor", "Value", RESET
+            ),
+        ];
+        lines.extend(page.iter().map(|sensor| {
+            format!(
+                "  {:<chip_w$} {:<16} {:>12}",
+                sanitize(&sensor.chip),
+                fit(&sanitize(&sensor.label), 16),
+                sensor.value_text()
+            )
+        }));
+        if list.sensors.len() > TAB_PAGE {
+            lines.push(self.page_footer(list.sensors.len()));
+        }
+        lines
+    }
+
+    // 地址列的宽度按当前页中最长的地址计算，IPv6 地址可能很长
+    fn sockets_lines(&self) -> Vec<String> {
+        let list = match &self.sockets {
+            Ok(list) if !list.sockets.is_empty() => list,
+            Ok(_) => return vec!["No sockets".to_owned()],
+            Err(err) => return vec![format!("Could not read sockets: {}", err)],
+        };
+        let count = |pick: fn(&&SocketInfo) -> bool| list.sockets.iter().filter(pick).count();
+        let page: Vec<&SocketInfo> = list
+            .sockets
+            .iter()
+            .skip(self.tab_offset)
+            .take(TAB_PAGE)
+            .collect();
+        let width = |addr: fn(&SocketInfo) -> String| {
+            page.iter()
+                .map(|socket| addr(socket).chars().count())
+                .max()
+                .unwrap_or(0)
+                .max(6)
+        };
+        let local_w = width(|socket| addr_text(&socket.local));
+        let remote_w = width(|socket| addr_text(&socket.remote));
+        let mut lines = vec![
+            format!(
+                "  TCP: {}  UDP: {}  Listening: {}",
+                count(|socket| socket.proto.is_tcp()),
+                count(|socket| !socket.proto.is_tcp()),
+                count(|socket| socket.is_listening())
+            ),
+            format!(
+                "{}  {:<5} {:<11} {:<local_w$} {:<remote_w$} Process{}",
+                BOLD, "Proto", "State", "Local", "Remote", RESET
+            ),
+        ];
+        lines.extend(page.iter().map(|socket| {
+            let owner = match &socket.owner {
+                Some((pid, name)) => format!("{}/{}", pid, sanitize(name)),
+                None => "-".to_owned(),
+            };
+            format!(
+                "  {:<5} {:<11} {:<local_w$} {:<remote_w$} {}",
+                socket.proto.name(),
+                socket.state,
+                addr_text(&socket.local),
+                addr_text(&socket.remote),
+                owner
+            )
+        }));
+        if list.sockets.len() > TAB_PAGE {
+            lines.push(self.page_footer(list.sockets.len()));
+        }
+        lines
+    }
+
+    fn battery_lines(&self) -> Vec<String> {
+        let Some(bat) = &self.battery else {
+            return vec!["No battery found".to_owned()];
+        };
+        let mut lines = vec![
+            format!("{}{}{}", BOLD, boxes::battery_text(bat), RESET),
+            format!("  Status: {}", bat.status.name()),
+        ];
+        if let Some(seconds) = bat.seconds {
+            let what = match bat.status {
+                BatteryStatus::Charging => "Until full",
+                _ => "Remaining",
+            };
+            lines.push(format!(
+                "  {}: {}h {:02}m",
+                what,
+                seconds / 3600,
+                seconds / 60 % 60
+            ));
+        }
+        // 最近的电量历史，每个字符两个采样
+        let symbol = Config::get_instance()
+            .lock()
+            .unwrap()
+            .get_string("graph_symbol");
+        lines.push(String::new());
+        for line in graph::graph(&bat.history, 40, 5, &symbol, false) {
+            lines.push(format!("  {}", line));
+        }
+        lines
+    }
+
+    fn draw_overlay(title: &str, lines: &[String], width: u16, height: u16) -> String {
+        if width < 8 || height < 3 {
+            return String::new();
+        }
+        let inner = lines
+            .iter()
+            .map(|l| strip_ansi(l).chars().count())
+            .max()
+            .unwrap_or(0)
+            .max(title.chars().count() + 4)
+            .min(width.saturating_sub(4) as usize);
+        let box_w = inner as u16 + 4;
+        let box_h = (lines.len() as u16 + 2).min(height);
+        let x = width.saturating_sub(box_w) / 2 + 1;
+        let y = height.saturating_sub(box_h) / 2 + 1;
+
+        let border = Border {
+            symbols: box_symbols(),
+            line_color: "",
+            title,
+            bottom_title: "",
+        };
+        let mut out = create_box(x, y, box_w, box_h, &border);
+        for (i, line) in lines.iter().take(box_h as usize - 2).enumerate() {
+            let len = strip_ansi(line).chars().count();
+            let text = if len > inner {
+                fit(&strip_ansi(line), inner)
+            } else {
+                format!("{}{}", line, " ".repeat(inner - len))
+            };
+            out += &format!("{}{}", mv_to(y + 1 + i as u16, x + 2), text);
+        }
+        out
+    }
+
+    pub fn draw(&self, width: u16, height: u16) -> String {
+        match self.active {
+            Some(MenuKind::Main) => {
+                // 菜单上方显示版本和系统信息
+                let system = HostInfo::get_instance().lock().unwrap().get_system();
+                let mut lines = vec![
+                    format!("{}btop-rs {}{}", BOLD, env!("CARGO_PKG_VERSION"), RESET),
+                    sanitize(&system).into_owned(),
+                    String::new(),
+                ];
+                lines.extend(MAIN_ENTRIES.iter().enumerate().map(|(i, entry)| {
+                    if i == self.selected {
+                        format!("{}{}{}", REVERSE, entry, RESET)
+                    } else {
+                        entry.to_string()
+                    }
+                }));
+                Menu::draw_overlay("menu", &lines, width, height)
+            }
+            Some(MenuKind::Options) => {
+                Menu::draw_overlay("options", &self.options_lines(), width, height)
+            }
+            Some(MenuKind::SysInfo) => {
+                Menu::draw_overlay("system info", &self.sysinfo_lines(), width, height)
+            }
+            Some(MenuKind::Battery) => {
+                Menu::draw_overlay("battery", &self.battery_lines(), width, height)
+            }
+            Some(MenuKind::Limits) => {
+                Menu::draw_overlay("system limits", &self.limits_lines(), width, height)
+            }
+            Some(MenuKind::Sensors) => {
+                Menu::draw_overlay("sensors", &self.sensors_lines(), width, height)
+            }
+            Some(MenuKind::Sockets) => {
+                Menu::draw_overlay("connections", &self.sockets_lines(), width, height)
+            }
+            Some(MenuKind::ProcDetail) => {
+                Menu::draw_overlay("process", &self.detail_lines(), width, height)
+            }
+            Some(MenuKind::Signal) => {
+                Menu::draw_overlay("signal", &self.signal_lines(), width, height)
+            }
+            Some(MenuKind::ColorTest) => {
+                let dither = Config::get_instance()
+                    .lock()
+                    .unwrap()
+                    .get_bool("lowcolor_dither");
+                let (caps, depth) = {
+                    let instance = Term::get_instance();
+                    let term = instance.lock().unwrap();
+                    (term.get_caps(), term.get_colors())
+                };
+                colortest::draw(width, height, &caps, depth, dither)
+            }
+            Some(MenuKind::Import) => {
+                Menu::draw_overlay("import", &self.import_lines(), width, height)
+            }
+            None => String::new(),
+        }
+    }
+}
+
+// 连续的 cpu 编号合并为区间，例如 "0-3,6"
+fn cpu_list(cpus: &[usize]) -> String {
+    if cpus.is_empty() {
+        return "none".to_owned();
+    }
+    let mut ranges: Vec<(usize, usize)> = Vec::new();
+    for &cpu in cpus {
+        match ranges.last_mut() {
+            Some((_, end)) if *end + 1 == cpu => *end = cpu,
+            _ => ranges.push((cpu, cpu)),
+        }
+    }
+    ranges
+        .iter()
+        .map(|&(start, end)| match start == end {
+            true => start.to_string(),
+            false => format!("{}-{}", start, end),
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+// 每次采集后按 show_detailed/detailed_pid 打开或刷新进程详细视图
+pub fn update(runner: &Runner) {
+    let (show, pid, smaps) = {
+        let instance = Config::get_instance();
+        let config = instance.lock().unwrap();
+        (
+            config.get_bool("show_detailed"),
+            config.get_int("detailed_pid"),
+            config.get_bool("proc_info_smaps"),
+        )
+    };
+    let instance = Menu::get_instance();
+    let mut menu = instance.lock().unwrap();
+    let found = runner.procs.iter().find(|p| p.pid == pid).cloned();
+    menu.cpu_stats = runner.cpu.stats.iter().map(|(name, _)| *name).collect();
+    menu.cores = runner
+        .cpu
+        .core_percent
+        .len()
+        .saturating_sub(runner.cpu.offline.len());
+
+    match menu.active {
+        Some(MenuKind::Limits) => {
+            menu.limits = Some(limits::collect());
+            menu.redraw = true;
+            return;
+        }
+        Some(MenuKind::Sensors) => {
+            menu.sensors = sensors::collect();
+            menu.tab_offset = menu.tab_offset.min(menu.tab_len().saturating_sub(TAB_PAGE));
+            menu.redraw = true;
+            return;
+        }
+        Some(MenuKind::Sockets) => {
+            menu.sockets = socket::collect().map_err(|err| err.to_string());
+            // 连接减少后保持在最后一页
+            menu.tab_offset = menu.tab_offset.min(menu.tab_len().saturating_sub(TAB_PAGE));
+            menu.redraw = true;
+            return;
+        }
+        Some(MenuKind::Battery) => {
+            menu.battery = runner.battery.clone();
+            menu.redraw = true;
+            return;
+        }
+        Some(MenuKind::ProcDetail) => {}
+        None if show && pid > 0 => {
+            if found.is_none() {
+                // 保存的进程已不存在，不再打开详细视图
+                Config::get_instance()
+                    .lock()
+                    .unwrap()
+                    .set_bool("show_detailed", false);
+                return;
+            }
+            menu.show(MenuKind::ProcDetail);
+            menu.message.clear();
+        }
+        _ => return,
+    }
+
+    menu.core_percent = runner.cpu.core_percent.clone();
+    menu.ioprio = found.as_ref().and_then(|p| proc::get_ioprio(p.pid).ok());
+    if let Some(p) = &found {
+        menu.allowed = proc::get_affinity(p.pid).map_err(|err| err.to_string());
+    }
+    menu.pss = found
+        .as_ref()
+        .filter(|_| smaps)
+        .and_then(|p| proc::get_pss(p.pid).ok());
+    menu.detail = found;
+    menu.read_tab();
+    menu.redraw = true;
+}
@@ -0,0 +1,59 @@
+use std::time::{Duration, Instant};
+
+use crate::{
+    collect::collector::{CollectCtx, Registry},
+    util::alloc,
+};
+
+// 对每个采集器重复采集 iterations 次，输出平均耗时和内存分配次数
+pub fn run(iterations: u32) {
+    let iterations = iterations.max(1);
+    let mut registry = Registry::platform();
+    let ctx = CollectCtx {
+        check_temp: true,
+        proc_per_core: true,
+        show_battery: true,
+        show_disks: true,
+        show_disk_temps: true,
+        only_physical: true,
+        disks_filter: String::new(),
+        show_log: true,
+    };
+    // 第一次采集会填充缓存并扩容缓冲区，不计入统计
+    registry.collect_all(&ctx);
+
+    let counting = cfg!(feature = "alloc-count");
+    println!(
+        "{:<8} {:>12} {:>14} {:>14}",
+        "collector", "time/iter", "allocs/iter", "bytes/iter"
+    );
+    for collector in registry.iter_mut() {
+        let mut elapsed = Duration::ZERO;
+        let (allocs_before, bytes_before) = alloc::allocations();
+        for _ in 0..iterations {
+            let start = Instant::now();
+            let sample = collector.collect(&ctx);
+            elapsed += start.elapsed();
+            // 释放结果的开销不计入采集耗时
+            drop(sample);
+        }
+        let (allocs_after, bytes_after) = alloc::allocations();
+        let per_iter = |v: u64| {
+            if counting {
+                format!("{}", v / iterations as u64)
+            } else {
+                "n/a".to_owned()
+            }
+        };
+        println!(
+            "{:<8} {:>12?} {:>14} {:>14}",
+            collector.name(),
+            elapsed / iterations,
+            per_iter(allocs_after - allocs_before),
+            per_iter(bytes_after - bytes_before)
+        );
+    }
+    if !counting {
+        println!("build with --features alloc-count to count allocations");
+    }
+}
@@ -0,0 +1,262 @@
+use std::{fs, path::PathBuf, time::Instant};
+
+use log::{info, warn};
+
+pub mod bench;
+
+use crate::{
+    collect::collector::{CollectCtx, Registry, Sample},
+    config::config::Config,
+    shared::{
+        alert::{AlertMetric, Alerts},
+        battery::BatteryInfo,
+        cpu::CpuInfo,
+        disk::DiskInfo,
+        history::Histories,
+        klog::KlogInfo,
+        mem::MemInfo,
+        net::NetInfo,
+        proc::{ProcCounts, ProcFilter, ProcInfo, ProcOrigin, ProcSort},
+    },
+    term::Term,
+};
+
+// 负责按 update_ms 周期调用各个采集器，并保存最近一次的结果
+pub struct Runner {
+    registry: Registry,
+    // 编译后的 proc_filter 和对应的文本与正则模式，只在修改后重新编译
+    filter_text: (String, bool),
+    filter: Result<ProcFilter, String>,
+
+    pub cpu: CpuInfo,
+    pub mem: MemInfo,
+    pub disks: DiskInfo,
+    pub net: NetInfo,
+    pub procs: Vec<ProcInfo>,
+    pub proc_counts: ProcCounts,
+    pub battery: Option<BatteryInfo>,
+    pub klog: KlogInfo,
+    pub history: Histories,
+    // btop-rs 启动时的 OOM 计数，之后的增量显示在 mem box 上
+    oom_base: Option<u64>,
+    pub oom_kills: u64,
+    // 最近一次发现新的 OOM kill 的时间
+    pub oom_at: Option<Instant>,
+    pub alerts: Alerts,
+}
+
+impl Default for Runner {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Runner {
+    pub fn new() -> Self {
+        let registry = Registry::platform();
+        info!("registered collectors: {:?}", registry.names());
+        Runner {
+            registry,
+            filter_text: (String::new(), false),
+            filter: Ok(ProcFilter::default()),
+            cpu: CpuInfo::default(),
+            mem: MemInfo::default(),
+            disks: DiskInfo::default(),
+            net: NetInfo::default(),
+            procs: Vec::new(),
+            proc_counts: ProcCounts::default(),
+            battery: None,
+            klog: KlogInfo::default(),
+            history: Histories::new(),
+            oom_base: None,
+            oom_kills: 0,
+            oom_at: None,
+            alerts: Alerts::new(),
+        }
+    }
+
+    fn ctx(config: &Config) -> CollectCtx {
+        CollectCtx {
+            check_temp: config.get_bool("check_temp"),
+            proc_per_core: config.get_bool("proc_per_core"),
+            show_battery: config.get_bool("show_battery"),
+            show_disks: config.get_bool("show_disks"),
+            show_disk_temps: config.get_bool("show_disk_temps"),
+            only_physical: config.get_bool("only_physical"),
+            disks_filter: config.get_string("disks_filter"),
+            show_log: config.get_current_boxes().iter().any(|name| name == "log"),
+        }
+    }
+
+    // 暂停结束后丢弃一次采集结果，只用来重新建立各采集器计算速率的基准，
+    // 之后的速率和百分比不会把暂停的这段时间平均进去
+    pub fn prime(&mut self) {
+        let ctx = Runner::ctx(&Config::get_instance().lock().unwrap());
+        self.registry.collect_all(&ctx);
+    }
+
+    pub fn collect(&mut self) {
+        let (ctx, origin, filter_text, (sort, reversed), range, (alert, alert_exec)) = {
+            let config = Config::get_instance();
+            let config = config.lock().unwrap();
+            (
+                Runner::ctx(&config),
+                ProcOrigin::from_name(&config.get_string("proc_origin")).unwrap_or(ProcOrigin::All),
+                (
+                    config.get_string("proc_filter"),
+                    config.get_bool("proc_filter_regex"),
+                ),
+                (
+                    ProcSort::from_name(&config.get_string("proc_sorting"))
+                        .unwrap_or(ProcSort::Cpu),
+                    config.get_bool("proc_reversed"),
+                ),
+                config.get_int("graph_range").max(1) as usize,
+                (config.get_string("alert"), config.get_string("alert_exec")),
+            )
+        };
+        if filter_text != self.filter_text {
+            self.filter = match filter_text.1 {
+                true => ProcFilter::parse_regex(&filter_text.0),
+                false => ProcFilter::parse(&filter_text.0),
+            };
+            self.filter_text = filter_text;
+        }
+        // 输入到一半的过滤条件可能无法解析，这时不过滤
+        let no_filter = ProcFilter::default();
+        let filter = self.filter.as_ref().unwrap_or(&no_filter);
+        let (width, _) = Term::get_instance().lock().unwrap().get_size();
+        self.history.set_width(width, range);
+
+        for (name, result) in self.registry.collect_all(&ctx) {
+            match result {
+                Ok(Sample::Cpu(cpu)) => {
+                    self.history.push_cpu(&cpu);
+                    self.cpu = cpu;
+                }
+                Ok(Sample::Mem(mem)) => {
+                    if let Some(kills) = mem.oom_kills {
+                        let base = *self.oom_base.get_or_insert(kills);
+                        let since = kills.saturating_sub(base);
+                        if since > self.oom_kills {
+                            warn!("kernel OOM killer: {} kills since start", since);
+                            self.oom_at = Some(Instant::now());
+                        }
+                        self.oom_kills = since;
+                    }
+                    self.mem = mem;
+                }
+                Ok(Sample::Disks(disks)) => self.disks = disks,
+                Ok(Sample::Net(net)) => {
+                    self.history.push_net(&net);
+                    self.net = net;
+                }
+                Ok(Sample::Battery(battery)) => self.battery = battery,
+                Ok(Sample::Klog(klog)) => self.klog = klog,
+                Ok(Sample::Procs(mut procs)) => {
+                    self.proc_counts = ProcCounts::count(&procs);
+                    self.history.push_procs(&procs);
+                    procs.retain(|proc| origin.matches(proc) && filter.matches(proc));
+                    sort.sort(&mut procs);
+                    if reversed {
+                        procs.reverse();
+                    }
+                    self.procs = procs;
+                }
+                Err(err) => warn!("failed to collect {} info: {}", name, err),
+            }
+        }
+
+        self.alerts.set_rules(&alert);
+        let values: Vec<Option<f64>> = self
+            .alerts
+            .get_rules()
+            .iter()
+            .map(|rule| self.alert_value(rule.metric))
+            .collect();
+        for fired in self.alerts.update(&values, Instant::now()) {
+            warn!(
+                "alert {}: {} is {:.1}",
+                fired.rule.text,
+                fired.rule.metric.name(),
+                fired.value
+            );
+        }
+        self.alerts.exec(&alert_exec);
+    }
+
+    // 告警规则检查的当前值，没有这项数据时为 None
+    fn alert_value(&self, metric: AlertMetric) -> Option<f64> {
+        let percent =
+            |used: u64, total: u64| (total > 0).then(|| used as f64 * 100.0 / total as f64);
+        match metric {
+            AlertMetric::Cpu => Some(self.cpu.cpu_percent),
+            AlertMetric::Load => Some(self.cpu.load_avg[0]),
+            AlertMetric::Temp => self.cpu.temp,
+            AlertMetric::Power => self.cpu.power,
+            AlertMetric::Mem => percent(self.mem.used, self.mem.total),
+            AlertMetric::Swap => percent(self.mem.swap_used, self.mem.swap_total),
+            AlertMetric::Disk => self
+                .disks
+                .disks
+                .iter()
+                .filter_map(|disk| percent(disk.used, disk.total))
+                .reduce(f64::max),
+            // 字节每秒换算为 Mebibits 每秒
+            AlertMetric::Download => Some(self.net.rates("").0 as f64 * 8.0 / 1048576.0),
+            AlertMetric::Upload => Some(self.net.rates("").1 as f64 * 8.0 / 1048576.0),
+        }
+    }
+
+    // save_history 关闭或没有配置目录时为 None
+    fn history_file(config: &Config) -> Option<PathBuf> {
+        (config.get_bool("save_history") && !config.get_dir().as_os_str().is_empty())
+            .then(|| config.get_dir().join("btop-rs.history"))
+    }
+
+    // 启动时读取上次退出时保存的图形历史，需要在终端初始化之后调用
+    pub fn load_history(&mut self) {
+        let (path, range) = {
+            let config = Config::get_instance();
+            let config = config.lock().unwrap();
+            (
+                Runner::history_file(&config),
+                config.get_int("graph_range").max(1) as usize,
+            )
+        };
+        let Some(path) = path else {
+            return;
+        };
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                let (width, _) = Term::get_instance().lock().unwrap().get_size();
+                self.history.set_width(width, range);
+                self.history.load(&text);
+                info!("loaded graph history from {:?}", path);
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {}
+            Err(err) => warn!("failed to read graph history {:?}: {}", path, err),
+        }
+    }
+
+    pub fn save_history(&self) {
+        let (path, limit) = {
+            let config = Config::get_instance();
+            let config = config.lock().unwrap();
+            (
+                Runner::history_file(&config),
+                config.get_int("save_history_size").max(1) as usize,
+            )
+        };
+        if let Some(path) = path {
+            if let Err(err) = fs::write(&path, self.history.save(limit)) {
+                warn!("failed to save graph history {:?}: {}", path, err);
+            }
+        }
+    }
+
+    // proc_filter 无法解析或正则表达式无法编译的原因
+    pub fn get_filter_error(&self) -> Option<&str> {
+        self.filter.as_ref().err().map(String::as_str)
+    }
+}
@@ -0,0 +1,572 @@
+// 端到端的画面测试：用固定的数据在固定的终端尺寸下绘制主界面，去掉样式后与
+// golden 目录中保存的画面比较。布局有意修改后使用 GOLDEN_UPDATE=1 cargo test 重新生成
+use std::{
+    env, fs,
+    path::PathBuf,
+    sync::{Mutex, MutexGuard},
+    time::{Duration, Instant},
+};
+
+use super::Screen;
+use crate::{
+    config::config::{Config, ConfigTransaction},
+    draw::{frame::Frame, strip_ansi},
+    runner::Runner,
+    shared::{
+        cpu::CpuInfo,
+        disk::{DiskInfo, DiskStat, RaidStatus},
+        host::HostInfo,
+        klog::KlogEntry,
+        mem::MemInfo,
+        net::{NetInfo, NetPackets, NetStat},
+        proc::{ProcCounts, ProcInfo},
+        socket::{SocketInfo, SocketProto},
+    },
+    term::Term,
+};
+
+// 配置和终端状态是全局单例，画面测试需要串行执行
+static LOCK: Mutex<()> = Mutex::new(());
+
+// 每个测试开始前恢复的配置，时钟关闭以免画面随时间变化
+const BASE: &[(&str, &str)] = &[
+    ("shown_boxes", "cpu mem net proc"),
+    ("clock_format", ""),
+    ("custom_cpu_name", "Test CPU"),
+    ("cpu_bottom", "False"),
+    ("mem_below_net", "False"),
+    ("proc_left", "False"),
+    ("proc_follow", "False"),
+    ("proc_start", "0"),
+    ("proc_selected", "0"),
+    ("net_iface", ""),
+    ("rounded_corners", "True"),
+    ("graph_symbol_cpu", "default"),
+    ("graph_symbol", "braille"),
+    ("cpu_single_graph", "False"),
+    ("show_host", "False"),
+    ("update_ms", "2000"),
+    ("net_overhead", "False"),
+    ("proc_full_cmd", "False"),
+    ("graph_range", "1"),
+    ("cpu_graph_stats", "False"),
+    ("net_graph_stats", "False"),
+    ("adaptive_layout", "False"),
+    ("cpu_box_height_pct", "30"),
+    ("proc_box_width_pct", "50"),
+    ("show_disks", "False"),
+    ("net_packets", "False"),
+    ("show_system", "False"),
+    ("show_power", "False"),
+    ("mem_numa", "False"),
+];
+
+fn setup(settings: &[(&str, &str)]) -> MutexGuard<'static, ()> {
+    let guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+    Term::get_instance().lock().unwrap().set_utf8(true);
+    let mut tx = ConfigTransaction::new();
+    for (key, value) in BASE.iter().chain(settings) {
+        tx.set(key, value);
+    }
+    let instance = Config::get_instance();
+    let mut config = instance.lock().unwrap();
+    if let Err(err) = config.apply(&tx) {
+        panic!("invalid test settings: {}", err);
+    }
+    guard
+}
+
+fn runner(cores: usize) -> Runner {
+    let mut runner = Runner::new();
+    runner.cpu = CpuInfo {
+        cpu_percent: 37.5,
+        core_percent: (0..cores).map(|i| (i * 37 % 101) as f64).collect(),
+        load_avg: [1.0, 0.5, 0.25],
+        stats: vec![("total", 37.5), ("user", 20.0)],
+        ..Default::default()
+    };
+    runner.mem = MemInfo {
+        total: 16 << 30,
+        available: 10 << 30,
+        used: 6 << 30,
+        free: 4 << 30,
+        cached: 5 << 30,
+        swap_total: 2 << 30,
+        swap_used: 256 << 20,
+        swap_free: (2 << 30) - (256 << 20),
+        ..Default::default()
+    };
+    runner.net = NetInfo {
+        interfaces: vec![
+            NetStat {
+                name: "eth0".to_owned(),
+                rx_rate: 1 << 20,
+                tx_rate: 64 << 10,
+                ..Default::default()
+            },
+            NetStat {
+                name: "lo".to_owned(),
+                rx_rate: 512,
+                tx_rate: 512,
+                ..Default::default()
+            },
+        ],
+    };
+    runner.procs = (1..=40)
+        .map(|pid| ProcInfo {
+            pid,
+            ppid: 1,
+            name: format!("proc{}", pid),
+            cmd: format!("/usr/bin/proc{} --flag", pid),
+            user: "root".to_owned(),
+            state: 'S',
+            threads: 1,
+            mem: (pid as u64) << 20,
+            cpu_p: (40 - pid) as f64 / 4.0,
+            ..Default::default()
+        })
+        .collect();
+    runner.proc_counts = ProcCounts::count(&runner.procs);
+    runner.battery = None;
+    // 填入几次采样让 cpu 图形有内容
+    for _ in 0..8 {
+        runner.history.push_cpu(&runner.cpu);
+    }
+    runner
+}
+
+fn render(runner: &Runner, width: u16, height: u16) -> String {
+    render_screen(&mut Screen::new(), runner, width, height)
+}
+
+// 需要预先设置 Screen 状态（端口列表、警告横幅等）时使用
+fn render_screen(screen: &mut Screen, runner: &Runner, width: u16, height: u16) -> String {
+    let mut frame = Frame::new(width, height);
+    frame.apply(&screen.draw(runner, width, height));
+    frame.text()
+}
+
+// 大部分画面测试的流程：在 BASE 加 settings 的配置下，用 prepare 修改 4 核的测试数据，
+// 再按每个 (名称, 宽, 高) 绘制并与 golden 比较，名称加上尺寸就是 golden 文件名。
+// 返回绘制的画面，用于再检查画面中必须出现的内容
+fn golden(
+    settings: &[(&str, &str)],
+    frames: &[(&str, u16, u16)],
+    prepare: impl FnOnce(&mut Runner),
+) -> Vec<String> {
+    let _guard = setup(settings);
+    let mut runner = runner(4);
+    prepare(&mut runner);
+    frames
+        .iter()
+        .map(|&(name, width, height)| {
+            let frame = render(&runner, width, height);
+            check(&format!("{}_{}x{}", name, width, height), &frame);
+            frame
+        })
+        .collect()
+}
+
+fn check(name: &str, frame: &str) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("src/screen/golden")
+        .join(format!("{}.txt", name));
+    if env::var_os("GOLDEN_UPDATE").is_some() {
+        fs::write(&path, frame).unwrap();
+        return;
+    }
+    let expected = fs::read_to_string(&path)
+        .unwrap_or_else(|_| panic!("missing {:?}, run with GOLDEN_UPDATE=1", path));
+    if expected == frame {
+        return;
+    }
+    let line = expected
+        .lines()
+        .zip(frame.lines())
+        .position(|(a, b)| a != b)
+        .unwrap_or(expected.lines().count().min(frame.lines().count()));
+    panic!(
+        "frame {} differs at line {}\nexpected: {:?}\n  actual: {:?}\n\n{}",
+        name,
+        line + 1,
+        expected.lines().nth(line).unwrap_or_default(),
+        frame.lines().nth(line).unwrap_or_default(),
+        frame
+    );
+}
+
+#[test]
+fn default_layout() {
+    // 重新生成 golden 时不会发现列被截断，这里单独检查
+    let frames = golden(&[], &[("default", 100, 30)], |_| {});
+    assert!(frames[0].contains("MemB Cpu%"));
+    assert!(frames[0].contains(" 9.8│"));
+    assert!(frames[0].contains("256.0 MiB / 2.0 GiB"));
+}
+
+#[test]
+fn alternate_positions() {
+    let settings = [
+        ("cpu_bottom", "True"),
+        ("proc_left", "True"),
+        ("mem_below_net", "True"),
+    ];
+    golden(&settings, &[("alternate", 100, 30)], |_| {});
+}
+
+#[test]
+fn small_terminal() {
+    golden(&[], &[("small", 60, 20)], |_| {});
+}
+
+#[test]
+fn proc_selection() {
+    let settings = [
+        ("shown_boxes", "proc"),
+        ("proc_selected", "3"),
+        ("proc_follow", "True"),
+    ];
+    golden(&settings, &[("proc", 80, 24)], |_| {});
+}
+
+#[test]
+fn proc_io_columns() {
+    // 宽度不够时不显示读写列
+    let frames = [("proc_io", 100, 12), ("proc_io", 60, 12)];
+    golden(&[("shown_boxes", "proc")], &frames, |runner| {
+        // 奇数 pid 模拟没有权限读取 I/O 的其它用户进程
+        for proc in runner.procs.iter_mut().filter(|proc| proc.pid % 2 == 0) {
+            proc.io_rate = Some(((proc.pid as u64) << 16, (proc.pid as u64) << 10));
+        }
+    });
+}
+
+#[test]
+fn proc_full_cmd() {
+    let settings = [("shown_boxes", "proc"), ("proc_full_cmd", "True")];
+    golden(&settings, &[("proc_full_cmd", 80, 12)], |runner| {
+        // 内核线程没有命令行
+        runner.procs[1].cmd.clear();
+    });
+}
+
+#[test]
+fn many_cores() {
+    let _guard = setup(&[("shown_boxes", "cpu"), ("cpu_single_graph", "True")]);
+    check("cores_40_120x16", &render(&runner(40), 120, 16));
+    check("cores_96_120x16", &render(&runner(96), 120, 16));
+}
+
+#[test]
+fn net_overhead() {
+    let settings = [("shown_boxes", "net"), ("net_overhead", "True")];
+    golden(&settings, &[("net_overhead", 60, 8)], |runner| {
+        runner.net.interfaces.push(NetStat {
+            name: "wg0".to_owned(),
+            rx_rate: 1000 << 10,
+            tx_rate: 60 << 10,
+            ..Default::default()
+        });
+    });
+}
+
+#[test]
+fn graph_stats() {
+    let settings = [
+        ("shown_boxes", "cpu net"),
+        ("cpu_graph_stats", "True"),
+        ("net_graph_stats", "True"),
+    ];
+    golden(&settings, &[("graph_stats", 80, 16)], |runner| {
+        for i in 0..8u64 {
+            runner.cpu.stats[0].1 = (i * 10) as f64;
+            runner.history.push_cpu(&runner.cpu);
+            runner.net.interfaces[0].rx_rate = i << 20;
+            runner.net.interfaces[0].tx_rate = (8 - i) << 10;
+            runner.history.push_net(&runner.net);
+        }
+    });
+}
+
+#[test]
+fn box_proportions() {
+    let settings = [("cpu_box_height_pct", "20"), ("proc_box_width_pct", "70")];
+    // 较窄的一侧放不下 mem/net 时显示提示
+    let frames = [("proportions", 100, 30), ("too_small_proportions", 60, 20)];
+    golden(&settings, &frames, |_| {});
+}
+
+#[test]
+fn narrow_layout() {
+    let frames = [("narrow", 80, 24), ("too_small_narrow", 30, 12)];
+    golden(&[("adaptive_layout", "True")], &frames, |_| {});
+}
+
+#[test]
+fn narrow_proc_left() {
+    let settings = [("adaptive_layout", "True"), ("proc_left", "True")];
+    golden(&settings, &[("narrow_proc_left", 60, 20)], |_| {});
+}
+
+#[test]
+fn show_disks() {
+    // 磁盘列放不下时只显示内存
+    let frames = [("disks", 100, 30), ("disks_narrow", 80, 24)];
+    let frames = golden(&[("show_disks", "True")], &frames, |runner| {
+        let disk = |device: &str, mount: &str, used: u64, io_percent| DiskStat {
+            io_percent,
+            ..DiskStat::new(
+                device.to_owned(),
+                mount.to_owned(),
+                100 << 30,
+                used << 30,
+                (100 - used) << 30,
+            )
+        };
+        runner.disks = DiskInfo {
+            disks: vec![
+                disk("/dev/nvme0n1p2", "/", 42, Some(7.0)),
+                disk("/dev/md0", "/home/user", 90, None),
+            ],
+        };
+        // 正在重建的降级阵列
+        runner.disks.disks[1].raid = Some(RaidStatus {
+            degraded: true,
+            sync_percent: Some(12.5),
+            ..Default::default()
+        });
+    });
+    assert!(frames[0].contains("256.0M/2.0G"));
+    assert!(frames[1].contains("256.0 MiB / 2.0 GiB"));
+}
+
+#[test]
+fn disk_temps() {
+    golden(
+        &[("show_disks", "True")],
+        &[("disk_temps", 100, 30)],
+        |runner| {
+            let mut disk = DiskStat::new(
+                "/dev/nvme0n1p2".to_owned(),
+                "/".to_owned(),
+                100 << 30,
+                42 << 30,
+                58 << 30,
+            );
+            disk.temp = Some(41.0);
+            runner.disks = DiskInfo { disks: vec![disk] };
+        },
+    );
+}
+
+#[test]
+fn net_packets() {
+    golden(
+        &[("net_packets", "True")],
+        &[("net_packets", 100, 30)],
+        |runner| {
+            runner.net.interfaces[0].packets = Some(NetPackets {
+                rx_packets: 1_000_000,
+                tx_packets: 400_000,
+                rx_errors: 3,
+                rx_drops: 1_250,
+                rx_rate: 820,
+                tx_rate: 45,
+                ..Default::default()
+            });
+        },
+    );
+}
+
+#[test]
+fn listening() {
+    let _guard = setup(&[]);
+    let socket = |proto, local: &str, remote: &str, owner: Option<(i32, &str)>| SocketInfo {
+        proto,
+        local: local.parse().unwrap(),
+        remote: remote.parse().unwrap(),
+        state: "LISTEN",
+        inode: 0,
+        owner: owner.map(|(pid, name)| (pid, name.to_owned())),
+    };
+    let sockets = vec![
+        socket(
+            SocketProto::Tcp,
+            "0.0.0.0:22",
+            "0.0.0.0:0",
+            Some((812, "sshd")),
+        ),
+        socket(SocketProto::Tcp6, "[::]:22", "[::]:0", Some((812, "sshd"))),
+        socket(SocketProto::Udp, "127.0.0.53:53", "0.0.0.0:0", None),
+    ];
+    let mut screen = Screen::new();
+    screen.listening = Some((Instant::now(), Ok(sockets)));
+    check(
+        "listening_100x30",
+        &render_screen(&mut screen, &runner(4), 100, 30),
+    );
+}
+
+#[test]
+fn show_system() {
+    let _guard = setup(&[("show_system", "True")]);
+    {
+        let instance = HostInfo::get_instance();
+        let mut host = instance.lock().unwrap();
+        host.distro = "Debian GNU/Linux 12".to_owned();
+        host.kernel = "6.1.0".to_owned();
+        host.arch = "x86_64".to_owned();
+    }
+    check("show_system_100x30", &render(&runner(4), 100, 30));
+}
+
+#[test]
+fn show_power() {
+    golden(
+        &[("show_power", "True")],
+        &[("show_power", 100, 30)],
+        |runner| {
+            for watts in [12.0, 18.5, 30.0, 42.0, 35.5, 20.0, 45.2] {
+                runner.cpu.power = Some(watts);
+                runner.history.push_cpu(&runner.cpu);
+            }
+        },
+    );
+}
+
+// 焦点在 mem box 时 n 切换每个 NUMA 节点的显示
+#[test]
+fn numa_key_toggles() {
+    let _guard = setup(&[("shown_boxes", "mem cpu")]);
+    let runner = runner(4);
+    let mut screen = Screen::new();
+    let numa = || Config::get_instance().lock().unwrap().get_bool("mem_numa");
+    assert!(screen.process("n", &runner));
+    assert!(numa());
+    assert!(screen.process("n", &runner));
+    assert!(!numa());
+    // 焦点在其他 box 时不处理
+    screen.cycle_focus(true);
+    assert!(!screen.process("n", &runner));
+    assert!(!numa());
+}
+
+#[test]
+fn hugepages() {
+    golden(&[], &[("hugepages", 100, 30)], |runner| {
+        runner.mem.shmem = 768 << 20;
+        runner.mem.hugepages_total = 4 << 30;
+        runner.mem.hugepages_free = 1 << 30;
+    });
+}
+
+#[test]
+fn oom_kills() {
+    golden(&[], &[("oom_kills", 100, 30)], |runner| {
+        runner.oom_kills = 3
+    });
+}
+
+#[test]
+fn alert_border() {
+    let _guard = setup(&[]);
+    let mut runner = runner(4);
+    let plain = Screen::new().draw(&runner, 100, 30);
+    runner.alerts.set_rules("cpu>30 for 10s, mem>90");
+    // 条件满足的时间还不够长时不触发
+    let now = Instant::now();
+    assert!(runner
+        .alerts
+        .update(&[Some(37.5), Some(37.5)], now)
+        .is_empty());
+    let fired = runner
+        .alerts
+        .update(&[Some(37.5), Some(37.5)], now + Duration::from_secs(10));
+    assert_eq!(fired.len(), 1);
+    assert_eq!(fired[0].rule.text, "cpu>30 for 10s");
+    // 告警只改变边框的颜色
+    let alert = Screen::new().draw(&runner, 100, 30);
+    assert_ne!(alert, plain);
+    assert_eq!(strip_ansi(&alert), strip_ansi(&plain));
+}
+
+#[test]
+fn log_box() {
+    let settings = [("shown_boxes", "cpu mem net proc log")];
+    golden(&settings, &[("log", 100, 30)], |runner| {
+        runner.klog.entries = [
+            (6, 0.0, "Linux version 6.1.0 (gcc 12.2.0)"),
+            (5, 0.52, "audit: initializing netlink subsys (disabled)"),
+            (
+                4,
+                3.1,
+                "ACPI Warning: SystemIO range conflicts with OpRegion",
+            ),
+            (3, 12.25, "nvme nvme0: I/O 12 QID 3 timeout, aborting"),
+            (7, 15.0, "usb 1-1: new high-speed USB device number 2"),
+            (
+                6,
+                1234.5,
+                "e1000e: eth0 NIC Link is Up 1000 Mbps Full Duplex",
+            ),
+        ]
+        .iter()
+        .map(|&(level, time, message)| KlogEntry {
+            level,
+            time,
+            message: message.to_owned(),
+        })
+        .collect();
+    });
+}
+
+#[test]
+fn startup_banner() {
+    let _guard = setup(&[]);
+    let runner = runner(4);
+    let warnings: Vec<String> = (1..=8)
+        .map(|line| format!("Line {} \"bad = value\": Unknown config name: bad", line))
+        .collect();
+    let mut screen = Screen::new();
+    screen.set_banner(&warnings[..2]);
+    check(
+        "banner_100x30",
+        &render_screen(&mut screen, &runner, 100, 30),
+    );
+    // 放不下的警告只显示数量
+    screen.set_banner(&warnings);
+    check("banner_80x16", &render_screen(&mut screen, &runner, 80, 16));
+    // 关闭后恢复原来的画面
+    assert!(screen.dismiss_banner());
+    assert!(!screen.dismiss_banner());
+    assert_eq!(
+        strip_ansi(&screen.draw(&runner, 100, 30)),
+        strip_ansi(&Screen::new().draw(&runner, 100, 30))
+    );
+}
+
+#[test]
+fn too_small() {
+    let _guard = setup(&[]);
+    check("too_small_50x12", &render(&runner(4), 50, 12));
+    // 任何尺寸都不能因为减法溢出而 panic
+    for (width, height) in [(0, 0), (1, 1), (5, 2), (59, 20), (60, 13)] {
+        render(&runner(4), width, height);
+    }
+}
+
+#[test]
+fn ascii_borders() {
+    let _guard = setup(&[]);
+    Term::get_instance().lock().unwrap().set_utf8(false);
+    let frame = render(&runner(4), 80, 24);
+    Term::get_instance().lock().unwrap().set_utf8(true);
+    check("ascii_80x24", &frame);
+}
+
+#[test]
+fn strip_ansi_keeps_visible_text() {
+    assert_eq!(strip_ansi("\x1b[1m\x1b[38;2;1;2;3mcpu\x1b[0m ok"), "cpu ok");
+    assert_eq!(strip_ansi("\x1b[5;10fA\x1b[2JB"), "AB");
+    assert_eq!(strip_ansi("plain ─┤text├"), "plain ─┤text├");
+}
@@ -0,0 +1,959 @@
+use std::{
+    collections::BTreeSet,
+    io,
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+#[cfg(test)]
+mod golden;
+
+use log::info;
+
+use crate::{
+    collect::{proc, socket},
+    config::config::Config,
+    draw::{
+        self,
+        boxes::{self, BorderStyle, BoxKind, BoxRect, CpuGraph, CpuHeader, Layout, ProcView},
+        clock,
+        frame::Frame,
+    },
+    export::screenshot::{self, ScreenshotFormat},
+    input,
+    menu::{Menu, MenuKind},
+    runner::Runner,
+    shared::{
+        host::HostInfo,
+        proc::{ProcInfo, ProcSort},
+        socket::SocketInfo,
+    },
+    term::{output::Output, Term},
+};
+
+// 主界面状态：当前获得焦点的 box 以及各 box 内的导航位置
+pub struct Screen {
+    focus: Option<BoxKind>,
+    cpu_offset: usize,
+    rects: Vec<BoxRect>,
+    // 上一次绘制的时钟文本，变化时才需要重绘
+    clocks: [String; 3],
+    // 调整 nice 值和暂停进程的结果，显示在 proc box 底部边框上，一段时间后消失
+    proc_message: Option<(String, Instant)>,
+    // 已经设置但还没有被采集到的 nice 值，连续按键时在此基础上调整
+    renice_pending: Option<(i32, i32)>,
+    // 已经发送 SIGSTOP/SIGCONT 但还没有被采集到的状态，true 表示已暂停
+    freeze_pending: Option<(i32, bool)>,
+    // 用空格标记的进程，批量发送信号时使用
+    tagged: BTreeSet<i32>,
+    // 正在 proc box 标题中输入 proc_filter
+    filtering: bool,
+    // net box 中的监听端口视图和读取的时间，扫描所有进程的描述符代价较高，只在打开和按 r 时读取
+    listening: Option<(Instant, Result<Vec<SocketInfo>, String>)>,
+    // log box 向上滚动的行数，为 0 时跟随最新的日志
+    log_offset: usize,
+    // 启动时的警告，显示在顶部直到按下任意键
+    banner: Vec<String>,
+    // 上一次输出到终端的画面，用于只重绘变化的部分
+    frame: Option<Frame>,
+    output: Output,
+    pub redraw: bool,
+}
+
+impl Screen {
+    fn new() -> Self {
+        Screen {
+            focus: None,
+            cpu_offset: 0,
+            rects: Vec::new(),
+            clocks: Default::default(),
+            proc_message: None,
+            renice_pending: None,
+            freeze_pending: None,
+            tagged: BTreeSet::new(),
+            filtering: false,
+            listening: None,
+            log_offset: 0,
+            banner: Vec::new(),
+            frame: None,
+            output: Output::new(),
+            redraw: false,
+        }
+    }
+
+    pub fn get_instance() -> Arc<Mutex<Screen>> {
+        static INSTANCE: OnceLock<Arc<Mutex<Screen>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(Screen::new())))
+            .clone()
+    }
+
+    fn shown() -> Vec<BoxKind> {
+        Config::get_instance()
+            .lock()
+            .unwrap()
+            .get_current_boxes()
+            .iter()
+            .filter_map(|name| BoxKind::from_name(name))
+            .collect()
+    }
+
+    // 焦点所在的 box 被隐藏时回到默认焦点：优先 proc，否则第一个显示的 box
+    pub fn get_focus(&self, shown: &[BoxKind]) -> Option<BoxKind> {
+        match self.focus {
+            Some(kind) if shown.contains(&kind) => Some(kind),
+            _ if shown.contains(&BoxKind::Proc) => Some(BoxKind::Proc),
+            _ => shown.first().copied(),
+        }
+    }
+
+    pub fn cycle_focus(&mut self, forward: bool) {
+        let shown = Screen::shown();
+        let Some(current) = self.get_focus(&shown) else {
+            return;
+        };
+        let pos = shown.iter().position(|kind| *kind == current).unwrap_or(0);
+        let next = if forward {
+            (pos + 1) % shown.len()
+        } else {
+            (pos + shown.len() - 1) % shown.len()
+        };
+        self.focus = Some(shown[next]);
+        self.redraw = true;
+    }
+
+    fn rows(&self, kind: BoxKind) -> usize {
+        self.rects
+            .iter()
+            .find(|rect| rect.kind == kind)
+            .map(|rect| rect.inner_height().saturating_sub(1))
+            .unwrap_or(0)
+    }
+
+    // log box 没有表头，所有行都用来显示日志
+    fn log_rows(&self) -> usize {
+        self.rects
+            .iter()
+            .find(|rect| rect.kind == BoxKind::Log)
+            .map_or(0, BoxRect::inner_height)
+    }
+
+    // 导航按键作用于获得焦点的 box，返回 false 表示按键未被处理
+    pub fn process(&mut self, key: &str, runner: &Runner) -> bool {
+        let focus = self.get_focus(&Screen::shown());
+        let handled = match (focus, key) {
+            // 鼠标点击不受焦点影响
+            (_, key) if key.starts_with("click:") => self.click(key, runner),
+            (Some(BoxKind::Cpu), "up" | "k") => {
+                self.cpu_offset = self.cpu_offset.saturating_sub(1);
+                true
+            }
+            (Some(BoxKind::Cpu), "down" | "j") => {
+                self.cpu_offset += 1;
+                true
+            }
+            (Some(BoxKind::Net), "up" | "down" | "left" | "right" | "k" | "j") => {
+                let forward = matches!(key, "down" | "right" | "j");
+                let instance = Config::get_instance();
+                let mut config = instance.lock().unwrap();
+                let iface = next_iface(runner, &config.get_string("net_iface"), forward);
+                config.set_string("net_iface", &iface);
+                true
+            }
+            (Some(kind @ (BoxKind::Cpu | BoxKind::Net)), "s") => {
+                let key = match kind {
+                    BoxKind::Cpu => "cpu_graph_stats",
+                    _ => "net_graph_stats",
+                };
+                let instance = Config::get_instance();
+                let mut config = instance.lock().unwrap();
+                let stats = !config.get_bool(key);
+                config.set_bool(key, stats);
+                info!("{}: {}", key, stats);
+                true
+            }
+            (Some(BoxKind::Net), "o") => {
+                let instance = Config::get_instance();
+                let mut config = instance.lock().unwrap();
+                let overhead = !config.get_bool("net_overhead");
+                config.set_bool("net_overhead", overhead);
+                true
+            }
+            (Some(BoxKind::Net), "l") => {
+                self.listening = match self.listening {
+                    Some(_) => None,
+                    None => Some(Screen::read_listening()),
+                };
+                true
+            }
+            (Some(BoxKind::Net), "r") if self.listening.is_some() => {
+                self.listening = Some(Screen::read_listening());
+                true
+            }
+            (Some(BoxKind::Net), "e") => {
+                let instance = Config::get_instance();
+                let mut config = instance.lock().unwrap();
+                let packets = !config.get_bool("net_packets");
+                config.set_bool("net_packets", packets);
+                info!("net_packets: {}", packets);
+                true
+            }
+            (Some(BoxKind::Mem), "n") => {
+                let instance = Config::get_instance();
+                let mut config = instance.lock().unwrap();
+                let numa = !config.get_bool("mem_numa");
+                config.set_bool("mem_numa", numa);
+                info!("mem_numa: {}", numa);
+                true
+            }
+            (
+                Some(BoxKind::Log),
+                "up" | "down" | "k" | "j" | "page_up" | "page_down" | "home" | "end",
+            ) => {
+                let rows = self.log_rows();
+                let last = runner.klog.entries.len().saturating_sub(rows);
+                self.log_offset = match key {
+                    "up" | "k" => self.log_offset + 1,
+                    "down" | "j" => self.log_offset.saturating_sub(1),
+                    "page_up" => self.log_offset + rows,
+                    "page_down" => self.log_offset.saturating_sub(rows),
+                    "home" => last,
+                    _ => 0,
+                }
+                .min(last);
+                true
+            }
+            (Some(BoxKind::Proc), "f7" | "f8") => self.renice(key == "f7", runner),
+            (Some(BoxKind::Proc), "z") => self.freeze(runner),
+            // 与 htop 相同，标记后移动到下一个进程
+            (Some(BoxKind::Proc), "space") => {
+                let (_, selected) = Screen::get_proc_position();
+                match selected.checked_sub(1).and_then(|i| runner.procs.get(i)) {
+                    Some(p) => {
+                        if !self.tagged.remove(&p.pid) {
+                            self.tagged.insert(p.pid);
+                        }
+                        Screen::process_proc("down", runner, self.rows(BoxKind::Proc))
+                    }
+                    None => false,
+                }
+            }
+            (Some(BoxKind::Proc), "U") => {
+                self.tagged.clear();
+                true
+            }
+            (Some(BoxKind::Proc), "left" | "right") => {
+                let sort = {
+                    let instance = Config::get_instance();
+                    let config = instance.lock().unwrap();
+                    Screen::get_sort(&config)
+                };
+                Screen::sort_by(sort.next(key == "right"), false)
+            }
+            (Some(BoxKind::Proc), "c") => {
+                let instance = Config::get_instance();
+                let mut config = instance.lock().unwrap();
+                let full_cmd = !config.get_bool("proc_full_cmd");
+                config.set_bool("proc_full_cmd", full_cmd);
+                info!("proc full cmd: {}", full_cmd);
+                true
+            }
+            (Some(BoxKind::Proc), "f" | "/") => {
+                self.filtering = true;
+                true
+            }
+            (Some(BoxKind::Proc), _) => Screen::process_proc(key, runner, self.rows(BoxKind::Proc)),
+            _ => false,
+        };
+        if handled {
+            self.clamp(runner);
+            self.redraw = true;
+        }
+        handled
+    }
+
+    fn get_sort(config: &Config) -> ProcSort {
+        ProcSort::from_name(&config.get_string("proc_sorting")).unwrap_or(ProcSort::Cpu)
+    }
+
+    // toggle 为 true 且已经按 sort 排序时反转顺序，修改后立即重新采集
+    fn sort_by(sort: ProcSort, toggle: bool) -> bool {
+        let instance = Config::get_instance();
+        let mut config = instance.lock().unwrap();
+        if toggle && Screen::get_sort(&config) == sort {
+            let reversed = !config.get_bool("proc_reversed");
+            config.set_bool("proc_reversed", reversed);
+            info!("proc reversed: {}", reversed);
+        } else {
+            config.set_string("proc_sorting", sort.name());
+            info!("proc sorting: {}", sort.name());
+        }
+        drop(config);
+        crate::Global::get_instance().lock().unwrap().set_refresh();
+        true
+    }
+
+    // 点击 proc box 表头中的一列时按这一列排序，再次点击同一列时反转顺序
+    fn click(&mut self, key: &str, runner: &Runner) -> bool {
+        let Some((x, y)) = input::parse_click(key) else {
+            return false;
+        };
+        let Some(rect) = self.rects.iter().find(|rect| rect.kind == BoxKind::Proc) else {
+            return false;
+        };
+        if y != rect.y + 1 || x <= rect.x {
+            return false;
+        }
+        let (start, _) = Screen::get_proc_position();
+        let col = (x - rect.x - 1) as usize;
+        let full_cmd = Config::get_instance()
+            .lock()
+            .unwrap()
+            .get_bool("proc_full_cmd");
+        match boxes::proc_columns(rect, &runner.procs, start, full_cmd)
+            .iter()
+            .find(|column| column.contains(col))
+            .and_then(|column| column.sort())
+        {
+            Some(sort) => Screen::sort_by(sort, true),
+            None => false,
+        }
+    }
+
+    pub fn is_filtering(&self) -> bool {
+        self.filtering
+    }
+
+    // 回车结束输入并保留过滤条件，escape 清除过滤条件，tab 切换正则模式，修改后立即重新采集
+    pub fn filter_input(&mut self, key: &str) {
+        let instance = Config::get_instance();
+        let mut config = instance.lock().unwrap();
+        let old = config.get_string("proc_filter");
+        let mut filter = old.clone();
+        match key {
+            "tab" => {
+                let regex = !config.get_bool("proc_filter_regex");
+                config.set_bool("proc_filter_regex", regex);
+                drop(config);
+                info!("proc filter regex: {}", regex);
+                self.redraw = true;
+                crate::Global::get_instance().lock().unwrap().set_refresh();
+                return;
+            }
+            "enter" | "down" => self.filtering = false,
+            "escape" => {
+                self.filtering = false;
+                filter.clear();
+            }
+            "backspace" => {
+                filter.pop();
+            }
+            "space" => filter.push(' '),
+            key if key.chars().count() == 1 => filter.push_str(key),
+            _ => return,
+        }
+        self.redraw = true;
+        if filter == old {
+            return;
+        }
+        config.set_string("proc_filter", &filter);
+        drop(config);
+        info!("proc filter: {:?}", filter);
+        crate::Global::get_instance().lock().unwrap().set_refresh();
+    }
+
+    // 数据或尺寸变化后保证滚动位置和选中行仍然有效
+    fn clamp(&mut self, runner: &Runner) {
+        let cores = runner.cpu.core_percent.len();
+        let visible = self
+            .rects
+            .iter()
+            .find(|rect| rect.kind == BoxKind::Cpu)
+            .map_or(0, |rect| boxes::cpu_visible_cores(rect, cores));
+        self.cpu_offset = self.cpu_offset.min(cores.saturating_sub(visible));
+        let log_rows = self.log_rows();
+        self.log_offset = self
+            .log_offset
+            .min(runner.klog.entries.len().saturating_sub(log_rows));
+
+        let proc_rows = self.rows(BoxKind::Proc);
+        let (mut start, mut selected) = Screen::get_proc_position();
+        // 跟随模式下选中行跟着 selected_pid 移动，进程退出后停留在原来的行
+        let (follow, pid) = {
+            let instance = Config::get_instance();
+            let config = instance.lock().unwrap();
+            (
+                config.get_bool("proc_follow"),
+                config.get_int("selected_pid"),
+            )
+        };
+        if follow && selected > 0 {
+            if let Some(pos) = runner.procs.iter().position(|p| p.pid == pid) {
+                selected = pos + 1;
+            }
+        }
+        selected = selected.min(runner.procs.len());
+        if selected > 0 {
+            if selected <= start {
+                start = selected - 1;
+            } else if selected > start + proc_rows {
+                start = selected - proc_rows;
+            }
+        }
+        start = start.min(runner.procs.len().saturating_sub(proc_rows));
+        Screen::set_proc_position(start, selected, &runner.procs);
+    }
+
+    // 进程列表的滚动位置和选中行保存在 proc_start/proc_selected 中，
+    // proc_selected 从 1 开始计数，0 表示没有选中
+    fn get_proc_position() -> (usize, usize) {
+        let instance = Config::get_instance();
+        let config = instance.lock().unwrap();
+        (
+            config.get_int("proc_start").max(0) as usize,
+            config.get_int("proc_selected").max(0) as usize,
+        )
+    }
+
+    // 同时记录选中进程的 pid 和名称，供跟随模式使用
+    fn set_proc_position(start: usize, selected: usize, procs: &[ProcInfo]) {
+        let (pid, name) = match selected.checked_sub(1).and_then(|i| procs.get(i)) {
+            Some(p) => (p.pid, p.name.as_str()),
+            None => (0, ""),
+        };
+        let instance = Config::get_instance();
+        let mut config = instance.lock().unwrap();
+        if config.get_int("proc_start") != start as i32 {
+            let _ = config.set_int("proc_start", &start.to_string());
+        }
+        if config.get_int("proc_selected") != selected as i32 {
+            let _ = config.set_int("proc_selected", &selected.to_string());
+        }
+        if config.get_int("selected_pid") != pid {
+            let _ = config.set_int("selected_pid", &pid.to_string());
+            config.set_string("selected_name", name);
+        }
+    }
+
+    // 有选中行时移动选中行，没有选中时只滚动列表
+    fn process_proc(key: &str, runner: &Runner, rows: usize) -> bool {
+        let (mut start, mut selected) = Screen::get_proc_position();
+        let rows = rows.max(1);
+        match key {
+            "up" | "k" => selected = selected.saturating_sub(1),
+            "down" | "j" => selected += 1,
+            "page_up" if selected > 0 => selected = selected.saturating_sub(rows).max(1),
+            "page_up" => start = start.saturating_sub(rows),
+            "page_down" if selected > 0 => selected += rows,
+            "page_down" => start += rows,
+            "home" if selected > 0 => selected = 1,
+            "home" => start = 0,
+            "end" if selected > 0 => selected = runner.procs.len(),
+            "end" => start = runner.procs.len(),
+            // 打开详细视图时记住选中行，关闭后恢复
+            "enter" if selected > 0 => {
+                let Some(p) = runner.procs.get(selected - 1) else {
+                    return false;
+                };
+                let instance = Config::get_instance();
+                let mut config = instance.lock().unwrap();
+                let _ = config.set_int("proc_last_selected", &selected.to_string());
+                let _ = config.set_int("detailed_pid", &p.pid.to_string());
+                config.set_bool("show_detailed", true);
+                selected = 0;
+            }
+            _ => return false,
+        }
+        Screen::set_proc_position(start, selected, &runner.procs);
+        true
+    }
+
+    // 与 htop 相同，F7 降低 nice 值（提高优先级），F8 提高 nice 值
+    fn renice(&mut self, raise: bool, runner: &Runner) -> bool {
+        let (_, selected) = Screen::get_proc_position();
+        let Some(p) = selected.checked_sub(1).and_then(|i| runner.procs.get(i)) else {
+            return false;
+        };
+        let current = match self.renice_pending {
+            Some((pid, nice)) if pid == p.pid => nice,
+            _ => p.nice,
+        };
+        let nice = match raise {
+            true => current - 1,
+            false => current + 1,
+        }
+        .clamp(NICE_MIN, NICE_MAX);
+        let message = match proc::set_nice(p.pid, nice) {
+            Ok(_) => {
+                self.renice_pending = Some((p.pid, nice));
+                format!("{} nice {}", draw::sanitize(&p.name), nice)
+            }
+            // 边框上的空间有限，权限错误用简短的说明代替完整的错误信息
+            Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                format!("{}: not permitted", draw::sanitize(&p.name))
+            }
+            Err(err) => format!("{}: {}", draw::sanitize(&p.name), err),
+        };
+        info!("renice {} to {}: {}", p.pid, nice, message);
+        self.proc_message = Some((message, Instant::now()));
+        true
+    }
+
+    // 有标记的进程时返回全部标记的进程，否则返回选中的进程，不包括 btop-rs 自己
+    pub fn signal_targets(&self, runner: &Runner) -> Vec<(i32, String)> {
+        let (_, selected) = Screen::get_proc_position();
+        let own = std::process::id() as i32;
+        runner
+            .procs
+            .iter()
+            .enumerate()
+            .filter(|(i, p)| match self.tagged.is_empty() {
+                true => i + 1 == selected,
+                false => self.tagged.contains(&p.pid),
+            })
+            .filter(|(_, p)| p.pid != own)
+            .map(|(_, p)| (p.pid, p.name.clone()))
+            .collect()
+    }
+
+    fn read_listening() -> (Instant, Result<Vec<SocketInfo>, String>) {
+        let sockets = socket::collect()
+            .map(|list| {
+                let mut sockets: Vec<SocketInfo> = list
+                    .sockets
+                    .into_iter()
+                    .filter(SocketInfo::is_listening)
+                    .collect();
+                // 按端口排列，同一端口的 IPv4 和 IPv6 套接字相邻
+                sockets.sort_by_key(|s| (s.local.port(), !s.proto.is_tcp()));
+                sockets
+            })
+            .map_err(|err| err.to_string());
+        (Instant::now(), sockets)
+    }
+
+    // 已暂停（状态为 T）的进程发送 SIGCONT，否则发送 SIGSTOP
+    fn freeze(&mut self, runner: &Runner) -> bool {
+        let (_, selected) = Screen::get_proc_position();
+        let Some(p) = selected.checked_sub(1).and_then(|i| runner.procs.get(i)) else {
+            return false;
+        };
+        // 暂停自己之后就无法再恢复了
+        if p.pid == std::process::id() as i32 {
+            self.proc_message = Some(("cannot freeze btop-rs".to_owned(), Instant::now()));
+            return true;
+        }
+        let frozen = match self.freeze_pending {
+            Some((pid, frozen)) if pid == p.pid => frozen,
+            _ => p.state == 'T',
+        };
+        let (signal, action) = match frozen {
+            true => (libc::SIGCONT, "resumed"),
+            false => (libc::SIGSTOP, "frozen"),
+        };
+        let message = match proc::send_signal(p.pid, signal) {
+            Ok(_) => {
+                self.freeze_pending = Some((p.pid, !frozen));
+                format!("{} {}", draw::sanitize(&p.name), action)
+            }
+            Err(err) if err.kind() == io::ErrorKind::PermissionDenied => {
+                format!("{}: not permitted", draw::sanitize(&p.name))
+            }
+            Err(err) => format!("{}: {}", draw::sanitize(&p.name), err),
+        };
+        info!("freeze {}: {}", p.pid, message);
+        self.proc_message = Some((message, Instant::now()));
+        true
+    }
+
+    // 把屏幕上的最后一帧（包括菜单）写入配置目录，结果显示在 proc box 底部
+    pub fn screenshot(&mut self) {
+        let Some(frame) = &self.frame else {
+            return;
+        };
+        let path = screenshot::default_path(&Config::get_instance().lock().unwrap());
+        let format = ScreenshotFormat::from_path(&path);
+        let message = match screenshot::write(frame, &path, format) {
+            // 完整路径写在日志中，边框上只放得下文件名
+            Ok(()) => format!(
+                "saved {}",
+                path.file_name().unwrap_or_default().to_string_lossy()
+            ),
+            Err(err) => format!("screenshot failed: {}", err),
+        };
+        self.proc_message = Some((message, Instant::now()));
+        self.redraw = true;
+    }
+
+    // 格式化温度时会读取配置，不能持有配置的锁
+    fn clocks(runner: &Runner) -> [String; 3] {
+        let (formats, scale) = {
+            let instance = Config::get_instance();
+            let config = instance.lock().unwrap();
+            (
+                clock::SLOTS.map(|key| config.get_string(key)),
+                config.get_string("temp_scale"),
+            )
+        };
+        formats.map(|format| clock::format(&format, runner, &scale))
+    }
+
+    pub fn draw(&mut self, runner: &Runner, width: u16, height: u16) -> String {
+        let shown = Screen::shown();
+        let (
+            opts,
+            (iface, net_overhead),
+            (show_swap, show_disks, temp_scale, mem_numa),
+            dither,
+            (cpu_name, show_host, show_system, show_power),
+            cpu_graph,
+        ) = {
+            let instance = Config::get_instance();
+            let config = instance.lock().unwrap();
+            let symbol = match config.get_string("graph_symbol_cpu") {
+                symbol if symbol == "default" => config.get_string("graph_symbol"),
+                symbol => symbol,
+            };
+            (
+                Layout {
+                    cpu_bottom: config.get_bool("cpu_bottom"),
+                    mem_below_net: config.get_bool("mem_below_net"),
+                    proc_left: config.get_bool("proc_left"),
+                    adaptive: config.get_bool("adaptive_layout"),
+                    cpu_height_pct: config.get_int("cpu_box_height_pct").clamp(10, 90) as u16,
+                    proc_width_pct: config.get_int("proc_box_width_pct").clamp(10, 90) as u16,
+                },
+                (
+                    config.get_string("net_iface"),
+                    config.get_bool("net_overhead"),
+                ),
+                (
+                    config.get_bool("show_swap"),
+                    config.get_bool("show_disks"),
+                    config.get_string("temp_scale"),
+                    config.get_bool("mem_numa"),
+                ),
+                config.get_bool("lowcolor_dither"),
+                (
+                    config.get_string("custom_cpu_name"),
+                    config.get_bool("show_host"),
+                    config.get_bool("show_system"),
+                    config.get_bool("show_power"),
+                ),
+                (
+                    config.get_string("cpu_graph_upper"),
+                    config.get_string("cpu_graph_lower"),
+                    config.get_bool("cpu_invert_lower"),
+                    config.get_bool("cpu_single_graph"),
+                    symbol,
+                ),
+            )
+        };
+        let (upper, lower, invert_lower, single_graph, symbol) = cpu_graph;
+        let depth = Term::get_instance().lock().unwrap().get_colors();
+        // 终端放不下显示的 box 时只显示提示，终端变大后在下一次绘制时恢复
+        let need = boxes::min_size(&shown, &opts, width);
+        if width < need.0 || height < need.1 {
+            self.rects.clear();
+            return boxes::draw_too_small(width, height, need, depth);
+        }
+        self.rects = boxes::layout(&shown, &opts, width, height);
+        self.clamp(runner);
+
+        let focus = self.get_focus(&shown);
+        let (proc_start, proc_selected) = Screen::get_proc_position();
+        let (
+            follow,
+            update_ms,
+            filter,
+            regex,
+            sort,
+            full_cmd,
+            (range, cpu_stats, net_stats, net_packets),
+        ) = {
+            let instance = Config::get_instance();
+            let config = instance.lock().unwrap();
+            (
+                config.get_bool("proc_follow"),
+                config.get_int("update_ms"),
+                config.get_string("proc_filter"),
+                config.get_bool("proc_filter_regex"),
+                (Screen::get_sort(&config), config.get_bool("proc_reversed")),
+                config.get_bool("proc_full_cmd"),
+                (
+                    config.get_int("graph_range").max(1) as usize,
+                    config.get_bool("cpu_graph_stats"),
+                    config.get_bool("net_graph_stats"),
+                    config.get_bool("net_packets"),
+                ),
+            )
+        };
+        let filter_error = runner.get_filter_error();
+        // 暂停的提示显示在第一个 box 的底部边框上
+        let paused = crate::Global::get_instance().lock().unwrap().get_paused();
+        let symbols = draw::symbols::box_symbols();
+        self.clocks = Screen::clocks(runner);
+        let cpu_graph = CpuGraph {
+            upper: runner.history.cpu(&upper),
+            lower: (!single_graph).then(|| runner.history.cpu(&lower)),
+            invert_lower,
+            symbol: &symbol,
+            range,
+            stats: cpu_stats,
+            power: show_power.then(|| runner.history.power()),
+            depth,
+            dither,
+        };
+        let mut out = String::new();
+        // custom_cpu_name 为空时显示检测到的型号
+        let cpu_name = match cpu_name.trim() {
+            "" => runner.cpu.model.as_str(),
+            name => name,
+        };
+        let (host, system) = {
+            let instance = HostInfo::get_instance();
+            let host = instance.lock().unwrap();
+            (
+                match show_host {
+                    true => format!("{}@{}", host.get_user(), host.get_hostname()),
+                    false => String::new(),
+                },
+                match show_system {
+                    true => draw::sanitize(&host.get_system()).into_owned(),
+                    false => String::new(),
+                },
+            )
+        };
+        for (i, rect) in self.rects.iter().enumerate() {
+            let title = match rect.kind {
+                BoxKind::Cpu if !cpu_name.is_empty() => format!("cpu {}", cpu_name),
+                BoxKind::Proc => boxes::proc_title(
+                    &filter,
+                    (regex, filter_error.is_some()),
+                    self.filtering,
+                    rect.inner_width(),
+                    depth,
+                ),
+                kind => kind.name().to_owned(),
+            };
+            let bottom_title = match rect.kind {
+                _ if paused && i == 0 => boxes::paused_text(depth),
+                BoxKind::Cpu => boxes::update_ms_text(update_ms),
+                BoxKind::Log => boxes::log_position(self.log_offset),
+                // 发现新的 OOM kill 后的一段时间内每秒交替反色
+                BoxKind::Mem if runner.oom_kills > 0 => {
+                    let flash = runner.oom_at.is_some_and(|at| {
+                        at.elapsed() < OOM_FLASH_TIME && at.elapsed().as_secs() % 2 == 0
+                    });
+                    boxes::oom_text(runner.oom_kills, flash, depth)
+                }
+                BoxKind::Proc => match &self.proc_message {
+                    Some((message, at)) if at.elapsed() < PROC_MESSAGE_TIME => {
+                        let width = rect.inner_width().saturating_sub(6);
+                        format!(" {} ", message.chars().take(width).collect::<String>())
+                    }
+                    // 无法解析的过滤条件不会生效，在这里说明原因
+                    _ if filter_error.is_some() => {
+                        let width = rect.inner_width().saturating_sub(6);
+                        let error = filter_error.unwrap_or_default();
+                        format!(" {} ", error.chars().take(width).collect::<String>())
+                    }
+                    _ => boxes::proc_counter(
+                        proc_selected,
+                        runner.procs.len(),
+                        follow,
+                        self.tagged.len(),
+                    ),
+                },
+                _ => String::new(),
+            };
+            // 告警触发后对应 box 的边框每秒交替闪烁
+            let alerting = runner.alerts.firing().any(|(rule, at)| {
+                rule.metric.box_name() == rect.kind.name() && at.elapsed().as_secs() % 2 == 0
+            });
+            let style = match (alerting, focus == Some(rect.kind)) {
+                (true, _) => BorderStyle::Alert,
+                (false, true) => BorderStyle::Focused,
+                (false, false) => BorderStyle::Normal,
+            };
+            out += &rect.draw_border(symbols, style, depth, &title, &bottom_title);
+            if rect.kind == BoxKind::Cpu {
+                let battery = runner.battery.as_ref().map(boxes::battery_text);
+                let header = CpuHeader {
+                    clocks: &self.clocks,
+                    host: &host,
+                    system: &system,
+                    battery: &battery.unwrap_or_default(),
+                };
+                out += &rect.draw_clocks(symbols, style, depth, &title, &header);
+            }
+            if rect.kind == BoxKind::Proc {
+                let summary = boxes::proc_summary(&runner.proc_counts);
+                out += &rect.draw_title_right(symbols, style, depth, &title, &summary);
+            }
+            out += &match rect.kind {
+                BoxKind::Cpu => boxes::draw_cpu(rect, &runner.cpu, self.cpu_offset, &cpu_graph),
+                BoxKind::Mem => boxes::draw_mem(
+                    rect,
+                    &runner.mem,
+                    show_swap,
+                    mem_numa,
+                    show_disks.then_some(&runner.disks),
+                    &temp_scale,
+                    depth,
+                ),
+                BoxKind::Net if self.listening.is_some() => {
+                    let (at, sockets) = self.listening.as_ref().unwrap();
+                    boxes::draw_listening(rect, sockets, at.elapsed().as_secs())
+                }
+                BoxKind::Net => boxes::draw_net(
+                    rect,
+                    &runner.net,
+                    &iface,
+                    net_overhead,
+                    net_stats.then(|| runner.history.net(&iface)),
+                    range,
+                    net_packets,
+                ),
+                BoxKind::Proc => boxes::draw_proc(
+                    rect,
+                    &runner.procs,
+                    &ProcView {
+                        start: proc_start,
+                        selected: proc_selected,
+                        tagged: &self.tagged,
+                        sort: sort.0,
+                        reversed: sort.1,
+                        full_cmd,
+                    },
+                    depth,
+                ),
+                BoxKind::Log => boxes::draw_log(rect, &runner.klog, self.log_offset, depth),
+            };
+        }
+        out += &boxes::draw_banner(symbols, &self.banner, width, height, depth);
+        out
+    }
+
+    pub fn set_banner(&mut self, lines: &[String]) {
+        self.banner = lines.to_vec();
+        self.redraw = true;
+    }
+
+    // 横幅显示时按键只用于关闭横幅，返回 true
+    pub fn dismiss_banner(&mut self) -> bool {
+        if self.banner.is_empty() {
+            return false;
+        }
+        self.banner.clear();
+        self.redraw = true;
+        true
+    }
+}
+
+// nice 值的范围，见 setpriority(2)
+const NICE_MIN: i32 = -20;
+const NICE_MAX: i32 = 19;
+
+// 调整 nice 值的结果在 proc box 上显示的时间
+const PROC_MESSAGE_TIME: Duration = Duration::from_secs(5);
+
+// 发现新的 OOM kill 后 mem box 上的计数闪烁的时间
+const OOM_FLASH_TIME: Duration = Duration::from_secs(30);
+
+// 在 "全部接口" 和各个接口之间循环切换
+fn next_iface(runner: &Runner, current: &str, forward: bool) -> String {
+    let mut names: Vec<&str> = vec![""];
+    names.extend(runner.net.interfaces.iter().map(|stat| stat.name.as_str()));
+    let pos = names.iter().position(|name| *name == current).unwrap_or(0);
+    let next = if forward {
+        (pos + 1) % names.len()
+    } else {
+        (pos + names.len() - 1) % names.len()
+    };
+    names[next].to_owned()
+}
+
+// 每次采集后重绘主界面
+pub fn update(runner: &Runner) {
+    let instance = Screen::get_instance();
+    let mut screen = instance.lock().unwrap();
+    screen.renice_pending = None;
+    screen.freeze_pending = None;
+    // 已退出的进程不再保留标记
+    let tagged = std::mem::take(&mut screen.tagged);
+    screen.tagged = runner
+        .procs
+        .iter()
+        .map(|p| p.pid)
+        .filter(|pid| tagged.contains(pid))
+        .collect();
+    // 有新触发的告警时响铃，BEL 随下一帧一起输出
+    if !runner.alerts.get_fired().is_empty()
+        && Config::get_instance()
+            .lock()
+            .unwrap()
+            .get_bool("alert_bell")
+    {
+        screen.output.push("\x07");
+    }
+    screen.redraw = true;
+}
+
+// 两次采集之间每秒调用一次，时钟文本变化时重绘
+pub fn tick(runner: &Runner) {
+    let clocks = Screen::clocks(runner);
+    let instance = Screen::get_instance();
+    let mut screen = instance.lock().unwrap();
+    // 闪烁的 OOM 计数和告警边框需要每秒重新绘制
+    let flashing = runner
+        .oom_at
+        .is_some_and(|at| at.elapsed() < OOM_FLASH_TIME + Duration::from_secs(1))
+        || runner.alerts.firing().next().is_some();
+    if screen.clocks != clocks || flashing {
+        screen.redraw = true;
+    }
+}
+
+// 不输出到终端，按指定尺寸绘制一帧主界面，用于 --screenshot
+pub fn snapshot(runner: &Runner, width: u16, height: u16) -> Frame {
+    let mut frame = Frame::new(width, height);
+    frame.apply(&Screen::new().draw(runner, width, height));
+    frame
+}
+
+// 主界面或菜单状态发生变化时重新绘制，菜单绘制在主界面之上
+pub fn render(runner: &Runner) {
+    let (resized, (width, height)) = {
+        let instance = Term::get_instance();
+        let mut term = instance.lock().unwrap();
+        (term.refresh(), term.get_size())
+    };
+    let menu_instance = Menu::get_instance();
+    let mut menu = menu_instance.lock().unwrap();
+    let instance = Screen::get_instance();
+    let mut screen = instance.lock().unwrap();
+    if !resized && !menu.redraw && !screen.redraw {
+        return;
+    }
+    menu.redraw = false;
+    screen.redraw = false;
+
+    let mut frame = Frame::new(width, height);
+    // 色彩测试界面占满整个屏幕
+    if menu.active != Some(MenuKind::ColorTest) {
+        frame.apply(&screen.draw(runner, width, height));
+    }
+    frame.apply(&menu.draw(width, height));
+    let screen = &mut *screen;
+    match &screen.frame {
+        Some(prev) if !resized && prev.get_size() == frame.get_size() => {
+            frame.diff(prev, &mut screen.output)
+        }
+        _ => frame.full(&mut screen.output),
+    }
+    screen.frame = Some(frame);
+    if !screen.output.is_empty() {
+        let _ = screen.output.flush();
+    }
+}
@@ -0,0 +1,398 @@
+use std::{
+    io,
+    process::{Child, Command, Stdio},
+    time::{Duration, Instant},
+};
+
+use log::warn;
+
+// 同时运行的 alert_exec 命令的上限，命令卡住时不再继续启动新进程
+const EXEC_LIMIT: usize = 8;
+
+// 告警规则可以检查的指标
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum AlertMetric {
+    // cpu 总使用率（%）
+    Cpu,
+    // 1 分钟平均负载
+    Load,
+    // cpu 温度（摄氏度）
+    Temp,
+    // cpu 功耗（W）
+    Power,
+    // 内存使用率（%）
+    Mem,
+    // swap 使用率（%）
+    Swap,
+    // 使用率最高的磁盘（%）
+    Disk,
+    // 所有接口合计的下载和上传速率，与 net_download 一样以 Mebibits/s 为单位
+    Download,
+    Upload,
+}
+
+impl AlertMetric {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "cpu" => Some(AlertMetric::Cpu),
+            "load" => Some(AlertMetric::Load),
+            "temp" => Some(AlertMetric::Temp),
+            "power" => Some(AlertMetric::Power),
+            "mem" => Some(AlertMetric::Mem),
+            "swap" => Some(AlertMetric::Swap),
+            "disk" => Some(AlertMetric::Disk),
+            "download" => Some(AlertMetric::Download),
+            "upload" => Some(AlertMetric::Upload),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            AlertMetric::Cpu => "cpu",
+            AlertMetric::Load => "load",
+            AlertMetric::Temp => "temp",
+            AlertMetric::Power => "power",
+            AlertMetric::Mem => "mem",
+            AlertMetric::Swap => "swap",
+            AlertMetric::Disk => "disk",
+            AlertMetric::Download => "download",
+            AlertMetric::Upload => "upload",
+        }
+    }
+
+    // 告警触发时边框闪烁的 box
+    pub fn box_name(&self) -> &'static str {
+        match self {
+            AlertMetric::Cpu | AlertMetric::Load | AlertMetric::Temp | AlertMetric::Power => "cpu",
+            AlertMetric::Mem | AlertMetric::Swap | AlertMetric::Disk => "mem",
+            AlertMetric::Download | AlertMetric::Upload => "net",
+        }
+    }
+}
+
+// 一条告警规则，例如 "cpu>90 for 30s"：指标持续 duration 高于（或低于）threshold 时触发
+#[derive(Clone, PartialEq, Debug)]
+pub struct AlertRule {
+    // 配置中的原文，用于日志
+    pub text: String,
+    pub metric: AlertMetric,
+    pub above: bool,
+    pub threshold: f64,
+    pub duration: Duration,
+}
+
+impl AlertRule {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let (cond, duration) = match text.split_once(" for ") {
+            Some((cond, duration)) => (cond, parse_duration(duration.trim())?),
+            None => (text, Duration::ZERO),
+        };
+        let Some(pos) = cond.find(['>', '<']) else {
+            return Err(format!("missing \">\" or \"<\" in \"{}\"", text.trim()));
+        };
+        let name = cond[..pos].trim();
+        let metric =
+            AlertMetric::from_name(name).ok_or_else(|| format!("unknown metric \"{}\"", name))?;
+        let value = cond[pos + 1..].trim();
+        let threshold = value
+            .parse::<f64>()
+            .ok()
+            .filter(|value| value.is_finite())
+            .ok_or_else(|| format!("invalid threshold \"{}\"", value))?;
+        Ok(AlertRule {
+            text: text.trim().to_owned(),
+            metric,
+            above: cond.as_bytes()[pos] == b'>',
+            threshold,
+            duration,
+        })
+    }
+
+    fn matches(&self, value: f64) -> bool {
+        match self.above {
+            true => value > self.threshold,
+            false => value < self.threshold,
+        }
+    }
+}
+
+// 规则之间用逗号分隔，空字符串表示没有规则
+pub fn parse_rules(value: &str) -> Result<Vec<AlertRule>, String> {
+    value
+        .split(',')
+        .filter(|rule| !rule.trim().is_empty())
+        .map(AlertRule::parse)
+        .collect()
+}
+
+// "30s"、"5m"、"1h"，没有单位时为秒
+fn parse_duration(text: &str) -> Result<Duration, String> {
+    let (number, scale) = match text.char_indices().last() {
+        Some((pos, 's')) => (&text[..pos], 1),
+        Some((pos, 'm')) => (&text[..pos], 60),
+        Some((pos, 'h')) => (&text[..pos], 3600),
+        _ => (text, 1),
+    };
+    match number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(scale))
+    {
+        Some(secs) => Ok(Duration::from_secs(secs)),
+        None => Err(format!("invalid duration \"{}\"", text)),
+    }
+}
+
+// 一条规则的状态：条件从什么时候开始满足，以及触发的时间
+#[derive(Clone, Default)]
+struct AlertState {
+    since: Option<Instant>,
+    fired_at: Option<Instant>,
+}
+
+// 一条刚触发的告警和触发时的值
+pub struct Fired {
+    pub rule: AlertRule,
+    pub value: f64,
+}
+
+// 所有告警规则和它们的状态，规则文本变化时重新解析
+#[derive(Default)]
+pub struct Alerts {
+    text: String,
+    rules: Vec<AlertRule>,
+    states: Vec<AlertState>,
+    // 最近一次 update 中新触发的告警
+    fired: Vec<Fired>,
+    // 还没有退出的 alert_exec 命令
+    children: Vec<Child>,
+}
+
+impl Alerts {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // 无法解析的规则在读取配置时已经报告过，这里直接忽略
+    pub fn set_rules(&mut self, text: &str) {
+        if text == self.text {
+            return;
+        }
+        self.rules = parse_rules(text).unwrap_or_default();
+        self.states = vec![AlertState::default(); self.rules.len()];
+        self.text = text.to_owned();
+    }
+
+    pub fn get_rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    // values 与规则一一对应，没有这项数据时为 None。
+    // 条件持续满足 duration 后触发一次，直到条件不再满足
+    pub fn update(&mut self, values: &[Option<f64>], now: Instant) -> &[Fired] {
+        self.fired.clear();
+        for ((rule, state), value) in self.rules.iter().zip(&mut self.states).zip(values) {
+            let Some(value) = value.filter(|value| rule.matches(*value)) else {
+                *state = AlertState::default();
+                continue;
+            };
+            let since = *state.since.get_or_insert(now);
+            if state.fired_at.is_none() && now.duration_since(since) >= rule.duration {
+                state.fired_at = Some(now);
+                self.fired.push(Fired {
+                    rule: rule.clone(),
+                    value,
+                });
+            }
+        }
+        &self.fired
+    }
+
+    pub fn get_fired(&self) -> &[Fired] {
+        &self.fired
+    }
+
+    // 正在触发的规则和触发的时间
+    pub fn firing(&self) -> impl Iterator<Item = (&AlertRule, Instant)> {
+        self.rules
+            .iter()
+            .zip(&self.states)
+            .filter_map(|(rule, state)| Some((rule, state.fired_at?)))
+    }
+
+    // 对每个新触发的告警用 sh -c 运行 command，指标名称、当前值、阈值和规则原文通过
+    // BTOP_ALERT_* 环境变量传递。命令在后台运行，输出丢弃以免破坏界面
+    pub fn exec(&mut self, command: &str) {
+        self.children
+            .retain_mut(|child| !matches!(child.try_wait(), Ok(Some(_)) | Err(_)));
+        if command.trim().is_empty() {
+            return;
+        }
+        for fired in &self.fired {
+            if self.children.len() >= EXEC_LIMIT {
+                warn!(
+                    "alert_exec: {} commands still running, skipping",
+                    EXEC_LIMIT
+                );
+                break;
+            }
+            match spawn(command, fired) {
+                Ok(child) => self.children.push(child),
+                Err(err) => warn!("alert_exec: could not run \"{}\": {}", command, err),
+            }
+        }
+    }
+}
+
+fn spawn(command: &str, fired: &Fired) -> io::Result<Child> {
+    Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .env("BTOP_ALERT_METRIC", fired.rule.metric.name())
+        .env("BTOP_ALERT_VALUE", format!("{:.1}", fired.value))
+        .env("BTOP_ALERT_THRESHOLD", fired.rule.threshold.to_string())
+        .env("BTOP_ALERT_RULE", &fired.rule.text)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use super::{parse_rules, AlertMetric, AlertRule, Alerts};
+
+    fn rule(text: &str) -> AlertRule {
+        AlertRule::parse(text).unwrap_or_else(|err| panic!("{:?}: {}", text, err))
+    }
+
+    fn error(text: &str) -> String {
+        AlertRule::parse(text).unwrap_err()
+    }
+
+    #[test]
+    fn parse_rule() {
+        assert_eq!(
+            rule(" cpu > 90 for 30s "),
+            AlertRule {
+                text: "cpu > 90 for 30s".to_owned(),
+                metric: AlertMetric::Cpu,
+                above: true,
+                threshold: 90.0,
+                duration: Duration::from_secs(30),
+            }
+        );
+        let below = rule("load<0.5");
+        assert!(!below.above);
+        assert_eq!(below.threshold, 0.5);
+        assert_eq!(below.duration, Duration::ZERO);
+        assert_eq!(rule("temp>-10").threshold, -10.0);
+        assert_eq!(rule("mem>90 for 5m").duration, Duration::from_secs(300));
+        assert_eq!(rule("swap>1 for 2h").duration, Duration::from_secs(7200));
+        // 没有单位时为秒
+        assert_eq!(rule("disk>95 for 45").duration, Duration::from_secs(45));
+        for name in [
+            "cpu", "load", "temp", "power", "mem", "swap", "disk", "download", "upload",
+        ] {
+            assert_eq!(rule(&format!("{}>1", name)).metric.name(), name);
+        }
+    }
+
+    #[test]
+    fn malformed_rules() {
+        assert_eq!(error("cpu 90"), "missing \">\" or \"<\" in \"cpu 90\"");
+        assert_eq!(error(""), "missing \">\" or \"<\" in \"\"");
+        assert_eq!(error(">90"), "unknown metric \"\"");
+        assert_eq!(error("CPU>90"), "unknown metric \"CPU\"");
+        assert_eq!(error("gpu>90"), "unknown metric \"gpu\"");
+        assert_eq!(error("cpu>"), "invalid threshold \"\"");
+        assert_eq!(error("cpu>>90"), "invalid threshold \">90\"");
+        assert_eq!(error("cpu>=90"), "invalid threshold \"=90\"");
+        assert_eq!(error("cpu>90%"), "invalid threshold \"90%\"");
+        assert_eq!(error("cpu>inf"), "invalid threshold \"inf\"");
+        assert_eq!(error("cpu>NaN"), "invalid threshold \"NaN\"");
+        // " for" 后面没有时长时整段都被当作阈值
+        assert_eq!(error("cpu>90 for"), "invalid threshold \"90 for\"");
+        assert_eq!(error("cpu>90 for "), "invalid duration \"\"");
+        assert_eq!(error("cpu>90 for s"), "invalid duration \"s\"");
+        assert_eq!(error("cpu>90 for -5s"), "invalid duration \"-5s\"");
+        assert_eq!(error("cpu>90 for 5ms"), "invalid duration \"5ms\"");
+        assert_eq!(error("cpu>90 for 1d"), "invalid duration \"1d\"");
+        assert_eq!(
+            error("cpu>90 for 30s for 5m"),
+            "invalid duration \"30s for 5m\""
+        );
+        // 乘以单位后溢出
+        assert_eq!(
+            error("cpu>90 for 18446744073709551615h"),
+            "invalid duration \"18446744073709551615h\""
+        );
+    }
+
+    #[test]
+    fn rule_lists() {
+        assert!(parse_rules("").unwrap().is_empty());
+        assert!(parse_rules(" , ,").unwrap().is_empty());
+        let rules = parse_rules("cpu>90 for 30s, mem>80,").unwrap();
+        assert_eq!(rules.len(), 2);
+        assert_eq!(rules[1].text, "mem>80");
+        // 一条无效整个列表无效
+        assert_eq!(
+            parse_rules("cpu>90, bogus>1").unwrap_err(),
+            "unknown metric \"bogus\""
+        );
+    }
+
+    #[test]
+    fn thresholds_and_duration() {
+        let mut alerts = Alerts::new();
+        alerts.set_rules("cpu>50 for 10s, load<1");
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+        let fired = |alerts: &mut Alerts, cpu, load, secs| -> Vec<String> {
+            alerts
+                .update(&[cpu, load], at(secs))
+                .iter()
+                .map(|fired| fired.rule.text.clone())
+                .collect()
+        };
+
+        // 等于阈值不算超过
+        assert!(fired(&mut alerts, Some(50.0), Some(1.0), 0).is_empty());
+        assert!(fired(&mut alerts, Some(60.0), Some(2.0), 0).is_empty());
+        assert!(fired(&mut alerts, Some(60.0), Some(2.0), 9).is_empty());
+        assert_eq!(
+            fired(&mut alerts, Some(70.0), Some(0.5), 10),
+            ["cpu>50 for 10s", "load<1"]
+        );
+        // 持续满足时只触发一次
+        assert!(fired(&mut alerts, Some(70.0), Some(0.5), 20).is_empty());
+        assert_eq!(alerts.firing().count(), 2);
+        // 条件不再满足或没有数据后重新计时
+        assert!(fired(&mut alerts, Some(40.0), None, 21).is_empty());
+        assert_eq!(alerts.firing().count(), 0);
+        assert_eq!(fired(&mut alerts, Some(70.0), Some(0.1), 22), ["load<1"]);
+        assert!(fired(&mut alerts, Some(70.0), Some(0.1), 31).is_empty());
+        assert_eq!(
+            fired(&mut alerts, Some(70.0), Some(0.1), 32),
+            ["cpu>50 for 10s"]
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_ignored() {
+        let mut alerts = Alerts::new();
+        alerts.set_rules("cpu>50");
+        assert_eq!(alerts.get_rules().len(), 1);
+        // 无效的规则文本清空所有规则，缺少的值不会触发
+        alerts.set_rules("cpu>50, nope");
+        assert!(alerts.get_rules().is_empty());
+        assert!(alerts.update(&[Some(99.0)], Instant::now()).is_empty());
+        alerts.set_rules("cpu>50, mem>50");
+        assert_eq!(alerts.update(&[Some(99.0)], Instant::now()).len(), 1);
+    }
+}
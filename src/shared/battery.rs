@@ -0,0 +1,155 @@
+use std::time::Instant;
+
+use super::history::History;
+
+#[derive(Clone, Copy, PartialEq, Default, Debug)]
+pub enum BatteryStatus {
+    Charging,
+    Discharging,
+    Full,
+    #[default]
+    Unknown,
+}
+
+impl BatteryStatus {
+    // /sys/class/power_supply/*/status 中的写法
+    pub fn from_name(name: &str) -> Self {
+        match name.trim() {
+            "Charging" => BatteryStatus::Charging,
+            "Discharging" => BatteryStatus::Discharging,
+            "Full" | "Not charging" => BatteryStatus::Full,
+            _ => BatteryStatus::Unknown,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            BatteryStatus::Charging => "charging",
+            BatteryStatus::Discharging => "discharging",
+            BatteryStatus::Full => "full",
+            BatteryStatus::Unknown => "unknown",
+        }
+    }
+}
+
+// 平台后端读取到的原始数据，不提供的项为 None
+#[derive(Clone, Default)]
+pub struct BatteryReading {
+    pub percent: f64,
+    pub status: BatteryStatus,
+    pub watts: Option<f64>,
+    // 当前和充满时的能量，单位 Wh
+    pub energy: Option<(f64, f64)>,
+    // 平台自己估算的剩余时间
+    pub seconds: Option<u64>,
+}
+
+#[derive(Clone, Default)]
+pub struct BatteryInfo {
+    pub percent: f64,
+    pub status: BatteryStatus,
+    pub watts: Option<f64>,
+    // 放电时为剩余时间，充电时为充满所需时间，无法估算时为 None
+    pub seconds: Option<u64>,
+    // 最近的电量百分比，最新的在最后
+    pub history: Vec<f64>,
+}
+
+// 剩余时间的平滑系数和电量历史的长度
+const SMOOTHING: f64 = 0.2;
+const HISTORY: usize = 300;
+
+// 多次采样之间平滑剩余时间，功率和电量的瞬时值波动很大
+pub struct BatteryEstimator {
+    last: Option<(Instant, f64, BatteryStatus)>,
+    // 电量变化速度，单位为每秒百分比
+    rate: Option<f64>,
+    seconds: Option<f64>,
+    history: History<f64>,
+}
+
+impl Default for BatteryEstimator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BatteryEstimator {
+    pub fn new() -> Self {
+        BatteryEstimator {
+            last: None,
+            rate: None,
+            seconds: None,
+            history: History::new(HISTORY),
+        }
+    }
+
+    // 优先按能量和功率计算，其次使用平台的估算，最后按电量变化速度推算
+    fn estimate(&self, reading: &BatteryReading) -> Option<f64> {
+        let charging = match reading.status {
+            BatteryStatus::Charging => true,
+            BatteryStatus::Discharging => false,
+            _ => return None,
+        };
+        if let (Some(watts), Some((now, full))) = (reading.watts, reading.energy) {
+            if watts > 0.0 {
+                let wh = if charging { full - now } else { now };
+                return Some(wh.max(0.0) / watts * 3600.0);
+            }
+        }
+        if let Some(seconds) = reading.seconds {
+            return Some(seconds as f64);
+        }
+        let rate = self.rate.filter(|rate| *rate > 0.0)?;
+        let left = if charging {
+            100.0 - reading.percent
+        } else {
+            reading.percent
+        };
+        Some(left.max(0.0) / rate)
+    }
+
+    pub fn update(&mut self, reading: BatteryReading) -> BatteryInfo {
+        let now = Instant::now();
+        match self.last {
+            // 充放电状态变化后之前的速度和估算不再适用
+            Some((_, _, status)) if status != reading.status => {
+                self.rate = None;
+                self.seconds = None;
+            }
+            Some((time, percent, _)) => {
+                let elapsed = now.duration_since(time).as_secs_f64();
+                let change = (reading.percent - percent).abs();
+                // 电量通常以 1% 为单位变化，只在发生变化时更新速度
+                if elapsed > 0.0 && change > 0.0 {
+                    let rate = change / elapsed;
+                    self.rate = Some(match self.rate {
+                        Some(last) => last + (rate - last) * SMOOTHING,
+                        None => rate,
+                    });
+                }
+            }
+            None => {}
+        }
+        if self.last.is_none_or(|(_, percent, status)| {
+            percent != reading.percent || status != reading.status
+        }) {
+            self.last = Some((now, reading.percent, reading.status));
+        }
+
+        self.seconds = match (self.estimate(&reading), self.seconds) {
+            (Some(raw), Some(last)) => Some(last + (raw - last) * SMOOTHING),
+            (raw, _) => raw,
+        };
+
+        self.history.push(reading.percent);
+
+        BatteryInfo {
+            percent: reading.percent,
+            status: reading.status,
+            watts: reading.watts,
+            seconds: self.seconds.map(|seconds| seconds as u64),
+            history: self.history.as_slice().to_vec(),
+        }
+    }
+}
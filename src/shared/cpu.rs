@@ -0,0 +1,168 @@
+// 每秒发生的中断、上下文切换和软中断次数
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct CpuEvents {
+    pub intr: u64,
+    pub ctxt: u64,
+    pub softirq: u64,
+}
+
+impl CpuEvents {
+    // self 和 last 为累计次数，计数器变小（例如溢出）时该项为 0。间隔为 0 时没有结果
+    pub fn rate(&self, last: &CpuEvents, elapsed: f64) -> Option<CpuEvents> {
+        let rate = |now: u64, last: u64| (now.saturating_sub(last) as f64 / elapsed) as u64;
+        (elapsed > 0.0).then(|| CpuEvents {
+            intr: rate(self.intr, last.intr),
+            ctxt: rate(self.ctxt, last.ctxt),
+            softirq: rate(self.softirq, last.softirq),
+        })
+    }
+}
+
+// cpu_graph_upper/cpu_graph_lower 可以选择的状态，实际可用的取决于平台和内核版本
+pub const CPU_STATS: &[&str] = &[
+    "total",
+    "user",
+    "nice",
+    "system",
+    "idle",
+    "iowait",
+    "irq",
+    "softirq",
+    "steal",
+    "guest",
+    "guest_nice",
+    "spin",
+];
+
+// 按两次采样之间的节拍增量计算各状态占 total 的百分比，names 与节拍的列一一对应
+pub fn state_stats(
+    total_percent: f64,
+    names: &[&'static str],
+    last: &[u64],
+    now: &[u64],
+    total: u64,
+) -> Vec<(&'static str, f64)> {
+    let mut stats = vec![("total", total_percent)];
+    for ((name, now), last) in names.iter().zip(now).zip(last) {
+        let percent = match total {
+            0 => 0.0,
+            _ => (now.saturating_sub(*last) as f64 * 100.0 / total as f64).clamp(0.0, 100.0),
+        };
+        stats.push((name, percent));
+    }
+    stats
+}
+
+// 去掉型号中的商标、厂商、频率等冗余部分，例如
+// "Intel(R) Core(TM) i7-8700K CPU @ 3.70GHz" -> "Core i7-8700K"
+// "AMD Ryzen 7 5800X 8-Core Processor" -> "Ryzen 7 5800X"
+pub fn clean_model(raw: &str) -> String {
+    let raw = raw.trim_matches(|c: char| c == '\0' || c.is_whitespace());
+    let name = raw.split(" @ ").next().unwrap_or(raw);
+    let name = name.split(" with ").next().unwrap_or(name);
+    let name = ["(R)", "(r)", "(TM)", "(tm)"]
+        .iter()
+        .fold(name.to_owned(), |name, mark| name.replace(mark, ""));
+    let name = name
+        .split_whitespace()
+        .filter(|word| !matches!(*word, "CPU" | "Processor" | "Intel" | "AMD"))
+        .filter(|word| !word.ends_with("-Core"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    match name.is_empty() {
+        true => raw.to_owned(),
+        false => name,
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct CpuInfo {
+    // 整理后的 cpu 型号，检测不到时为空
+    pub model: String,
+    pub cpu_percent: f64,
+    pub core_percent: Vec<f64>,
+    // 离线的核心编号，core_percent 中对应的值为 0
+    pub offline: Vec<usize>,
+    pub load_avg: [f64; 3],
+    // 整体 cpu 各状态的百分比，第一项总是 "total"
+    pub stats: Vec<(&'static str, f64)>,
+    // 平台不支持或还没有上一次采样时为 None
+    pub events: Option<CpuEvents>,
+    // 摄氏度，没有找到温度传感器时为 None
+    pub temp: Option<f64>,
+    // 所有 cpu 封装的功耗（瓦），平台不支持或还没有上一次采样时为 None
+    pub power: Option<f64>,
+    // 只有树莓派提供
+    pub throttled: Option<Throttled>,
+}
+
+// 树莓派当前的降频原因，对应 get_throttled 的低 4 位，高位记录的是开机以来发生过的
+#[derive(Clone, Copy, Default)]
+pub struct Throttled {
+    pub under_voltage: bool,
+    pub freq_capped: bool,
+    pub throttled: bool,
+    pub soft_temp_limit: bool,
+}
+
+impl Throttled {
+    pub fn from_bits(bits: u32) -> Self {
+        Throttled {
+            under_voltage: bits & 0x1 != 0,
+            freq_capped: bits & 0x2 != 0,
+            throttled: bits & 0x4 != 0,
+            soft_temp_limit: bits & 0x8 != 0,
+        }
+    }
+
+    // 没有任何降频时为空
+    pub fn labels(&self) -> Vec<&'static str> {
+        [
+            (self.under_voltage, "under-voltage"),
+            (self.freq_capped, "capped"),
+            (self.throttled, "throttled"),
+            (self.soft_temp_limit, "temp limit"),
+        ]
+        .iter()
+        .filter(|(active, _)| *active)
+        .map(|(_, label)| *label)
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CpuEvents, Throttled};
+
+    #[test]
+    fn event_rates() {
+        let last = CpuEvents {
+            intr: 1000,
+            ctxt: 5000,
+            softirq: 300,
+        };
+        let now = CpuEvents {
+            intr: 3000,
+            ctxt: 5000,
+            softirq: 200,
+        };
+        let rate = CpuEvents {
+            intr: 1000,
+            ctxt: 0,
+            softirq: 0,
+        };
+        assert_eq!(now.rate(&last, 2.0), Some(rate));
+        assert_eq!(now.rate(&last, 0.0), None);
+    }
+
+    // 高位是开机以来发生过的降频，不显示
+    #[test]
+    fn throttled_labels() {
+        assert_eq!(
+            Throttled::from_bits(0x50005).labels(),
+            ["under-voltage", "throttled"]
+        );
+        assert_eq!(Throttled::from_bits(0xa).labels(), ["capped", "temp limit"]);
+        assert!(Throttled::from_bits(0x50000).labels().is_empty());
+    }
+}
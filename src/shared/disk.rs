@@ -0,0 +1,144 @@
+// 一个已挂载文件系统的空间和读写情况，数值单位均为字节
+#[derive(Clone, Default)]
+pub struct DiskStat {
+    // 显示的名称，根目录为 "root"，其余为挂载点的最后一段
+    pub name: String,
+    pub mount: String,
+    pub device: String,
+    pub total: u64,
+    pub used: u64,
+    // 普通用户可用的空间，不包括为 root 保留的部分
+    pub free: u64,
+    // 每秒字节数
+    pub read_rate: u64,
+    pub write_rate: u64,
+    // 两次采集之间设备忙于读写的时间占比，平台不支持或第一次采集时为 None
+    pub io_percent: Option<f64>,
+    // 位于 md 阵列或 device-mapper 设备上时的健康状态
+    pub raid: Option<RaidStatus>,
+    // 硬盘的温度（摄氏度），show_disk_temps 关闭或没有传感器时为 None
+    pub temp: Option<f64>,
+}
+
+#[derive(Clone, Default)]
+pub struct RaidStatus {
+    // 阵列缺少成员或有成员故障
+    pub degraded: bool,
+    // device-mapper 设备被挂起，读写会阻塞
+    pub suspended: bool,
+    // 重建、同步或改变布局的进度百分比
+    pub sync_percent: Option<f64>,
+}
+
+impl RaidStatus {
+    // 一个设备由多个阵列组成时（LVM 跨多个 md），任一阵列降级即为降级
+    pub fn merge(self, other: RaidStatus) -> RaidStatus {
+        RaidStatus {
+            degraded: self.degraded || other.degraded,
+            suspended: self.suspended || other.suspended,
+            sync_percent: match (self.sync_percent, other.sync_percent) {
+                (Some(a), Some(b)) => Some(a.min(b)),
+                (a, b) => a.or(b),
+            },
+        }
+    }
+
+    // 需要用警告色显示
+    pub fn is_warning(&self) -> bool {
+        self.degraded || self.suspended
+    }
+
+    // 显示在磁盘名称后的状态，正常且没有同步时为空
+    pub fn label(&self) -> String {
+        let mut label = match (self.suspended, self.degraded) {
+            (true, _) => "suspended".to_owned(),
+            (false, true) => "degraded".to_owned(),
+            (false, false) => String::new(),
+        };
+        if let Some(percent) = self.sync_percent {
+            if !label.is_empty() {
+                label.push(' ');
+            }
+            label += &format!("sync {:.0}%", percent.floor());
+        }
+        label
+    }
+}
+
+impl DiskStat {
+    pub fn new(device: String, mount: String, total: u64, used: u64, free: u64) -> Self {
+        DiskStat {
+            name: disk_name(&mount),
+            mount,
+            device,
+            total,
+            used,
+            free,
+            ..Default::default()
+        }
+    }
+
+    // 与 df 相同，按已用加可用计算，为 root 保留的空间不计入
+    pub fn used_percent(&self) -> f64 {
+        match self.used + self.free {
+            0 => 0.0,
+            size => self.used as f64 * 100.0 / size as f64,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct DiskInfo {
+    pub disks: Vec<DiskStat>,
+}
+
+impl DiskInfo {
+    // 同一个设备挂载多次（bind mount、btrfs 子卷）时只保留第一次
+    pub fn push(&mut self, stat: DiskStat) {
+        if stat.total == 0 || self.disks.iter().any(|d| d.device == stat.device) {
+            return;
+        }
+        self.disks.push(stat);
+    }
+
+    // 根目录排在最前，其余保持挂载顺序
+    pub fn sort(&mut self) {
+        self.disks.sort_by_key(|d| d.mount != "/");
+    }
+}
+
+fn disk_name(mount: &str) -> String {
+    match mount.trim_end_matches('/').rsplit('/').next() {
+        Some(name) if !name.is_empty() => name.to_owned(),
+        _ => "root".to_owned(),
+    }
+}
+
+// only_physical 时只显示块设备上的文件系统，ZFS 的数据集不以 /dev/ 开头但同样显示。
+// squashfs 通常是 snap 等只读镜像的 loop 设备
+pub fn is_physical(device: &str, fstype: &str) -> bool {
+    fstype == "zfs" || (device.starts_with("/dev/") && fstype != "squashfs")
+}
+
+// disks_filter：以 "exclude=" 开头时隐藏列出的挂载点，否则只显示列出的挂载点，为空时全部显示
+pub struct DiskFilter {
+    exclude: bool,
+    mounts: Vec<String>,
+}
+
+impl DiskFilter {
+    pub fn parse(filter: &str) -> Self {
+        let (exclude, list) = match filter.trim().strip_prefix("exclude=") {
+            Some(list) => (true, list),
+            None => (false, filter),
+        };
+        DiskFilter {
+            exclude,
+            mounts: list.split_whitespace().map(str::to_owned).collect(),
+        }
+    }
+
+    pub fn matches(&self, mount: &str) -> bool {
+        self.mounts.is_empty() || self.mounts.iter().any(|m| m == mount) != self.exclude
+    }
+}
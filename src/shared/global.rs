@@ -1,8 +1,16 @@
 use std::{
     path::PathBuf,
-    sync::{atomic::AtomicBool, Arc, Mutex},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex, OnceLock,
+    },
 };
 
+// 信号处理函数设置的标志，由主循环读取后处理
+static REOPEN: AtomicBool = AtomicBool::new(false);
+static HANGUP: AtomicBool = AtomicBool::new(false);
+static TERMINATE: AtomicBool = AtomicBool::new(false);
+
 pub struct Global {
     pub banner_src: Vec<[String; 2]>,
     pub start_time: u64,
@@ -11,19 +19,31 @@ pub struct Global {
     pub self_path: PathBuf,
     pub arg_low_color: bool,
     pub arg_tty: bool,
-    pub arg_preset: i32,
+    pub arg_utf_force: bool,
+    pub arg_preset: Option<usize>,
+    pub arg_keys_from: PathBuf,
+    pub arg_keys_record: PathBuf,
+    pub arg_daemon: bool,
+    pub arg_screenshot: PathBuf,
+    pub arg_config_file: PathBuf,
+    pub arg_config_dir: PathBuf,
+    pub arg_profile: String,
+    // --boxes、--update、--filter 对应的 (配置项, 值)
+    pub arg_overrides: Vec<(String, String)>,
+    pub control_socket: PathBuf,
     pub quitting: AtomicBool,
     pub resized: AtomicBool,
+    pub refresh: AtomicBool,
+    pub paused: bool,
+    pub warnings: Vec<String>,
 }
 
 impl Global {
     pub fn get_instance() -> Arc<Mutex<Global>> {
-        static mut instance: Option<Arc<Mutex<Global>>> = None;
-        unsafe {
-            instance
-                .get_or_insert_with(|| Arc::new(Mutex::new(Global::new(0, "1.0.0"))))
-                .clone()
-        }
+        static INSTANCE: OnceLock<Arc<Mutex<Global>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(Global::new(0, "1.0.0"))))
+            .clone()
     }
 
     fn new(start_time: u64, version: &str) -> Self {
@@ -41,10 +61,23 @@ impl Global {
             counter: 0,
             self_path: PathBuf::new(),
             arg_tty: false,
+            arg_utf_force: false,
             arg_low_color: false,
-            arg_preset: -1,
+            arg_preset: None,
+            arg_keys_from: PathBuf::new(),
+            arg_keys_record: PathBuf::new(),
+            arg_daemon: false,
+            arg_screenshot: PathBuf::new(),
+            arg_config_file: PathBuf::new(),
+            arg_config_dir: PathBuf::new(),
+            arg_profile: String::new(),
+            arg_overrides: Vec::new(),
+            control_socket: PathBuf::new(),
             quitting: AtomicBool::new(false),
             resized: AtomicBool::new(false),
+            refresh: AtomicBool::new(false),
+            paused: false,
+            warnings: Vec::new(),
         }
     }
 
@@ -92,4 +125,151 @@ impl Global {
     pub fn get_arg_lc(&self) -> bool {
         self.arg_low_color
     }
+
+    pub fn set_utf_force(&mut self) {
+        self.arg_utf_force = true;
+    }
+
+    pub fn get_utf_force(&self) -> bool {
+        self.arg_utf_force
+    }
+
+    pub fn set_preset(&mut self, preset: usize) {
+        self.arg_preset = Some(preset);
+    }
+
+    pub fn get_preset(&self) -> Option<usize> {
+        self.arg_preset
+    }
+
+    pub fn set_keys_from(&mut self, path: PathBuf) {
+        self.arg_keys_from = path;
+    }
+
+    pub fn get_keys_from(&self) -> &PathBuf {
+        &self.arg_keys_from
+    }
+
+    pub fn set_keys_record(&mut self, path: PathBuf) {
+        self.arg_keys_record = path;
+    }
+
+    pub fn get_keys_record(&self) -> &PathBuf {
+        &self.arg_keys_record
+    }
+
+    pub fn set_screenshot(&mut self, path: PathBuf) {
+        self.arg_screenshot = path;
+    }
+
+    pub fn get_screenshot(&self) -> &PathBuf {
+        &self.arg_screenshot
+    }
+
+    pub fn set_config_file(&mut self, path: PathBuf) {
+        self.arg_config_file = path;
+    }
+
+    pub fn get_config_file(&self) -> &PathBuf {
+        &self.arg_config_file
+    }
+
+    pub fn set_config_dir(&mut self, path: PathBuf) {
+        self.arg_config_dir = path;
+    }
+
+    pub fn get_config_dir(&self) -> &PathBuf {
+        &self.arg_config_dir
+    }
+
+    pub fn set_profile(&mut self, name: &str) {
+        self.arg_profile = name.to_owned();
+    }
+
+    pub fn get_profile(&self) -> &str {
+        &self.arg_profile
+    }
+
+    pub fn add_override(&mut self, key: &str, value: &str) {
+        self.arg_overrides.push((key.to_owned(), value.to_owned()));
+    }
+
+    pub fn get_overrides(&self) -> &[(String, String)] {
+        &self.arg_overrides
+    }
+
+    pub fn set_daemon(&mut self) {
+        self.arg_daemon = true;
+    }
+
+    pub fn get_daemon(&self) -> bool {
+        self.arg_daemon
+    }
+
+    // 以下三个由信号处理函数调用，只写静态的原子变量，不能获取 Global 的锁
+    pub fn set_reopen() {
+        REOPEN.store(true, Ordering::Release);
+    }
+
+    // 读取并清除重新打开输出文件的请求
+    pub fn take_reopen() -> bool {
+        REOPEN.swap(false, Ordering::AcqRel)
+    }
+
+    pub fn set_hangup() {
+        HANGUP.store(true, Ordering::Release);
+    }
+
+    pub fn get_hangup() -> bool {
+        HANGUP.load(Ordering::Acquire)
+    }
+
+    // 读取并清除终端挂断的通知
+    pub fn take_hangup() -> bool {
+        HANGUP.swap(false, Ordering::AcqRel)
+    }
+
+    pub fn set_terminate() {
+        TERMINATE.store(true, Ordering::Release);
+    }
+
+    pub fn get_terminate() -> bool {
+        TERMINATE.load(Ordering::Acquire)
+    }
+
+    pub fn set_refresh(&self) {
+        self.refresh
+            .store(true, std::sync::atomic::Ordering::Release);
+    }
+
+    // 读取并清除立即重新采集的请求
+    pub fn take_refresh(&self) -> bool {
+        self.refresh
+            .swap(false, std::sync::atomic::Ordering::AcqRel)
+    }
+
+    // 暂停时不采集，画面保持在暂停时的数据
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    pub fn get_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn set_control_socket(&mut self, path: PathBuf) {
+        self.control_socket = path;
+    }
+
+    pub fn get_control_socket(&self) -> &PathBuf {
+        &self.control_socket
+    }
+
+    pub fn add_warnings(&mut self, warnings: &[String]) {
+        self.warnings.extend_from_slice(warnings);
+    }
+
+    pub fn get_warnings(&self) -> &Vec<String> {
+        &self.warnings
+    }
 }
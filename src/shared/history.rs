@@ -0,0 +1,488 @@
+use std::{collections::HashMap, iter};
+
+use log::warn;
+
+use super::{
+    cpu::{CpuInfo, CPU_STATS},
+    net::NetInfo,
+    proc::ProcInfo,
+};
+
+// 只保留最近 capacity 个采样的历史记录，最新的在最后。
+// 缓冲区满两倍容量时一次丢弃较旧的部分，最近的采样始终连续，绘制时可以直接借用
+pub struct History<T> {
+    values: Vec<T>,
+    capacity: usize,
+}
+
+impl<T: Copy> History<T> {
+    pub fn new(capacity: usize) -> Self {
+        History {
+            values: Vec::with_capacity(capacity * 2),
+            capacity,
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        if self.values.len() >= self.capacity * 2 {
+            self.values.drain(..self.values.len() - self.capacity);
+        }
+        self.values.push(value);
+    }
+
+    pub fn as_slice(&self) -> &[T] {
+        &self.values[self.values.len().saturating_sub(self.capacity)..]
+    }
+
+    pub fn last(&self) -> Option<T> {
+        self.as_slice().last().copied()
+    }
+
+    pub fn len(&self) -> usize {
+        self.as_slice().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.as_slice().is_empty()
+    }
+
+    pub fn get_capacity(&self) -> usize {
+        self.capacity
+    }
+
+    // 缩小时丢弃超出的旧采样
+    pub fn set_capacity(&mut self, capacity: usize) {
+        let start = self.values.len().saturating_sub(capacity);
+        self.values.drain(..start);
+        self.capacity = capacity;
+    }
+}
+
+// 一段历史中的最小值、平均值、最大值和最新的值
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Stats {
+    pub min: f64,
+    pub avg: f64,
+    pub max: f64,
+    pub now: f64,
+}
+
+impl Stats {
+    // 没有采样时为 None
+    pub fn of(values: impl IntoIterator<Item = f64>) -> Option<Self> {
+        let mut count = 0;
+        let mut stats = Stats {
+            min: f64::MAX,
+            avg: 0.0,
+            max: f64::MIN,
+            now: 0.0,
+        };
+        for value in values {
+            stats.min = stats.min.min(value);
+            stats.max = stats.max.max(value);
+            stats.avg += value;
+            stats.now = value;
+            count += 1;
+        }
+        stats.avg /= count as f64;
+        (count > 0).then_some(stats)
+    }
+}
+
+// 没有终端（守护模式）时保留的采样数
+const MIN_DEPTH: usize = 120;
+// 历史文件中核心编号的上限，与 Linux 的 CONFIG_NR_CPUS 最大值相同，
+// 损坏的文件不会让 load 分配大量内存
+const MAX_CORES: usize = 8192;
+
+// 各采集结果的历史，由 Runner 在每次采集后更新。
+// 图形每个字符显示两个采样，保留终端宽度两倍（乘以 graph_range）的采样，
+// 终端变宽后图形可以用已有的采样重新绘制
+pub struct Histories {
+    depth: usize,
+    // 进程的历史不受 graph_range 影响，避免进程很多时占用过多内存
+    proc_depth: usize,
+    // cpu_graph_upper/cpu_graph_lower 可以选择的各项状态
+    cpu: Vec<(&'static str, History<f64>)>,
+    cores: Vec<History<f64>>,
+    // cpu 封装的功耗（瓦），没有 RAPL 时为空
+    power: History<f64>,
+    // 每个接口的 (接收, 发送) 速率，名称为空的一项是除回环接口外所有接口的合计
+    net: Vec<(String, History<(u64, u64)>)>,
+    // 每个进程的 cpu 占用，进程退出后删除
+    procs: HashMap<i32, History<f64>>,
+}
+
+impl Default for Histories {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Histories {
+    pub fn new() -> Self {
+        Histories {
+            depth: MIN_DEPTH,
+            proc_depth: MIN_DEPTH,
+            cpu: Vec::new(),
+            cores: Vec::new(),
+            power: History::new(MIN_DEPTH),
+            net: Vec::new(),
+            procs: HashMap::new(),
+        }
+    }
+
+    pub fn get_depth(&self) -> usize {
+        self.depth
+    }
+
+    // width 为终端的列数，range 为 graph_range
+    pub fn set_width(&mut self, width: u16, range: usize) {
+        let proc_depth = match width {
+            0 => MIN_DEPTH,
+            width => width as usize * 2,
+        };
+        let depth = proc_depth * range.max(1);
+        if (depth, proc_depth) == (self.depth, self.proc_depth) {
+            return;
+        }
+        self.depth = depth;
+        self.proc_depth = proc_depth;
+        self.cpu
+            .iter_mut()
+            .for_each(|(_, history)| history.set_capacity(depth));
+        self.cores
+            .iter_mut()
+            .for_each(|history| history.set_capacity(depth));
+        self.power.set_capacity(depth);
+        self.net
+            .iter_mut()
+            .for_each(|(_, history)| history.set_capacity(depth));
+        self.procs
+            .values_mut()
+            .for_each(|history| history.set_capacity(proc_depth));
+    }
+
+    pub fn push_cpu(&mut self, cpu: &CpuInfo) {
+        for (name, percent) in &cpu.stats {
+            let pos = match self.cpu.iter().position(|(n, _)| n == name) {
+                Some(pos) => pos,
+                None => {
+                    self.cpu.push((name, History::new(self.depth)));
+                    self.cpu.len() - 1
+                }
+            };
+            self.cpu[pos].1.push(*percent);
+        }
+        // 核心数量可能因 cpu 热插拔而变化
+        self.cores
+            .resize_with(cpu.core_percent.len(), || History::new(self.depth));
+        for (history, percent) in self.cores.iter_mut().zip(&cpu.core_percent) {
+            history.push(*percent);
+        }
+        if let Some(power) = cpu.power {
+            self.power.push(power);
+        }
+    }
+
+    pub fn push_net(&mut self, net: &NetInfo) {
+        // 已经消失的接口不再保留
+        self.net.retain(|(name, _)| {
+            name.is_empty() || net.interfaces.iter().any(|stat| stat.name == *name)
+        });
+        let rates = net
+            .interfaces
+            .iter()
+            .map(|stat| (stat.name.as_str(), (stat.rx_rate, stat.tx_rate)))
+            .chain(iter::once(("", net.rates(""))));
+        for (iface, rate) in rates {
+            let pos = match self.net.iter().position(|(name, _)| name == iface) {
+                Some(pos) => pos,
+                None => {
+                    self.net.push((iface.to_owned(), History::new(self.depth)));
+                    self.net.len() - 1
+                }
+            };
+            self.net[pos].1.push(rate);
+        }
+    }
+
+    // 在过滤之前调用，修改过滤条件不会丢失进程的历史
+    pub fn push_procs(&mut self, procs: &[ProcInfo]) {
+        let mut last = std::mem::take(&mut self.procs);
+        for p in procs {
+            let mut history = last
+                .remove(&p.pid)
+                .unwrap_or_else(|| History::new(self.proc_depth));
+            history.push(p.cpu_p);
+            self.procs.insert(p.pid, history);
+        }
+    }
+
+    // 选择的状态在当前平台不可用时退回 total
+    pub fn cpu(&self, stat: &str) -> &[f64] {
+        self.cpu
+            .iter()
+            .find(|(name, _)| *name == stat)
+            .or_else(|| self.cpu.iter().find(|(name, _)| *name == "total"))
+            .map(|(_, history)| history.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn core(&self, core: usize) -> &[f64] {
+        self.cores.get(core).map(History::as_slice).unwrap_or(&[])
+    }
+
+    pub fn power(&self) -> &[f64] {
+        self.power.as_slice()
+    }
+
+    pub fn net(&self, iface: &str) -> &[(u64, u64)] {
+        self.net
+            .iter()
+            .find(|(name, _)| name == iface)
+            .map(|(_, history)| history.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn proc(&self, pid: i32) -> &[f64] {
+        self.procs.get(&pid).map(History::as_slice).unwrap_or(&[])
+    }
+
+    // 保存到文件的文本，每行一个图形：名称后面是以空格分隔的采样。
+    // 采样多于 limit 个时把相邻的采样平均，缩减到不超过 limit 个
+    pub fn save(&self, limit: usize) -> String {
+        let mut out = String::new();
+        let mut line = |name: String, values: Vec<String>| {
+            if !values.is_empty() {
+                out += &format!("{} {}\n", name, values.join(" "));
+            }
+        };
+        let floats = |values: &[f64]| -> Vec<String> {
+            downsample(values, limit, |chunk| {
+                chunk.iter().sum::<f64>() / chunk.len() as f64
+            })
+            .iter()
+            .map(|value| format!("{:.1}", value))
+            .collect()
+        };
+        for (name, history) in &self.cpu {
+            line(format!("cpu {}", name), floats(history.as_slice()));
+        }
+        for (core, history) in self.cores.iter().enumerate() {
+            line(format!("core {}", core), floats(history.as_slice()));
+        }
+        line("power".to_owned(), floats(self.power.as_slice()));
+        for (name, history) in &self.net {
+            let values = downsample(history.as_slice(), limit, |chunk| {
+                let (rx, tx) = chunk
+                    .iter()
+                    .fold((0, 0), |(rx, tx), rate| (rx + rate.0, tx + rate.1));
+                (rx / chunk.len() as u64, tx / chunk.len() as u64)
+            });
+            // 名称为空的合计一项保存为 "*"
+            let name = match name.as_str() {
+                "" => "*",
+                name => name,
+            };
+            line(
+                format!("net {}", name),
+                values
+                    .iter()
+                    .map(|(rx, tx)| format!("{}:{}", rx, tx))
+                    .collect(),
+            );
+        }
+        out
+    }
+
+    // 读取 save 保存的文本，无法解析的行和超出 MAX_CORES 的核心忽略。超出当前深度的旧采样丢弃
+    pub fn load(&mut self, text: &str) {
+        let depth = self.depth;
+        let floats = |values: &[&str]| -> History<f64> {
+            let mut history = History::new(depth);
+            values
+                .iter()
+                .filter_map(|value| value.parse::<f64>().ok())
+                .filter(|value| value.is_finite())
+                .for_each(|value| history.push(value));
+            history
+        };
+        for line in text.lines() {
+            let fields: Vec<&str> = line.split(' ').collect();
+            match fields.as_slice() {
+                ["cpu", name, values @ ..] => {
+                    if let Some(name) = CPU_STATS.iter().find(|stat| *stat == name) {
+                        self.cpu.retain(|(stat, _)| stat != name);
+                        self.cpu.push((name, floats(values)));
+                    }
+                }
+                ["core", core, values @ ..] => {
+                    if let Ok(core) = core.parse::<usize>() {
+                        if core >= MAX_CORES {
+                            warn!("ignoring graph history of core {}", core);
+                            continue;
+                        }
+                        if core >= self.cores.len() {
+                            self.cores.resize_with(core + 1, || History::new(depth));
+                        }
+                        self.cores[core] = floats(values);
+                    }
+                }
+                ["power", values @ ..] => self.power = floats(values),
+                ["net", name, values @ ..] => {
+                    let name = match *name {
+                        "*" => "",
+                        name => name,
+                    };
+                    let mut history = History::new(depth);
+                    values
+                        .iter()
+                        .filter_map(|value| value.split_once(':'))
+                        .filter_map(|(rx, tx)| Some((rx.parse().ok()?, tx.parse().ok()?)))
+                        .for_each(|rate| history.push(rate));
+                    self.net.retain(|(iface, _)| iface != name);
+                    self.net.push((name.to_owned(), history));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+// 把 values 按顺序分成若干组，每组用 merge 合并为一个值，使结果不超过 limit 个
+fn downsample<T: Copy>(values: &[T], limit: usize, merge: impl Fn(&[T]) -> T) -> Vec<T> {
+    let chunk = values.len().div_ceil(limit.max(1)).max(1);
+    // 从最新的采样开始分组，不完整的一组是最旧的部分
+    let mut out: Vec<T> = values.rchunks(chunk).map(merge).collect();
+    out.reverse();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{downsample, Histories, History, MAX_CORES, MIN_DEPTH};
+    use crate::shared::{
+        cpu::CpuInfo,
+        net::{NetInfo, NetStat},
+    };
+
+    #[test]
+    fn push_keeps_latest_capacity() {
+        let mut history = History::new(4);
+        for value in 0..5 {
+            history.push(value);
+        }
+        assert_eq!(history.as_slice(), &[1, 2, 3, 4]);
+        // 超过两倍容量时丢弃旧的部分，之后仍然是最近的 4 个
+        for value in 5..=20 {
+            history.push(value);
+            assert_eq!(history.len(), 4);
+            assert_eq!(history.last(), Some(value));
+            assert!(history.values.len() <= 8);
+        }
+        assert_eq!(history.as_slice(), &[17, 18, 19, 20]);
+    }
+
+    #[test]
+    fn set_capacity_keeps_newest() {
+        let mut history = History::new(0);
+        assert!(history.is_empty());
+        assert_eq!(history.last(), None::<i32>);
+        history.set_capacity(6);
+        for value in 0..6 {
+            history.push(value);
+        }
+        history.set_capacity(3);
+        assert_eq!(history.as_slice(), &[3, 4, 5]);
+        // 放大后旧的采样不会回来，新的采样继续追加
+        history.set_capacity(5);
+        history.push(6);
+        assert_eq!(history.as_slice(), &[3, 4, 5, 6]);
+    }
+
+    #[test]
+    fn set_width_resizes_histories() {
+        let mut histories = Histories::new();
+        histories.set_width(10, 2);
+        assert_eq!(histories.get_depth(), 40);
+        let cpu = CpuInfo {
+            stats: vec![("total", 0.0)],
+            core_percent: vec![0.0],
+            ..Default::default()
+        };
+        for _ in 0..50 {
+            histories.push_cpu(&cpu);
+        }
+        assert_eq!(histories.cpu("total").len(), 40);
+        histories.set_width(5, 1);
+        assert_eq!(histories.get_depth(), 10);
+        assert_eq!(histories.cpu("total").len(), 10);
+        assert_eq!(histories.core(0).len(), 10);
+        // 宽度为 0（守护模式）时使用默认深度
+        histories.set_width(0, 1);
+        assert_eq!(histories.get_depth(), MIN_DEPTH);
+    }
+
+    #[test]
+    fn downsample_groups_from_newest() {
+        let sum = |chunk: &[u32]| chunk.iter().sum();
+        assert_eq!(downsample(&[1, 2, 3, 4], 4, sum), [1, 2, 3, 4]);
+        assert_eq!(downsample(&[1, 2, 3, 4], 2, sum), [3, 7]);
+        // 不完整的一组是最旧的部分
+        assert_eq!(downsample(&[1, 2, 3, 4, 5], 2, sum), [3, 12]);
+        assert_eq!(downsample(&[1, 2, 3], 0, sum), [6]);
+        assert_eq!(downsample(&[] as &[u32], 2, sum), [] as [u32; 0]);
+    }
+
+    #[test]
+    fn save_load_round_trip() {
+        let mut histories = Histories::new();
+        for i in 0..10 {
+            let percent = i as f64 * 10.0;
+            histories.push_cpu(&CpuInfo {
+                stats: vec![("total", percent), ("user", percent / 2.0)],
+                core_percent: vec![percent, 100.0 - percent],
+                power: Some(percent / 4.0),
+                ..Default::default()
+            });
+            histories.push_net(&NetInfo {
+                interfaces: vec![NetStat {
+                    name: "eth0".to_owned(),
+                    rx_rate: i * 1000,
+                    tx_rate: i,
+                    ..Default::default()
+                }],
+            });
+        }
+
+        let text = histories.save(100);
+        let mut loaded = Histories::new();
+        loaded.load(&text);
+        assert_eq!(loaded.cpu("total"), histories.cpu("total"));
+        assert_eq!(loaded.cpu("user"), histories.cpu("user"));
+        assert_eq!(loaded.core(1), histories.core(1));
+        assert_eq!(loaded.power(), histories.power());
+        assert_eq!(loaded.net("eth0"), histories.net("eth0"));
+        assert_eq!(loaded.net(""), histories.net(""));
+        assert_eq!(loaded.save(100), text);
+
+        // 超过 limit 时相邻的采样取平均
+        let mut loaded = Histories::new();
+        loaded.load(&histories.save(5));
+        assert_eq!(loaded.cpu("total"), &[5.0, 25.0, 45.0, 65.0, 85.0]);
+        assert_eq!(loaded.net("eth0")[0], (500, 0));
+    }
+
+    #[test]
+    fn load_skips_out_of_range_cores() {
+        let mut histories = Histories::new();
+        histories.load(&format!(
+            "core 4000000000 1.0\ncore {} 2.0\ncore 1 3.0 4.0\n",
+            MAX_CORES
+        ));
+        assert_eq!(histories.cores.len(), 2);
+        assert_eq!(histories.core(0), &[] as &[f64]);
+        assert_eq!(histories.core(1), &[3.0, 4.0]);
+    }
+}
@@ -0,0 +1,122 @@
+use std::{
+    env,
+    ffi::CStr,
+    fs,
+    sync::{Arc, Mutex, OnceLock},
+};
+
+// 主机名、当前用户和系统信息只在启动时读取一次，之后可以通过控制套接字的 refresh 命令重新读取
+#[derive(Clone)]
+pub struct HostInfo {
+    pub hostname: String,
+    pub user: String,
+    // 发行版名称，没有 os-release 时为内核名称（例如 Darwin、OpenBSD）
+    pub distro: String,
+    pub kernel: String,
+    pub arch: String,
+}
+
+fn hostname() -> String {
+    let mut buf = [0u8; 256];
+    let ret = unsafe { libc::gethostname(buf.as_mut_ptr() as *mut libc::c_char, buf.len()) };
+    if ret != 0 {
+        return String::new();
+    }
+    CStr::from_bytes_until_nul(&buf)
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+// 优先使用有效 uid 对应的用户名，查不到时（例如容器中没有 passwd 条目）退回环境变量
+fn user() -> String {
+    let pw = unsafe { libc::getpwuid(libc::geteuid()) };
+    if !pw.is_null() {
+        return unsafe { CStr::from_ptr((*pw).pw_name) }
+            .to_string_lossy()
+            .into_owned();
+    }
+    ["USER", "LOGNAME"]
+        .iter()
+        .find_map(|key| env::var(key).ok().filter(|name| !name.is_empty()))
+        .unwrap_or_else(|| unsafe { libc::geteuid() }.to_string())
+}
+
+// uname 的 (sysname, release, machine)
+fn uname() -> (String, String, String) {
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut name) } != 0 {
+        return (String::new(), String::new(), env::consts::ARCH.to_owned());
+    }
+    let field = |field: &[libc::c_char]| {
+        unsafe { CStr::from_ptr(field.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    (
+        field(&name.sysname),
+        field(&name.release),
+        field(&name.machine),
+    )
+}
+
+// os-release 中的 PRETTY_NAME，没有时使用 NAME 和 VERSION
+fn os_release() -> Option<String> {
+    let text = ["/etc/os-release", "/usr/lib/os-release"]
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())?;
+    let value = |key: &str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(key)?.strip_prefix('='))
+            .map(|value| value.trim().trim_matches('"').to_owned())
+            .filter(|value| !value.is_empty())
+    };
+    value("PRETTY_NAME").or_else(|| {
+        let name = value("NAME")?;
+        Some(match value("VERSION") {
+            Some(version) => format!("{} {}", name, version),
+            None => name,
+        })
+    })
+}
+
+impl HostInfo {
+    pub fn get_instance() -> Arc<Mutex<HostInfo>> {
+        static INSTANCE: OnceLock<Arc<Mutex<HostInfo>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(HostInfo::new())))
+            .clone()
+    }
+
+    fn new() -> Self {
+        let (sysname, kernel, arch) = uname();
+        HostInfo {
+            hostname: hostname(),
+            user: user(),
+            distro: os_release().unwrap_or(sysname),
+            kernel,
+            arch,
+        }
+    }
+
+    pub fn refresh(&mut self) {
+        *self = HostInfo::new();
+    }
+
+    pub fn get_hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    pub fn get_user(&self) -> &str {
+        &self.user
+    }
+
+    // 显示在 cpu box 标题栏和菜单中，例如 "Debian GNU/Linux 12 (bookworm) 6.1.0 x86_64"
+    pub fn get_system(&self) -> String {
+        [&self.distro, &self.kernel, &self.arch]
+            .iter()
+            .filter(|part| !part.is_empty())
+            .map(|part| part.as_str())
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
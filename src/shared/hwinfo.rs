@@ -0,0 +1,19 @@
+#[derive(Clone, Default)]
+pub struct CacheInfo {
+    pub level: u32,
+    pub cache_type: String,
+    pub size: u64,
+    pub instances: usize,
+}
+
+#[derive(Clone, Default)]
+pub struct HwInfo {
+    pub sockets: usize,
+    pub cores: usize,
+    pub threads: usize,
+    pub caches: Vec<CacheInfo>,
+    // dimm_count 为 0 时 mem_total 来自内存块或 /proc/meminfo
+    pub dimm_count: usize,
+    pub mem_total: u64,
+    pub gpus: Vec<String>,
+}
@@ -0,0 +1,32 @@
+// log box 中保留的内核日志条数
+pub const KLOG_LINES: usize = 500;
+
+// 内核日志中的一条记录，level 为 syslog 优先级，0（emerg）到 7（debug）
+#[derive(Clone)]
+pub struct KlogEntry {
+    pub level: u8,
+    // 开机以来的秒数
+    pub time: f64,
+    pub message: String,
+}
+
+// 最近的内核日志，最新的在最后。无法读取时 error 为原因
+#[derive(Clone, Default)]
+pub struct KlogInfo {
+    pub entries: Vec<KlogEntry>,
+    pub error: Option<String>,
+}
+
+// /dev/kmsg 的一条记录："6,339,5140900,-;NET: Registered PF_INET6 protocol family"，
+// 分号前为优先级、序号、微秒时间戳和标志，消息后面可能还有以空格开头的 KEY=value 行
+pub fn parse_kmsg(record: &str) -> Option<KlogEntry> {
+    let (prefix, rest) = record.split_once(';')?;
+    let mut fields = prefix.split(',');
+    let priority: u32 = fields.next()?.parse().ok()?;
+    let usec: u64 = fields.nth(1)?.parse().ok()?;
+    Some(KlogEntry {
+        level: (priority & 7) as u8,
+        time: usec as f64 / 1e6,
+        message: rest.lines().next().unwrap_or_default().to_owned(),
+    })
+}
@@ -0,0 +1,26 @@
+// 容易被耗尽的系统资源，max 为 0 表示没有上限
+#[derive(Clone, Default)]
+pub struct Limit {
+    pub name: String,
+    pub used: u64,
+    pub max: u64,
+}
+
+impl Limit {
+    pub fn new(name: &str, used: u64, max: u64) -> Self {
+        Limit {
+            name: name.to_owned(),
+            used,
+            max,
+        }
+    }
+
+    pub fn percent(&self) -> Option<f64> {
+        (self.max > 0).then(|| self.used as f64 * 100.0 / self.max as f64)
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SysLimits {
+    pub limits: Vec<Limit>,
+}
@@ -0,0 +1,94 @@
+// 所有数值单位均为字节
+#[derive(Clone, Default)]
+pub struct MemInfo {
+    pub total: u64,
+    pub available: u64,
+    pub used: u64,
+    pub free: u64,
+    pub cached: u64,
+    // tmpfs 和共享内存段，已经包含在 cached 中
+    pub shmem: u64,
+    // 预留的大页不计入 available，也不属于任何进程的常规内存
+    pub hugepages_total: u64,
+    pub hugepages_free: u64,
+    pub swap_total: u64,
+    pub swap_free: u64,
+    pub swap_used: u64,
+    pub swaps: Vec<SwapDevice>,
+    // 平台不支持或还没有上一次采样时为 None
+    pub paging: Option<PagingRates>,
+    // 开机以来内核 OOM killer 杀死的进程数，内核 4.13 之前没有这个计数
+    pub oom_kills: Option<u64>,
+    // 每个 NUMA 节点的内存，只有一个节点或平台不支持时为空
+    pub numa: Vec<NumaNode>,
+}
+
+// /sys/devices/system/node/nodeN/meminfo 中的一个节点，used 不包括页缓存
+#[derive(Clone, Default)]
+pub struct NumaNode {
+    pub id: u32,
+    pub total: u64,
+    pub used: u64,
+    pub free: u64,
+    pub cached: u64,
+}
+
+// 每秒换入、换出交换空间的字节数和需要读取磁盘的缺页次数
+#[derive(Clone, Copy, Default, PartialEq, Debug)]
+pub struct PagingRates {
+    pub swap_in: u64,
+    pub swap_out: u64,
+    pub major_faults: u64,
+}
+
+impl PagingRates {
+    // self 和 last 为累计值，计数器变小时该项为 0。间隔为 0 时没有结果
+    pub fn rate(&self, last: &PagingRates, elapsed: f64) -> Option<PagingRates> {
+        let rate = |now: u64, last: u64| (now.saturating_sub(last) as f64 / elapsed) as u64;
+        (elapsed > 0.0).then(|| PagingRates {
+            swap_in: rate(self.swap_in, last.swap_in),
+            swap_out: rate(self.swap_out, last.swap_out),
+            major_faults: rate(self.major_faults, last.major_faults),
+        })
+    }
+}
+
+// /proc/swaps 中的一个交换分区或交换文件
+#[derive(Clone, Default)]
+pub struct SwapDevice {
+    pub name: String,
+    // "partition" 或 "file"
+    pub kind: String,
+    pub size: u64,
+    pub used: u64,
+    // 数值越大越优先使用
+    pub priority: i32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PagingRates;
+
+    #[test]
+    fn paging_rates() {
+        let last = PagingRates {
+            swap_in: 4 << 20,
+            swap_out: 1 << 20,
+            major_faults: 100,
+        };
+        let now = PagingRates {
+            swap_in: 10 << 20,
+            swap_out: 0,
+            major_faults: 400,
+        };
+        assert_eq!(
+            now.rate(&last, 2.0),
+            Some(PagingRates {
+                swap_in: 3 << 20,
+                swap_out: 0,
+                major_faults: 150,
+            })
+        );
+        assert_eq!(now.rate(&last, 0.0), None);
+    }
+}
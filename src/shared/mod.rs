@@ -1 +1,15 @@
+pub mod alert;
+pub mod battery;
+pub mod cpu;
+pub mod disk;
 pub mod global;
+pub mod history;
+pub mod host;
+pub mod hwinfo;
+pub mod klog;
+pub mod limits;
+pub mod mem;
+pub mod net;
+pub mod proc;
+pub mod sensors;
+pub mod socket;
@@ -0,0 +1,163 @@
+#[derive(Clone, Default)]
+pub struct NetStat {
+    pub name: String,
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    // 每秒字节数
+    pub rx_rate: u64,
+    pub tx_rate: u64,
+    // 平台不提供包计数时为 None
+    pub packets: Option<NetPackets>,
+    // 不是无线接口时为 None
+    pub wifi: Option<WifiInfo>,
+}
+
+// 无线接口的连接信息，驱动不提供的项为 None
+#[derive(Clone, Default)]
+pub struct WifiInfo {
+    pub ssid: Option<String>,
+    // 链路质量的百分比
+    pub quality: Option<f64>,
+    // 信号强度，单位为 dBm
+    pub signal: Option<i32>,
+    // 发送速率，单位为 bit/s
+    pub bitrate: Option<u64>,
+}
+
+// 包数、错误和丢弃为累计值，速率为每秒包数
+#[derive(Clone, Copy, Default)]
+pub struct NetPackets {
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+    pub rx_errors: u64,
+    pub tx_errors: u64,
+    pub rx_drops: u64,
+    pub tx_drops: u64,
+    pub rx_rate: u64,
+    pub tx_rate: u64,
+}
+
+#[derive(Clone, Default)]
+pub struct NetInfo {
+    pub interfaces: Vec<NetStat>,
+}
+
+impl NetInfo {
+    // iface 为空时汇总除回环接口外的所有接口
+    pub fn rates(&self, iface: &str) -> (u64, u64) {
+        self.interfaces
+            .iter()
+            .filter(|stat| {
+                if iface.is_empty() {
+                    stat.name != "lo"
+                } else {
+                    stat.name == iface
+                }
+            })
+            .fold((0, 0), |(rx, tx), stat| {
+                (rx + stat.rx_rate, tx + stat.tx_rate)
+            })
+    }
+
+    // 与 rates 选择相同的接口，所有接口都没有包计数时为 None
+    pub fn packets(&self, iface: &str) -> Option<NetPackets> {
+        self.interfaces
+            .iter()
+            .filter(|stat| match iface {
+                "" => stat.name != "lo",
+                iface => stat.name == iface,
+            })
+            .filter_map(|stat| stat.packets)
+            .reduce(|a, b| NetPackets {
+                rx_packets: a.rx_packets + b.rx_packets,
+                tx_packets: a.tx_packets + b.tx_packets,
+                rx_errors: a.rx_errors + b.rx_errors,
+                tx_errors: a.tx_errors + b.tx_errors,
+                rx_drops: a.rx_drops + b.rx_drops,
+                tx_drops: a.tx_drops + b.tx_drops,
+                rx_rate: a.rx_rate + b.rx_rate,
+                tx_rate: a.tx_rate + b.tx_rate,
+            })
+    }
+}
+
+// 常见 VPN 和隧道接口的名称前缀，包括 WireGuard、OpenVPN、Tailscale、ZeroTier 和 macOS 的 utun
+const TUNNEL_PREFIXES: &[&str] = &[
+    "tun",
+    "tap",
+    "wg",
+    "utun",
+    "ppp",
+    "ipsec",
+    "tailscale",
+    "zt",
+    "nordlynx",
+    "gre",
+    "gif",
+    "vti",
+];
+
+// 不直接连到外部网络的虚拟接口，计算开销时既不算隧道也不算物理接口
+const VIRTUAL_PREFIXES: &[&str] = &[
+    "lo", "docker", "veth", "br-", "virbr", "vmnet", "vboxnet", "cni", "flannel", "cali", "lxc",
+    "bridge", "awdl", "llw", "anpi",
+];
+
+pub fn is_tunnel(name: &str) -> bool {
+    TUNNEL_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+}
+
+pub fn is_physical(name: &str) -> bool {
+    !is_tunnel(name)
+        && !VIRTUAL_PREFIXES
+            .iter()
+            .any(|prefix| name.starts_with(prefix))
+}
+
+// 隧道接口上的流量为有效载荷，物理接口上的流量为实际发送和接收的字节
+pub struct Overhead {
+    pub tunnels: Vec<String>,
+    pub physical: Vec<String>,
+    pub payload: (u64, u64),
+    pub wire: (u64, u64),
+}
+
+impl Overhead {
+    // 物理接口上也可能有不经过隧道的流量，此时算出的开销偏大
+    pub fn percent(payload: u64, wire: u64) -> Option<f64> {
+        match payload {
+            0 => None,
+            _ => Some((wire as f64 - payload as f64) * 100.0 / payload as f64),
+        }
+    }
+}
+
+impl NetInfo {
+    // 没有正在传输数据的隧道接口或没有物理接口时返回 None
+    pub fn overhead(&self) -> Option<Overhead> {
+        let mut overhead = Overhead {
+            tunnels: Vec::new(),
+            physical: Vec::new(),
+            payload: (0, 0),
+            wire: (0, 0),
+        };
+        for stat in &self.interfaces {
+            let (names, (rx, tx)) = if is_tunnel(&stat.name) {
+                if stat.rx_rate + stat.tx_rate == 0 {
+                    continue;
+                }
+                (&mut overhead.tunnels, &mut overhead.payload)
+            } else if is_physical(&stat.name) {
+                (&mut overhead.physical, &mut overhead.wire)
+            } else {
+                continue;
+            };
+            names.push(stat.name.clone());
+            *rx += stat.rx_rate;
+            *tx += stat.tx_rate;
+        }
+        (!overhead.tunnels.is_empty() && !overhead.physical.is_empty()).then_some(overhead)
+    }
+}
@@ -0,0 +1,727 @@
+use std::fmt;
+
+use crate::util::regex::Regex;
+
+#[derive(Clone, Default)]
+pub struct ProcInfo {
+    pub pid: i32,
+    pub ppid: i32,
+    pub name: String,
+    pub cmd: String,
+    pub user: String,
+    pub uid: u32,
+    pub state: char,
+    pub threads: u32,
+    pub nice: i32,
+    // 常驻内存，单位字节
+    pub mem: u64,
+    pub cpu_p: f64,
+    // utime + stime，单位为时钟节拍
+    pub cpu_time: u64,
+    pub container: bool,
+    // /proc/[pid]/io 中累计读写的字节数和每秒速率，没有权限读取时为 None
+    pub io_bytes: Option<(u64, u64)>,
+    pub io_rate: Option<(u64, u64)>,
+}
+
+// 系统中的进程和线程总数，以及运行中和僵尸进程的数量，在按来源和 proc_filter 过滤之前统计
+#[derive(Clone, Copy, Default, PartialEq)]
+pub struct ProcCounts {
+    pub procs: usize,
+    pub threads: u64,
+    pub running: usize,
+    pub zombie: usize,
+}
+
+impl ProcCounts {
+    pub fn count(procs: &[ProcInfo]) -> Self {
+        ProcCounts {
+            procs: procs.len(),
+            threads: procs.iter().map(|proc| proc.threads as u64).sum(),
+            running: procs.iter().filter(|proc| proc.state == 'R').count(),
+            zombie: procs.iter().filter(|proc| proc.state == 'Z').count(),
+        }
+    }
+}
+
+// 进程资源限制中的一项，值保留原文，例如 "unlimited"
+#[derive(Clone, Default)]
+pub struct ProcLimit {
+    pub name: String,
+    pub soft: String,
+    pub hard: String,
+    pub unit: String,
+}
+
+// 进程阻塞所在的内核函数和内核调用栈，没有阻塞时 wchan 为空
+#[derive(Clone, Default)]
+pub struct ProcStack {
+    pub wchan: String,
+    // 没有权限读取内核栈时为 None
+    pub frames: Option<Vec<String>>,
+}
+
+// 文件描述符指向的对象类别
+#[derive(Clone, Copy, PartialEq)]
+pub enum FdKind {
+    File,
+    Socket,
+    Pipe,
+    Other,
+}
+
+impl FdKind {
+    pub const ALL: [FdKind; 4] = [FdKind::File, FdKind::Socket, FdKind::Pipe, FdKind::Other];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            FdKind::File => "Files",
+            FdKind::Socket => "Sockets",
+            FdKind::Pipe => "Pipes",
+            FdKind::Other => "Other",
+        }
+    }
+}
+
+// 进程打开的一个文件描述符，套接字的 target 为解析后的地址
+#[derive(Clone)]
+pub struct ProcFd {
+    pub fd: i32,
+    pub kind: FdKind,
+    pub target: String,
+}
+
+// 进程中的一个线程，cpu_time 为累计的 cpu 时间，单位秒
+#[derive(Clone)]
+pub struct ProcThread {
+    pub tid: i32,
+    pub name: String,
+    pub state: char,
+    pub cpu_time: f64,
+    // 由调用方根据两次读取之间的 cpu_time 计算，第一次读取时为 None
+    pub cpu_p: Option<f64>,
+}
+
+// 各平台读到的环境变量都是以 '\0' 分隔的 KEY=VALUE，排序后便于查找
+pub fn parse_environ(data: &[u8]) -> Vec<String> {
+    let mut vars: Vec<String> = data
+        .split(|&b| b == 0)
+        .filter(|var| !var.is_empty())
+        .map(|var| String::from_utf8_lossy(var).into_owned())
+        .collect();
+    vars.sort();
+    vars
+}
+
+// 按照 cgroup 判断进程来源，用于区分宿主机服务与容器负载
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProcOrigin {
+    All,
+    Host,
+    Container,
+}
+
+impl ProcOrigin {
+    pub const NAMES: [&'static str; 3] = ["all", "host", "container"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "all" => Some(ProcOrigin::All),
+            "host" => Some(ProcOrigin::Host),
+            "container" => Some(ProcOrigin::Container),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcOrigin::All => "all",
+            ProcOrigin::Host => "host",
+            ProcOrigin::Container => "container",
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            ProcOrigin::All => ProcOrigin::Host,
+            ProcOrigin::Host => ProcOrigin::Container,
+            ProcOrigin::Container => ProcOrigin::All,
+        }
+    }
+
+    pub fn matches(&self, proc: &ProcInfo) -> bool {
+        match self {
+            ProcOrigin::All => true,
+            ProcOrigin::Host => !proc.container,
+            ProcOrigin::Container => proc.container,
+        }
+    }
+}
+
+// 过滤条件中可以比较大小的字段
+#[derive(Clone, Copy, PartialEq)]
+enum FilterField {
+    Pid,
+    Ppid,
+    Nice,
+    Threads,
+    Cpu,
+    Mem,
+}
+
+impl FilterField {
+    fn value(&self, proc: &ProcInfo) -> f64 {
+        match self {
+            FilterField::Pid => proc.pid as f64,
+            FilterField::Ppid => proc.ppid as f64,
+            FilterField::Nice => proc.nice as f64,
+            FilterField::Threads => proc.threads as f64,
+            FilterField::Cpu => proc.cpu_p,
+            FilterField::Mem => proc.mem as f64,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum FilterCmp {
+    Less,
+    LessEq,
+    Equal,
+    GreaterEq,
+    Greater,
+}
+
+#[derive(Clone, PartialEq)]
+enum FilterMatch {
+    // 不带前缀的文本在名称、命令行、用户和 pid 中查找，不区分大小写
+    Text(String),
+    Name(String),
+    User(String),
+    State(char),
+    Number(FilterField, FilterCmp, f64),
+}
+
+// 过滤条件中的一项，以 "!" 开头表示取反
+#[derive(Clone, PartialEq)]
+struct FilterTerm {
+    negate: bool,
+    matcher: FilterMatch,
+}
+
+// proc_filter 解析后的过滤表达式，以空白分隔的各项需要同时满足，例如
+// "user:alice state:zombie pid:>1000 python"。正则模式下整个 proc_filter 是一个表达式
+#[derive(Default)]
+pub struct ProcFilter {
+    terms: Vec<FilterTerm>,
+    regex: Option<Regex>,
+}
+
+// 与 ps(1) 的 STAT 列相同的状态字母，也可以使用全称
+fn parse_state(value: &str) -> Option<char> {
+    let state = match value.to_lowercase().as_str() {
+        "running" | "r" => 'R',
+        "sleeping" | "s" => 'S',
+        "disk" | "d" => 'D',
+        "zombie" | "z" => 'Z',
+        "stopped" | "t" => 'T',
+        "idle" | "i" => 'I',
+        "dead" | "x" => 'X',
+        _ => return None,
+    };
+    Some(state)
+}
+
+// mem 的值可以带 K、M、G、T 后缀，按 1024 进位
+fn parse_number(field: FilterField, value: &str) -> Option<f64> {
+    let (digits, scale) = match value.char_indices().last()? {
+        (at, 'k' | 'K') if field == FilterField::Mem => (&value[..at], 1u64 << 10),
+        (at, 'm' | 'M') if field == FilterField::Mem => (&value[..at], 1 << 20),
+        (at, 'g' | 'G') if field == FilterField::Mem => (&value[..at], 1 << 30),
+        (at, 't' | 'T') if field == FilterField::Mem => (&value[..at], 1 << 40),
+        _ => (value, 1),
+    };
+    let number = digits.parse::<f64>().ok()?;
+    number.is_finite().then_some(number * scale as f64)
+}
+
+impl ProcFilter {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let terms = text
+            .split_whitespace()
+            // 单独的 "!" 是还没输入完的取反，不作为条件
+            .filter(|word| *word != "!")
+            .map(|word| {
+                let (negate, word) = match word.strip_prefix('!') {
+                    Some(rest) => (true, rest),
+                    None => (false, word),
+                };
+                let Some((key, value)) = word.split_once(':') else {
+                    return Ok(FilterTerm {
+                        negate,
+                        matcher: FilterMatch::Text(word.to_lowercase()),
+                    });
+                };
+                if value.is_empty() {
+                    return Err(format!("missing value for {}", key));
+                }
+                let field = match key {
+                    "name" => {
+                        return Ok(FilterTerm {
+                            negate,
+                            matcher: FilterMatch::Name(value.to_lowercase()),
+                        })
+                    }
+                    "user" => {
+                        return Ok(FilterTerm {
+                            negate,
+                            matcher: FilterMatch::User(value.to_owned()),
+                        })
+                    }
+                    "state" => {
+                        let state =
+                            parse_state(value).ok_or_else(|| format!("unknown state {}", value))?;
+                        return Ok(FilterTerm {
+                            negate,
+                            matcher: FilterMatch::State(state),
+                        });
+                    }
+                    "pid" => FilterField::Pid,
+                    "ppid" => FilterField::Ppid,
+                    "nice" => FilterField::Nice,
+                    "threads" => FilterField::Threads,
+                    "cpu" => FilterField::Cpu,
+                    "mem" => FilterField::Mem,
+                    _ => return Err(format!("unknown filter {}", key)),
+                };
+                let (cmp, number) = [
+                    (">=", FilterCmp::GreaterEq),
+                    ("<=", FilterCmp::LessEq),
+                    (">", FilterCmp::Greater),
+                    ("<", FilterCmp::Less),
+                    ("=", FilterCmp::Equal),
+                ]
+                .iter()
+                .find_map(|(op, cmp)| value.strip_prefix(op).map(|rest| (*cmp, rest)))
+                .unwrap_or((FilterCmp::Equal, value));
+                let number = parse_number(field, number)
+                    .ok_or_else(|| format!("invalid number for {}: {}", key, number))?;
+                Ok(FilterTerm {
+                    negate,
+                    matcher: FilterMatch::Number(field, cmp, number),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(ProcFilter { terms, regex: None })
+    }
+
+    // 与普通文本相同，在名称、命令行、用户和 pid 中查找
+    pub fn parse_regex(pattern: &str) -> Result<Self, String> {
+        if pattern.trim().is_empty() {
+            return Ok(ProcFilter::default());
+        }
+        Ok(ProcFilter {
+            terms: Vec::new(),
+            regex: Some(Regex::new(pattern)?),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty() && self.regex.is_none()
+    }
+
+    pub fn matches(&self, proc: &ProcInfo) -> bool {
+        if let Some(regex) = &self.regex {
+            return regex.is_match(&proc.name)
+                || regex.is_match(&proc.cmd)
+                || regex.is_match(&proc.user)
+                || regex.is_match(&proc.pid.to_string());
+        }
+        self.terms.iter().all(|term| {
+            let matched = match &term.matcher {
+                FilterMatch::Text(text) => {
+                    proc.name.to_lowercase().contains(text)
+                        || proc.cmd.to_lowercase().contains(text)
+                        || proc.user.to_lowercase().contains(text)
+                        || proc.pid.to_string().contains(text)
+                }
+                FilterMatch::Name(name) => proc.name.to_lowercase().contains(name),
+                FilterMatch::User(user) => proc.user == *user,
+                FilterMatch::State(state) => proc.state == *state,
+                FilterMatch::Number(field, cmp, number) => {
+                    let value = field.value(proc);
+                    match cmp {
+                        FilterCmp::Less => value < *number,
+                        FilterCmp::LessEq => value <= *number,
+                        FilterCmp::Equal => value == *number,
+                        FilterCmp::GreaterEq => value >= *number,
+                        FilterCmp::Greater => value > *number,
+                    }
+                }
+            };
+            matched != term.negate
+        })
+    }
+}
+
+// 进程列表的排序字段，名称与 proc_sorting 的写法一致
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProcSort {
+    Pid,
+    Program,
+    Command,
+    Threads,
+    User,
+    Memory,
+    Cpu,
+}
+
+impl ProcSort {
+    pub const NAMES: [&'static str; 7] = [
+        "pid",
+        "program",
+        "arguments",
+        "threads",
+        "user",
+        "memory",
+        "cpu",
+    ];
+    // 与 proc box 中各列的顺序一致，左右方向键按这个顺序切换
+    pub const ALL: [ProcSort; 7] = [
+        ProcSort::Pid,
+        ProcSort::Program,
+        ProcSort::Command,
+        ProcSort::Threads,
+        ProcSort::User,
+        ProcSort::Memory,
+        ProcSort::Cpu,
+    ];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pid" => Some(ProcSort::Pid),
+            "program" | "name" => Some(ProcSort::Program),
+            "arguments" | "command" | "cmd" => Some(ProcSort::Command),
+            "threads" => Some(ProcSort::Threads),
+            "user" => Some(ProcSort::User),
+            "memory" | "mem" => Some(ProcSort::Memory),
+            "cpu" | "cpu direct" | "cpu lazy" | "cpu responsive" => Some(ProcSort::Cpu),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ProcSort::Pid => "pid",
+            ProcSort::Program => "program",
+            ProcSort::Command => "arguments",
+            ProcSort::Threads => "threads",
+            ProcSort::User => "user",
+            ProcSort::Memory => "memory",
+            ProcSort::Cpu => "cpu",
+        }
+    }
+
+    pub fn next(&self, forward: bool) -> Self {
+        let pos = ProcSort::ALL
+            .iter()
+            .position(|sort| sort == self)
+            .unwrap_or(0);
+        let len = ProcSort::ALL.len();
+        match forward {
+            true => ProcSort::ALL[(pos + 1) % len],
+            false => ProcSort::ALL[(pos + len - 1) % len],
+        }
+    }
+
+    pub fn descending(&self) -> bool {
+        matches!(self, ProcSort::Threads | ProcSort::Memory | ProcSort::Cpu)
+    }
+
+    // 数值字段从大到小，文本字段和 pid 从小到大，相同时按 pid 排列保证顺序稳定
+    pub fn sort(&self, procs: &mut [ProcInfo]) {
+        procs.sort_by(|a, b| {
+            let order = match self {
+                ProcSort::Pid => a.pid.cmp(&b.pid),
+                ProcSort::Program => a.name.cmp(&b.name),
+                ProcSort::Command => a.cmd.cmp(&b.cmd),
+                ProcSort::Threads => b.threads.cmp(&a.threads),
+                ProcSort::User => a.user.cmp(&b.user),
+                ProcSort::Memory => b.mem.cmp(&a.mem),
+                ProcSort::Cpu => b.cpu_p.total_cmp(&a.cpu_p),
+            };
+            order.then(a.pid.cmp(&b.pid))
+        });
+    }
+}
+
+// I/O 调度类别与优先级，对应 ioprio_get(2) 中的 IOPRIO_CLASS_* 和 0-7 的级别
+#[derive(Clone, Copy, PartialEq)]
+pub enum IoClass {
+    // 未设置时内核按 nice 值推算尽力而为级别
+    None,
+    RealTime,
+    BestEffort,
+    Idle,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct IoPriority {
+    pub class: IoClass,
+    pub level: u8,
+}
+
+impl IoPriority {
+    const CLASS_SHIFT: i32 = 13;
+    pub const MAX_LEVEL: u8 = 7;
+
+    pub fn from_raw(raw: i32) -> Self {
+        let class = match raw >> Self::CLASS_SHIFT {
+            1 => IoClass::RealTime,
+            2 => IoClass::BestEffort,
+            3 => IoClass::Idle,
+            _ => IoClass::None,
+        };
+        IoPriority {
+            class,
+            level: (raw & ((1 << Self::CLASS_SHIFT) - 1)).min(Self::MAX_LEVEL as i32) as u8,
+        }
+    }
+
+    pub fn to_raw(self) -> i32 {
+        let class = match self.class {
+            IoClass::None => 0,
+            IoClass::RealTime => 1,
+            IoClass::BestEffort => 2,
+            IoClass::Idle => 3,
+        };
+        let level = match self.class {
+            IoClass::RealTime | IoClass::BestEffort => self.level.min(Self::MAX_LEVEL) as i32,
+            IoClass::None | IoClass::Idle => 0,
+        };
+        (class << Self::CLASS_SHIFT) | level
+    }
+
+    // 与 ionice(1) 相同的写法："none"、"idle"、"be/4"、"rt/0"
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (class, level) = match name.split_once('/') {
+            Some((class, level)) => (class, Some(level.parse::<u8>().ok()?)),
+            None => (name, None),
+        };
+        let class = match class {
+            "none" => IoClass::None,
+            "rt" | "realtime" => IoClass::RealTime,
+            "be" | "best-effort" => IoClass::BestEffort,
+            "idle" => IoClass::Idle,
+            _ => return None,
+        };
+        let level = level.unwrap_or(4);
+        if level > Self::MAX_LEVEL {
+            return None;
+        }
+        Some(IoPriority { class, level })
+    }
+
+    pub fn next_class(self) -> Self {
+        let class = match self.class {
+            // 内核对未设置的进程返回级别 0，这里改用默认级别 4
+            IoClass::None => {
+                return IoPriority {
+                    class: IoClass::BestEffort,
+                    level: 4,
+                }
+            }
+            IoClass::BestEffort => IoClass::Idle,
+            IoClass::Idle => IoClass::RealTime,
+            IoClass::RealTime => IoClass::None,
+        };
+        IoPriority { class, ..self }
+    }
+}
+
+impl fmt::Display for IoPriority {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.class {
+            IoClass::None => write!(f, "none"),
+            IoClass::Idle => write!(f, "idle"),
+            IoClass::RealTime => write!(f, "rt/{}", self.level),
+            IoClass::BestEffort => write!(f, "be/{}", self.level),
+        }
+    }
+}
+
+// 提升到实时范围前的检查结果
+#[derive(Clone, PartialEq, Debug)]
+pub enum BoostCheck {
+    // 需要用户再次确认，附带风险说明
+    Confirm(String),
+    // 会让系统中其它进程得不到调度，直接拒绝
+    Refuse(String),
+}
+
+// 占用超过整机 cpu 这个比例的进程不允许提升到实时范围
+const STARVE_PERCENT: f64 = 90.0;
+
+// 实时类别的进程排在所有普通进程之前。已经在等待 I/O（D 状态）或几乎占满 cpu 的进程
+// 提升后会让包括 btop-rs 在内的其它进程无法运行，用户也就无法再把它改回来。
+// cores 为在线核心数，per_core 与 proc_per_core 相同，决定 cpu_p 的基准
+pub fn check_realtime(proc: &ProcInfo, cores: usize, per_core: bool) -> BoostCheck {
+    let usage = match per_core {
+        true => proc.cpu_p / cores.max(1) as f64,
+        false => proc.cpu_p,
+    };
+    if proc.state == 'D' {
+        return BoostCheck::Refuse(format!(
+            "{} is waiting on I/O, realtime would starve the system",
+            proc.name
+        ));
+    }
+    if usage >= STARVE_PERCENT {
+        return BoostCheck::Refuse(format!(
+            "{} uses {:.0}% of all cpus, realtime would starve the system",
+            proc.name, usage
+        ));
+    }
+    BoostCheck::Confirm(format!(
+        "Realtime runs {} ahead of every other process and can lock up the machine",
+        proc.name
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ProcFilter, ProcInfo};
+
+    fn procs() -> Vec<ProcInfo> {
+        let proc = |pid, name: &str, user: &str, state, mem, cpu_p| ProcInfo {
+            pid,
+            ppid: 1,
+            name: name.to_owned(),
+            cmd: format!("/usr/bin/{} --serve", name),
+            user: user.to_owned(),
+            state,
+            threads: pid as u32 % 7 + 1,
+            mem,
+            cpu_p,
+            ..Default::default()
+        };
+        vec![
+            proc(1, "init", "root", 'S', 8 << 20, 0.0),
+            proc(812, "sshd", "root", 'S', 12 << 20, 0.5),
+            proc(1500, "python3", "alice", 'R', 2 << 30, 87.5),
+            proc(2048, "Python3", "bob", 'Z', 0, 0.0),
+            proc(4096, "vim", "alice", 'T', 64 << 20, 0.0),
+        ]
+    }
+
+    // 满足过滤条件的 pid
+    fn matching(text: &str) -> Vec<i32> {
+        let filter = match ProcFilter::parse(text) {
+            Ok(filter) => filter,
+            Err(err) => panic!("{:?} failed to parse: {}", text, err),
+        };
+        procs()
+            .iter()
+            .filter(|proc| filter.matches(proc))
+            .map(|proc| proc.pid)
+            .collect()
+    }
+
+    fn error(text: &str) -> String {
+        match ProcFilter::parse(text) {
+            Ok(_) => panic!("{:?} should not parse", text),
+            Err(err) => err,
+        }
+    }
+
+    #[test]
+    fn empty_filter() {
+        for text in ["", "   ", "\t\n"] {
+            assert!(ProcFilter::parse(text).unwrap().is_empty());
+            assert_eq!(matching(text), [1, 812, 1500, 2048, 4096]);
+        }
+    }
+
+    #[test]
+    fn text_terms() {
+        // 不区分大小写，也在命令行、用户和 pid 中查找
+        assert_eq!(matching("PYTHON"), [1500, 2048]);
+        assert_eq!(matching("--serve"), [1, 812, 1500, 2048, 4096]);
+        assert_eq!(matching("alice"), [1500, 4096]);
+        assert_eq!(matching("81"), [812]);
+        assert_eq!(matching("python alice"), [1500]);
+        assert_eq!(matching("!python"), [1, 812, 4096]);
+        // 还没输入完的 "!" 不过滤任何进程
+        assert_eq!(matching("!"), [1, 812, 1500, 2048, 4096]);
+        assert_eq!(matching("vim !"), [4096]);
+    }
+
+    #[test]
+    fn field_terms() {
+        assert_eq!(matching("name:PY"), [1500, 2048]);
+        // user 需要完全一致
+        assert_eq!(matching("user:alice"), [1500, 4096]);
+        assert!(matching("user:Alice").is_empty());
+        assert_eq!(matching("!user:root"), [1500, 2048, 4096]);
+        assert_eq!(matching("state:zombie"), [2048]);
+        assert_eq!(matching("state:R"), [1500]);
+        assert_eq!(matching("state:stopped"), [4096]);
+    }
+
+    #[test]
+    fn numeric_terms() {
+        assert_eq!(matching("pid:812"), [812]);
+        assert_eq!(matching("pid:=812"), [812]);
+        assert_eq!(matching("pid:>1500"), [2048, 4096]);
+        assert_eq!(matching("pid:>=1500"), [1500, 2048, 4096]);
+        assert_eq!(matching("pid:<812"), [1]);
+        assert_eq!(matching("pid:<=812"), [1, 812]);
+        assert_eq!(matching("ppid:1"), [1, 812, 1500, 2048, 4096]);
+        assert_eq!(matching("cpu:>50"), [1500]);
+        assert_eq!(matching("cpu:>0.25"), [812, 1500]);
+        assert_eq!(matching("threads:>=5"), [2048]);
+        assert_eq!(matching("pid:>-1 pid:<2"), [1]);
+    }
+
+    #[test]
+    fn memory_suffixes() {
+        assert_eq!(matching("mem:>1G"), [1500]);
+        assert_eq!(matching("mem:>=2g"), [1500]);
+        assert_eq!(matching("mem:>32M"), [1500, 4096]);
+        assert_eq!(matching("mem:<=12288k"), [1, 812, 2048]);
+        assert_eq!(matching("mem:>0.5T"), Vec::<i32>::new());
+        assert_eq!(matching("mem:1.5G"), Vec::<i32>::new());
+        // 只有 mem 可以带后缀
+        assert_eq!(error("pid:>1k"), "invalid number for pid: 1k");
+    }
+
+    #[test]
+    fn malformed_terms() {
+        assert_eq!(error("name:"), "missing value for name");
+        assert_eq!(error("!pid:"), "missing value for pid");
+        assert_eq!(error(":foo"), "unknown filter ");
+        assert_eq!(error("color:red"), "unknown filter color");
+        assert_eq!(error("state:Q"), "unknown state Q");
+        assert_eq!(error("pid:>"), "invalid number for pid: ");
+        assert_eq!(error("pid:>>5"), "invalid number for pid: >5");
+        assert_eq!(error("pid:abc"), "invalid number for pid: abc");
+        assert_eq!(error("cpu:NaN"), "invalid number for cpu: NaN");
+        assert_eq!(error("cpu:inf"), "invalid number for cpu: inf");
+        assert_eq!(error("mem:G"), "invalid number for mem: G");
+        // 一项无效整个过滤条件无效
+        assert_eq!(error("python pid:x"), "invalid number for pid: x");
+    }
+
+    #[test]
+    fn regex_filter() {
+        assert!(ProcFilter::parse_regex("  ").unwrap().is_empty());
+        // 与普通文本一样不区分大小写
+        let filter = ProcFilter::parse_regex("^py.*3$").unwrap();
+        let matched: Vec<i32> = procs()
+            .iter()
+            .filter(|proc| filter.matches(proc))
+            .map(|proc| proc.pid)
+            .collect();
+        assert_eq!(matched, [1500, 2048]);
+        assert!(ProcFilter::parse_regex("(unclosed").is_err());
+    }
+}
@@ -0,0 +1,35 @@
+#[derive(Clone, Copy, PartialEq)]
+pub enum SensorKind {
+    Fan,
+    Voltage,
+}
+
+// hwmon 中的一个风扇或电压输入，chip 为驱动的 name，label 没有提供时为 fan1、in0 这类名称
+#[derive(Clone)]
+pub struct Sensor {
+    pub chip: String,
+    pub label: String,
+    pub kind: SensorKind,
+    // 风扇为 RPM，电压为伏特
+    pub value: f64,
+}
+
+impl Sensor {
+    pub fn value_text(&self) -> String {
+        match self.kind {
+            SensorKind::Fan => format!("{:.0} RPM", self.value),
+            SensorKind::Voltage => format!("{:.3} V", self.value),
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SensorList {
+    pub sensors: Vec<Sensor>,
+}
+
+impl SensorList {
+    pub fn count(&self, kind: SensorKind) -> usize {
+        self.sensors.iter().filter(|s| s.kind == kind).count()
+    }
+}
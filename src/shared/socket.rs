@@ -0,0 +1,84 @@
+use std::net::SocketAddr;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum SocketProto {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+}
+
+impl SocketProto {
+    pub const ALL: [SocketProto; 4] = [
+        SocketProto::Tcp,
+        SocketProto::Tcp6,
+        SocketProto::Udp,
+        SocketProto::Udp6,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            SocketProto::Tcp => "tcp",
+            SocketProto::Tcp6 => "tcp6",
+            SocketProto::Udp => "udp",
+            SocketProto::Udp6 => "udp6",
+        }
+    }
+
+    pub fn is_tcp(&self) -> bool {
+        matches!(self, SocketProto::Tcp | SocketProto::Tcp6)
+    }
+}
+
+// 一个 TCP 或 UDP 套接字，未连接时 remote 的端口为 0
+#[derive(Clone)]
+pub struct SocketInfo {
+    pub proto: SocketProto,
+    pub local: SocketAddr,
+    pub remote: SocketAddr,
+    // 与 ss 相同的状态名称，未连接的 UDP 套接字为 UNCONN
+    pub state: &'static str,
+    pub inode: u64,
+    // 持有该套接字的进程，没有权限读取其描述符时为 None
+    pub owner: Option<(i32, String)>,
+}
+
+impl SocketInfo {
+    // 监听中的 TCP 套接字和已绑定但未连接的 UDP 套接字
+    pub fn is_listening(&self) -> bool {
+        match self.proto.is_tcp() {
+            true => self.state == "LISTEN",
+            false => self.remote.port() == 0,
+        }
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct SocketList {
+    pub sockets: Vec<SocketInfo>,
+}
+
+impl SocketList {
+    // 监听的排在最前，其余按协议和本地端口排列
+    pub fn sort(&mut self) {
+        self.sockets.sort_by_key(|s| {
+            (
+                !s.is_listening(),
+                !s.proto.is_tcp(),
+                s.local.port(),
+                s.remote,
+            )
+        });
+    }
+}
+
+// 端口为 0 时显示为 *，与 ss 相同
+pub fn addr_text(addr: &SocketAddr) -> String {
+    match addr.port() {
+        0 => match addr {
+            SocketAddr::V4(v4) => format!("{}:*", v4.ip()),
+            SocketAddr::V6(v6) => format!("[{}]:*", v6.ip()),
+        },
+        _ => addr.to_string(),
+    }
+}
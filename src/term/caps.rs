@@ -0,0 +1,166 @@
+use std::{env, fs, path::PathBuf};
+
+// 终端支持的色彩数量，从多到少排列
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum ColorDepth {
+    TrueColor,
+    Color256,
+    Color16,
+}
+
+impl ColorDepth {
+    pub const NAMES: [&'static str; 3] = ["truecolor", "256", "16"];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "truecolor" | "24bit" => Some(ColorDepth::TrueColor),
+            "256" => Some(ColorDepth::Color256),
+            "16" => Some(ColorDepth::Color16),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ColorDepth::TrueColor => "truecolor",
+            ColorDepth::Color256 => "256",
+            ColorDepth::Color16 => "16",
+        }
+    }
+
+    // 两者中色彩较少的一个，用于在检测结果上叠加降级的设置
+    pub fn min(self, other: Self) -> Self {
+        match (self, other) {
+            (ColorDepth::Color16, _) | (_, ColorDepth::Color16) => ColorDepth::Color16,
+            (ColorDepth::Color256, _) | (_, ColorDepth::Color256) => ColorDepth::Color256,
+            _ => ColorDepth::TrueColor,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Capabilities {
+    pub colors: ColorDepth,
+    pub italics: bool,
+}
+
+// terminfo 中 colors 数值项和 sitm 字符串项的序号，见 term(5)
+const TI_COLORS: usize = 13;
+const TI_SITM: usize = 311;
+// 传统格式的数值项为 16 位，ncurses 6.1 之后的扩展格式为 32 位
+const TI_MAGIC: i16 = 0o432;
+const TI_MAGIC_32: i16 = 0o1036;
+
+struct TermInfo {
+    colors: Option<i32>,
+    italics: bool,
+}
+
+// 按 ncurses 的顺序查找 terminfo 目录，macOS 上子目录使用首字母的十六进制编码
+fn terminfo_path(term: &str) -> Option<PathBuf> {
+    let first = term.chars().next()?;
+    let mut dirs: Vec<PathBuf> = Vec::new();
+    if let Ok(dir) = env::var("TERMINFO") {
+        dirs.push(dir.into());
+    }
+    if let Ok(home) = env::var("HOME") {
+        dirs.push(PathBuf::from(home).join(".terminfo"));
+    }
+    if let Ok(list) = env::var("TERMINFO_DIRS") {
+        dirs.extend(
+            list.split(':')
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from),
+        );
+    }
+    dirs.extend(
+        [
+            "/etc/terminfo",
+            "/lib/terminfo",
+            "/usr/share/terminfo",
+            "/usr/share/misc/terminfo",
+        ]
+        .iter()
+        .map(PathBuf::from),
+    );
+    dirs.into_iter()
+        .flat_map(|dir| {
+            [
+                dir.join(first.to_string()).join(term),
+                dir.join(format!("{:x}", first as u32)).join(term),
+            ]
+        })
+        .find(|path| path.is_file())
+}
+
+// 只读取编译后 terminfo 的基本部分，忽略扩展项
+fn parse_terminfo(data: &[u8]) -> Option<TermInfo> {
+    let short = |pos: usize| -> Option<i16> {
+        Some(i16::from_le_bytes([*data.get(pos)?, *data.get(pos + 1)?]))
+    };
+    let magic = short(0)?;
+    let number_size = match magic {
+        TI_MAGIC => 2,
+        TI_MAGIC_32 => 4,
+        _ => return None,
+    };
+    let [names, bools, numbers, strings] =
+        [2, 4, 6, 8].map(|pos| short(pos).map(|v| v.max(0) as usize));
+    let (names, bools, numbers, strings) = (names?, bools?, numbers?, strings?);
+
+    let mut pos = 12 + names + bools;
+    // 数值部分从偶数偏移开始
+    pos += pos % 2;
+    let colors = (TI_COLORS < numbers)
+        .then(|| {
+            let at = pos + TI_COLORS * number_size;
+            match number_size {
+                2 => short(at).map(i32::from),
+                _ => data
+                    .get(at..at + 4)
+                    .map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]])),
+            }
+        })
+        .flatten()
+        .filter(|colors| *colors > 0);
+    pos += numbers * number_size;
+    // 字符串偏移为 -1 表示不支持，-2 表示被取消
+    let italics = TI_SITM < strings && short(pos + TI_SITM * 2).is_some_and(|offset| offset >= 0);
+    Some(TermInfo { colors, italics })
+}
+
+fn terminfo(term: &str) -> Option<TermInfo> {
+    parse_terminfo(&fs::read(terminfo_path(term)?).ok()?)
+}
+
+// 依次参考 $COLORTERM、$TERM 的名称和 terminfo 中的 colors，
+// 都无法判断时假定为 256 色，这是目前绝大多数终端模拟器的最低水平
+pub fn detect() -> Capabilities {
+    let term = env::var("TERM").unwrap_or_default();
+    let colorterm = env::var("COLORTERM").unwrap_or_default().to_lowercase();
+    let info = terminfo(&term);
+
+    let colors = if colorterm == "truecolor" || colorterm == "24bit" || term.ends_with("-direct") {
+        ColorDepth::TrueColor
+    } else if let Some(colors) = info.as_ref().and_then(|info| info.colors) {
+        match colors {
+            0x1000000.. => ColorDepth::TrueColor,
+            256.. => ColorDepth::Color256,
+            _ => ColorDepth::Color16,
+        }
+    } else if term.is_empty()
+        || term == "linux"
+        || term == "dumb"
+        || term.starts_with("vt")
+        || term.starts_with("cons")
+    {
+        ColorDepth::Color16
+    } else {
+        ColorDepth::Color256
+    };
+
+    Capabilities {
+        colors,
+        italics: info.is_some_and(|info| info.italics),
+    }
+}
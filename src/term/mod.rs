@@ -0,0 +1,192 @@
+use std::{
+    sync::{Arc, Mutex, OnceLock},
+    time::{Duration, Instant},
+};
+
+use libc::{termios, STDIN_FILENO, STDOUT_FILENO};
+
+pub mod caps;
+pub mod output;
+
+use caps::{Capabilities, ColorDepth};
+use output::Output;
+
+use crate::{
+    draw::{ALT_SCREEN, CLEAR, HIDE_CURSOR, MOUSE_OFF, MOUSE_ON, NORMAL_SCREEN, SHOW_CURSOR},
+    input,
+};
+
+pub struct Term {
+    pub initialized: bool,
+    pub width: u16,
+    pub height: u16,
+    // 没有检测到 UTF-8 locale 时边框退回到 ASCII 字符
+    utf8: bool,
+    // 启动时探测到的终端能力，以及叠加配置和命令行参数之后实际使用的色彩模式
+    caps: Capabilities,
+    colors: ColorDepth,
+    saved_termios: Option<termios>,
+}
+
+impl Term {
+    fn new() -> Self {
+        let caps = caps::detect();
+        Term {
+            initialized: false,
+            width: 0,
+            height: 0,
+            utf8: utf8_locale(),
+            caps,
+            colors: caps.colors,
+            saved_termios: None,
+        }
+    }
+
+    pub fn get_instance() -> Arc<Mutex<Term>> {
+        static INSTANCE: OnceLock<Arc<Mutex<Term>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(Term::new())))
+            .clone()
+    }
+
+    // 关闭回显和行缓冲，让按键可以被立即读取
+    pub fn init(&mut self) -> bool {
+        if self.initialized {
+            return true;
+        }
+        if unsafe { libc::isatty(STDIN_FILENO) } == 0 {
+            return false;
+        }
+
+        let mut settings: termios = unsafe { std::mem::zeroed() };
+        if unsafe { libc::tcgetattr(STDIN_FILENO, &mut settings) } != 0 {
+            return false;
+        }
+        self.saved_termios = Some(settings);
+
+        settings.c_lflag &= !(libc::ICANON | libc::ECHO);
+        settings.c_cc[libc::VMIN] = 0;
+        settings.c_cc[libc::VTIME] = 0;
+        if unsafe { libc::tcsetattr(STDIN_FILENO, libc::TCSANOW, &settings) } != 0 {
+            return false;
+        }
+
+        self.initialized = true;
+        self.refresh();
+        let mut out = Output::new();
+        out.push(ALT_SCREEN);
+        out.push(HIDE_CURSOR);
+        out.push(MOUSE_ON);
+        out.push(CLEAR);
+        let _ = out.flush();
+        true
+    }
+
+    pub fn restore(&mut self) {
+        if !self.initialized {
+            return;
+        }
+        if let Some(settings) = self.saved_termios.take() {
+            unsafe { libc::tcsetattr(STDIN_FILENO, libc::TCSANOW, &settings) };
+        }
+        // 终端可能已经挂断，写入失败时忽略
+        let mut out = Output::new();
+        out.push(MOUSE_OFF);
+        out.push(SHOW_CURSOR);
+        out.push(NORMAL_SCREEN);
+        let _ = out.flush();
+        self.initialized = false;
+    }
+
+    // 重新读取终端尺寸，尺寸发生变化时返回 true
+    pub fn refresh(&mut self) -> bool {
+        let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+        if unsafe { libc::ioctl(STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) } != 0 {
+            return false;
+        }
+        let changed = size.ws_col != self.width || size.ws_row != self.height;
+        self.width = size.ws_col;
+        self.height = size.ws_row;
+        changed
+    }
+
+    pub fn get_size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    pub fn is_utf8(&self) -> bool {
+        self.utf8
+    }
+
+    pub fn set_utf8(&mut self, utf8: bool) {
+        self.utf8 = utf8;
+    }
+
+    pub fn get_caps(&self) -> Capabilities {
+        self.caps
+    }
+
+    pub fn get_colors(&self) -> ColorDepth {
+        self.colors
+    }
+
+    pub fn set_colors(&mut self, colors: ColorDepth) {
+        self.colors = colors;
+    }
+
+    // 通过 OSC 11 查询终端背景色。不支持的终端不会应答，最多等待 timeout，
+    // 需要在开始读取按键之前调用，否则应答会被当作按键
+    pub fn query_background(&self, timeout: Duration) -> Option<(u8, u8, u8)> {
+        if !self.initialized {
+            return None;
+        }
+        let mut out = Output::new();
+        out.push("\x1b]11;?\x1b\\");
+        out.flush().ok()?;
+        let deadline = Instant::now() + timeout;
+        let mut reply = String::new();
+        // 应答以 BEL 或 ST 结束
+        while !reply.ends_with('\x07') && !reply.ends_with("\x1b\\") {
+            let now = Instant::now();
+            if now >= deadline || !input::poll(deadline - now) {
+                break;
+            }
+            let raw = input::read_raw();
+            if raw.is_empty() {
+                break;
+            }
+            reply += &raw;
+        }
+        parse_osc_color(&reply)
+    }
+}
+
+// 应答形如 "\x1b]11;rgb:RRRR/GGGG/BBBB\x1b\\"，每个分量为 1-4 位十六进制
+fn parse_osc_color(reply: &str) -> Option<(u8, u8, u8)> {
+    let rgb = &reply[reply.find("rgb:")? + 4..];
+    let rgb = rgb
+        .trim_end_matches(['\x07', '\\'])
+        .trim_end_matches('\x1b');
+    let mut parts = rgb.split('/').map(|part| {
+        if part.is_empty() || part.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(part, 16).ok()?;
+        let max = (1u32 << (4 * part.len())) - 1;
+        Some((value * 255 / max) as u8)
+    });
+    Some((parts.next()??, parts.next()??, parts.next()??))
+}
+
+// 按 LC_ALL、LC_CTYPE、LANG 的优先级取第一个非空的值
+fn utf8_locale() -> bool {
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .map(|value| {
+            let value = value.to_lowercase();
+            value.contains("utf-8") || value.contains("utf8")
+        })
+        .unwrap_or(false)
+}
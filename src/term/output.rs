@@ -0,0 +1,64 @@
+use std::{fmt, io};
+
+use libc::STDOUT_FILENO;
+
+// 一帧的全部输出先写入缓冲区，再用一次 write(2) 写到终端，避免闪烁和半帧画面。
+// 缓冲区在帧之间保留容量
+pub struct Output {
+    buf: String,
+}
+
+impl Default for Output {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Output {
+    pub fn new() -> Self {
+        Output {
+            buf: String::with_capacity(16 * 1024),
+        }
+    }
+
+    pub fn push(&mut self, text: &str) {
+        self.buf.push_str(text);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.buf.is_empty()
+    }
+
+    // 只有终端缓冲区已满或被信号打断时才会出现部分写入，此时继续写剩余部分
+    pub fn flush(&mut self) -> io::Result<()> {
+        let mut data = self.buf.as_bytes();
+        let mut result = Ok(());
+        while !data.is_empty() {
+            let ret = unsafe {
+                libc::write(
+                    STDOUT_FILENO,
+                    data.as_ptr() as *const libc::c_void,
+                    data.len(),
+                )
+            };
+            if ret < 0 {
+                let err = io::Error::last_os_error();
+                if err.kind() == io::ErrorKind::Interrupted {
+                    continue;
+                }
+                result = Err(err);
+                break;
+            }
+            data = &data[ret as usize..];
+        }
+        self.buf.clear();
+        result
+    }
+}
+
+impl fmt::Write for Output {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.buf.push_str(s);
+        Ok(())
+    }
+}
@@ -0,0 +1,330 @@
+use std::{collections::VecDeque, path::PathBuf};
+
+use crate::{
+    export::top::{TopFormat, TopQuery},
+    shared::proc::ProcSort,
+};
+
+pub const USAGE: &str = "\
+usage: btop-rs [-h] [-v] [-lc] [-/+t] [-p <id>] [--utf-force] [--debug] [options]
+
+optional arguments:
+  -h, --help            show this help message and exit
+  -v, --version         show version info and exit
+  -lc, --low-color      disable truecolor, converts 24-bit colors to 256-color
+  -t, --tty_on          force (ON) tty mode, max 16 colors and tty friendly graph symbols
+  +t, --tty_off         force (OFF) tty mode
+  -p, --preset <id>     start with preset, integer value between 0-9
+  --utf-force           force start even if no UTF-8 locale was detected
+  --keys-record <file>  record keystrokes with timestamps to <file>
+  --keys-from <file>    replay keystrokes recorded with --keys-record from <file>
+  --benchmark [n]       run every collector n times (100 by default), print timings and exit
+  --top <n>             print the top <n> processes and exit
+  --sort <field>        sort --top output by pid, program, threads, user, memory or cpu
+  --format <json|csv>   output format of --top, defaults to json
+  --screenshot <file>   render one frame to <file> and exit, .html for a web page,
+                        any other extension for ANSI colored text
+  --config <file>       use <file> instead of btop-rs.conf in the config directory
+                        (read as TOML when it ends in .toml)
+  --config-dir <dir>    keep config, log, themes and history in <dir>
+                        instead of $XDG_CONFIG_HOME/btop-rs
+  --profile <name>      apply the [profile.<name>] section of the config file
+  --boxes <boxes>       show these boxes for this session, e.g. \"cpu net\"
+  --update <ms>         update interval in milliseconds for this session
+  --filter <text>       start with this process filter for this session
+  --daemon              run headless, only collecting and exporting metrics
+                        (SIGTERM/SIGINT to stop, SIGUSR1 to reopen output files)
+  --debug               start with loglevel set to DEBUG
+
+options that take a value also accept --option=value, short options can be
+combined, e.g. -tp 1.";
+
+// 解析完参数后立即执行并退出的操作
+pub enum Action {
+    Help,
+    Version,
+    Benchmark(u32),
+    Top(TopQuery),
+}
+
+// 命令行参数的解析结果，由 main 写入 Global 和配置
+#[derive(Default)]
+pub struct Args {
+    pub action: Option<Action>,
+    pub low_color: bool,
+    pub utf_force: bool,
+    pub daemon: bool,
+    pub preset: Option<usize>,
+    pub keys_record: Option<PathBuf>,
+    pub keys_from: Option<PathBuf>,
+    pub screenshot: Option<PathBuf>,
+    pub config_file: Option<PathBuf>,
+    pub config_dir: Option<PathBuf>,
+    pub profile: Option<String>,
+    // 只在本次运行中生效的 (配置项, 值)，来自 --boxes、-t、--debug 等
+    pub overrides: Vec<(String, String)>,
+}
+
+// 需要一个值的参数
+const VALUE_ARGS: &[&str] = &[
+    "-p",
+    "--preset",
+    "--keys-record",
+    "--keys-from",
+    "--top",
+    "--sort",
+    "--format",
+    "--screenshot",
+    "--config",
+    "--config-dir",
+    "--profile",
+    "--boxes",
+    "--update",
+    "--filter",
+];
+
+// 合并在一起的短参数，例如 -tp 1 和 -p1。-lc 沿用 btop++ 的写法，是单独的一个参数
+fn split_short(arg: &str) -> Option<Vec<String>> {
+    let flags = arg.strip_prefix('-').filter(|flags| {
+        flags.len() > 1 && !flags.starts_with('-') && !flags.contains('=') && *flags != "lc"
+    })?;
+    let mut out = Vec::new();
+    for (i, flag) in flags.char_indices() {
+        out.push(format!("-{}", flag));
+        // 需要值的参数之后的部分作为它的值
+        if VALUE_ARGS.contains(&out[out.len() - 1].as_str()) {
+            let value = &flags[i + flag.len_utf8()..];
+            if !value.is_empty() {
+                out.push(value.to_owned());
+            }
+            break;
+        }
+    }
+    Some(out)
+}
+
+impl Args {
+    // 第一个参数为程序名。-h 和 -v 立即返回，其余参数全部解析完才检查 --top 的组合
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let mut parsed = Args::default();
+        let mut top: Option<usize> = None;
+        let mut top_sort: Option<ProcSort> = None;
+        let mut top_format: Option<TopFormat> = None;
+        let mut rest: VecDeque<String> = args.iter().skip(1).cloned().collect();
+        while let Some(arg) = rest.pop_front() {
+            // 参数的值不会走到这里，不会被当作合并的短参数展开
+            if let Some(flags) = split_short(&arg) {
+                for flag in flags.into_iter().rev() {
+                    rest.push_front(flag);
+                }
+                continue;
+            }
+            // --option=value 与 --option value 等价
+            let (name, inline) = match arg.split_once('=') {
+                Some((name, value)) if name.starts_with('-') => (name, Some(value.to_owned())),
+                _ => (arg.as_str(), None),
+            };
+            let value = match VALUE_ARGS.contains(&name) {
+                true => match inline {
+                    Some(value) => value,
+                    None => rest
+                        .pop_front()
+                        .ok_or_else(|| format!("{} requires a value", name))?,
+                },
+                false if name == "--benchmark" => match inline {
+                    Some(value) => value,
+                    // 次数可以省略
+                    None => match rest.front().is_some_and(|next| next.parse::<u32>().is_ok()) {
+                        true => rest.pop_front().unwrap_or_default(),
+                        false => "100".to_owned(),
+                    },
+                },
+                false if inline.is_some() => {
+                    return Err(format!("{} does not take a value", name));
+                }
+                false => String::new(),
+            };
+
+            match name {
+                "-h" | "--help" => return Ok(Args::action(Action::Help)),
+                "-v" | "--version" => return Ok(Args::action(Action::Version)),
+                "-lc" | "--low-color" => parsed.low_color = true,
+                "-t" | "--tty_on" => parsed.set("force_tty", "True"),
+                "+t" | "--tty_off" => parsed.set("force_tty", "False"),
+                "-p" | "--preset" => match value.parse::<usize>() {
+                    Ok(preset) if preset <= 9 => parsed.preset = Some(preset),
+                    _ => return Err(format!("{} must be an integer between 0-9", name)),
+                },
+                "--utf-force" | "--utf-foce" => parsed.utf_force = true,
+                "--debug" => parsed.set("log_level", "DEBUG"),
+                "--daemon" => parsed.daemon = true,
+                "--keys-record" => parsed.keys_record = Some(PathBuf::from(value)),
+                "--keys-from" => parsed.keys_from = Some(PathBuf::from(value)),
+                "--screenshot" => parsed.screenshot = Some(PathBuf::from(value)),
+                // 相对路径按当前目录解析
+                "--config" | "--config-dir" => {
+                    let path = std::path::absolute(&value).unwrap_or_else(|_| value.into());
+                    match name {
+                        "--config" => parsed.config_file = Some(path),
+                        _ => parsed.config_dir = Some(path),
+                    }
+                }
+                "--profile" => parsed.profile = Some(value),
+                "--boxes" => parsed.set("shown_boxes", &value),
+                "--update" => parsed.set("update_ms", &value),
+                "--filter" => parsed.set("proc_filter", &value),
+                "--benchmark" => match value.parse() {
+                    Ok(iterations) => parsed.action = Some(Action::Benchmark(iterations)),
+                    Err(_) => return Err("--benchmark requires a number of iterations".to_owned()),
+                },
+                "--top" => match value.parse() {
+                    Ok(count) => top = Some(count),
+                    Err(_) => return Err("--top requires a number of processes".to_owned()),
+                },
+                "--sort" => match ProcSort::from_name(&value) {
+                    Some(sort) => top_sort = Some(sort),
+                    None => {
+                        return Err(format!(
+                            "--sort must be one of: {}",
+                            ProcSort::NAMES.join(", ")
+                        ))
+                    }
+                },
+                "--format" => match TopFormat::from_name(&value) {
+                    Some(format) => top_format = Some(format),
+                    None => return Err("--format must be json or csv".to_owned()),
+                },
+                _ => return Err(format!("unknown argument: {}", arg)),
+            }
+        }
+
+        match top {
+            Some(count) => {
+                parsed.action = Some(Action::Top(TopQuery {
+                    count,
+                    sort: top_sort.unwrap_or(ProcSort::Cpu),
+                    format: top_format.unwrap_or(TopFormat::Json),
+                }))
+            }
+            // 只对 --top 的输出有效，单独使用多半是漏写了 --top
+            None if top_sort.is_some() => return Err("--sort requires --top".to_owned()),
+            None if top_format.is_some() => return Err("--format requires --top".to_owned()),
+            None => {}
+        }
+        Ok(parsed)
+    }
+
+    fn action(action: Action) -> Self {
+        Args {
+            action: Some(action),
+            ..Default::default()
+        }
+    }
+
+    fn set(&mut self, key: &str, value: &str) {
+        self.overrides.push((key.to_owned(), value.to_owned()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Args, String> {
+        let args: Vec<String> = ["btop-rs"]
+            .iter()
+            .chain(args)
+            .map(|arg| arg.to_string())
+            .collect();
+        Args::parse(&args)
+    }
+
+    fn overrides(args: &Args) -> Vec<(&str, &str)> {
+        args.overrides
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn inline_values() {
+        let args = parse(&["--boxes=cpu net", "--update", "500", "--filter=a=b"]).unwrap();
+        assert_eq!(
+            overrides(&args),
+            [
+                ("shown_boxes", "cpu net"),
+                ("update_ms", "500"),
+                ("proc_filter", "a=b")
+            ]
+        );
+        assert_eq!(parse(&["--preset=3"]).unwrap().preset, Some(3));
+        assert!(parse(&["--daemon=yes"]).is_err());
+        assert!(parse(&["--boxes"]).is_err());
+    }
+
+    #[test]
+    fn preset_range() {
+        assert_eq!(parse(&["-p", "0"]).unwrap().preset, Some(0));
+        assert_eq!(parse(&["-p", "9"]).unwrap().preset, Some(9));
+        for value in ["10", "-1", "x", ""] {
+            assert!(parse(&["-p", value]).is_err(), "-p {:?}", value);
+        }
+    }
+
+    #[test]
+    fn unknown_arguments() {
+        for arg in ["--nope", "-x", "-", "-tx", "lc", "--low_color"] {
+            let err = parse(&[arg]).err().unwrap_or_default();
+            assert!(err.starts_with("unknown argument"), "{}: {:?}", arg, err);
+        }
+        // -h 在遇到未知参数之前立即返回
+        assert!(matches!(
+            parse(&["-h", "--nope"]).unwrap().action,
+            Some(Action::Help)
+        ));
+    }
+
+    #[test]
+    fn combined_short_flags() {
+        let args = parse(&["-tp", "2"]).unwrap();
+        assert_eq!(args.preset, Some(2));
+        assert_eq!(overrides(&args), [("force_tty", "True")]);
+        assert_eq!(parse(&["-p4"]).unwrap().preset, Some(4));
+        assert_eq!(parse(&["-tp5"]).unwrap().preset, Some(5));
+        assert!(parse(&["-lc"]).unwrap().low_color);
+        assert!(matches!(
+            parse(&["-tv"]).unwrap().action,
+            Some(Action::Version)
+        ));
+        // 值本身以 - 开头时不展开
+        let args = parse(&["--filter", "-tp"]).unwrap();
+        assert_eq!(overrides(&args), [("proc_filter", "-tp")]);
+        assert!(parse(&["-tp"]).is_err());
+    }
+
+    #[test]
+    fn top_options() {
+        let args = parse(&["--top", "5", "--sort=memory", "--format", "csv"]).unwrap();
+        let Some(Action::Top(query)) = args.action else {
+            panic!("expected --top");
+        };
+        assert_eq!(query.count, 5);
+        assert!(query.sort == ProcSort::Memory && query.format == TopFormat::Csv);
+        let Some(Action::Top(query)) = parse(&["--top=3"]).unwrap().action else {
+            panic!("expected --top");
+        };
+        assert!(query.sort == ProcSort::Cpu && query.format == TopFormat::Json);
+
+        // --sort 和 --format 只能与 --top 一起使用
+        assert_eq!(
+            parse(&["--sort", "pid"]).err().unwrap(),
+            "--sort requires --top"
+        );
+        assert_eq!(
+            parse(&["--format=csv"]).err().unwrap(),
+            "--format requires --top"
+        );
+        assert!(parse(&["--top", "x"]).is_err());
+        assert!(parse(&["--top", "3", "--sort", "size"]).is_err());
+    }
+}
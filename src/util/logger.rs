@@ -1,17 +1,183 @@
 use std::{
+    fs::{File, OpenOptions},
+    io::Write,
+    os::unix::net::UnixDatagram,
     path::PathBuf,
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock,
+    },
 };
 
+use log::{Level, LevelFilter, Log, Metadata, Record};
+
+use crate::{config::config::Config, draw::clock::strftime};
+
+const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+// 日志写到哪里，由 log_target 选择
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum LogTarget {
+    // 日志目录下的 btop-rs.log
+    File,
+    Syslog,
+    Journald,
+}
+
+impl LogTarget {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "file" => Some(LogTarget::File),
+            "syslog" => Some(LogTarget::Syslog),
+            "journald" => Some(LogTarget::Journald),
+            _ => None,
+        }
+    }
+}
+
+// 当前的日志输出。和 Logger 分开加锁，持有 Logger 时写日志不会死锁，
+// 因此写入过程中不能再调用 log 的宏
+enum Sink {
+    None,
+    File(PathBuf, Option<File>),
+    Syslog,
+    Journald(UnixDatagram),
+}
+
+static SINK: Mutex<Sink> = Mutex::new(Sink::None);
+// 写入 SINK 的最高级别，保存 LevelFilter 的值
+static SINK_LEVEL: AtomicUsize = AtomicUsize::new(0);
+
+fn sink_level() -> LevelFilter {
+    match SINK_LEVEL.load(Ordering::Relaxed) {
+        0 => LevelFilter::Off,
+        1 => LevelFilter::Error,
+        2 => LevelFilter::Warn,
+        3 => LevelFilter::Info,
+        _ => LevelFilter::Debug,
+    }
+}
+
+fn level_name(level: Level) -> &'static str {
+    match level {
+        Level::Error => "ERROR",
+        Level::Warn => "WARNING",
+        Level::Info => "INFO",
+        Level::Debug | Level::Trace => "DEBUG",
+    }
+}
+
+impl Sink {
+    fn write(&mut self, record: &Record) {
+        match self {
+            Sink::None => {}
+            Sink::File(path, file) => {
+                if file.is_none() {
+                    *file = OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(&*path)
+                        .ok();
+                }
+                if let Some(file) = file {
+                    let _ = writeln!(
+                        file,
+                        "{} | {}: {}",
+                        strftime("%Y/%m/%d (%H:%M:%S)"),
+                        level_name(record.level()),
+                        record.args()
+                    );
+                }
+            }
+            Sink::Syslog => {
+                let priority = match record.level() {
+                    Level::Error => libc::LOG_ERR,
+                    Level::Warn => libc::LOG_WARNING,
+                    Level::Info => libc::LOG_INFO,
+                    Level::Debug | Level::Trace => libc::LOG_DEBUG,
+                };
+                let Ok(message) = std::ffi::CString::new(record.args().to_string()) else {
+                    return;
+                };
+                unsafe { libc::syslog(priority, c"%s".as_ptr(), message.as_ptr()) };
+            }
+            Sink::Journald(socket) => {
+                let priority = match record.level() {
+                    Level::Error => 3,
+                    Level::Warn => 4,
+                    Level::Info => 6,
+                    Level::Debug | Level::Trace => 7,
+                };
+                let message = record.args().to_string();
+                let mut datagram =
+                    format!("PRIORITY={}\nSYSLOG_IDENTIFIER=btop-rs\n", priority).into_bytes();
+                // 含换行的值使用长度前缀的二进制格式
+                if message.contains('\n') {
+                    datagram.extend_from_slice(b"MESSAGE\n");
+                    datagram.extend_from_slice(&(message.len() as u64).to_le_bytes());
+                    datagram.extend_from_slice(message.as_bytes());
+                    datagram.push(b'\n');
+                } else {
+                    datagram.extend_from_slice(format!("MESSAGE={}\n", message).as_bytes());
+                }
+                let _ = socket.send(&datagram);
+            }
+        }
+    }
+}
+
+// 终端输出由 env_logger 按 RUST_LOG 过滤，log_level 决定写入 log_target 的内容
+struct Dispatch {
+    stderr: env_logger::Logger,
+}
+
+impl Log for Dispatch {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.stderr.enabled(metadata) || metadata.level() <= sink_level()
+    }
+
+    fn log(&self, record: &Record) {
+        if self.stderr.matches(record) {
+            self.stderr.log(record);
+        }
+        if record.level() <= sink_level() {
+            if let Ok(mut sink) = SINK.lock() {
+                sink.write(record);
+            }
+        }
+    }
+
+    fn flush(&self) {
+        self.stderr.flush();
+    }
+}
+
+// 替代 env_logger::init，程序启动时调用一次。
+// 输出到 stderr 的内容会混进界面，只有设置了 RUST_LOG 时才打开
+pub fn init() {
+    let stderr =
+        env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("off")).build();
+    let max_level = stderr.filter().max(LevelFilter::Debug);
+    if log::set_logger(Box::leak(Box::new(Dispatch { stderr }))).is_ok() {
+        log::set_max_level(max_level);
+    }
+}
+
 pub struct Logger {
     pub log_file: PathBuf,
+    // 启动时日志文件不可写，不再写入日志文件，syslog 和 journald 不受影响
+    pub file_disabled: bool,
     pub log_levels: Vec<String>,
+    // 当前生效的 log_level 和 log_target，变化时才切换输出
+    level: String,
+    target: String,
 }
 
 impl Logger {
     fn new() -> Self {
         Logger {
             log_file: PathBuf::new(),
+            file_disabled: false,
             log_levels: vec![
                 "DISABLED".to_owned(),
                 "ERROR".to_owned(),
@@ -19,16 +185,16 @@ impl Logger {
                 "INFO".to_owned(),
                 "DEBUG".to_owned(),
             ],
+            level: String::new(),
+            target: String::new(),
         }
     }
 
     pub fn get_instance() -> Arc<Mutex<Logger>> {
-        static mut instance: Option<Arc<Mutex<Logger>>> = None;
-        unsafe {
-            instance
-                .get_or_insert_with(|| Arc::new(Mutex::new(Logger::new())))
-                .clone()
-        }
+        static INSTANCE: OnceLock<Arc<Mutex<Logger>>> = OnceLock::new();
+        INSTANCE
+            .get_or_init(|| Arc::new(Mutex::new(Logger::new())))
+            .clone()
     }
 
     pub fn set_file(&mut self, file_path: PathBuf) {
@@ -39,7 +205,67 @@ impl Logger {
         &self.log_file
     }
 
+    pub fn set_file_disabled(&mut self, disabled: bool) {
+        self.file_disabled = disabled;
+    }
+
     pub fn get_levels(&self) -> &Vec<String> {
         &self.log_levels
     }
+
+    // log_level 或 log_target 变化时切换输出。无法连接 journald 时退回日志文件，返回警告
+    pub fn update(&mut self, config: &Config) -> Option<String> {
+        let level = config.get_string("log_level");
+        let target = config.get_string("log_target");
+        if level == self.level && target == self.target {
+            return None;
+        }
+        let level_index = self.log_levels.iter().position(|name| *name == level);
+        SINK_LEVEL.store(level_index.unwrap_or(2), Ordering::Relaxed);
+
+        let mut warning = None;
+        let file_sink = match self.log_file.as_os_str().is_empty() || self.file_disabled {
+            true => Sink::None,
+            false => Sink::File(self.log_file.clone(), None),
+        };
+        let sink = match LogTarget::from_name(&target).unwrap_or(LogTarget::File) {
+            LogTarget::File => file_sink,
+            LogTarget::Syslog => {
+                unsafe { libc::openlog(c"btop-rs".as_ptr(), libc::LOG_PID, libc::LOG_USER) };
+                Sink::Syslog
+            }
+            LogTarget::Journald => {
+                match UnixDatagram::unbound().and_then(|socket| {
+                    socket.connect(JOURNALD_SOCKET)?;
+                    Ok(socket)
+                }) {
+                    Ok(socket) => Sink::Journald(socket),
+                    Err(err) => {
+                        warning = Some(format!(
+                            "Could not connect to journald ({}), logging to {:?}.",
+                            err, self.log_file
+                        ));
+                        file_sink
+                    }
+                }
+            }
+        };
+        let mut current = SINK.lock().unwrap();
+        if matches!(*current, Sink::Syslog) && !matches!(sink, Sink::Syslog) {
+            unsafe { libc::closelog() };
+        }
+        *current = sink;
+        drop(current);
+
+        self.level = level;
+        self.target = target;
+        warning
+    }
+
+    // 外部轮转（例如 logrotate）之后重新打开日志文件
+    pub fn reopen(&self) {
+        if let Sink::File(_, file) = &mut *SINK.lock().unwrap() {
+            *file = None;
+        }
+    }
 }
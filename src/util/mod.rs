@@ -1,8 +1,11 @@
 use core::str;
 use std::time::{SystemTime, UNIX_EPOCH};
 
+pub mod alloc;
+pub mod args;
 pub mod logger;
 pub mod macro_def;
+pub mod regex;
 
 pub fn time_s() -> u64 {
     SystemTime::now()
@@ -28,15 +31,28 @@ pub fn parse_bool(value: &str) -> Option<bool> {
 }
 
 pub fn is_int(value: &str) -> bool {
-    match value.parse::<i32>() {
-        Ok(_) => true,
-        Err(_) => false,
-    }
+    value.parse::<i32>().is_ok()
 }
 
-pub fn ssplit<'a>(s: &'a str, delim: char) -> Vec<&'a str> {
+pub fn ssplit(s: &str, delim: char) -> Vec<&str> {
     s.split(delim)
         .map(|substring| substring.trim())
         .filter(|substring| !substring.is_empty())
         .collect()
 }
+
+// 以 1024 为进制转换为可读的大小，例如 "15.6 GiB"
+pub fn human_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
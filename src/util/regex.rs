@@ -0,0 +1,112 @@
+#[cfg(test)]
+use std::cell::Cell;
+use std::ffi::{CStr, CString};
+
+// 测试中统计 regfree 的调用次数
+#[cfg(test)]
+thread_local! {
+    static FREED: Cell<usize> = const { Cell::new(0) };
+}
+
+// libc 提供的 POSIX 扩展正则表达式，不区分大小写，只判断是否匹配
+pub struct Regex {
+    // regcomp 之后 regex_t 的地址不能改变
+    raw: Box<libc::regex_t>,
+}
+
+impl Regex {
+    pub fn new(pattern: &str) -> Result<Self, String> {
+        let pattern =
+            CString::new(pattern).map_err(|_| "pattern contains a nul byte".to_owned())?;
+        let mut raw: Box<libc::regex_t> = Box::new(unsafe { std::mem::zeroed() });
+        let flags = libc::REG_EXTENDED | libc::REG_ICASE | libc::REG_NOSUB;
+        let ret = unsafe { libc::regcomp(raw.as_mut(), pattern.as_ptr(), flags) };
+        if ret != 0 {
+            // 编译失败时没有需要 regfree 的内容
+            let mut buf = [0 as libc::c_char; 128];
+            unsafe { libc::regerror(ret, raw.as_ref(), buf.as_mut_ptr(), buf.len()) };
+            let message = unsafe { CStr::from_ptr(buf.as_ptr()) };
+            return Err(message.to_string_lossy().into_owned());
+        }
+        Ok(Regex { raw })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let Ok(text) = CString::new(text) else {
+            return false;
+        };
+        unsafe { libc::regexec(self.raw.as_ref(), text.as_ptr(), 0, std::ptr::null_mut(), 0) == 0 }
+    }
+}
+
+impl Drop for Regex {
+    fn drop(&mut self) {
+        unsafe { libc::regfree(self.raw.as_mut()) };
+        #[cfg(test)]
+        FREED.with(|freed| freed.set(freed.get() + 1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Regex, FREED};
+
+    fn regex(pattern: &str) -> Regex {
+        match Regex::new(pattern) {
+            Ok(regex) => regex,
+            Err(err) => panic!("{:?} failed to compile: {}", pattern, err),
+        }
+    }
+
+    #[test]
+    fn matches() {
+        let re = regex("^(python|perl)[0-9]*$");
+        assert!(re.is_match("python3"));
+        assert!(re.is_match("perl"));
+        assert!(!re.is_match("ipython3"));
+        assert!(!re.is_match("python3-config"));
+        // 只判断是否包含匹配的部分
+        assert!(regex("ssh").is_match("/usr/sbin/sshd -D"));
+        assert!(!regex("ssh").is_match(""));
+    }
+
+    #[test]
+    fn ignores_case() {
+        assert!(regex("firefox").is_match("Firefox"));
+        assert!(regex("^FIRE").is_match("firefox"));
+    }
+
+    #[test]
+    fn invalid_patterns() {
+        for pattern in ["(", "[a-", "a{2,1}", "a\0b"] {
+            assert!(Regex::new(pattern).is_err(), "{:?}", pattern);
+        }
+        // 含有 nul 的文本不会传给 regexec
+        assert!(!regex("a").is_match("a\0"));
+    }
+
+    // 不调用 setlocale 时按字节匹配，非 ASCII 的字面量仍然可以匹配
+    #[test]
+    fn non_ascii() {
+        let re = regex("café");
+        assert!(re.is_match("Café crème"));
+        assert!(!re.is_match("cafe"));
+        assert!(regex("^日本").is_match("日本語"));
+        assert!(!regex("^日本").is_match("中日本"));
+    }
+
+    #[test]
+    fn drop_frees_once() {
+        let freed = || FREED.with(|freed| freed.get());
+        let before = freed();
+        let re = regex("a+");
+        // 移动不会复制 regex_t
+        let moved = vec![re];
+        assert_eq!(freed(), before);
+        drop(moved);
+        assert_eq!(freed(), before + 1);
+        // 编译失败时没有需要释放的内容
+        assert!(Regex::new("(").is_err());
+        assert_eq!(freed(), before + 1);
+    }
+}